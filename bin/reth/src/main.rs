@@ -13,8 +13,13 @@ static MALLOC_CONF: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
 
 use clap::Parser;
 use reth::cli::Cli;
+use reth_chainspec::EthChainSpec;
+use reth_era_utils::{EraHistoryRpc, ExportConfig, ImportConfig};
 use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use reth_node_api::FullNodeComponents;
 use reth_node_ethereum::EthereumNode;
+use reth_rpc_api::RethEraApiServer;
+use reth_rpc_server_types::RethRpcModule;
 use tracing::info;
 
 fn main() {
@@ -39,7 +44,31 @@ fn main() {
 
     if let Err(err) = Cli::<EthereumChainSpecParser>::parse().run(async move |builder, _| {
         info!(target: "reth::cli", "Launching node");
-        let handle = builder.node(EthereumNode::default()).launch_with_debug_capabilities().await?;
+        let handle = builder
+            .node(EthereumNode::default())
+            // serve the opt-in `reth_era` namespace when it is selected for a transport
+            .extend_rpc_modules(|ctx| {
+                let provider = ctx.provider().clone();
+                let network = ctx.config().chain.chain().to_string();
+                let shutdown = ctx.node().task_executor().on_shutdown_signal().clone();
+                ctx.modules.merge_if_module_configured_with(RethRpcModule::RethEra, || {
+                    EraHistoryRpc::new(provider)
+                        .with_import_config(ImportConfig {
+                            shutdown: Some(shutdown.clone()),
+                            ..Default::default()
+                        })
+                        .with_export_config(ExportConfig {
+                            network,
+                            shutdown: Some(shutdown),
+                            ..Default::default()
+                        })
+                        .into_rpc()
+                        .into()
+                })?;
+                Ok(())
+            })
+            .launch_with_debug_capabilities()
+            .await?;
 
         handle.wait_for_node_exit().await
     }) {
@@ -320,4 +320,54 @@ async fn dev_node_send_tx_and_mine() {
     assert_eq!(balance, U256::from(1_000_000));
 }
 
+// ── Opt-in RPC modules ───────────────────────────────────────────────────────
+//
+// `reth_era` is not in the default module set, so it is only served once
+// selected with `--http.api`.
+
+#[tokio::test]
+async fn dev_node_reth_era_status() {
+    use alloy_provider::{Provider, ProviderBuilder};
+    use reth_rpc_api::EraStatus;
+    use std::{net::TcpListener, time::Duration};
+
+    let datadir = tempfile::tempdir().expect("failed to create temp dir");
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    let mut reth = Command::new(RETH)
+        .env("RUST_LOG", "off")
+        .args(["node", "--dev", "--disable-discovery", "--ipcdisable"])
+        .args(["--max-outbound-peers", "0", "--max-inbound-peers", "0"])
+        .args(["--port", "0", "--authrpc.port", "0"])
+        .args(["--http", "--http.port", &port.to_string(), "--http.api", "reth_era"])
+        .arg("--datadir")
+        .arg(datadir.path())
+        .spawn()
+        .expect("failed to spawn reth");
+
+    let provider =
+        ProviderBuilder::new().connect_http(format!("http://127.0.0.1:{port}").parse().unwrap());
+
+    // Poll until the HTTP server is up.
+    let mut status = None;
+    for _ in 0..100 {
+        match provider.raw_request::<_, EraStatus>("reth_eraStatus".into(), ()).await {
+            Ok(res) => {
+                status = Some(res);
+                break
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+    let block_number = provider.get_block_number().await;
+
+    reth.kill().expect("failed to kill reth");
+    reth.wait().expect("failed to wait for reth");
+
+    // No job was started yet.
+    assert_eq!(status.expect("reth_eraStatus never answered"), EraStatus::default());
+    // Only the selected module is served.
+    assert!(block_number.is_err(), "eth namespace should not be served");
+}
+
 const fn main() {}
@@ -0,0 +1,62 @@
+//! `reth era` command
+
+use crate::{common::CliNodeTypes, export_era::ExportEraCommand, import_era::ImportEraCommand};
+use clap::{Parser, Subcommand};
+use reth_chainspec::{EthChainSpec, EthereumHardforks};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_tasks::shutdown::Shutdown;
+use std::sync::Arc;
+
+pub mod serve;
+pub mod verify;
+
+/// `reth era` command
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    #[command(subcommand)]
+    pub command: Subcommands<C>,
+}
+
+/// `reth era` subcommands
+#[derive(Subcommand, Debug)]
+pub enum Subcommands<C: ChainSpecParser> {
+    /// Import ERA encoded blocks from a local directory or a remote host.
+    Import(ImportEraCommand<C>),
+    /// Export blocks from the database into ERA files.
+    Export(ExportEraCommand<C>),
+    /// Verify local ERA1 files without a database.
+    Verify(verify::Command<C>),
+    /// Serve a directory of ERA files over HTTP for other nodes to download.
+    Serve(serve::Command),
+}
+
+impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> Command<C> {
+    /// Execute `era` command, stopping imports, exports and the server once `shutdown` fires
+    pub async fn execute<N>(
+        self,
+        runtime: reth_tasks::Runtime,
+        shutdown: Shutdown,
+    ) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<ChainSpec = C::ChainSpec>,
+    {
+        match self.command {
+            Subcommands::Import(command) => command.execute::<N>(runtime, shutdown).await,
+            Subcommands::Export(command) => command.execute::<N>(runtime, shutdown).await,
+            Subcommands::Verify(command) => command.execute::<N>().await,
+            Subcommands::Serve(command) => command.execute(runtime, shutdown).await,
+        }
+    }
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Returns the underlying chain being used to run this command
+    pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
+        match self.command {
+            Subcommands::Import(ref command) => command.chain_spec(),
+            Subcommands::Export(ref command) => command.chain_spec(),
+            Subcommands::Verify(ref command) => command.chain_spec(),
+            Subcommands::Serve(_) => None,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Command that serves a directory of ERA files over HTTP.
+
+use clap::Parser;
+use reth_era::common::file_ops::EraFileType;
+use reth_era_utils::EraFileServer;
+use reth_tasks::{shutdown::Shutdown, TaskExecutor};
+use std::{net::SocketAddr, path::PathBuf};
+use tokio::net::TcpListener;
+
+/// Serves a directory of ERA files over HTTP, with the `index.html` listing and `checksums.txt`
+/// other nodes' era downloaders fetch, so the node acts as a mirror of its archive.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The directory holding the ERA files, such as the output of `reth era export`.
+    #[arg(long, value_name = "ERA_PATH")]
+    path: PathBuf,
+
+    /// The address to listen on.
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+}
+
+impl Command {
+    /// Execute `era serve` command, serving connections on `executor` until `shutdown` fires
+    pub async fn execute(self, executor: TaskExecutor, shutdown: Shutdown) -> eyre::Result<()> {
+        let Some(file_type) = EraFileType::from_dir(&self.path)? else {
+            eyre::bail!("No ERA files to serve found in {}", self.path.display());
+        };
+
+        let listener = TcpListener::bind(self.addr).await?;
+        EraFileServer::new(self.path, file_type).serve(listener, executor, shutdown).await
+    }
+}
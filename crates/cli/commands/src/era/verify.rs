@@ -0,0 +1,154 @@
+//! Command that verifies local ERA1 files without a database.
+
+use crate::common::CliNodeTypes;
+use clap::Parser;
+use reth_chainspec::{EthChainSpec, EthereumHardfork, EthereumHardforks};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
+use reth_era_utils as era;
+use reth_fs_util as fs;
+use reth_node_api::{BodyTy, HeaderTy};
+use std::{
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::{error, info};
+
+/// Verifies that local ERA1 files decode, match the roots committed in their headers and follow
+/// each other without gaps.
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    /// The chain the files hold history of.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = C::help_message(),
+        default_value = C::default_value(),
+        value_parser = C::parser()
+    )]
+    chain: Arc<C::ChainSpec>,
+
+    /// The directory holding the ERA1 files.
+    #[arg(long, value_name = "ERA_PATH")]
+    path: PathBuf,
+
+    /// Only verify the files holding blocks from this height on.
+    #[arg(long, value_name = "FROM_BLOCK")]
+    from_block: Option<u64>,
+
+    /// Only verify the files holding blocks up to this height.
+    #[arg(long, value_name = "TO_BLOCK")]
+    to_block: Option<u64>,
+
+    /// Number of files to verify at once.
+    ///
+    /// Defaults to the number of available cores.
+    #[arg(long, value_name = "THREADS", verbatim_doc_comment)]
+    threads: Option<NonZeroUsize>,
+}
+
+impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> Command<C> {
+    /// Execute `era verify` command
+    pub async fn execute<N>(self) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<ChainSpec = C::ChainSpec>,
+    {
+        let files = self.files()?;
+        if files.is_empty() {
+            eyre::bail!("No ERA1 (.era1) files to verify found in {}", self.path.display());
+        }
+
+        let threads = self
+            .threads
+            .unwrap_or_else(|| std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN));
+        info!(target: "reth::cli", files = files.len(), %threads, path = %self.path.display(), "Verifying ERA1 files");
+
+        verify_files::<N>(&self.chain, &files, threads)
+    }
+
+    /// Returns the ERA1 files in the directory, in order, that hold blocks in the requested range.
+    fn files(&self) -> eyre::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "era1") && self.in_range(&path)
+            {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        Ok(files)
+    }
+
+    /// Returns `true` if `file` holds blocks in the requested range, as told from the era number
+    /// in its name. Files whose name carries none are always verified.
+    fn in_range(&self, file: &Path) -> bool {
+        let Some(era) = era_number(file) else { return true };
+        let first_block = era * MAX_BLOCKS_PER_ERA1 as u64;
+        let last_block = first_block + MAX_BLOCKS_PER_ERA1 as u64 - 1;
+        self.from_block.is_none_or(|from_block| last_block >= from_block) &&
+            self.to_block.is_none_or(|to_block| first_block <= to_block)
+    }
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Returns the underlying chain being used to run this command
+    pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
+        Some(&self.chain)
+    }
+}
+
+/// Verifies `files` with [`era::verify_era1_files`] on `threads` threads, logging the outcome of
+/// each.
+///
+/// Fails if any file fails verification or doesn't start right after the previous one.
+pub(crate) fn verify_files<N: CliNodeTypes>(
+    chain: &N::ChainSpec,
+    files: &[PathBuf],
+    threads: NonZeroUsize,
+) -> eyre::Result<()> {
+    let outcomes = era::verify_era1_files::<HeaderTy<N>, BodyTy<N>>(
+        files,
+        Some(&chain.chain().to_string()),
+        chain.ethereum_fork_activation(EthereumHardfork::Paris).ttd(),
+        threads,
+    );
+
+    let mut failed = 0;
+    let mut last_block = None;
+    for (file, outcome) in files.iter().zip(outcomes) {
+        match outcome {
+            Ok(blocks) => {
+                if let Some(last_block) = last_block &&
+                    *blocks.start() != last_block + 1
+                {
+                    failed += 1;
+                    error!(target: "reth::cli", file = %file.display(), ?blocks, last_block, "ERA1 file does not start right after the previous one");
+                } else {
+                    info!(target: "reth::cli", file = %file.display(), ?blocks, "Verified ERA1 file");
+                }
+                last_block = Some(*blocks.end());
+            }
+            Err(err) => {
+                failed += 1;
+                error!(target: "reth::cli", file = %file.display(), %err, "ERA1 file failed verification");
+            }
+        }
+    }
+
+    if failed > 0 {
+        eyre::bail!("{failed} of {} ERA1 files failed verification", files.len());
+    }
+    info!(target: "reth::cli", files = files.len(), "All ERA1 files verified");
+
+    Ok(())
+}
+
+/// Parses the era number out of a file named `<network>-<era-number>-<short-hash>.era1`.
+fn era_number(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.rsplitn(3, '-').nth(1)?.parse().ok()
+}
@@ -2,14 +2,29 @@
 
 use crate::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
 use clap::{Args, Parser};
-use reth_chainspec::{EthChainSpec, EthereumHardforks};
+use reth_chainspec::{EthChainSpec, EthereumHardfork, EthereumHardforks};
 use reth_cli::chainspec::ChainSpecParser;
+use reth_db::DatabaseEnv;
 use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
 use reth_era_utils as era;
-use reth_provider::DatabaseProviderFactory;
-use std::{path::PathBuf, sync::Arc};
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_provider::{DatabaseProviderFactory, ProviderFactory};
+use reth_tasks::shutdown::Shutdown;
+use std::{
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
 use tracing::info;
 
+/// How often `--watch` checks the finalized head for a newly finalized era.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Seconds in a day, the span a date given to `--last-date` covers.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
 #[derive(Debug, Parser)]
 pub struct ExportEraCommand<C: ChainSpecParser> {
     #[command(flatten)]
@@ -21,7 +36,8 @@ pub struct ExportEraCommand<C: ChainSpecParser> {
 
 #[derive(Debug, Args)]
 pub struct ExportArgs {
-    /// The ERA file format to export: `era1` writes `.era1` files, `ere` writes `.ere` files.
+    /// The ERA file format to export: `era1` writes `.era1` files, `ere` writes `.ere` files,
+    /// `e2hs` writes Portal Network `.e2hs` files for pre-merge epochs.
     #[arg(long, value_enum, default_value_t = ExportFileType::Era1, verbatim_doc_comment)]
     file_type: ExportFileType,
     /// Optional first block number to export from the db.
@@ -32,36 +48,139 @@ pub struct ExportArgs {
     /// It is by default 8191.
     #[arg(long, value_name = "last-block-number", verbatim_doc_comment)]
     last_block_number: Option<u64>,
+    /// Optional first date to export blocks from, instead of `--first-block-number`.
+    /// A UTC date such as `2016-01-01`, or a timestamp such as `2016-01-01T12:00:00Z`.
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_first_date,
+        conflicts_with_all = ["first_block_number", "last_block_number", "watch"],
+        verbatim_doc_comment
+    )]
+    first_date: Option<u64>,
+    /// Optional last date to export blocks up to, instead of `--last-block-number`.
+    /// A UTC date such as `2016-06-30`, which includes the whole day, or a timestamp.
+    #[arg(
+        long,
+        value_name = "DATE",
+        value_parser = parse_last_date,
+        conflicts_with_all = ["first_block_number", "last_block_number", "watch"],
+        verbatim_doc_comment
+    )]
+    last_date: Option<u64>,
     /// The maximum number of blocks per file, it can help you to decrease the size of the files.
     /// Must be less than or equal to 8192.
     #[arg(long, value_name = "max-blocks-per-file", verbatim_doc_comment)]
     max_blocks_per_file: Option<u64>,
     /// The directory where the exported ERA files are written.
-    /// Defaults to `<data-dir>/<chain>/<format>-export/`, where `<format>` is `era1` or `ere`.
+    /// Defaults to `<data-dir>/<chain>/<format>-export/`, where `<format>` is `era1`, `ere` or
+    /// `e2hs`.
     #[arg(long, value_name = "EXPORT_PATH", verbatim_doc_comment)]
     path: Option<PathBuf>,
+    /// Also write an `index.html` listing and a `checksums.txt` for the export directory, so it
+    /// can be served to other nodes as-is.
+    #[arg(long, verbatim_doc_comment)]
+    write_listing: bool,
+    /// Also write a `cids.txt` mapping each file of the export directory to its IPFS CID, as
+    /// `ipfs add --cid-version 1` computes it, so the directory can be published to IPFS.
+    #[arg(long, verbatim_doc_comment)]
+    write_cids: bool,
+    /// Only export whole 8192-block eras starting on an era boundary, as the spec lays them out.
+    /// Rejects other ranges and chunk sizes, and stops at the last whole era before the head.
+    #[arg(long, verbatim_doc_comment)]
+    spec_compliant: bool,
+    /// Keep running after the export and write a new file each time another whole era finalizes,
+    /// so a hosted archive stays up to date. Starts at `--first-block-number`, which must be an
+    /// era boundary, and ignores `--last-block-number` and `--max-blocks-per-file`.
+    #[arg(long, verbatim_doc_comment)]
+    watch: bool,
+    /// Number of files to build at once, each read through its own database transaction.
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::MIN)]
+    parallel_files: NonZeroUsize,
+    /// Skip the files an earlier, interrupted export of the same range completed, resuming at
+    /// the first file that is missing or incomplete.
+    #[arg(long, conflicts_with = "watch", verbatim_doc_comment)]
+    resume: bool,
+    /// List completed files in the export manifest with XXH3 checksums instead of SHA-256, which
+    /// is much faster for exports only imported by the operator's own nodes.
+    /// The `checksums.txt` written by `--write-listing` always uses SHA-256.
+    #[arg(long, verbatim_doc_comment)]
+    fast_checksums: bool,
+    /// Leave receipts out of the exported files, which roughly halves their size, for consumers
+    /// that only need blocks. Only the `ere` format supports it, naming such files `-noreceipts`.
+    #[arg(long, verbatim_doc_comment)]
+    blocks_only: bool,
+    /// Encrypt each exported file at rest with AES-256-GCM, for archives kept on shared or
+    /// third-party storage. Takes a file holding the 32-byte key hex-encoded, as
+    /// `openssl rand -hex 32` prints it. Files are written with an `.enc` suffix, next to an
+    /// `.enc.json` key descriptor naming the key by the start of its SHA-256 hash.
+    #[arg(
+        long,
+        value_name = "KEY_FILE",
+        conflicts_with_all = ["write_listing", "write_cids"],
+        verbatim_doc_comment
+    )]
+    encryption_key: Option<PathBuf>,
+    /// List the block ranges of the files the export would write, without writing anything.
+    #[arg(long, conflicts_with = "watch", verbatim_doc_comment)]
+    dry_run: bool,
+}
+
+impl ExportArgs {
+    /// Returns the block timestamps selected by `--first-date` and `--last-date`, if either is set.
+    fn time_range(&self) -> Option<RangeInclusive<u64>> {
+        (self.first_date.is_some() || self.last_date.is_some())
+            .then(|| self.first_date.unwrap_or(0)..=self.last_date.unwrap_or(u64::MAX))
+    }
+}
+
+/// Parses `--first-date`, where a date stands for the start of that day.
+fn parse_first_date(value: &str) -> eyre::Result<u64> {
+    parse_date(value, false)
+}
+
+/// Parses `--last-date`, where a date stands for the end of that day.
+fn parse_last_date(value: &str) -> eyre::Result<u64> {
+    parse_date(value, true)
+}
+
+/// Parses a UTC date such as `2016-01-01`, or an RFC 3339 timestamp, into seconds since the Unix
+/// epoch. A date stands for its last second if `end_of_day` is set, and its first otherwise.
+fn parse_date(value: &str, end_of_day: bool) -> eyre::Result<u64> {
+    let is_date = value.len() == "2016-01-01".len();
+    let time = if is_date {
+        humantime::parse_rfc3339(&format!("{value}T00:00:00Z"))?
+    } else {
+        humantime::parse_rfc3339_weak(value)?
+    };
+    let seconds = time.duration_since(UNIX_EPOCH)?.as_secs();
+    Ok(if is_date && end_of_day { seconds + SECONDS_PER_DAY - 1 } else { seconds })
 }
 
 /// ERA formats accepted by `--file-type`.
 ///
-/// Only `era1`/`ere` are exportable; `era` is listed but rejected at runtime.
+/// Only `era1`/`ere`/`e2hs` are exportable; `era` is listed but rejected at runtime.
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 enum ExportFileType {
     /// Execution blocks written in the `.era1` format.
     Era1,
     /// Execution blocks written in the `.ere` format.
     Ere,
+    /// Pre-merge execution blocks with accumulator proofs, written in the `.e2hs` format.
+    E2hs,
     /// Consensus-layer `.era` format. Not exportable from an execution client; selecting it is an
     /// error.
     Era,
 }
 
 impl ExportFileType {
-    /// The format name (`era1` / `ere` / `era`), used for log lines and the default directory name.
+    /// The format name (`era1` / `ere` / `e2hs` / `era`), used for log lines and the default
+    /// directory name.
     const fn format(&self) -> &'static str {
         match self {
             Self::Era1 => "era1",
             Self::Ere => "ere",
+            Self::E2hs => "e2hs",
             Self::Era => "era",
         }
     }
@@ -85,8 +204,12 @@ fn era_not_exportable() -> eyre::Report {
 }
 
 impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ExportEraCommand<C> {
-    /// Execute `export-era` command
-    pub async fn execute<N>(self, runtime: reth_tasks::Runtime) -> eyre::Result<()>
+    /// Execute `export-era` command, stopping before the next file once `shutdown` fires
+    pub async fn execute<N>(
+        self,
+        runtime: reth_tasks::Runtime,
+        shutdown: Shutdown,
+    ) -> eyre::Result<()>
     where
         N: CliNodeTypes<ChainSpec = C::ChainSpec>,
     {
@@ -107,6 +230,7 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ExportEraC
                 .join(format!("{format}-export")),
         };
 
+        let paris = self.env.chain.ethereum_fork_activation(EthereumHardfork::Paris);
         let export_config = era::ExportConfig {
             network: self.env.chain.chain().to_string(),
             first_block_number: self.export.first_block_number.unwrap_or(0),
@@ -114,29 +238,103 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ExportEraC
                 .export
                 .last_block_number
                 .unwrap_or(MAX_BLOCKS_PER_ERA1 as u64 - 1),
+            time_range: self.export.time_range(),
             max_blocks_per_file: self
                 .export
                 .max_blocks_per_file
                 .unwrap_or(MAX_BLOCKS_PER_ERA1 as u64),
             dir: data_dir,
+            write_listing: self.export.write_listing,
+            write_cids: self.export.write_cids,
+            naming: Default::default(),
+            profile: if self.export.blocks_only {
+                era::ArchiveProfile::BlocksOnly
+            } else {
+                era::ArchiveProfile::Full
+            },
+            spec_compliant: self.export.spec_compliant,
+            tuning: era::ExportTuning {
+                parallel_files: self.export.parallel_files,
+                ..Default::default()
+            },
+            terminal_total_difficulty: paris.ttd(),
+            merge_block: paris.block_number(),
+            resume: self.export.resume,
+            checksum: if self.export.fast_checksums {
+                era::ChecksumAlgorithm::Xxh3
+            } else {
+                era::ChecksumAlgorithm::Sha256
+            },
+            encryption: self
+                .export
+                .encryption_key
+                .as_deref()
+                .map(era::EncryptionKey::from_file)
+                .transpose()?,
+            shutdown: Some(shutdown.clone()),
         };
 
+        if self.export.watch {
+            return match file_type {
+                ExportFileType::Era1 => {
+                    watch::<era::Era1, N>(&provider_factory, export_config, format, shutdown).await
+                }
+                ExportFileType::Ere => {
+                    watch::<era::Ere, N>(&provider_factory, export_config, format, shutdown).await
+                }
+                ExportFileType::E2hs => {
+                    watch::<era::E2hs, N>(&provider_factory, export_config, format, shutdown).await
+                }
+                // Rejected above by `ensure_exportable`.
+                ExportFileType::Era => Err(era_not_exportable()),
+            }
+        }
+
         export_config.validate()?;
 
-        info!(
-            target: "reth::cli",
-            "Starting {format} block export: blocks {}-{} to {}",
-            export_config.first_block_number,
-            export_config.last_block_number,
-            export_config.dir.display()
-        );
+        if self.export.dry_run {
+            let ranges =
+                era::plan_export(&provider_factory.database_provider_ro()?, &export_config)?;
+            for blocks in &ranges {
+                info!(target: "reth::cli", ?blocks, "Dry run: would export {format} file");
+            }
+            info!(
+                target: "reth::cli",
+                "Dry run: would export {} {format} files to {}",
+                ranges.len(),
+                export_config.dir.display()
+            );
+            return Ok(())
+        }
 
-        // Only read access is needed for the database provider.
-        let provider = provider_factory.database_provider_ro()?;
+        match &export_config.time_range {
+            Some(timestamps) => info!(
+                target: "reth::cli",
+                "Starting {format} block export: timestamps {}-{} to {}",
+                timestamps.start(),
+                timestamps.end(),
+                export_config.dir.display()
+            ),
+            None => info!(
+                target: "reth::cli",
+                "Starting {format} block export: blocks {}-{} to {}",
+                export_config.first_block_number,
+                export_config.last_block_number,
+                export_config.dir.display()
+            ),
+        }
 
+        // Files are read through read-only providers, one per file being built.
         let exported_files = match file_type {
-            ExportFileType::Era1 => era::export::<era::Era1, _>(&provider, &export_config)?,
-            ExportFileType::Ere => era::export::<era::Ere, _>(&provider, &export_config)?,
+            ExportFileType::Era1 => {
+                era::export_parallel::<era::Era1, _>(&provider_factory, &export_config)?
+            }
+            ExportFileType::Ere => {
+                era::export_parallel::<era::Ere, _>(&provider_factory, &export_config)?
+            }
+            ExportFileType::E2hs => {
+                era::export_parallel::<era::E2hs, _>(&provider_factory, &export_config)?
+            }
             // Rejected above by `ensure_exportable`.
             ExportFileType::Era => return Err(era_not_exportable()),
         };
@@ -152,6 +350,43 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ExportEraC
     }
 }
 
+/// Exports whole eras as they finalize until `shutdown` fires.
+async fn watch<W, N>(
+    provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+    config: era::ExportConfig,
+    format: &str,
+    mut shutdown: Shutdown,
+) -> eyre::Result<()>
+where
+    W: era::EraBlockWriter,
+    N: CliNodeTypes,
+{
+    let dir = config.dir.clone();
+    let mut exporter = era::FinalizedExporter::<W>::new(config)?;
+    info!(
+        target: "reth::cli",
+        "Watching for finalized eras from block {} to export as {format} to {}",
+        exporter.next_block(),
+        dir.display()
+    );
+
+    loop {
+        // A fresh read transaction per poll, so finalization progress made by the node is seen.
+        let files = exporter.poll(&provider_factory.database_provider_ro()?)?;
+        for file in files {
+            info!(target: "reth::cli", "Exported finalized era to {}", file.display());
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+            _ = &mut shutdown => {
+                info!(target: "reth::cli", "Export watch interrupted by user");
+                return Ok(())
+            }
+        }
+    }
+}
+
 impl<C: ChainSpecParser> ExportEraCommand<C> {
     /// Returns the underlying chain being used to run this command
     pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
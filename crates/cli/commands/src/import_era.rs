@@ -1,22 +1,41 @@
 //! Command that initializes the node by importing a chain from ERA files.
-use crate::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
+use crate::{
+    common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs},
+    era::verify::verify_files,
+};
 use alloy_chains::{ChainKind, NamedChain};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{BlockHash, BlockNumber};
 use clap::{Args, Parser};
 use eyre::eyre;
+use futures::{StreamExt, TryStreamExt};
 use reqwest::{Client, Url};
-use reth_chainspec::{EthChainSpec, EthereumHardforks};
+use reth_chainspec::{EthChainSpec, EthereumHardfork, EthereumHardforks};
 use reth_cli::chainspec::ChainSpecParser;
+use reth_db::DatabaseEnv;
 use reth_era::common::file_ops::EraFileType;
-use reth_era_downloader::{read_dir, read_era_dir, EraClient, EraStream, EraStreamConfig};
+use reth_era_downloader::{
+    read_dir, read_era1_stream, read_era_dir, EraClient, EraMeta, EraStream, EraStreamConfig,
+};
 use reth_era_utils as era;
 use reth_etl::Collector;
 use reth_fs_util as fs;
+use reth_node_builder::NodeTypesWithDBAdapter;
 use reth_node_core::version::version_metadata;
-use reth_provider::StaticFileProviderFactory;
-use reth_static_file_types::StaticFileSegment;
-use std::{path::PathBuf, sync::Arc};
+use reth_provider::ProviderFactory;
+use reth_tasks::shutdown::Shutdown;
+use std::{
+    collections::BTreeMap,
+    num::{NonZeroU64, NonZeroUsize},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use tracing::info;
 
+/// How often `--watch` checks the directory for new files.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
 /// Syncs ERA encoded blocks from a local or remote source.
 #[derive(Debug, Parser)]
 pub struct ImportEraCommand<C: ChainSpecParser> {
@@ -32,6 +51,95 @@ pub struct ImportEraCommand<C: ChainSpecParser> {
     /// import ends. By default all available blocks are imported.
     #[arg(long, value_name = "TO_BLOCK", verbatim_doc_comment)]
     to_block: Option<u64>,
+
+    /// Log a warning instead of failing when a block's transactions or ommers don't match the
+    /// roots committed in its header.
+    ///
+    /// Meant for recovering data from damaged archives; the imported history may not match the
+    /// canonical chain.
+    #[arg(long, verbatim_doc_comment)]
+    warn_on_mismatch: bool,
+
+    /// Check the ethash proof-of-work seal of one in every N imported pre-merge headers, picked
+    /// at random; 1 checks every header.
+    ///
+    /// Off by default, as each check costs about a millisecond and each 30000-block epoch first
+    /// needs a cache of 16 MB and up built.
+    #[arg(long, value_name = "N", verbatim_doc_comment)]
+    verify_seals: Option<NonZeroU64>,
+
+    /// Import only headers and, for ERA1 files, their total difficulty.
+    ///
+    /// Bodies and receipts are skipped and the bodies stage checkpoint is left untouched, so
+    /// bodies are downloaded by the pipeline later.
+    #[arg(long, verbatim_doc_comment)]
+    headers_only: bool,
+
+    /// Import headers and bodies without decompressing or checking ERA1 receipts.
+    ///
+    /// For nodes that don't serve historical receipts.
+    #[arg(long, conflicts_with = "headers_only", verbatim_doc_comment)]
+    skip_receipts: bool,
+
+    /// Recompute each receipt's log bloom from its logs when checking ERA1 receipts, instead of
+    /// trusting the blooms the files encode.
+    ///
+    /// Slower, as every logged address and topic is hashed. Blooms that differ are logged and
+    /// counted, and the receipts are checked with the recomputed ones.
+    #[arg(long, conflicts_with_all = ["headers_only", "skip_receipts"], verbatim_doc_comment)]
+    recompute_blooms: bool,
+
+    /// Limit the import to this many megabytes of ERA files per second.
+    ///
+    /// Keeps disk bandwidth free for a node running alongside the import.
+    #[arg(long, value_name = "MB", verbatim_doc_comment)]
+    max_mb_per_sec: Option<NonZeroU64>,
+
+    /// Limit the import to this many blocks per second.
+    #[arg(long, value_name = "BLOCKS", conflicts_with = "max_mb_per_sec")]
+    max_blocks_per_sec: Option<NonZeroU64>,
+
+    /// Recover transaction senders on this many threads while importing, instead of leaving
+    /// them to the sender recovery stage.
+    ///
+    /// Requires the sender recovery stage to have caught up with the stored headers.
+    #[arg(long, value_name = "THREADS", conflicts_with = "headers_only", verbatim_doc_comment)]
+    sender_recovery_threads: Option<NonZeroUsize>,
+
+    /// Write the block and transaction hash indices along with each file, so imported history
+    /// can be queried by hash as soon as each file is committed.
+    ///
+    /// Requires the transaction lookup stage to have caught up with the stored headers.
+    #[arg(long, verbatim_doc_comment)]
+    index_hashes: bool,
+
+    /// Megabytes of decoded blocks the import buffers in memory at once.
+    ///
+    /// Defaults to 512 MB, which suits hosts with 8 GB of memory. Raising it on larger hosts
+    /// writes recovered senders and transaction hashes in fewer, larger batches.
+    #[arg(long, value_name = "MB", verbatim_doc_comment)]
+    memory_budget: Option<NonZeroUsize>,
+
+    /// Decode the next ERA files on this many threads while the current one is written.
+    ///
+    /// Each thread holds one whole decoded file in memory until it is written.
+    #[arg(long, value_name = "THREADS", verbatim_doc_comment)]
+    decode_threads: Option<NonZeroUsize>,
+
+    /// Check the source without writing anything to the database.
+    ///
+    /// Lists the files that would be imported from a local directory and verifies ERA1 files on
+    /// their own, as `reth era verify` does. A remote source is only resolved.
+    #[arg(long, verbatim_doc_comment)]
+    dry_run: bool,
+
+    /// Keep watching `--path` and import the ERA1 and ERE files in it, along with those copied
+    /// into it later, such as with rsync.
+    ///
+    /// A file is imported once its size stops changing between two checks. ERA1 files are
+    /// verified on their own first, and one that fails is skipped until it changes.
+    #[arg(long, requires = "path", conflicts_with = "dry_run", verbatim_doc_comment)]
+    watch: bool,
 }
 
 #[derive(Debug, Args)]
@@ -39,7 +147,8 @@ pub struct ImportEraCommand<C: ChainSpecParser> {
 pub struct ImportArgs {
     /// The path to a directory for import.
     ///
-    /// The ERA1 files are read from the local directory parsing headers and bodies.
+    /// The ERA1 files are read from the local directory parsing headers and bodies. Pass `-` to
+    /// read concatenated ERA1 files from standard input instead.
     #[arg(long, value_name = "IMPORT_ERA_PATH", verbatim_doc_comment)]
     path: Option<PathBuf>,
 
@@ -71,31 +180,93 @@ impl TryFromChain for ChainKind {
 }
 
 impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ImportEraCommand<C> {
-    /// Execute `import-era` command
-    pub async fn execute<N>(self, runtime: reth_tasks::Runtime) -> eyre::Result<()>
+    /// Execute `import-era` command, stopping after the file at hand once `shutdown` fires
+    pub async fn execute<N>(
+        self,
+        runtime: reth_tasks::Runtime,
+        shutdown: Shutdown,
+    ) -> eyre::Result<()>
     where
         N: CliNodeTypes<ChainSpec = C::ChainSpec>,
     {
         info!(target: "reth::cli", "reth {} starting", version_metadata().short_version);
 
-        let Environment { provider_factory, config, .. } =
-            self.env.init::<N>(AccessRights::RW, runtime)?;
+        let access = if self.dry_run { AccessRights::RO } else { AccessRights::RW };
+        let Environment { provider_factory, config, .. } = self.env.init::<N>(access, runtime)?;
 
         let mut hash_collector = Collector::new(config.stages.etl.file_size, config.stages.etl.dir);
+        // Rollups may start their history at a genesis offset rather than block 0.
+        let genesis_block = self.env.chain.genesis_header().number();
+        let import_config = era::ImportConfig {
+            to_block: self.to_block,
+            verification: if self.warn_on_mismatch {
+                era::VerificationMode::Warn
+            } else {
+                era::VerificationMode::Strict
+            },
+            mode: if self.headers_only {
+                era::ImportMode::HeadersOnly
+            } else if self.skip_receipts {
+                era::ImportMode::SkipReceipts
+            } else {
+                era::ImportMode::Database
+            },
+            throttle: self.throttle(),
+            terminal_total_difficulty: self
+                .env
+                .chain
+                .ethereum_fork_activation(EthereumHardfork::Paris)
+                .ttd(),
+            seal_verification: match self.verify_seals {
+                None => era::SealVerification::Off,
+                Some(n) if n.get() == 1 => era::SealVerification::Full,
+                Some(n) => era::SealVerification::Sampled(n),
+            },
+            // Refuse archives of another network before any of their blocks are written. Files
+            // read from standard input carry no name to check.
+            network: (!self.reads_stdin()).then(|| self.env.chain.chain().to_string()),
+            known_block_hashes: BTreeMap::from([(genesis_block, self.env.chain.genesis_hash())]),
+            genesis_block,
+            // The node isn't running, so no consensus client reports a finalized block.
+            finalized_block: None,
+            recompute_blooms: self.recompute_blooms,
+            sender_recovery_threads: self.sender_recovery_threads,
+            index_hashes: self.index_hashes,
+            memory_budget: self
+                .memory_budget
+                .map(|mb| mb.saturating_mul(NonZeroUsize::new(1024 * 1024).unwrap())),
+            decode_threads: self.decode_threads,
+            shutdown: Some(shutdown.clone()),
+        };
 
-        let next_block = provider_factory
-            .static_file_provider()
-            .get_highest_static_file_block(StaticFileSegment::Headers)
-            .unwrap_or_default() +
-            1;
+        // Blocks whose header is stored but not their body are imported again for their bodies.
+        let stored = era::stored_history(&provider_factory.provider()?)?;
+        let next_block = if self.headers_only { stored.headers } else { stored.bodies } + 1;
 
-        if let Some(path) = self.import.path {
-            let era_type = EraFileType::from_dir(&path)?.ok_or_else(|| {
-                eyre!(
-                    "No ERA (.era), ERA1 (.era1) or ERE (.ere, .erae) files found in {}",
-                    path.display()
-                )
-            })?;
+        if self.dry_run {
+            return self.dry_run::<N>(next_block).await
+        }
+
+        if self.watch {
+            let Some(path) = self.import.path.filter(|path| path != Path::new("-")) else {
+                return Err(eyre!("--watch needs a directory to watch, not standard input"))
+            };
+            let importer = era::DirectoryImporter::new(path, import_config);
+            return watch(&provider_factory, importer, &mut hash_collector, shutdown).await
+        }
+
+        let summary = if self.reads_stdin() {
+            info!(target: "reth::cli", to_block = ?self.to_block, "Starting ERA import from standard input");
+
+            era::import::<era::Era1, _, _, _, _, _, _>(
+                read_era1_stream(tokio::io::stdin(), "stdin"),
+                &provider_factory,
+                &mut hash_collector,
+                &import_config,
+                &mut (),
+            )?
+        } else if let Some(path) = self.import.path {
+            let era_type = era_type_of_dir(&path)?;
 
             info!(target: "reth::cli", ?era_type, path = %path.display(), to_block = ?self.to_block, "Starting ERA import");
 
@@ -104,26 +275,26 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ImportEraC
                     read_era_dir(path)?,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
                 EraFileType::Ere => era::import::<era::Ere, _, _, _, _, _, _>(
                     read_dir(path, next_block)?,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
                 EraFileType::Era1 => era::import::<era::Era1, _, _, _, _, _, _>(
                     read_dir(path, next_block)?,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
-            };
+            }
         } else {
-            let url = match self.import.url {
-                Some(url) => url,
-                None => self.env.chain.chain().kind().try_to_url()?,
-            };
+            let url = self.url()?;
             let era_type = EraFileType::from_url(url.as_str());
 
             info!(target: "reth::cli", ?era_type, %url, to_block = ?self.to_block, "Starting ERA import");
@@ -148,21 +319,88 @@ impl<C: ChainSpecParser<ChainSpec: EthChainSpec + EthereumHardforks>> ImportEraC
                     stream,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
                 EraFileType::Era1 => era::import::<era::Era1, _, _, _, _, _, _>(
                     stream,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
                 EraFileType::Era => era::import::<era::Era, _, _, _, _, _, _>(
                     stream,
                     &provider_factory,
                     &mut hash_collector,
-                    self.to_block,
+                    &import_config,
+                    &mut (),
                 )?,
-            };
+            }
+        };
+
+        let era::ImportSummary {
+            last_block,
+            blocks_written,
+            receipts_written,
+            bytes_read,
+            validation_warnings,
+            bloom_mismatches,
+            files_imported,
+            files_skipped,
+            timings,
+            interrupted,
+        } = summary;
+        info!(
+            target: "reth::cli",
+            last_block,
+            blocks_written,
+            receipts_written,
+            bytes_read,
+            validation_warnings,
+            bloom_mismatches,
+            files_imported,
+            files_skipped,
+            decode = ?timings.decode,
+            write = ?timings.write,
+            index = ?timings.index,
+            commit = ?timings.commit,
+            interrupted,
+            "ERA import finished"
+        );
+
+        Ok(())
+    }
+
+    /// Checks the source for `--dry-run`, importing blocks from `next_block` on.
+    async fn dry_run<N>(&self, next_block: BlockNumber) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<ChainSpec = C::ChainSpec>,
+    {
+        let Some(path) = &self.import.path else {
+            let url = self.url()?;
+            info!(target: "reth::cli", %url, next_block, "Dry run: would import ERA files from the remote host");
+            return Ok(())
+        };
+        if self.reads_stdin() {
+            info!(target: "reth::cli", next_block, "Dry run: would import ERA1 files from standard input");
+            return Ok(())
+        }
+
+        let era_type = era_type_of_dir(path)?;
+        let stream = match era_type {
+            EraFileType::Era => read_era_dir(path.clone())?.boxed(),
+            EraFileType::Era1 | EraFileType::Ere => read_dir(path.clone(), next_block)?.boxed(),
+        };
+        let files = stream.map_ok(|file| file.path().to_path_buf()).try_collect::<Vec<_>>().await?;
+        for file in &files {
+            info!(target: "reth::cli", file = %file.display(), "Dry run: would import ERA file");
+        }
+        info!(target: "reth::cli", ?era_type, files = files.len(), next_block, to_block = ?self.to_block, "Dry run: found ERA files to import");
+
+        if matches!(era_type, EraFileType::Era1) && !files.is_empty() {
+            let threads = std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+            verify_files::<N>(&self.env.chain, &files, threads)?;
         }
 
         Ok(())
@@ -174,4 +412,65 @@ impl<C: ChainSpecParser> ImportEraCommand<C> {
     pub fn chain_spec(&self) -> Option<&Arc<C::ChainSpec>> {
         Some(&self.env.chain)
     }
+
+    /// Returns the remote host to import from: the one given on the command line, or the known
+    /// host for the chain.
+    fn url(&self) -> eyre::Result<Url> {
+        match &self.import.url {
+            Some(url) => Ok(url.clone()),
+            None => self.env.chain.chain().kind().try_to_url(),
+        }
+    }
+
+    /// Returns `true` if ERA1 files are read from standard input, as requested with `--path -`.
+    fn reads_stdin(&self) -> bool {
+        self.import.path.as_deref() == Some(Path::new("-"))
+    }
+
+    /// Returns the write limit requested on the command line, if any.
+    fn throttle(&self) -> Option<era::ImportThrottle> {
+        const MB: NonZeroU64 = NonZeroU64::new(1024 * 1024).unwrap();
+
+        if let Some(mb) = self.max_mb_per_sec {
+            return Some(era::ImportThrottle::BytesPerSecond(mb.saturating_mul(MB)))
+        }
+        self.max_blocks_per_sec.map(|blocks| era::ImportThrottle::BlocksPerInterval {
+            blocks,
+            interval: Duration::from_secs(1),
+        })
+    }
+}
+
+/// Imports the files copied into the watched directory until `shutdown` fires.
+async fn watch<N>(
+    provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<N, DatabaseEnv>>,
+    mut importer: era::DirectoryImporter,
+    hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    mut shutdown: Shutdown,
+) -> eyre::Result<()>
+where
+    N: CliNodeTypes,
+{
+    info!(target: "reth::cli", dir = %importer.dir().display(), "Watching for ERA files to import");
+
+    loop {
+        for file in importer.poll(provider_factory, hash_collector)? {
+            info!(target: "reth::cli", file = %file.display(), "Imported ERA file");
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+            _ = &mut shutdown => {
+                info!(target: "reth::cli", "Import watch interrupted by user");
+                return Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the type of the ERA files in `dir`.
+fn era_type_of_dir(dir: &Path) -> eyre::Result<EraFileType> {
+    EraFileType::from_dir(dir)?.ok_or_else(|| {
+        eyre!("No ERA (.era), ERA1 (.era1) or ERE (.ere, .erae) files found in {}", dir.display())
+    })
 }
@@ -10,7 +10,7 @@
 
 //! Entrypoint for running commands.
 
-use reth_tasks::{PanickedTaskError, TaskExecutor};
+use reth_tasks::{shutdown::Shutdown, PanickedTaskError, TaskExecutor};
 use std::{future::Future, pin::pin, sync::mpsc, time::Duration};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info};
@@ -174,6 +174,40 @@ impl CliRunner {
 
         Ok(())
     }
+
+    /// Executes a regular future as a spawned blocking task until completion, handing it a
+    /// [`Shutdown`] that fires on the first external signal.
+    ///
+    /// Unlike [`CliRunner::run_blocking_until_ctrl_c`], the command isn't dropped on that signal
+    /// but left to stop at a consistent point, unless a second signal is received.
+    pub fn run_blocking_until_shutdown<F, E>(
+        self,
+        command: impl FnOnce(Shutdown) -> F + Send + 'static,
+    ) -> Result<(), E>
+    where
+        F: Future<Output = Result<(), E>> + Send + 'static,
+        E: Send + Sync + From<std::io::Error> + 'static,
+    {
+        let (signal, shutdown) = reth_tasks::shutdown::signal();
+        let handle = self.runtime.handle().clone();
+        let handle2 = handle.clone();
+        let fut = handle.spawn_blocking(move || handle2.block_on(command(shutdown)));
+        let res = self.runtime.handle().block_on(async move {
+            let mut fut = pin!(async move { fut.await.expect("Failed to join task") });
+            tokio::select! {
+                res = &mut fut => return res,
+                res = run_until_ctrl_c(std::future::pending::<Result<(), E>>()) => res?,
+            }
+
+            info!(target: "reth::cli", "Shutting down once the command stops, signal again to exit now");
+            signal.fire();
+            run_until_ctrl_c(fut).await
+        });
+
+        runtime_shutdown(self.runtime, false);
+
+        res
+    }
 }
 
 /// Extracts the task manager handle from the runtime and creates the [`CliContext`].
@@ -146,10 +146,14 @@ impl StageConfig {
 }
 
 /// ERA stage configuration.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct EraConfig {
+    /// Whether history is imported from ERA files before the headers stage runs.
+    ///
+    /// The `--era.enable` flag enables the import regardless.
+    pub enabled: bool,
     /// Path to a local directory where ERA1 files are located.
     ///
     /// Conflicts with `url`.
@@ -162,6 +166,28 @@ pub struct EraConfig {
     ///
     /// Required for `url`.
     pub folder: Option<PathBuf>,
+    /// The maximum number of files to download from `url` at once.
+    ///
+    /// Default: 3
+    pub max_concurrent_downloads: usize,
+    /// The maximum number of files downloaded from `url` kept in `folder`, including those being
+    /// downloaded. Files are deleted once imported.
+    ///
+    /// Default: 5
+    pub max_files: usize,
+}
+
+impl Default for EraConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            url: None,
+            folder: None,
+            max_concurrent_downloads: 3,
+            max_files: 5,
+        }
+    }
 }
 
 impl EraConfig {
@@ -170,6 +196,18 @@ impl EraConfig {
         self.folder = Some(dir.as_ref().join("era"));
         self
     }
+
+    /// Validates the ERA stage configuration.
+    ///
+    /// Returns an error if no file could be downloaded or kept at once.
+    pub fn validate(&self) -> eyre::Result<()> {
+        eyre::ensure!(
+            self.max_concurrent_downloads > 0,
+            "ERA max concurrent downloads must be greater than 0"
+        );
+        eyre::ensure!(self.max_files > 0, "ERA max files must be greater than 0");
+        Ok(())
+    }
 }
 
 /// Header stage configuration.
@@ -638,7 +676,7 @@ where
 
 #[cfg(all(test, feature = "serde"))]
 mod tests {
-    use super::{Config, EXTENSION};
+    use super::{Config, EraConfig, EXTENSION};
     use crate::PruneConfig;
     use alloy_primitives::Address;
     use reth_network_peers::TrustedPeer;
@@ -793,6 +831,28 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_load_era_stage() {
+        let s = r#"
+[stages.era]
+enabled = true
+url = "https://example.com/era1/"
+max_files = 10
+"#;
+        let config: Config = toml::from_str(s).unwrap();
+        let era = config.stages.era;
+        assert!(era.enabled);
+        assert_eq!(era.url.unwrap().as_str(), "https://example.com/era1/");
+        assert_eq!(era.max_concurrent_downloads, 3);
+        assert_eq!(era.max_files, 10);
+        assert!(era.validate().is_ok());
+
+        let era = EraConfig { max_files: 0, ..Default::default() };
+        assert!(era.validate().is_err());
+        let era = EraConfig { max_concurrent_downloads: 0, ..Default::default() };
+        assert!(era.validate().is_err());
+    }
+
     // ensures config deserialization is backwards compatible
     #[test]
     fn test_backwards_compatibility() {
@@ -36,10 +36,12 @@
 
 mod client;
 mod fs;
+mod reader;
 mod stream;
 
 pub use client::{EraClient, HttpClient};
 pub use fs::{read_dir, read_era_dir};
-pub use stream::{EraMeta, EraStream, EraStreamConfig};
+pub use reader::{read_era1_stream, EraStreamedMeta};
+pub use stream::{EraMeta, EraRead, EraStream, EraStreamConfig};
 
 pub(crate) const BLOCKS_PER_FILE: usize = 8192;
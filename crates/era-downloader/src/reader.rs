@@ -0,0 +1,114 @@
+use crate::{stream::EraRead, EraMeta};
+use bytes::Bytes;
+use eyre::eyre;
+use futures_util::{stream, Stream};
+use reth_era::e2s::types::{Header, VERSION};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Creates an ordered asynchronous [`Stream`] of the ERA1 files concatenated in `reader`, such as
+/// an archive piped into standard input.
+///
+/// Each file starts with an e2store version record, which is where the stream is split. Files are
+/// held in memory one at a time and never written to disk. As the stream carries no file names,
+/// each file is named `<name>-<index>.era1` after its position in the stream.
+pub fn read_era1_stream<R>(
+    reader: R,
+    name: impl Into<String>,
+) -> impl Stream<Item = eyre::Result<EraStreamedMeta>> + Send + Unpin + 'static
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    let name = name.into();
+    let state = Some((reader, Vec::new(), 0usize));
+
+    Box::pin(stream::unfold(state, move |state| {
+        let name = name.clone();
+        async move {
+            let (mut reader, contents, index) = state?;
+            match next_file(&mut reader, contents).await {
+                Ok(Some((file, next))) => {
+                    let path = PathBuf::from(format!("{name}-{index:05}.era1"));
+                    let meta = EraStreamedMeta { path: path.into_boxed_path(), contents: file };
+                    Some((Ok(meta), Some((reader, next, index + 1))))
+                }
+                Ok(None) => None,
+                // A broken stream can't be split any further.
+                Err(err) => Some((Err(err), None)),
+            }
+        }
+    }))
+}
+
+/// Reads records from `reader` onto `contents` up to the version record starting the next file,
+/// returning the file read along with the start of the next one, or `None` once the stream ends.
+async fn next_file<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    mut contents: Vec<u8>,
+) -> eyre::Result<Option<(Bytes, Vec<u8>)>> {
+    loop {
+        let Some(header_bytes) = read_header(reader).await? else {
+            return Ok((!contents.is_empty()).then(|| (contents.into(), Vec::new())))
+        };
+        let header = Header::read(&mut header_bytes.as_slice())?
+            .ok_or_else(|| eyre!("e2store record header is incomplete"))?;
+
+        if header.header_type == VERSION {
+            if !contents.is_empty() {
+                return Ok(Some((contents.into(), header_bytes.to_vec())))
+            }
+        } else if contents.is_empty() {
+            return Err(eyre!("ERA1 stream does not start with an e2store version record"))
+        }
+
+        let start = contents.len();
+        contents.extend_from_slice(&header_bytes);
+        contents.resize(start + Header::SIZE + header.length as usize, 0);
+        reader
+            .read_exact(&mut contents[start + Header::SIZE..])
+            .await
+            .map_err(|err| eyre!("ERA1 stream ends inside an e2store record: {err}"))?;
+    }
+}
+
+/// Reads the header of the next record, or `None` if the stream ends before it.
+async fn read_header<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> eyre::Result<Option<[u8; Header::SIZE]>> {
+    let mut header = [0; Header::SIZE];
+    let mut read = 0;
+    while read < header.len() {
+        match reader.read(&mut header[read..]).await? {
+            0 if read == 0 => return Ok(None),
+            0 => return Err(eyre!("ERA1 stream ends inside an e2store record header")),
+            n => read += n,
+        }
+    }
+    Ok(Some(header))
+}
+
+/// Contains an ERA1 file read from a stream by [`read_era1_stream`], held in memory.
+#[derive(Debug)]
+pub struct EraStreamedMeta {
+    path: Box<Path>,
+    contents: Bytes,
+}
+
+impl EraMeta for EraStreamedMeta {
+    /// A no-op, the file is dropped along with `self`.
+    fn mark_as_processed(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    /// The name given to the file after its position in the stream; nothing exists at this path.
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(&self) -> eyre::Result<Box<dyn EraRead>> {
+        Ok(Box::new(io::Cursor::new(self.contents.clone())))
+    }
+}
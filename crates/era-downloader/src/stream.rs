@@ -7,6 +7,7 @@ use std::{
     collections::VecDeque,
     fmt::{Debug, Formatter},
     future::Future,
+    io::{Read, Seek},
     path::Path,
     pin::Pin,
     task::{Context, Poll},
@@ -114,8 +115,16 @@ pub trait EraMeta: Debug {
 
     /// A path to the era file.
     ///
-    /// File should be openable and treated as read-only.
+    /// File should be openable and treated as read-only, unless [`EraMeta::open`] is overridden.
     fn path(&self) -> &Path;
+
+    /// Opens the era file for reading.
+    ///
+    /// Defaults to opening the file at [`EraMeta::path`]. Sources that hold their files in memory,
+    /// like [`read_era1_stream`](crate::read_era1_stream), read them from there instead.
+    fn open(&self) -> eyre::Result<Box<dyn EraRead>> {
+        Ok(Box::new(fs::open(self.path())?))
+    }
 }
 
 impl<T: EraMeta> EraMeta for Box<T> {
@@ -126,8 +135,17 @@ impl<T: EraMeta> EraMeta for Box<T> {
     fn path(&self) -> &Path {
         T::path(self)
     }
+
+    fn open(&self) -> eyre::Result<Box<dyn EraRead>> {
+        T::open(self)
+    }
 }
 
+/// The contents of an era file, as opened by [`EraMeta::open`].
+pub trait EraRead: Read + Seek {}
+
+impl<T: Read + Seek> EraRead for T {}
+
 /// Contains information about ERA file that is hosted remotely and represented by a temporary
 /// local file.
 #[derive(Debug)]
@@ -4,6 +4,7 @@ mod checksums;
 mod download;
 mod fs;
 mod list;
+mod reader;
 mod stream;
 
 const fn main() {}
@@ -0,0 +1,53 @@
+use futures_util::StreamExt;
+use reth_era_downloader::{read_era1_stream, EraMeta};
+use std::{io::Read, path::Path};
+
+/// An e2store version record.
+const VERSION: [u8; 8] = [0x65, 0x32, 0, 0, 0, 0, 0, 0];
+
+/// A file of a version record followed by a record holding `data`.
+fn file(data: &[u8]) -> Vec<u8> {
+    let mut file = VERSION.to_vec();
+    file.extend_from_slice(&[0x03, 0x00]);
+    file.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    file.extend_from_slice(&[0, 0]);
+    file.extend_from_slice(data);
+    file
+}
+
+#[tokio::test]
+async fn test_streaming_concatenated_files_splits_them_at_version_records() {
+    let (first, second) = (file(b"first"), file(b"second"));
+    let stream =
+        read_era1_stream(std::io::Cursor::new([first.clone(), second.clone()].concat()), "stdin");
+
+    let files = stream.collect::<Vec<_>>().await;
+    assert_eq!(files.len(), 2);
+    for (index, (file, expected)) in files.into_iter().zip([first, second]).enumerate() {
+        let file = file.unwrap();
+        assert_eq!(file.path(), Path::new(&format!("stdin-{index:05}.era1")));
+
+        let mut contents = Vec::new();
+        file.open().unwrap().read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_streaming_truncated_file_fails() {
+    let mut contents = file(b"first");
+    contents.pop();
+    let mut stream = read_era1_stream(std::io::Cursor::new(contents), "stdin");
+
+    let err = stream.next().await.unwrap().unwrap_err().to_string();
+    assert!(err.contains("ends inside an e2store record"), "{err}");
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_streaming_without_version_record_fails() {
+    let mut stream = read_era1_stream(std::io::Cursor::new(file(b"first")[8..].to_vec()), "stdin");
+
+    let err = stream.next().await.unwrap().unwrap_err().to_string();
+    assert_eq!(err, "ERA1 stream does not start with an e2store version record");
+}
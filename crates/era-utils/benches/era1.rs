@@ -0,0 +1,160 @@
+#![allow(missing_docs, unreachable_pub)]
+use alloy_consensus::Header;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use reth_chainspec::MAINNET;
+use reth_db_common::init::init_genesis;
+use reth_era::{
+    common::file_ops::FileReader,
+    e2s::error::E2sError,
+    era1::{file::Era1Reader, types::execution::BlockTuple},
+};
+use reth_era_downloader::read_dir;
+use reth_era_utils::{
+    decode_with_receipts, import, test_utils::SyntheticHistory, Era1, ExportConfig, ImportConfig,
+};
+use reth_ethereum_primitives::{BlockBody, Receipt, TxType};
+use reth_etl::Collector;
+use reth_primitives_traits::BlockBody as _;
+use reth_provider::test_utils::create_test_provider_factory;
+use std::path::{Path, PathBuf};
+use tempfile::{tempdir, TempDir};
+
+/// Fixture shapes, as blocks and transactions per block: a sparse early-chain file and a dense one.
+const FIXTURES: [(u64, u8); 2] = [(1024, 8), (128, 128)];
+
+/// Writes a single `.era1` fixture of `blocks` blocks extending the mainnet genesis, with the
+/// `checksums.txt` the downloader reads alongside it.
+fn write_fixture(blocks: u64, transactions_per_block: u8) -> (TempDir, PathBuf) {
+    let dir = tempdir().unwrap();
+    let history = SyntheticHistory {
+        blocks,
+        transactions_per_block,
+        transaction_types: vec![TxType::Legacy, TxType::Eip1559],
+        ..SyntheticHistory::for_chain(&**MAINNET)
+    };
+    let config = ExportConfig {
+        dir: dir.path().to_path_buf(),
+        max_blocks_per_file: blocks,
+        write_listing: true,
+        ..Default::default()
+    };
+    let file = history.write_era1(&config).unwrap().remove(0);
+    (dir, file)
+}
+
+fn read_blocks(file: &Path) -> Vec<BlockTuple> {
+    Era1Reader::open(file, "mainnet").unwrap().group.blocks
+}
+
+fn bench_decompress(c: &mut Criterion) {
+    let mut group = c.benchmark_group("era1_decompress");
+    for (blocks, transactions) in FIXTURES {
+        let (_dir, file) = write_fixture(blocks, transactions);
+        let tuples = read_blocks(&file);
+        group.throughput(Throughput::Elements(blocks));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{blocks}x{transactions}")),
+            |b| {
+                b.iter(|| {
+                    for tuple in &tuples {
+                        tuple.header.decompress().unwrap();
+                        tuple.body.decompress().unwrap();
+                        tuple.receipts.decompress().unwrap();
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("era1_decode");
+    for (blocks, transactions) in FIXTURES {
+        let (_dir, file) = write_fixture(blocks, transactions);
+        let tuples = read_blocks(&file);
+        group.throughput(Throughput::Elements(blocks));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{blocks}x{transactions}")),
+            |b| {
+                b.iter_batched(
+                    || tuples.clone(),
+                    |tuples| {
+                        for tuple in tuples {
+                            decode_with_receipts::<Header, BlockBody, Receipt, E2sError>(Ok(tuple))
+                                .unwrap();
+                        }
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_sender_recovery(c: &mut Criterion) {
+    let mut group = c.benchmark_group("era1_sender_recovery");
+    for (blocks, transactions) in FIXTURES {
+        let (_dir, file) = write_fixture(blocks, transactions);
+        let bodies = read_blocks(&file)
+            .into_iter()
+            .map(|tuple| {
+                decode_with_receipts::<Header, BlockBody, Receipt, E2sError>(Ok(tuple))
+                    .unwrap()
+                    .body
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(blocks * u64::from(transactions)));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{blocks}x{transactions}")),
+            |b| {
+                b.iter(|| {
+                    for body in &bodies {
+                        body.recover_signers().unwrap();
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_import(c: &mut Criterion) {
+    let mut group = c.benchmark_group("era1_import");
+    group.sample_size(10);
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let _guard = runtime.enter();
+
+    let (blocks, transactions) = FIXTURES[0];
+    let (dir, _) = write_fixture(blocks, transactions);
+    group.throughput(Throughput::Elements(blocks));
+    group.bench_function(BenchmarkId::from_parameter(format!("{blocks}x{transactions}")), |b| {
+        b.iter_batched(
+            || {
+                let provider_factory = create_test_provider_factory();
+                init_genesis(&provider_factory).unwrap();
+                let collector_dir = tempdir().unwrap();
+                let collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
+                (provider_factory, collector, collector_dir)
+            },
+            |(provider_factory, mut collector, _collector_dir)| {
+                let summary = import::<Era1, _, _, _, _, _, _>(
+                    read_dir(dir.path().to_path_buf(), 0).unwrap(),
+                    &provider_factory,
+                    &mut collector,
+                    &ImportConfig::default(),
+                    &mut (),
+                )
+                .unwrap();
+                assert_eq!(summary.last_block, blocks);
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_decompress, bench_decode, bench_sender_recovery, bench_import);
+criterion_main!(benches);
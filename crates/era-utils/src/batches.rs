@@ -0,0 +1,285 @@
+//! Arrow [`RecordBatch`] streams over `.era1` file content, handing decoded history to dataframe
+//! and OLAP engines in memory instead of through files.
+//!
+//! Batches hold the same rows and columns as the CSV tables of
+//! [`export_tables`](crate::export_tables). Numbers are `UInt64`, quantities `Decimal256(76, 0)`,
+//! hashes and addresses `FixedSizeBinary` and byte strings `Binary`.
+
+use crate::tables::{block_rows, Kind, Table, Value};
+use arrow_array::{
+    builder::{
+        BinaryBuilder, BooleanBuilder, Decimal256Builder, FixedSizeBinaryBuilder, UInt64Builder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_buffer::i256;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use eyre::{eyre, Result};
+use reth_era::{
+    common::file_ops::FileReader,
+    era1::{file::Era1Reader, types::execution::BlockTuple},
+};
+use reth_primitives_traits::FullBlockBody;
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+    vec,
+};
+
+/// Rows a batch holds by default.
+const DEFAULT_BATCH_SIZE: usize = 8192;
+
+impl Table {
+    /// Returns the Arrow schema of the table's batches.
+    pub fn arrow_schema(&self) -> SchemaRef {
+        let fields = self
+            .columns()
+            .iter()
+            .map(|(name, kind, nullable)| Field::new(*name, data_type(*kind), *nullable))
+            .collect::<Vec<_>>();
+        Arc::new(Schema::new(fields))
+    }
+}
+
+/// Iterator over the rows of one [`Table`] of a set of `.era1` files as Arrow [`RecordBatch`]es,
+/// with block bodies decoded as `BB`.
+///
+/// Files are read one at a time, in the order given, and each batch holds at least
+/// [`RecordBatches::with_batch_size`] rows, as many as the blocks read for it add, except for the
+/// last. Iteration ends after the first error, such as a file that fails to read.
+pub struct RecordBatches<BB> {
+    files: VecDeque<PathBuf>,
+    table: Table,
+    schema: SchemaRef,
+    batch_size: usize,
+    recover_senders: bool,
+    /// Blocks of the file being read that are left to read.
+    blocks: Option<vec::IntoIter<BlockTuple>>,
+    _body: PhantomData<BB>,
+}
+
+impl<BB> fmt::Debug for RecordBatches<BB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordBatches")
+            .field("files", &self.files)
+            .field("table", &self.table)
+            .field("batch_size", &self.batch_size)
+            .field("recover_senders", &self.recover_senders)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<BB: FullBlockBody> RecordBatches<BB> {
+    /// Creates a stream of the rows of `table` in the `.era1` files at `files`.
+    pub fn new(files: &[impl AsRef<Path>], table: Table) -> Self {
+        Self {
+            files: files.iter().map(|path| path.as_ref().to_path_buf()).collect(),
+            table,
+            schema: table.arrow_schema(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            recover_senders: true,
+            blocks: None,
+            _body: PhantomData,
+        }
+    }
+
+    /// Sets the rows a batch holds, `8192` by default.
+    pub const fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets whether to recover the sender of each transaction for the `from` column, which takes
+    /// most of the time of a transactions stream. Enabled by default; the column is null otherwise.
+    pub const fn with_senders(mut self, recover_senders: bool) -> Self {
+        self.recover_senders = recover_senders;
+        self
+    }
+
+    /// Returns the schema of the stream's batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Builds the next batch, returning `None` once every block was read.
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut columns: Vec<_> =
+            self.table.columns().iter().map(|(_, kind, _)| ColumnBuilder::new(*kind)).collect();
+        let mut rows = 0;
+
+        while rows < self.batch_size.max(1) {
+            let Some(block) = self.next_block()? else { break };
+            block_rows::<BB>(&block, &[self.table], self.recover_senders, |_, row| {
+                rows += 1;
+                append(&mut columns, row)
+            })?;
+        }
+
+        if rows == 0 {
+            return Ok(None)
+        }
+        let columns = columns.into_iter().map(ColumnBuilder::finish).collect();
+        Ok(Some(RecordBatch::try_new(self.schema.clone(), columns)?))
+    }
+
+    /// Returns the next block, opening the next file once the one being read runs out.
+    fn next_block(&mut self) -> Result<Option<BlockTuple>> {
+        loop {
+            if let Some(block) = self.blocks.as_mut().and_then(Iterator::next) {
+                return Ok(Some(block))
+            }
+            let Some(path) = self.files.pop_front() else { return Ok(None) };
+            self.blocks = Some(Era1Reader::open(&path, "")?.group.blocks.into_iter());
+        }
+    }
+}
+
+impl<BB: FullBlockBody> Iterator for RecordBatches<BB> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.next_batch().transpose();
+        if matches!(batch, Some(Err(_))) {
+            self.files.clear();
+            self.blocks = None;
+        }
+        batch
+    }
+}
+
+/// Returns the Arrow type of columns of `kind`.
+const fn data_type(kind: Kind) -> DataType {
+    match kind {
+        Kind::Number => DataType::UInt64,
+        Kind::Quantity => DataType::Decimal256(76, 0),
+        Kind::Flag => DataType::Boolean,
+        Kind::Hash => DataType::FixedSizeBinary(32),
+        Kind::Address => DataType::FixedSizeBinary(20),
+        Kind::Bytes => DataType::Binary,
+    }
+}
+
+/// Appends the fields of `row` to the builders of their columns.
+fn append(columns: &mut [ColumnBuilder], row: &[Value<'_>]) -> Result<()> {
+    for (column, value) in columns.iter_mut().zip(row) {
+        column.append(value)?;
+    }
+    Ok(())
+}
+
+/// Builder of a column of a batch, by [`Kind`].
+enum ColumnBuilder {
+    Number(UInt64Builder),
+    Quantity(Decimal256Builder),
+    Flag(BooleanBuilder),
+    Fixed(FixedSizeBinaryBuilder),
+    Bytes(BinaryBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(kind: Kind) -> Self {
+        match kind {
+            Kind::Number => Self::Number(UInt64Builder::new()),
+            Kind::Quantity => {
+                Self::Quantity(Decimal256Builder::new().with_data_type(data_type(kind)))
+            }
+            Kind::Flag => Self::Flag(BooleanBuilder::new()),
+            Kind::Hash => Self::Fixed(FixedSizeBinaryBuilder::new(32)),
+            Kind::Address => Self::Fixed(FixedSizeBinaryBuilder::new(20)),
+            Kind::Bytes => Self::Bytes(BinaryBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: &Value<'_>) -> Result<()> {
+        match (self, value) {
+            (Self::Number(builder), Value::Number(value)) => builder.append_value(*value),
+            // Quantities held by blocks stay far below `2^255`, so fit a signed 256-bit decimal.
+            (Self::Quantity(builder), Value::Quantity(value)) => {
+                builder.append_value(i256::from_be_bytes(value.to_be_bytes()))
+            }
+            (Self::Flag(builder), Value::Flag(value)) => builder.append_value(*value),
+            (Self::Fixed(builder), Value::Hash(hash)) => builder.append_value(hash)?,
+            (Self::Fixed(builder), Value::Address(address)) => builder.append_value(address)?,
+            (Self::Bytes(builder), Value::Bytes(bytes)) => builder.append_value(bytes),
+            (Self::Number(builder), Value::Null) => builder.append_null(),
+            (Self::Quantity(builder), Value::Null) => builder.append_null(),
+            (Self::Flag(builder), Value::Null) => builder.append_null(),
+            (Self::Fixed(builder), Value::Null) => builder.append_null(),
+            (Self::Bytes(builder), Value::Null) => builder.append_null(),
+            (_, value) => return Err(eyre!("{value:?} doesn't fit its column")),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            Self::Number(mut builder) => Arc::new(builder.finish()),
+            Self::Quantity(mut builder) => Arc::new(builder.finish()),
+            Self::Flag(mut builder) => Arc::new(builder.finish()),
+            Self::Fixed(mut builder) => Arc::new(builder.finish()),
+            Self::Bytes(mut builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock, ExportConfig};
+    use alloy_consensus::Header;
+    use alloy_primitives::{B256, U256};
+    use arrow_array::{Array, Decimal256Array, UInt64Array};
+    use reth_ethereum_primitives::{BlockBody, Receipt};
+
+    #[test]
+    fn streams_headers_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..5u64)
+            .map(|number| {
+                let header = Header {
+                    number,
+                    parent_hash,
+                    difficulty: U256::from(10),
+                    ..Default::default()
+                };
+                parent_hash = header.hash_slow();
+                ExportBlock::<_, BlockBody, Receipt> {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody::default(),
+                    receipts: Vec::new(),
+                    total_difficulty: U256::from((number + 1) * 10),
+                }
+            })
+            .collect::<Vec<_>>();
+        let file = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        let batches = RecordBatches::<BlockBody>::new(&[file], Table::Headers)
+            .with_batch_size(2)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(batches.iter().map(RecordBatch::num_rows).collect::<Vec<_>>(), vec![2, 2, 1]);
+
+        let last = &batches[2];
+        assert_eq!(last.schema(), Table::Headers.arrow_schema());
+        let numbers = last.column(0).as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(numbers.value(0), 4);
+        let base_fees = last.column_by_name("base_fee_per_gas").unwrap();
+        assert!(base_fees.is_null(0));
+        let total_difficulty = last
+            .column_by_name("total_difficulty")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Decimal256Array>()
+            .unwrap();
+        assert_eq!(total_difficulty.value(0), i256::from_i128(50));
+    }
+}
@@ -0,0 +1,122 @@
+//! Decoding of ERA files on worker threads, ahead of the import writing them.
+
+use eyre::eyre;
+use std::{
+    collections::VecDeque,
+    num::NonZeroUsize,
+    sync::{mpsc, Arc, Mutex},
+    thread::Scope,
+};
+
+/// A file along with the blocks decoded from it.
+type Decoded<M, T> = (M, eyre::Result<Vec<T>>);
+
+/// A file queued for decoding, with where to send it once decoded.
+type Job<M, T> = (M, mpsc::SyncSender<Decoded<M, T>>);
+
+/// Decodes queued files on worker threads and hands them back in the order they were queued, so
+/// a single writer can commit them in order while later files are still being decoded.
+///
+/// At most one file per worker is queued, which bounds how many decoded files wait in memory.
+#[derive(Debug)]
+pub(crate) struct DecodeAhead<M, T> {
+    jobs: mpsc::Sender<Job<M, T>>,
+    queued: VecDeque<mpsc::Receiver<Decoded<M, T>>>,
+    capacity: usize,
+}
+
+impl<M: Send, T: Send> DecodeAhead<M, T> {
+    /// Spawns `threads` workers in `scope`, each decoding files with `decode`.
+    ///
+    /// Workers exit once `self` is dropped and the file at hand is decoded.
+    pub(crate) fn spawn<'scope, F>(
+        scope: &'scope Scope<'scope, '_>,
+        threads: NonZeroUsize,
+        decode: &'scope F,
+    ) -> Self
+    where
+        F: Fn(&M) -> eyre::Result<Vec<T>> + Sync,
+        M: 'scope,
+        T: 'scope,
+    {
+        let (jobs, receiver) = mpsc::channel::<Job<M, T>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..threads.get() {
+            let receiver = Arc::clone(&receiver);
+            scope.spawn(move || loop {
+                // The lock is only held while waiting for a job, so files are decoded in parallel.
+                let job =
+                    receiver.lock().expect("workers don't panic while waiting for jobs").recv();
+                let Ok((meta, done)) = job else { break };
+
+                let blocks = decode(&meta);
+                // The writer stops waiting for files once the import ends early.
+                let _ = done.send((meta, blocks));
+            });
+        }
+
+        Self { jobs, queued: VecDeque::new(), capacity: threads.get() }
+    }
+
+    /// Returns `true` if as many files are queued as there are workers.
+    pub(crate) fn is_full(&self) -> bool {
+        self.queued.len() >= self.capacity
+    }
+
+    /// Returns `true` if no file is queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Queues `meta` for decoding.
+    pub(crate) fn push(&mut self, meta: M) {
+        let (done, decoded) = mpsc::sync_channel(1);
+        // Workers only exit once `self` is dropped, so the job is always received.
+        let _ = self.jobs.send((meta, done));
+        self.queued.push_back(decoded);
+    }
+
+    /// Waits for the earliest queued file to be decoded, returning `None` if none is queued.
+    pub(crate) fn pop(&mut self) -> Option<eyre::Result<Decoded<M, T>>> {
+        let decoded = self.queued.pop_front()?;
+        Some(decoded.recv().map_err(|_| eyre!("ERA file decoding worker exited unexpectedly")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn returns_files_in_queued_order() {
+        // Earlier files take longer to decode, so they finish after later ones.
+        let decode = |file: &u64| {
+            thread::sleep(Duration::from_millis(10 * (4 - file)));
+            Ok::<_, eyre::Report>(vec![*file; *file as usize])
+        };
+
+        thread::scope(|scope| {
+            let mut ahead = DecodeAhead::spawn(scope, NonZeroUsize::new(3).unwrap(), &decode);
+            let mut decoded = Vec::new();
+            for file in 0..4 {
+                if ahead.is_full() {
+                    decoded.push(ahead.pop().unwrap().unwrap());
+                }
+                ahead.push(file);
+            }
+            while let Some(file) = ahead.pop() {
+                decoded.push(file.unwrap());
+            }
+
+            let decoded = decoded
+                .into_iter()
+                .map(|(file, blocks)| (file, blocks.unwrap()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                decoded,
+                (0..4).map(|file| (file, vec![file; file as usize])).collect::<Vec<_>>()
+            );
+        });
+    }
+}
@@ -0,0 +1,384 @@
+//! Light verification of the ethash proof-of-work seals of pre-merge headers.
+//!
+//! Follows the [ethash spec]: each seal is checked against the cache of its 30000-block epoch,
+//! computing the few dataset items it touches rather than the whole multi-gigabyte dataset.
+//!
+//! [ethash spec]: https://ethereum.org/en/developers/docs/consensus-mechanisms/pow/mining/mining-algorithms/ethash/
+
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{keccak256, BlockNumber, B256, U256};
+use alloy_rlp::Encodable;
+use eyre::eyre;
+use parking_lot::{const_mutex, Mutex};
+use sha3::{Digest, Keccak512};
+use std::{
+    hash::{BuildHasher, RandomState},
+    num::NonZeroU64,
+    sync::{Arc, OnceLock},
+};
+
+/// Blocks sharing a cache.
+const EPOCH_LENGTH: u64 = 30_000;
+/// Size of the cache of epoch zero, in bytes.
+const CACHE_BYTES_INIT: u64 = 1 << 24;
+/// Growth of the cache per epoch, in bytes.
+const CACHE_BYTES_GROWTH: u64 = 1 << 17;
+/// Size of the dataset of epoch zero, in bytes.
+const DATASET_BYTES_INIT: u64 = 1 << 30;
+/// Growth of the dataset per epoch, in bytes.
+const DATASET_BYTES_GROWTH: u64 = 1 << 23;
+/// Size of a cache node or dataset item, in bytes.
+const HASH_BYTES: u64 = 64;
+/// Size of the mix, in bytes.
+const MIX_BYTES: u64 = 128;
+/// 32-bit words in a cache node or dataset item.
+const HASH_WORDS: usize = 16;
+/// 32-bit words in the mix.
+const MIX_WORDS: usize = 32;
+/// Cache nodes mixed into each dataset item.
+const DATASET_PARENTS: u32 = 256;
+/// Rounds of the memory-hard pass over the cache.
+const CACHE_ROUNDS: usize = 3;
+/// Dataset rows read by each seal.
+const ACCESSES: u32 = 64;
+/// Prime of the FNV-like mixing function.
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// A cache node or dataset item, as little-endian words.
+type Node = [u32; HASH_WORDS];
+
+/// Which imported proof-of-work headers have their ethash seal checked with [`verify_seal`].
+///
+/// ERA1 files are otherwise trusted to hold the canonical chain on the strength of their
+/// accumulator roots and block hashes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SealVerification {
+    /// No seal is checked.
+    #[default]
+    Off,
+    /// About one header in every `n` is checked, picked at random so files can't be crafted around
+    /// the sample.
+    Sampled(NonZeroU64),
+    /// Every header is checked.
+    Full,
+}
+
+impl SealVerification {
+    /// Returns `true` if the seal of block `number` is checked, picking the sample with `picker`.
+    pub(crate) fn checks(self, picker: &RandomState, number: BlockNumber) -> bool {
+        match self {
+            Self::Off => false,
+            Self::Sampled(n) => picker.hash_one(number).is_multiple_of(n.get()),
+            Self::Full => true,
+        }
+    }
+}
+
+/// Checks that `header` carries a valid ethash seal: a mix digest and nonce that meet its
+/// difficulty.
+///
+/// Headers carrying zero difficulty, as proof-of-stake blocks do, and the genesis block aren't
+/// mined, so they pass. Checking a header of a new epoch first builds that epoch's cache, of 16 MiB
+/// and up, which takes a few seconds; only the last epoch's cache is kept.
+pub fn verify_seal<H: BlockHeader>(header: &H) -> eyre::Result<()> {
+    let number = header.number();
+    let difficulty = header.difficulty();
+    if difficulty.is_zero() || number == 0 {
+        return Ok(())
+    }
+
+    let (Some(mix_hash), Some(nonce)) = (header.mix_hash(), header.nonce()) else {
+        return Err(eyre!("block {number} carries no ethash seal"))
+    };
+
+    let epoch = number / EPOCH_LENGTH;
+    let slot = light_slot(epoch);
+    let light = slot.get_or_init(|| Light::new(epoch));
+    let (digest, result) = light.hashimoto(seal_hash(header), u64::from_be_bytes(nonce.0));
+    if digest != mix_hash {
+        return Err(eyre!(
+            "block {number} has mix hash {mix_hash}, but its ethash seal computes {digest}"
+        ))
+    }
+    if U256::from_be_bytes(result.0) > U256::MAX / difficulty {
+        return Err(eyre!("block {number} has an ethash seal short of its difficulty {difficulty}"))
+    }
+
+    Ok(())
+}
+
+/// Returns the slot holding the cache of `epoch`, replacing the last one unless it is for `epoch`.
+///
+/// Headers arrive in order, so keeping the last slot builds each cache once. The cache is built
+/// into the slot after the lock is released: callers checking another epoch don't wait for the
+/// build, and those checking the same one wait on the slot rather than building it again.
+fn light_slot(epoch: u64) -> Arc<OnceLock<Light>> {
+    static LAST: Mutex<Option<(u64, Arc<OnceLock<Light>>)>> = const_mutex(None);
+
+    let mut last = LAST.lock();
+    let slot = last
+        .take()
+        .filter(|(last_epoch, _)| *last_epoch == epoch)
+        .map_or_else(Arc::default, |(_, slot)| slot);
+    *last = Some((epoch, Arc::clone(&slot)));
+    slot
+}
+
+/// Hash of `header` without its seal, which the seal commits to.
+fn seal_hash<H: BlockHeader>(header: &H) -> B256 {
+    let parent_hash = header.parent_hash();
+    let ommers_hash = header.ommers_hash();
+    let beneficiary = header.beneficiary();
+    let state_root = header.state_root();
+    let transactions_root = header.transactions_root();
+    let receipts_root = header.receipts_root();
+    let logs_bloom = header.logs_bloom();
+    let difficulty = header.difficulty();
+    let number = header.number();
+    let gas_limit = header.gas_limit();
+    let gas_used = header.gas_used();
+    let timestamp = header.timestamp();
+    let base_fee = header.base_fee_per_gas();
+
+    let mut fields: Vec<&dyn Encodable> = vec![
+        &parent_hash,
+        &ommers_hash,
+        &beneficiary,
+        &state_root,
+        &transactions_root,
+        &receipts_root,
+        &logs_bloom,
+        &difficulty,
+        &number,
+        &gas_limit,
+        &gas_used,
+        &timestamp,
+        header.extra_data(),
+    ];
+    // Pre-merge London blocks carry a base fee too.
+    if let Some(base_fee) = &base_fee {
+        fields.push(base_fee);
+    }
+
+    let payload_length = fields.iter().map(|field| field.length()).sum();
+    let mut out = Vec::with_capacity(payload_length + alloy_rlp::length_of_length(payload_length));
+    alloy_rlp::Header { list: true, payload_length }.encode(&mut out);
+    for field in fields {
+        field.encode(&mut out);
+    }
+    keccak256(out)
+}
+
+/// The cache of an epoch, from which the dataset items a seal touches are computed.
+#[derive(Debug)]
+struct Light {
+    cache: Vec<Node>,
+    /// Size of the epoch's dataset, in bytes.
+    dataset_size: u64,
+}
+
+impl Light {
+    /// Builds the cache of `epoch`.
+    fn new(epoch: u64) -> Self {
+        let n = (cache_size(epoch) / HASH_BYTES) as usize;
+        let mut cache = Vec::with_capacity(n);
+        cache.push(keccak512(seed_hash(epoch).as_slice()));
+        for i in 1..n {
+            cache.push(hash_node(&cache[i - 1]));
+        }
+
+        for _ in 0..CACHE_ROUNDS {
+            for i in 0..n {
+                let other = &cache[cache[i][0] as usize % n];
+                let mut node = cache[(i + n - 1) % n];
+                for (word, other) in node.iter_mut().zip(other) {
+                    *word ^= other;
+                }
+                cache[i] = hash_node(&node);
+            }
+        }
+
+        Self { cache, dataset_size: dataset_size(epoch) }
+    }
+
+    /// Computes dataset item `index` from the cache.
+    fn dataset_item(&self, index: u32) -> Node {
+        let n = self.cache.len();
+        let mut mix = self.cache[index as usize % n];
+        mix[0] ^= index;
+        let mut mix = hash_node(&mix);
+
+        for parent in 0..DATASET_PARENTS {
+            let node = fnv(index ^ parent, mix[parent as usize % HASH_WORDS]) as usize % n;
+            for (word, parent) in mix.iter_mut().zip(&self.cache[node]) {
+                *word = fnv(*word, *parent);
+            }
+        }
+
+        hash_node(&mix)
+    }
+
+    /// Returns the mix digest and result of sealing `seal_hash` with `nonce`.
+    fn hashimoto(&self, seal_hash: B256, nonce: u64) -> (B256, B256) {
+        let rows = (self.dataset_size / MIX_BYTES) as u32;
+
+        let mut seed = [0; 40];
+        seed[..32].copy_from_slice(seal_hash.as_slice());
+        seed[32..].copy_from_slice(&nonce.to_le_bytes());
+        let seed = keccak512(&seed);
+
+        let mut mix = [0; MIX_WORDS];
+        mix[..HASH_WORDS].copy_from_slice(&seed);
+        mix[HASH_WORDS..].copy_from_slice(&seed);
+
+        for i in 0..ACCESSES {
+            let row = fnv(i ^ seed[0], mix[i as usize % MIX_WORDS]) % rows;
+            for (half, words) in mix.chunks_exact_mut(HASH_WORDS).enumerate() {
+                let item = self.dataset_item(2 * row + half as u32);
+                for (word, item) in words.iter_mut().zip(item) {
+                    *word = fnv(*word, item);
+                }
+            }
+        }
+
+        let mut digest = B256::ZERO;
+        for (bytes, words) in digest.chunks_exact_mut(4).zip(mix.chunks_exact(4)) {
+            let word = fnv(fnv(fnv(words[0], words[1]), words[2]), words[3]);
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+
+        let mut result = [0; 96];
+        result[..64].copy_from_slice(&node_bytes(&seed));
+        result[64..].copy_from_slice(digest.as_slice());
+        (digest, keccak256(result))
+    }
+}
+
+/// Size of the cache of `epoch`, in bytes.
+fn cache_size(epoch: u64) -> u64 {
+    let mut size = CACHE_BYTES_INIT + CACHE_BYTES_GROWTH * epoch - HASH_BYTES;
+    while !is_prime(size / HASH_BYTES) {
+        size -= 2 * HASH_BYTES;
+    }
+    size
+}
+
+/// Size of the dataset of `epoch`, in bytes.
+fn dataset_size(epoch: u64) -> u64 {
+    let mut size = DATASET_BYTES_INIT + DATASET_BYTES_GROWTH * epoch - MIX_BYTES;
+    while !is_prime(size / MIX_BYTES) {
+        size -= 2 * MIX_BYTES;
+    }
+    size
+}
+
+fn is_prime(n: u64) -> bool {
+    n >= 2 &&
+        (2..)
+            .take_while(|divisor| divisor * divisor <= n)
+            .all(|divisor| !n.is_multiple_of(divisor))
+}
+
+/// Seed of the cache of `epoch`, hashed once per epoch from zero.
+fn seed_hash(epoch: u64) -> B256 {
+    (0..epoch).fold(B256::ZERO, |seed, _| keccak256(seed))
+}
+
+const fn fnv(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(FNV_PRIME) ^ b
+}
+
+fn keccak512(data: &[u8]) -> Node {
+    let digest = Keccak512::digest(data);
+    let mut node = [0; HASH_WORDS];
+    for (word, bytes) in node.iter_mut().zip(digest.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("chunks are 4 bytes"));
+    }
+    node
+}
+
+fn hash_node(node: &Node) -> Node {
+    keccak512(&node_bytes(node))
+}
+
+fn node_bytes(node: &Node) -> [u8; HASH_BYTES as usize] {
+    let mut bytes = [0; HASH_BYTES as usize];
+    for (bytes, word) in bytes.chunks_exact_mut(4).zip(node) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Header, EMPTY_OMMER_ROOT_HASH, EMPTY_ROOT_HASH};
+    use alloy_primitives::{address, b256, bytes, B64};
+
+    #[test]
+    fn sizes_epochs_as_the_spec() {
+        assert_eq!(cache_size(0), 16_776_896);
+        assert_eq!(dataset_size(0), 1_073_739_904);
+        assert_eq!(cache_size(1), 16_907_456);
+        assert_eq!(dataset_size(1), 1_082_130_304);
+    }
+
+    #[test]
+    fn seeds_epochs_by_hashing() {
+        assert_eq!(seed_hash(0), B256::ZERO);
+        assert_eq!(
+            seed_hash(1),
+            b256!("0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")
+        );
+    }
+
+    #[test]
+    fn samples_seals() {
+        let picker = RandomState::new();
+        assert!(!(0..100).any(|number| SealVerification::Off.checks(&picker, number)));
+        assert!((0..100).all(|number| SealVerification::Full.checks(&picker, number)));
+
+        let sampled = SealVerification::Sampled(NonZeroU64::new(10).unwrap());
+        let checked = (0..10_000).filter(|&number| sampled.checks(&picker, number)).count();
+        assert!((500..1500).contains(&checked), "{checked}");
+    }
+
+    #[test]
+    fn passes_unmined_headers() {
+        verify_seal(&Header::default()).unwrap();
+        verify_seal(&Header { number: 1, ..Default::default() }).unwrap();
+    }
+
+    #[test]
+    fn verifies_mainnet_block_one() {
+        let header = Header {
+            parent_hash: b256!(
+                "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            ),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary: address!("0x05a56e2d52c817161883f50c441c3228cfe54d9f"),
+            state_root: b256!("0xd67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"),
+            transactions_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            difficulty: U256::from(17_171_480_576u64),
+            number: 1,
+            gas_limit: 5000,
+            timestamp: 1_438_269_988,
+            extra_data: bytes!("476574682f76312e302e302f6c696e75782f676f312e342e32"),
+            mix_hash: b256!("0x969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59"),
+            nonce: B64::new(0x539bd4979fef1ec4u64.to_be_bytes()),
+            ..Default::default()
+        };
+        assert_eq!(
+            header.hash_slow(),
+            b256!("0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6")
+        );
+        verify_seal(&header).unwrap();
+
+        let err =
+            verify_seal(&Header { nonce: B64::new(0x539bd4979fef1ec5u64.to_be_bytes()), ..header })
+                .unwrap_err()
+                .to_string();
+        assert!(err.contains("but its ethash seal computes"), "{err}");
+    }
+}
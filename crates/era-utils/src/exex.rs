@@ -0,0 +1,129 @@
+//! Execution extension archiving finalized history into ERA files.
+
+use crate::{save_archived_blocks, EraBlockWriter, ExportConfig, FinalizedExporter};
+use alloy_consensus::{BlockHeader, Sealable};
+use alloy_rlp::Encodable;
+use futures_util::TryStreamExt;
+use reth_exex::ExExContext;
+use reth_node_api::{FullNodeComponents, FullNodeTypes};
+use reth_primitives_traits::{Block, Receipt};
+use reth_storage_api::{
+    BlockReader, ChainStateBlockReader, ChainStateBlockWriter, DBProvider, DatabaseProviderFactory,
+    HeaderProvider, ReceiptProvider,
+};
+use tracing::{error, info, warn};
+
+/// The read-only database provider of `Node` that eras are exported from.
+type ArchiveProvider<Node> =
+    <<Node as FullNodeTypes>::Provider as DatabaseProviderFactory>::Provider;
+
+/// The read-write database provider of `Node` that archived blocks are recorded with.
+type RecordProvider<Node> =
+    <<Node as FullNodeTypes>::Provider as DatabaseProviderFactory>::ProviderRW;
+
+/// An ExEx archiving the finalized chain into files of the `W` format, one whole era at a time.
+///
+/// Every committed chain prompts a [`FinalizedExporter::poll`] against a fresh read-only database
+/// provider, so each era is written once it has finalized and been persisted. This keeps a hosted
+/// archive current and lets history older than the archive be expired from the node: archived
+/// blocks are recorded with [`save_archived_blocks`], which bodies and receipts pruning honour.
+///
+/// Archiving ends at the first failed export, such as a pre-merge format reaching the merge; the
+/// failure is logged and the ExEx keeps acknowledging notifications so it never holds the node
+/// back.
+pub struct EraArchiveExEx<Node: FullNodeComponents, W> {
+    ctx: ExExContext<Node>,
+    /// `None` once archiving has ended.
+    exporter: Option<FinalizedExporter<W>>,
+}
+
+impl<Node, W> EraArchiveExEx<Node, W>
+where
+    Node: FullNodeComponents,
+    W: EraBlockWriter,
+    ArchiveProvider<Node>: BlockReader + ChainStateBlockReader,
+    RecordProvider<Node>: ChainStateBlockReader + ChainStateBlockWriter,
+    <ArchiveProvider<Node> as HeaderProvider>::Header: BlockHeader + Sealable + Encodable,
+    <<ArchiveProvider<Node> as BlockReader>::Block as Block>::Body: Encodable,
+    <ArchiveProvider<Node> as ReceiptProvider>::Receipt: Receipt,
+{
+    /// Creates the ExEx, archiving from [`ExportConfig::first_block_number`] on.
+    ///
+    /// To resume an existing archive, start at the first era missing from
+    /// [`ExportConfig::dir`]. See [`FinalizedExporter::new`] for how `config` is applied.
+    pub fn new(ctx: ExExContext<Node>, config: ExportConfig) -> eyre::Result<Self> {
+        Ok(Self { ctx, exporter: Some(FinalizedExporter::new(config)?) })
+    }
+
+    /// Runs the ExEx until the notification stream ends.
+    pub async fn run(mut self) -> eyre::Result<()> {
+        while let Some(notification) = self.ctx.notifications.try_next().await? {
+            let Some(committed) = notification.committed_chain() else { continue };
+
+            self.archive();
+            self.ctx.send_finished_height(committed.tip().num_hash())?;
+        }
+
+        Ok(())
+    }
+
+    /// Exports every era finalized since the last call, ending archiving on failure.
+    fn archive(&mut self) {
+        let Some(exporter) = &mut self.exporter else { return };
+
+        let exported = self
+            .ctx
+            .provider()
+            .database_provider_ro()
+            .map_err(eyre::Report::from)
+            .and_then(|provider| tokio::task::block_in_place(|| exporter.poll(&provider)));
+
+        match exported {
+            Ok(files) => {
+                for file in &files {
+                    info!(target: "era::history::export", path = %file.display(), "Archived era");
+                }
+                if !files.is_empty() &&
+                    let Err(error) = self.record_archived()
+                {
+                    warn!(target: "era::history::export", %error, "Failed to record archived blocks");
+                }
+            }
+            Err(error) => {
+                error!(
+                    target: "era::history::export",
+                    %error,
+                    next_block = exporter.next_block(),
+                    "Archiving failed, no further eras will be exported"
+                );
+                self.exporter = None;
+            }
+        }
+    }
+}
+
+impl<Node: FullNodeComponents, W: EraBlockWriter> EraArchiveExEx<Node, W>
+where
+    RecordProvider<Node>: ChainStateBlockReader + ChainStateBlockWriter,
+{
+    /// Records every block exported so far as archived.
+    fn record_archived(&self) -> eyre::Result<()> {
+        let Some(blocks) = self.exporter.as_ref().and_then(FinalizedExporter::exported_blocks)
+        else {
+            return Ok(())
+        };
+
+        let provider = self.ctx.provider().database_provider_rw()?;
+        save_archived_blocks(&provider, blocks)?;
+        provider.commit()?;
+        Ok(())
+    }
+}
+
+impl<Node: FullNodeComponents, W: EraBlockWriter> std::fmt::Debug for EraArchiveExEx<Node, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EraArchiveExEx")
+            .field("next_block", &self.exporter.as_ref().map(FinalizedExporter::next_block))
+            .finish_non_exhaustive()
+    }
+}
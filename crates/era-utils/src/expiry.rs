@@ -0,0 +1,61 @@
+//! Records of the history archived to ERA files, which history expiry may drop from storage.
+
+use alloy_primitives::BlockNumber;
+use reth_storage_api::{ChainStateBlockReader, ChainStateBlockWriter};
+use std::ops::RangeInclusive;
+use tracing::{info, warn};
+
+/// Records `blocks` as durably archived, so that bodies and receipts pruning may drop them from
+/// storage, see [`ChainStateBlockReader::last_archived_block_number`].
+///
+/// Only history contiguous from genesis, which is rebuilt from the chain spec, counts as archived:
+/// the record advances to the end of `blocks` if they start no later than the block after it, and
+/// is left as is past a gap.
+///
+/// Returns the last archived block afterwards.
+pub fn save_archived_blocks<P>(
+    provider: &P,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Option<BlockNumber>>
+where
+    P: ChainStateBlockReader + ChainStateBlockWriter,
+{
+    let archived = provider.last_archived_block_number()?;
+    let next = archived.map_or(1, |last| last + 1);
+    if blocks.is_empty() || *blocks.end() < next {
+        return Ok(archived)
+    }
+    if *blocks.start() > next {
+        warn!(
+            target: "era::history",
+            first = blocks.start(),
+            missing = next,
+            "Not recording archived blocks past a gap in the archive"
+        );
+        return Ok(archived)
+    }
+
+    provider.save_archived_block_number(*blocks.end())?;
+    info!(target: "era::history", last = blocks.end(), "Recorded archived history");
+    Ok(Some(*blocks.end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::{test_utils::create_test_provider_factory, DatabaseProviderFactory};
+
+    #[test]
+    fn record_only_advances_over_contiguous_history() {
+        let pf = create_test_provider_factory();
+        let provider = pf.database_provider_rw().unwrap();
+
+        assert_eq!(save_archived_blocks(&provider, 0..=8191).unwrap(), Some(8191));
+        // Already archived.
+        assert_eq!(save_archived_blocks(&provider, 0..=99).unwrap(), Some(8191));
+        // Leaves a gap after the record.
+        assert_eq!(save_archived_blocks(&provider, 16384..=24575).unwrap(), Some(8191));
+        assert_eq!(save_archived_blocks(&provider, 8192..=16383).unwrap(), Some(16383));
+        assert_eq!(provider.last_archived_block_number().unwrap(), Some(16383));
+    }
+}
@@ -0,0 +1,107 @@
+//! Checksums the export manifest lists exported files with.
+
+use super::listing::file_checksum;
+use alloy_primitives::B256;
+use eyre::{eyre, Result};
+use reth_fs_util as fs;
+use std::{fmt, io::Read, path::Path, str::FromStr};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Prefix of XXH3 checksums in the export manifest. SHA-256 checksums carry none, as manifests
+/// listed only those before.
+const XXH3_PREFIX: &str = "xxh3:";
+
+/// Hash an export lists its files in the manifest with, to tell complete files from partial ones
+/// on resume.
+///
+/// The `checksums.txt` written along with a listing always uses SHA-256, as the era downloader
+/// expects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    /// SHA-256.
+    #[default]
+    Sha256,
+    /// 128-bit XXH3, many times faster than SHA-256 but not collision resistant, so it only guards
+    /// against damaged files, not tampered ones. Suits exports imported by the same operator.
+    Xxh3,
+}
+
+/// Checksum of an exported file, as listed in the export manifest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Checksum {
+    Sha256(B256),
+    Xxh3(u128),
+}
+
+impl Checksum {
+    /// Hashes the file at `path` with `algorithm`.
+    pub(crate) fn of_file(path: &Path, algorithm: ChecksumAlgorithm) -> Result<Self> {
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => file_checksum(path).map(Self::Sha256),
+            ChecksumAlgorithm::Xxh3 => {
+                let mut file = fs::open(path)?;
+                let mut hasher = Xxh3::new();
+                let mut buf = vec![0; 1 << 16];
+                loop {
+                    match file.read(&mut buf)? {
+                        0 => break,
+                        read => hasher.update(&buf[..read]),
+                    }
+                }
+                Ok(Self::Xxh3(hasher.digest128()))
+            }
+        }
+    }
+
+    /// Returns the hash this checksum was taken with.
+    pub(crate) const fn algorithm(&self) -> ChecksumAlgorithm {
+        match self {
+            Self::Sha256(_) => ChecksumAlgorithm::Sha256,
+            Self::Xxh3(_) => ChecksumAlgorithm::Xxh3,
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256(checksum) => write!(f, "{checksum}"),
+            Self::Xxh3(checksum) => write!(f, "{XXH3_PREFIX}{checksum:032x}"),
+        }
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_prefix(XXH3_PREFIX) {
+            Some(hex) if hex.len() == 32 => Ok(Self::Xxh3(u128::from_str_radix(hex, 16)?)),
+            Some(_) => Err(eyre!("XXH3 checksum {s} is not 32 hex digits long")),
+            None => Ok(Self::Sha256(s.parse()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn checksums_roundtrip_through_the_manifest_format() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("chunk.era1");
+        fs::write(&file, [7u8; 100_000]).unwrap();
+
+        for algorithm in [ChecksumAlgorithm::Sha256, ChecksumAlgorithm::Xxh3] {
+            let checksum = Checksum::of_file(&file, algorithm).unwrap();
+            assert_eq!(checksum.algorithm(), algorithm);
+            assert_eq!(checksum.to_string().parse::<Checksum>().unwrap(), checksum);
+        }
+
+        let xxh3 = Checksum::of_file(&file, ChecksumAlgorithm::Xxh3).unwrap();
+        assert_eq!(xxh3, Checksum::Xxh3(xxhash_rust::xxh3::xxh3_128(&[7u8; 100_000])));
+        assert!("xxh3:1234".parse::<Checksum>().is_err());
+    }
+}
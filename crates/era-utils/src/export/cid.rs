@@ -0,0 +1,205 @@
+//! IPFS content identifiers of exported files, for distributing exports over content-addressed
+//! networks.
+//!
+//! A file's CID is the one `ipfs add --cid-version 1` gives it: the file is cut into 256 KiB raw
+//! leaves, which are linked by a balanced tree of UnixFS `dag-pb` nodes of at most 174 links each.
+//! A file that fits in a single leaf is identified by that leaf.
+
+use eyre::{eyre, Result};
+use reth_era::common::file_ops::EraFileType;
+use reth_fs_util as fs;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Name of the file mapping each exported file to its CID.
+const CIDS_FILE: &str = "cids.txt";
+
+/// Size of the leaves files are cut into.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Most links a tree node holds.
+const MAX_LINKS: usize = 174;
+
+/// Multicodec of raw leaves.
+const RAW: u8 = 0x55;
+
+/// Multicodec of tree nodes.
+const DAG_PB: u8 = 0x70;
+
+/// Multihash prefix of a SHA-256 digest.
+const SHA2_256: [u8; 2] = [0x12, 0x20];
+
+/// A leaf or tree node: its binary CID, its size with everything it links to and the number of
+/// file bytes under it.
+#[derive(Debug)]
+struct Block {
+    cid: Vec<u8>,
+    total_size: u64,
+    file_size: u64,
+}
+
+/// Returns the CIDv1 of the file at `path`, encoded in base32 as IPFS prints it.
+pub fn file_cid(path: impl AsRef<Path>) -> Result<String> {
+    let mut reader = BufReader::new(fs::open(path)?);
+    let mut blocks = Vec::new();
+    loop {
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        (&mut reader).take(CHUNK_SIZE as u64).read_to_end(&mut chunk)?;
+        if chunk.is_empty() && !blocks.is_empty() {
+            break
+        }
+        let done = chunk.len() < CHUNK_SIZE;
+        blocks.push(Block {
+            cid: cid(RAW, &chunk),
+            total_size: chunk.len() as u64,
+            file_size: chunk.len() as u64,
+        });
+        if done {
+            break
+        }
+    }
+
+    while blocks.len() > 1 {
+        blocks = blocks.chunks(MAX_LINKS).map(node).collect();
+    }
+    Ok(base32(&blocks.pop().expect("a file has at least one leaf").cid))
+}
+
+/// Writes a `cids.txt` to `dir` mapping each of its `file_type` files to its [`file_cid`],
+/// returning its path.
+///
+/// Each line holds a file's era number, CID and name, separated by spaces, in file name order.
+/// The file is rewritten from whatever is in `dir`, so files left by earlier exports are included.
+pub fn write_cids(dir: impl AsRef<Path>, file_type: EraFileType) -> Result<PathBuf> {
+    let dir = dir.as_ref();
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(name) = name.to_str() &&
+            EraFileType::from_filename(name) == Some(file_type)
+        {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+
+    let mut cids = String::new();
+    for name in names {
+        let era = name
+            .split('-')
+            .nth(1)
+            .and_then(|era| u64::from_str(era).ok())
+            .ok_or_else(|| eyre!("No era number in the name of {name}"))?;
+        let cid = file_cid(dir.join(&name))?;
+        writeln!(cids, "{era} {cid} {name}")?;
+    }
+
+    let path = dir.join(CIDS_FILE);
+    fs::write(&path, cids)?;
+    Ok(path)
+}
+
+/// Builds the UnixFS file node linking `children`.
+fn node(children: &[Block]) -> Block {
+    let file_size = children.iter().map(|child| child.file_size).sum();
+
+    // UnixFS `Data`: type `File`, file size and the file size under each link.
+    let mut data = vec![0x08, 0x02, 0x18];
+    varint(&mut data, file_size);
+    for child in children {
+        data.push(0x20);
+        varint(&mut data, child.file_size);
+    }
+
+    // `PBNode`: links, each with its CID, an empty name and total size, then the data.
+    let mut encoded = Vec::new();
+    for child in children {
+        let mut link = vec![0x0a];
+        varint(&mut link, child.cid.len() as u64);
+        link.extend_from_slice(&child.cid);
+        link.extend_from_slice(&[0x12, 0x00, 0x18]);
+        varint(&mut link, child.total_size);
+
+        encoded.push(0x12);
+        varint(&mut encoded, link.len() as u64);
+        encoded.extend_from_slice(&link);
+    }
+    encoded.push(0x0a);
+    varint(&mut encoded, data.len() as u64);
+    encoded.extend_from_slice(&data);
+
+    Block {
+        cid: cid(DAG_PB, &encoded),
+        total_size: encoded.len() as u64 +
+            children.iter().map(|child| child.total_size).sum::<u64>(),
+        file_size,
+    }
+}
+
+/// Returns the binary CIDv1 of `bytes` encoded with `codec`, hashed with SHA-256.
+fn cid(codec: u8, bytes: &[u8]) -> Vec<u8> {
+    let mut cid = vec![0x01, codec];
+    cid.extend_from_slice(&SHA2_256);
+    cid.extend_from_slice(&Sha256::digest(bytes));
+    cid
+}
+
+/// Appends `value` as an unsigned LEB128 varint, as protobuf and multiformats encode integers.
+fn varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Encodes `bytes` in lowercase, unpadded base32 with the `b` multibase prefix.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+    let mut encoded = String::from("b");
+    let (mut buffer, mut bits) = (0u16, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn computes_cids_as_ipfs_does() {
+        let dir = tempdir().unwrap();
+        let empty = dir.path().join("empty");
+        fs::write(&empty, []).unwrap();
+        assert_eq!(
+            file_cid(&empty).unwrap(),
+            "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+        );
+
+        let name = "mainnet-00003-00000000.era1";
+        fs::write(dir.path().join(name), vec![7; CHUNK_SIZE * 2 + 1]).unwrap();
+        // Three leaves under a single `dag-pb` node
+        let cid = file_cid(dir.path().join(name)).unwrap();
+        assert_eq!(cid, "bafybeiam4zmyknj3wiatg2raurbn3jqmb7ox4hhxufwb3zqplpvb5gapr4");
+
+        let cids = fs::read_to_string(write_cids(dir.path(), EraFileType::Era1).unwrap()).unwrap();
+        assert_eq!(cids, format!("3 {cid} {name}\n"));
+    }
+}
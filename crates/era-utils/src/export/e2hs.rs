@@ -0,0 +1,508 @@
+//! `.e2hs` block-history writer.
+//!
+//! `e2hs` is the history format consumed by Portal Network clients. Each block is stored as a
+//! header with its proof, its body and its receipts, followed by a block index:
+//!
+//! `Version | (HeaderWithProof | Body | Receipts)* | BlockIndex`
+//!
+//! Only pre-merge proofs, against the epoch's historical hashes accumulator, can be built from
+//! reth storage; post-merge proofs need beacon state the node does not keep.
+//!
+//! The same proofs are built for the blocks of an `.era1` file by [`headers_with_proofs`], for
+//! bridges feeding headers into the Portal Network, and by [`inclusion_proofs`] as bare SSZ Merkle
+//! branches of block hashes, for light clients that only need to trust an epoch's root.
+
+use super::{ChunkAccumulator, EraBlockWriter, ExportBlock, ExportConfig};
+use alloy_consensus::{BlockHeader, Header as ConsensusHeader, TxReceipt};
+use alloy_primitives::{keccak256, BlockNumber, B256, U256};
+use alloy_rlp::Encodable;
+use eyre::{eyre, Result};
+use reth_era::{
+    common::{
+        compression::snappy_compress,
+        file_ops::{EraFileId, EraFileType, FileReader},
+    },
+    e2s::{
+        file::E2StoreWriter,
+        types::{Entry, Header, IndexEntry},
+    },
+    era1::{
+        file::{Era1File, Era1Reader},
+        types::{
+            execution::{
+                Accumulator, CompressedBody, CompressedReceipts, COMPRESSED_HEADER,
+                MAX_BLOCKS_PER_ERA1,
+            },
+            group::{BlockIndex, Era1Id},
+        },
+    },
+};
+use reth_primitives_traits::Receipt;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Blocks per epoch, the span of one historical hashes accumulator.
+const EPOCH_SIZE: usize = MAX_BLOCKS_PER_ERA1;
+
+/// Depth of the merkle tree over an epoch's header records.
+const EPOCH_TREE_DEPTH: usize = EPOCH_SIZE.ilog2() as usize;
+
+/// Length of a `BlockProofHistoricalHashesAccumulator`: the total-difficulty sibling within the
+/// header record, one sibling per tree level, and the list length mixed into the root.
+const ACCUMULATOR_PROOF_LEN: usize = 1 + EPOCH_TREE_DEPTH + 1;
+
+/// A pre-merge block header with the proof of its hash against the historical hashes accumulator
+/// of its epoch, as Portal Network history clients exchange it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderWithProof {
+    /// The block header.
+    pub header: ConsensusHeader,
+    /// The `BlockProofHistoricalHashesAccumulator`, from the leaf up: the block's total
+    /// difficulty, the sibling at each level of the epoch's tree, then the epoch's block count.
+    pub proof: [B256; ACCUMULATOR_PROOF_LEN],
+}
+
+impl HeaderWithProof {
+    /// SSZ-encodes the `HeaderWithProof` container, with the header RLP-encoded, as Portal Network
+    /// content and `.e2hs` files hold it.
+    pub fn encode(&self) -> Vec<u8> {
+        encode_header_with_proof(&alloy_rlp::encode(&self.header), &self.proof)
+    }
+
+    /// Returns `true` if the proof resolves the header's hash to `accumulator_root`, the root of
+    /// its epoch's accumulator.
+    pub fn verify(&self, accumulator_root: B256) -> bool {
+        self.inclusion_proof().verify(accumulator_root)
+    }
+
+    /// Returns the proof of the header's hash alone.
+    pub fn inclusion_proof(&self) -> HeaderInclusionProof {
+        HeaderInclusionProof {
+            number: self.header.number,
+            block_hash: self.header.hash_slow(),
+            branch: self.proof,
+        }
+    }
+}
+
+/// Proof that block `number` with hash `block_hash` is part of the historical hashes accumulator of
+/// its epoch, as the SSZ Merkle branch of the hash within the accumulator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderInclusionProof {
+    /// The block number, which places the hash within its epoch.
+    pub number: BlockNumber,
+    /// The block hash being proven.
+    pub block_hash: B256,
+    /// Sibling nodes from the hash up to the accumulator root, the same nodes as
+    /// [`HeaderWithProof::proof`].
+    pub branch: [B256; ACCUMULATOR_PROOF_LEN],
+}
+
+impl HeaderInclusionProof {
+    /// Returns the SSZ generalized index of the block hash within its epoch's accumulator, a
+    /// `List[HeaderRecord, 8192]` whose records hold the block hash then the total difficulty.
+    pub const fn generalized_index(&self) -> u64 {
+        // Records hang below the list's data root, itself the left child of the root, and the
+        // hash is the left field of its record.
+        (2 * EPOCH_SIZE as u64 + self.number % EPOCH_SIZE as u64) * 2
+    }
+
+    /// Returns `true` if the branch resolves the block hash to `accumulator_root`, the root of its
+    /// epoch's accumulator.
+    pub fn verify(&self, accumulator_root: B256) -> bool {
+        is_valid_merkle_branch(
+            self.block_hash,
+            &self.branch,
+            self.generalized_index(),
+            accumulator_root,
+        )
+    }
+}
+
+/// Returns `true` if `branch`, the siblings from `leaf` up, resolves `leaf` at generalized index
+/// `index` to `root`, as the consensus specs' `is_valid_merkle_branch` checks.
+pub fn is_valid_merkle_branch(leaf: B256, branch: &[B256], index: u64, root: B256) -> bool {
+    if index >> branch.len() != 1 {
+        return false
+    }
+    let node = branch.iter().enumerate().fold(leaf, |node, (depth, sibling)| {
+        if (index >> depth) & 1 == 0 {
+            sha256_pair(node, *sibling)
+        } else {
+            sha256_pair(*sibling, node)
+        }
+    });
+    node == root
+}
+
+/// Builds the [`HeaderWithProof`] of each block of the `.era1` file at `file`, in block order.
+///
+/// Proofs are taken against the accumulator of the file's blocks, so the file must start on an
+/// epoch boundary, and its blocks must hash to the accumulator it holds. Only a file spanning a
+/// whole epoch, or the merge epoch up to its last pre-merge block, is proven against the epoch's
+/// canonical accumulator; the accumulators of `mainnet` files are checked against the published
+/// roots.
+pub fn headers_with_proofs(file: impl AsRef<Path>) -> Result<Vec<HeaderWithProof>> {
+    let (era1, proofs) = prove_era1(file.as_ref())?;
+    era1.group
+        .blocks
+        .iter()
+        .zip(proofs)
+        .map(|(block, proof)| {
+            Ok(HeaderWithProof { header: block.header.decode_header()?, proof: proof.branch })
+        })
+        .collect()
+}
+
+/// Builds the [`HeaderWithProof`] of block `number`, held by the `.era1` file at `file`, as
+/// [`headers_with_proofs`] does.
+pub fn header_with_proof(file: impl AsRef<Path>, number: BlockNumber) -> Result<HeaderWithProof> {
+    let file = file.as_ref();
+    headers_with_proofs(file)?
+        .into_iter()
+        .find(|header| header.header.number == number)
+        .ok_or_else(|| eyre!("{} does not hold block {number}", file.display()))
+}
+
+/// Builds the [`HeaderInclusionProof`] of each block of the `.era1` file at `file`, in block
+/// order, without decoding the headers.
+///
+/// The file is checked as [`headers_with_proofs`] checks it.
+pub fn inclusion_proofs(file: impl AsRef<Path>) -> Result<Vec<HeaderInclusionProof>> {
+    Ok(prove_era1(file.as_ref())?.1)
+}
+
+/// Reads the `.era1` file at `file`, checks its accumulator, and returns it with the inclusion
+/// proof of each of its blocks.
+fn prove_era1(file: &Path) -> Result<(Era1File, Vec<HeaderInclusionProof>)> {
+    let era1 = Era1Reader::open(file, "")?;
+    let first_block = *era1.block_range().start();
+    if !first_block.is_multiple_of(EPOCH_SIZE as u64) {
+        return Err(eyre!(
+            "{} starts at block {first_block}, not on an epoch boundary",
+            file.display()
+        ))
+    }
+
+    let records = era1
+        .group
+        .blocks
+        .iter()
+        .map(|block| Ok((keccak256(block.header.decompress()?), block.total_difficulty.value)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let root = Accumulator::from_pairs(&records)?.root;
+    if root != era1.group.accumulator.root {
+        return Err(eyre!(
+            "{} holds accumulator {}, but its blocks hash to {root}",
+            file.display(),
+            era1.group.accumulator.root
+        ))
+    }
+    let network = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('-').next())
+        .unwrap_or_default();
+    super::mainnet::check_accumulator_root(network, first_block, records.len(), root)?;
+
+    let proofs = era1
+        .block_range()
+        .zip(&records)
+        .zip(accumulator_proofs(&records))
+        .map(|((number, (block_hash, _)), branch)| HeaderInclusionProof {
+            number,
+            block_hash: *block_hash,
+            branch,
+        })
+        .collect();
+    Ok((era1, proofs))
+}
+
+/// Writes `.e2hs` files.
+///
+/// Files span whole pre-merge epochs, since every proof is taken against the accumulator of the
+/// epoch its block belongs to. The epoch holding the merge is cut at its last pre-merge block.
+#[derive(Debug)]
+pub struct E2hs;
+
+impl EraBlockWriter for E2hs {
+    /// The era downloader doesn't fetch `e2hs` files.
+    const FILE_TYPE: Option<EraFileType> = None;
+
+    fn write_file<H, B, R>(
+        config: &ExportConfig,
+        blocks: &[ExportBlock<H, B, R>],
+    ) -> Result<PathBuf>
+    where
+        H: BlockHeader + Encodable + Sync,
+        B: Encodable + Sync,
+        R: Receipt,
+    {
+        if !config.profile.has_receipts() {
+            return Err(eyre!(
+                "e2hs files always carry receipts, export blocks-only archives as ere instead"
+            ));
+        }
+        let first_block = blocks[0].header.number();
+        if !first_block.is_multiple_of(EPOCH_SIZE as u64) {
+            return Err(eyre!(
+                "e2hs files must start on an epoch boundary, got block {first_block}"
+            ));
+        }
+
+        let pre_merge_count = super::pre_merge_len(config, blocks)?;
+        if pre_merge_count == 0 {
+            return Err(eyre!(
+                "Block {first_block} is post-merge, e2hs proofs for it need beacon state reth does \
+                 not store"
+            ));
+        }
+        if pre_merge_count == blocks.len() && blocks.len() < EPOCH_SIZE {
+            return Err(eyre!(
+                "e2hs files must span a whole epoch, got {} blocks from block {first_block}",
+                blocks.len()
+            ));
+        }
+        if pre_merge_count < blocks.len() {
+            warn!(
+                target: "era::history::export",
+                "Leaving {} post-merge blocks out of the e2hs file starting at block {first_block}",
+                blocks.len() - pre_merge_count
+            );
+        }
+        let blocks = &blocks[..pre_merge_count];
+
+        let records = blocks.iter().map(ExportBlock::header_record).collect::<Vec<_>>();
+        let accumulator = Accumulator::from_pairs(&records)?;
+        super::mainnet::check_accumulator_root(
+            &config.network,
+            first_block,
+            blocks.len(),
+            accumulator.root,
+        )?;
+        let proofs = accumulator_proofs(&records);
+
+        let id = Era1Id::new(&config.network, first_block, blocks.len() as u32)
+            .with_hash(super::short_hash(accumulator.root));
+        let file_path = config.dir.join(file_name(config, &id));
+
+        let entries = config
+            .tuning
+            .compress_all(&blocks.iter().zip(&proofs).collect::<Vec<_>>(), |(block, proof)| {
+                block_entries(block, proof)
+            })?;
+
+        let mut writer = E2StoreWriter::with_version(config.tuning.create_file(&file_path)?)?;
+        let mut offsets = Vec::<i64>::with_capacity(blocks.len());
+        let mut position = Header::SIZE as i64; // past the leading version record
+        for block_records in &entries {
+            offsets.push(position);
+            for entry in block_records {
+                position += entry.size() as i64;
+                writer.write_entry(entry)?;
+            }
+        }
+
+        // Offsets are relative to the block-index record, which follows the last block.
+        let relative = offsets.iter().map(|&abs| abs - position).collect();
+        writer.write_entry(&BlockIndex::new(first_block, relative).to_entry())?;
+        writer.flush()?;
+
+        Ok(file_path)
+    }
+}
+
+/// Renders the file name, which follows the `era1` scheme with its own extension.
+fn file_name(config: &ExportConfig, id: &Era1Id) -> String {
+    let name = config.naming.file_name(id);
+    let stem = name.strip_suffix(Era1Id::FILE_TYPE.extension()).unwrap_or(&name);
+    format!("{stem}.e2hs")
+}
+
+/// Encodes one block as its header-with-proof, body and receipts records.
+fn block_entries<H, B, R>(
+    block: &ExportBlock<H, B, R>,
+    proof: &[B256; ACCUMULATOR_PROOF_LEN],
+) -> Result<[Entry; 3]>
+where
+    H: BlockHeader + Encodable,
+    B: Encodable,
+    R: Receipt,
+{
+    let header =
+        snappy_compress(&encode_header_with_proof(&alloy_rlp::encode(&block.header), proof))?;
+    let body = CompressedBody::from_body(&block.body)?;
+    let receipts_with_bloom: Vec<_> =
+        block.receipts.iter().map(|r| TxReceipt::with_bloom_ref(r)).collect();
+    let receipts = CompressedReceipts::from_encodable_list(&receipts_with_bloom)
+        .map_err(|e| eyre!("Failed to compress receipts: {e}"))?;
+
+    Ok([Entry::new(COMPRESSED_HEADER, header), body.to_entry(), receipts.to_entry()])
+}
+
+/// SSZ-encodes the `HeaderWithProof` container: `{ header: ByteList, proof: ByteList }`, where
+/// `proof` is the ssz `Vector[Bytes32, 15]` of a `BlockProofHistoricalHashesAccumulator`.
+fn encode_header_with_proof(header_rlp: &[u8], proof: &[B256; ACCUMULATOR_PROOF_LEN]) -> Vec<u8> {
+    // Both fields are variable-size, so the fixed part holds their two 4-byte offsets.
+    const FIXED_LEN: u32 = 8;
+    let mut out = Vec::with_capacity(FIXED_LEN as usize + header_rlp.len() + proof.len() * 32);
+    out.extend_from_slice(&FIXED_LEN.to_le_bytes());
+    out.extend_from_slice(&(FIXED_LEN + header_rlp.len() as u32).to_le_bytes());
+    out.extend_from_slice(header_rlp);
+    for node in proof {
+        out.extend_from_slice(node.as_slice());
+    }
+    out
+}
+
+/// Builds the proof of each record's block hash against the accumulator of `records`, the
+/// `hash_tree_root` of `List[HeaderRecord, 8192]`.
+///
+/// Proofs run from the leaf up: the record's total difficulty, the sibling at each tree level, then
+/// the list length.
+fn accumulator_proofs(records: &[(B256, U256)]) -> Vec<[B256; ACCUMULATOR_PROOF_LEN]> {
+    let mut levels = Vec::with_capacity(EPOCH_TREE_DEPTH);
+    let mut level = records
+        .iter()
+        .map(|(block_hash, total_difficulty)| {
+            sha256_pair(*block_hash, B256::from(total_difficulty.to_le_bytes::<32>()))
+        })
+        .collect::<Vec<_>>();
+    level.resize(EPOCH_SIZE, B256::ZERO);
+    while level.len() > 1 {
+        let parent = level.chunks_exact(2).map(|pair| sha256_pair(pair[0], pair[1])).collect();
+        levels.push(std::mem::replace(&mut level, parent));
+    }
+
+    let mut length = B256::ZERO;
+    length[..8].copy_from_slice(&(records.len() as u64).to_le_bytes());
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(index, (_, total_difficulty))| {
+            let mut proof = [B256::ZERO; ACCUMULATOR_PROOF_LEN];
+            proof[0] = B256::from(total_difficulty.to_le_bytes::<32>());
+            for (depth, level) in levels.iter().enumerate() {
+                proof[1 + depth] = level[(index >> depth) ^ 1];
+            }
+            proof[ACCUMULATOR_PROOF_LEN - 1] = length;
+            proof
+        })
+        .collect()
+}
+
+fn sha256_pair(left: B256, right: B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from(<[u8; 32]>::from(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_era::e2s::file::E2StoreReader;
+    use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
+    use tempfile::tempdir;
+
+    #[test]
+    fn proofs_resolve_to_accumulator_root() {
+        let records = (0..5u64)
+            .map(|i| (B256::repeat_byte(i as u8 + 1), U256::from(i * 1_000 + 7)))
+            .collect::<Vec<_>>();
+        let root = Accumulator::from_pairs(&records).unwrap().root;
+
+        for (index, (block_hash, _)) in records.iter().enumerate() {
+            let proof = accumulator_proofs(&records)[index];
+            let mut node = sha256_pair(*block_hash, proof[0]);
+            for (depth, sibling) in proof[1..=EPOCH_TREE_DEPTH].iter().enumerate() {
+                node = if (index >> depth) & 1 == 0 {
+                    sha256_pair(node, *sibling)
+                } else {
+                    sha256_pair(*sibling, node)
+                };
+            }
+            assert_eq!(sha256_pair(node, proof[ACCUMULATOR_PROOF_LEN - 1]), root);
+        }
+    }
+
+    #[test]
+    fn merge_epoch_is_cut_at_last_pre_merge_block() {
+        let blocks = (0..4u64)
+            .map(|number| ExportBlock::<_, _, EthReceipt> {
+                header: ConsensusHeader {
+                    number,
+                    difficulty: U256::from(u64::from(number < 3)),
+                    ..Default::default()
+                },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::from(number.min(2) + 1),
+            })
+            .collect::<Vec<_>>();
+        let dir = tempdir().unwrap();
+        let config = ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() };
+
+        let path = E2hs::write_file(&config, &blocks).unwrap();
+        assert!(path.to_str().unwrap().ends_with(".e2hs"));
+
+        let entries = E2StoreReader::new(std::fs::File::open(path).unwrap()).entries().unwrap();
+        // Version, three records for each of the three pre-merge blocks, then the index.
+        assert_eq!(entries.len(), 1 + 3 * 3 + 1);
+        assert_eq!(entries.last().unwrap().entry_type, BlockIndex::entry_type());
+
+        // A pre-merge chunk short of a whole epoch can't be proven.
+        assert!(E2hs::write_file(&config, &blocks[..2]).is_err());
+    }
+
+    #[test]
+    fn proves_headers_of_era1_files() {
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..4u64)
+            .map(|number| {
+                let header = ConsensusHeader {
+                    number,
+                    parent_hash,
+                    difficulty: U256::from(10),
+                    ..Default::default()
+                };
+                parent_hash = header.hash_slow();
+                ExportBlock::<_, BlockBody, EthReceipt> {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody::default(),
+                    receipts: Vec::new(),
+                    total_difficulty: U256::from((number + 1) * 10),
+                }
+            })
+            .collect::<Vec<_>>();
+        let dir = tempdir().unwrap();
+        let config = ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() };
+        let file = crate::Era1::write_file(&config, &blocks).unwrap();
+        let root = Era1Reader::open(&file, "").unwrap().group.accumulator.root;
+
+        let proven = headers_with_proofs(&file).unwrap();
+        assert_eq!(proven.len(), 4);
+        assert!(proven.iter().all(|header| header.verify(root)));
+        assert!(!proven[1].verify(B256::repeat_byte(1)));
+
+        let third = header_with_proof(&file, 2).unwrap();
+        assert_eq!(third, proven[2]);
+        assert_eq!(third.encode()[..4], 8u32.to_le_bytes());
+        assert!(header_with_proof(&file, 4).is_err());
+
+        let inclusion = inclusion_proofs(&file).unwrap();
+        assert_eq!(inclusion[2], third.inclusion_proof());
+        assert_eq!(inclusion[2].generalized_index(), 2 * (2 * 8192 + 2));
+        assert!(inclusion.iter().all(|proof| proof.verify(root)));
+        let forged =
+            HeaderInclusionProof { block_hash: B256::repeat_byte(2), ..inclusion[2].clone() };
+        assert!(!forged.verify(root));
+        let misplaced = HeaderInclusionProof { number: 3, ..inclusion[2].clone() };
+        assert!(!misplaced.verify(root));
+    }
+}
@@ -0,0 +1,310 @@
+//! Encryption at rest of exported files, for archives kept on shared or third-party storage.
+//!
+//! An encrypted file replaces the file it was written as, under the same name with an `.enc`
+//! suffix, next to a key descriptor with an `.enc.json` suffix telling how to decrypt it:
+//!
+//! ```json
+//! {
+//!   "algorithm": "aes-256-gcm-stream",
+//!   "key_id": "3f2a9c01d4e5b687",
+//!   "nonce_prefix": "0x…",
+//!   "chunk_size": 1048576,
+//!   "name": "mainnet-00000-5ec1ffb8.era1",
+//!   "size": 1203520
+//! }
+//! ```
+//!
+//! The file is encrypted with AES-256-GCM in chunks of `chunk_size` bytes, each with its own nonce:
+//! the descriptor's 7-byte random prefix, the chunk's index as a big-endian `u32`, and a byte set
+//! to 1 for the last chunk only, so chunks can't be reordered or the file truncated unnoticed.
+//! Every chunk is authenticated along with the file's `name`, so an encrypted file can't pass for
+//! another. The key itself is never written: `key_id`, the first 8 bytes of its SHA-256 hash,
+//! tells which key decrypts the file.
+
+use aes_gcm::{
+    aead::{AeadInPlace, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use alloy_primitives::hex;
+use eyre::{eyre, Result, WrapErr};
+use reth_fs_util as fs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Algorithm key descriptors name the encryption with.
+const ALGORITHM: &str = "aes-256-gcm-stream";
+
+/// Bytes of plaintext encrypted under each nonce.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Largest chunk size accepted from key descriptors, bounding the memory decryption takes.
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Bytes of the authentication tag following each encrypted chunk.
+const TAG_SIZE: usize = 16;
+
+/// A 256-bit AES key exported files are encrypted with, see [`ExportConfig::encryption`].
+///
+/// [`ExportConfig::encryption`]: super::ExportConfig::encryption
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Creates a key from its 32 bytes.
+    pub const fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Reads a key from the file at `path`, holding its 32 bytes hex-encoded as
+    /// `openssl rand -hex 32` prints them.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let bytes = hex::decode(contents.trim())
+            .wrap_err_with(|| format!("Encryption key file {} is not hex", path.display()))?;
+        let key = <[u8; 32]>::try_from(bytes).map_err(|bytes| {
+            eyre!(
+                "Encryption key file {} holds {} bytes, but AES-256 keys are 32 bytes",
+                path.display(),
+                bytes.len()
+            )
+        })?;
+        Ok(Self(key))
+    }
+
+    /// Returns the identifier key descriptors name the key by: the first 8 bytes of its SHA-256
+    /// hash, hex-encoded.
+    pub fn id(&self) -> String {
+        hex::encode(&Sha256::digest(self.0)[..8])
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.0.into())
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only the identifier, so the key never ends up in logs.
+        f.debug_tuple("EncryptionKey").field(&self.id()).finish()
+    }
+}
+
+/// Contents of the key descriptor next to an encrypted file.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyDescriptor {
+    /// Always [`ALGORITHM`].
+    algorithm: String,
+    /// [`EncryptionKey::id`] of the key the file is encrypted with.
+    key_id: String,
+    /// Random prefix of every chunk's nonce, hex-encoded.
+    nonce_prefix: String,
+    /// Bytes of plaintext per chunk.
+    chunk_size: usize,
+    /// Name of the file before it was encrypted.
+    name: String,
+    /// Size of the file before it was encrypted, in bytes.
+    size: u64,
+}
+
+/// Encrypts the file at `path` with `key`, replacing it with `<name>.enc` and writing its key
+/// descriptor to `<name>.enc.json`. Returns the path of the encrypted file.
+pub(crate) fn encrypt_file(path: &Path, key: &EncryptionKey) -> Result<PathBuf> {
+    let name = file_name(path)?;
+    let encrypted = path.with_file_name(format!("{name}.enc"));
+    let nonce_prefix: [u8; 7] = rand::random();
+    let cipher = key.cipher();
+
+    let mut source = fs::open(path)?;
+    let size = source.metadata()?.len();
+    let chunks = chunk_count(size, CHUNK_SIZE)?;
+    fs::atomic_write_file(&encrypted, |file| -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE + TAG_SIZE);
+        for index in 0..chunks {
+            buffer.clear();
+            (&mut source).take(CHUNK_SIZE as u64).read_to_end(&mut buffer)?;
+            let nonce = chunk_nonce(nonce_prefix, index, chunks);
+            cipher
+                .encrypt_in_place(Nonce::from_slice(&nonce), name.as_bytes(), &mut buffer)
+                .map_err(|_| io::Error::other("AES-GCM encryption failed"))?;
+            writer.write_all(&buffer)?;
+        }
+        writer.flush()
+    })?;
+
+    let descriptor = KeyDescriptor {
+        algorithm: ALGORITHM.to_owned(),
+        key_id: key.id(),
+        nonce_prefix: hex::encode_prefixed(nonce_prefix),
+        chunk_size: CHUNK_SIZE,
+        name: name.to_owned(),
+        size,
+    };
+    let contents = serde_json::to_vec_pretty(&descriptor)?;
+    fs::atomic_write_file(&descriptor_path(&encrypted), |file| file.write_all(&contents))?;
+    // Only removed once the encrypted file and its descriptor are in place.
+    fs::remove_file(path)?;
+
+    Ok(encrypted)
+}
+
+/// Decrypts the file at `path`, encrypted by an export with [`ExportConfig::encryption`] set to
+/// `key`, next to it under the name it was exported with. Returns the path of the decrypted file.
+///
+/// The file's key descriptor is read from `<path>.json`. The encrypted file is kept. A file
+/// encrypted with another key, or tampered with, is refused without writing anything.
+///
+/// [`ExportConfig::encryption`]: super::ExportConfig::encryption
+pub fn decrypt_file(path: &Path, key: &EncryptionKey) -> Result<PathBuf> {
+    let descriptor_path = descriptor_path(path);
+    let descriptor: KeyDescriptor = serde_json::from_str(&fs::read_to_string(&descriptor_path)?)
+        .wrap_err_with(|| format!("Malformed key descriptor {}", descriptor_path.display()))?;
+    if descriptor.algorithm != ALGORITHM {
+        eyre::bail!(
+            "{} is encrypted with {}, only {ALGORITHM} is supported",
+            path.display(),
+            descriptor.algorithm
+        );
+    }
+    if descriptor.key_id != key.id() {
+        eyre::bail!(
+            "{} is encrypted with key {}, not with key {}",
+            path.display(),
+            descriptor.key_id,
+            key.id()
+        );
+    }
+    // The name is joined to the directory of `path`, so it must not lead anywhere else.
+    if Path::new(&descriptor.name).file_name().and_then(|name| name.to_str()) !=
+        Some(descriptor.name.as_str())
+    {
+        eyre::bail!("Key descriptor {} names an invalid file", descriptor_path.display());
+    }
+    let nonce_prefix = <[u8; 7]>::try_from(hex::decode(&descriptor.nonce_prefix)?)
+        .map_err(|_| eyre!("Key descriptor {} has an invalid nonce", descriptor_path.display()))?;
+
+    if !(1..=MAX_CHUNK_SIZE).contains(&descriptor.chunk_size) {
+        eyre::bail!(
+            "Key descriptor {} has an invalid chunk size {}",
+            descriptor_path.display(),
+            descriptor.chunk_size
+        );
+    }
+
+    let chunks = chunk_count(descriptor.size, descriptor.chunk_size)?;
+    let mut source = fs::open(path)?;
+    let expected = descriptor.size + chunks * TAG_SIZE as u64;
+    let actual = source.metadata()?.len();
+    if actual != expected {
+        eyre::bail!(
+            "{} holds {actual} bytes, but its key descriptor implies {expected}",
+            path.display()
+        );
+    }
+
+    let decrypted = path.with_file_name(&descriptor.name);
+    let cipher = key.cipher();
+    fs::atomic_write_file(&decrypted, |file| -> io::Result<()> {
+        let mut writer = BufWriter::new(file);
+        let mut buffer = Vec::with_capacity(descriptor.chunk_size + TAG_SIZE);
+        for index in 0..chunks {
+            buffer.clear();
+            (&mut source)
+                .take((descriptor.chunk_size + TAG_SIZE) as u64)
+                .read_to_end(&mut buffer)?;
+            let nonce = chunk_nonce(nonce_prefix, index, chunks);
+            cipher
+                .decrypt_in_place(
+                    Nonce::from_slice(&nonce),
+                    descriptor.name.as_bytes(),
+                    &mut buffer,
+                )
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("chunk {index} fails authentication"),
+                    )
+                })?;
+            writer.write_all(&buffer)?;
+        }
+        writer.flush()
+    })
+    .wrap_err_with(|| format!("Failed to decrypt {}", path.display()))?;
+
+    Ok(decrypted)
+}
+
+/// Returns the path of the key descriptor of the encrypted file at `path`.
+fn descriptor_path(path: &Path) -> PathBuf {
+    let mut descriptor = path.as_os_str().to_owned();
+    descriptor.push(".json");
+    descriptor.into()
+}
+
+fn file_name(path: &Path) -> Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| eyre!("Exported file {} has no file name", path.display()))
+}
+
+/// Returns how many chunks of `chunk_size` bytes a file of `size` bytes is encrypted in, at least
+/// one so empty files are authenticated too.
+fn chunk_count(size: u64, chunk_size: usize) -> Result<u64> {
+    let chunks = size.div_ceil(chunk_size as u64).max(1);
+    if chunks > u64::from(u32::MAX) {
+        eyre::bail!("Files of {size} bytes are too large to encrypt in {chunk_size}-byte chunks");
+    }
+    Ok(chunks)
+}
+
+/// Returns the nonce of chunk `index` of `chunks`.
+fn chunk_nonce(prefix: [u8; 7], index: u64, chunks: u64) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[..7].copy_from_slice(&prefix);
+    nonce[7..11].copy_from_slice(&(index as u32).to_be_bytes());
+    nonce[11] = u8::from(index + 1 == chunks);
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn encrypted_files_decrypt_with_their_key_only() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mainnet-00000-5ec1ffb8.era1");
+        // Spans two chunks, the last one partial.
+        let contents = (0..CHUNK_SIZE + 5).map(|byte| byte as u8).collect::<Vec<_>>();
+        fs::write(&path, &contents).unwrap();
+
+        let key = EncryptionKey::new([7; 32]);
+        let encrypted = encrypt_file(&path, &key).unwrap();
+        assert_eq!(encrypted, dir.path().join("mainnet-00000-5ec1ffb8.era1.enc"));
+        assert!(!path.exists());
+        assert_eq!(
+            fs::metadata(&encrypted).unwrap().len(),
+            contents.len() as u64 + 2 * TAG_SIZE as u64
+        );
+
+        let err = decrypt_file(&encrypted, &EncryptionKey::new([8; 32])).unwrap_err().to_string();
+        assert!(err.contains(&format!("is encrypted with key {}", key.id())), "{err}");
+
+        assert_eq!(decrypt_file(&encrypted, &key).unwrap(), path);
+        assert_eq!(fs::read(&path).unwrap(), contents);
+
+        // A flipped byte fails authentication, leaving no decrypted file behind.
+        fs::remove_file(&path).unwrap();
+        let mut tampered = fs::read(&encrypted).unwrap();
+        tampered[CHUNK_SIZE + TAG_SIZE] ^= 1;
+        fs::write(&encrypted, tampered).unwrap();
+        assert!(decrypt_file(&encrypted, &key).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,210 @@
+//! `.era` consensus-history writer.
+//!
+//! Unlike `.era1` and `.ere`, a `.era` file holds beacon blocks and an era-boundary beacon state,
+//! none of which reth stores. The data is pulled from a caller-provided [`BeaconSource`], e.g. a
+//! beacon node API or an existing archive, and laid out as the format requires.
+
+use alloy_primitives::B256;
+use eyre::{eyre, Result};
+use reth_era::{
+    common::file_ops::{EraFileFormat, EraFileId, EraFileType, StreamWriter},
+    e2s::types::{Header, IndexEntry},
+    era::{
+        file::{EraFile, EraWriter},
+        types::{
+            consensus::{CompressedBeaconState, CompressedSignedBeaconBlock},
+            group::{EraGroup, EraId, SlotIndex, SLOTS_PER_HISTORICAL_ROOT},
+        },
+    },
+};
+use reth_fs_util as fs;
+use std::path::PathBuf;
+use tracing::info;
+
+/// Source of the consensus-layer data written to `.era` files.
+pub trait BeaconSource {
+    /// Returns the ssz-encoded `SignedBeaconBlock` proposed at `slot`, or `None` if the slot is
+    /// empty.
+    fn signed_beacon_block(&self, slot: u64) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the ssz-encoded `BeaconState` at `slot`, an era boundary.
+    fn beacon_state(&self, slot: u64) -> Result<Vec<u8>>;
+
+    /// Returns the last historical root in the state at `slot`, which names the file holding it.
+    ///
+    /// Defaults to `None`, in which case file names carry no short hash.
+    fn historical_root(&self, slot: u64) -> Result<Option<B256>> {
+        let _ = slot;
+        Ok(None)
+    }
+}
+
+/// Configuration to export consensus history to `.era` files.
+#[derive(Clone, Debug)]
+pub struct ConsensusExportConfig {
+    /// Directory to export `.era` files to.
+    pub dir: PathBuf,
+    /// First era to export.
+    pub first_era: u64,
+    /// Last era to export.
+    pub last_era: u64,
+    /// Network name.
+    pub network: String,
+    /// Whether to also write an `index.html` listing the `.era` files in
+    /// [`ConsensusExportConfig::dir`], so it can be served to other nodes' era downloaders as-is.
+    pub write_listing: bool,
+}
+
+impl Default for ConsensusExportConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::new(),
+            first_era: 0,
+            last_era: 0,
+            network: "mainnet".to_string(),
+            write_listing: false,
+        }
+    }
+}
+
+/// Writes eras `first_era..=last_era` from `source` as `.era` files, one per era.
+///
+/// Era `n` holds the blocks of slots `(n - 1) * 8192..n * 8192` and the state at slot `n * 8192`;
+/// era `0` holds the genesis state alone.
+///
+/// Returns the paths of the files that were created.
+pub fn export_consensus<S: BeaconSource>(
+    source: &S,
+    config: &ConsensusExportConfig,
+) -> Result<Vec<PathBuf>> {
+    if config.first_era > config.last_era {
+        return Err(eyre!(
+            "First era ({}) is after last era ({})",
+            config.first_era,
+            config.last_era
+        ));
+    }
+
+    if !config.dir.exists() {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| eyre!("Failed to create output directory: {}", e))?;
+    }
+
+    let mut created_files = Vec::new();
+    for era in config.first_era..=config.last_era {
+        let file_path = write_era(source, era, config)?;
+        info!(target: "era::history::export", "Wrote ERA file: {file_path:?}");
+        created_files.push(file_path);
+    }
+
+    if config.write_listing {
+        super::listing::write_listing(&config.dir, EraFileType::Era)?;
+    }
+
+    Ok(created_files)
+}
+
+/// Writes era `era` as a single `.era` file in [`ConsensusExportConfig::dir`].
+fn write_era<S: BeaconSource>(
+    source: &S,
+    era: u64,
+    config: &ConsensusExportConfig,
+) -> Result<PathBuf> {
+    let state_slot = era * SLOTS_PER_HISTORICAL_ROOT;
+    let state = CompressedBeaconState::from_ssz(&source.beacon_state(state_slot)?)?;
+
+    // Absolute positions of each record, rebased onto their slot-index record once its position is
+    // known.
+    let mut position = Header::SIZE as i64; // past the leading version record
+    let mut blocks = Vec::new();
+    let mut block_positions = Vec::new();
+    if era > 0 {
+        for slot in state_slot - SLOTS_PER_HISTORICAL_ROOT..state_slot {
+            let Some(ssz) = source.signed_beacon_block(slot)? else {
+                block_positions.push(None);
+                continue
+            };
+            let block = CompressedSignedBeaconBlock::from_ssz(&ssz)?;
+            block_positions.push(Some(position));
+            position += block.to_entry().size() as i64;
+            blocks.push(block);
+        }
+    }
+
+    let state_position = position;
+    position += state.to_entry().size() as i64;
+
+    let block_index = (era > 0).then(|| {
+        let offsets = block_positions
+            .iter()
+            .map(|abs| abs.map_or(0, |abs| abs - position))
+            .collect::<Vec<_>>();
+        SlotIndex::new(state_slot - SLOTS_PER_HISTORICAL_ROOT, offsets)
+    });
+    if let Some(index) = &block_index {
+        position += index.to_entry().size() as i64;
+    }
+    let state_index = SlotIndex::new(state_slot, vec![state_position - position]);
+
+    let mut id = EraId::new(&config.network, state_slot, SLOTS_PER_HISTORICAL_ROOT as u32);
+    if let Some(root) = source.historical_root(state_slot)? {
+        id = id.with_hash(super::short_hash(root));
+    }
+    let file_path = config.dir.join(id.to_file_name());
+
+    let group = match block_index {
+        Some(index) => EraGroup::with_block_index(blocks, state, index, state_index),
+        None => EraGroup::new(blocks, state, state_index),
+    };
+    EraWriter::new(std::fs::File::create(&file_path)?)
+        .write_file(&EraFile::new(group, id))
+        .map_err(|e| eyre!("Failed to write ERA file {file_path:?}: {e}"))?;
+
+    Ok(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_era::{common::file_ops::StreamReader, era::file::EraReader};
+    use tempfile::tempdir;
+
+    /// Proposes a block in every other slot.
+    struct TestSource;
+
+    impl BeaconSource for TestSource {
+        fn signed_beacon_block(&self, slot: u64) -> Result<Option<Vec<u8>>> {
+            Ok((slot % 2 == 0).then(|| slot.to_le_bytes().to_vec()))
+        }
+
+        fn beacon_state(&self, slot: u64) -> Result<Vec<u8>> {
+            Ok(slot.to_be_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn exports_blocks_and_boundary_state_per_era() {
+        let dir = tempdir().unwrap();
+        let config = ConsensusExportConfig {
+            dir: dir.path().to_path_buf(),
+            first_era: 0,
+            last_era: 1,
+            ..Default::default()
+        };
+
+        let files = export_consensus(&TestSource, &config).unwrap();
+        assert_eq!(files.len(), 2);
+
+        let genesis = EraReader::new(fs::open(&files[0]).unwrap()).read("mainnet".into()).unwrap();
+        assert!(genesis.group.blocks.is_empty());
+        assert_eq!(genesis.group.era_state.decompress().unwrap(), 0u64.to_be_bytes());
+
+        let era = EraReader::new(fs::open(&files[1]).unwrap()).read("mainnet".into()).unwrap();
+        assert_eq!(era.group.blocks.len(), SLOTS_PER_HISTORICAL_ROOT as usize / 2);
+        assert_eq!(era.group.blocks[1].decompress().unwrap(), 2u64.to_le_bytes());
+        assert_eq!(
+            era.group.era_state.decompress().unwrap(),
+            SLOTS_PER_HISTORICAL_ROOT.to_be_bytes()
+        );
+    }
+}
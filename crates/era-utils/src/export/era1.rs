@@ -1,13 +1,13 @@
 //! `.era1` block-history writer.
 
-use super::{ChunkAccumulator, EraBlockWriter, ExportBlock};
+use super::{ChunkAccumulator, EraBlockWriter, ExportBlock, ExportConfig};
 use crate::Era1;
 use alloy_consensus::{BlockHeader, TxReceipt};
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{BlockNumber, B256, U256};
 use alloy_rlp::Encodable;
 use eyre::{eyre, Result};
 use reth_era::{
-    common::file_ops::{EraFileId, StreamWriter},
+    common::file_ops::{EraFileType, StreamWriter},
     e2s::types::{Header, IndexEntry},
     era1::{
         file::Era1Writer,
@@ -21,46 +21,99 @@ use reth_era::{
     },
 };
 use reth_primitives_traits::Receipt;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use tracing::warn;
 
 impl EraBlockWriter for Era1 {
+    const FILE_TYPE: Option<EraFileType> = Some(EraFileType::Era1);
+
     fn write_file<H, B, R>(
-        network: &str,
-        max_blocks_per_file: u64,
+        config: &ExportConfig,
         blocks: &[ExportBlock<H, B, R>],
-        dir: &Path,
     ) -> Result<PathBuf>
     where
-        H: BlockHeader + Encodable,
-        B: Encodable,
+        H: BlockHeader + Encodable + Sync,
+        B: Encodable + Sync,
         R: Receipt,
     {
-        let accumulator = super::accumulator::<Accumulator, _, _, _>(blocks)?;
-        let file_path = dir.join(file_name(network, max_blocks_per_file, blocks, &accumulator));
-
-        let mut writer = Era1Writer::new(std::fs::File::create(&file_path)?);
-        writer.write_version()?;
-
-        // `era1` streams its records block by block; offsets track the running write position and
-        // are rebased onto the block-index record once that record's position is known.
-        let mut offsets = Vec::<i64>::with_capacity(blocks.len());
-        let mut position = Header::SIZE as i64; // past the leading version record
-        for block in blocks {
-            let tuple = compress_block(block)?;
-            offsets.push(position);
-            position += tuple.size() as i64;
-            writer.write_block(&tuple)?;
+        if !config.profile.has_receipts() {
+            return Err(eyre!(
+                "era1 files always carry receipts, export blocks-only archives as ere instead"
+            ));
         }
+        let blocks = pre_merge_blocks(config, blocks)?;
+        let accumulator = super::accumulator::<Accumulator, _, _, _>(blocks)?;
+        super::mainnet::check_accumulator_root(
+            config,
+            blocks[0].header.number(),
+            blocks.len(),
+            accumulator.root,
+        )?;
+        let first_block = blocks[0].header.number();
+        let tuples = config.tuning.compress_all(blocks, compress_block)?;
+        write_tuples(config, first_block, &tuples, &accumulator)
+    }
+}
+
+/// Writes `tuples`, the blocks from `first_block` on, to a file in [`ExportConfig::dir`] along
+/// with `accumulator` and a block index of them, returning its path.
+pub(super) fn write_tuples(
+    config: &ExportConfig,
+    first_block: BlockNumber,
+    tuples: &[BlockTuple],
+    accumulator: &Accumulator,
+) -> Result<PathBuf> {
+    let file_path = config.dir.join(file_name(config, first_block, tuples.len(), accumulator));
+    let mut writer = Era1Writer::new(config.tuning.create_file(&file_path)?);
+    writer.write_version()?;
 
-        let index_position = position + accumulator.to_entry().size() as i64;
-        let relative: Vec<i64> = offsets.iter().map(|&abs| abs - index_position).collect();
+    // `era1` writes its records block by block; offsets track the running write position and
+    // are rebased onto the block-index record once that record's position is known.
+    let mut offsets = Vec::<i64>::with_capacity(tuples.len());
+    let mut position = Header::SIZE as i64; // past the leading version record
+    for tuple in tuples {
+        offsets.push(position);
+        position += tuple.size() as i64;
+        writer.write_block(tuple)?;
+    }
+
+    let index_position = position + accumulator.to_entry().size() as i64;
+    let relative: Vec<i64> = offsets.iter().map(|&abs| abs - index_position).collect();
 
-        writer.write_accumulator(&accumulator)?;
-        writer.write_block_index(&BlockIndex::new(blocks[0].header.number(), relative))?;
-        writer.flush()?;
+    writer.write_accumulator(accumulator)?;
+    writer.write_block_index(&BlockIndex::new(first_block, relative))?;
+    writer.flush()?;
+
+    Ok(file_path)
+}
 
-        Ok(file_path)
+/// Cuts `blocks` at the merge when [`ExportConfig::terminal_total_difficulty`] is set, as `era1`
+/// only holds pre-merge history.
+fn pre_merge_blocks<'a, H: BlockHeader, B, R>(
+    config: &ExportConfig,
+    blocks: &'a [ExportBlock<H, B, R>],
+) -> Result<&'a [ExportBlock<H, B, R>]> {
+    if config.terminal_total_difficulty.is_none() {
+        return Ok(blocks)
+    }
+
+    let pre_merge = super::pre_merge_len(config, blocks)?;
+    let first_block = blocks[0].header.number();
+    if pre_merge == 0 {
+        return Err(eyre!(
+            "Block {first_block} is post-merge, era1 files only hold pre-merge history"
+        ));
+    }
+    if pre_merge < blocks.len() {
+        warn!(
+            target: "era::history::export",
+            "Cutting the era1 file starting at block {first_block} at the merge, leaving out {} \
+             post-merge blocks",
+            blocks.len() - pre_merge
+        );
     }
+
+    Ok(&blocks[..pre_merge])
 }
 
 /// Compresses one block into an `era1` [`BlockTuple`] (header, body, bloom-bearing receipts,
@@ -91,20 +144,84 @@ impl ChunkAccumulator for Accumulator {
     }
 }
 
-/// Builds the output filename, taking the short hash from the accumulator root.
-fn file_name<H: BlockHeader, B, R>(
-    network: &str,
-    max_blocks_per_file: u64,
-    blocks: &[ExportBlock<H, B, R>],
+/// Builds the filename of the `count` blocks from `first_block` on, taking the short hash from the
+/// accumulator root.
+fn file_name(
+    config: &ExportConfig,
+    first_block: BlockNumber,
+    count: usize,
     accumulator: &Accumulator,
 ) -> String {
     let file_hash = super::short_hash(accumulator.root);
-    let id =
-        Era1Id::new(network, blocks[0].header.number(), blocks.len() as u32).with_hash(file_hash);
+    let id = Era1Id::new(&config.network, first_block, count as u32).with_hash(file_hash);
     // Custom block-per-file exports tag the era count into the filename.
-    if max_blocks_per_file == MAX_BLOCKS_PER_ERA1 as u64 {
-        id.to_file_name()
+    if config.max_blocks_per_file == MAX_BLOCKS_PER_ERA1 as u64 {
+        config.naming.file_name(&id)
     } else {
-        id.with_era_count().to_file_name()
+        config.naming.file_name(&id.with_era_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArchiveProfile;
+    use alloy_consensus::Header as ConsensusHeader;
+    use reth_era::{common::file_ops::FileReader, era1::file::Era1Reader};
+    use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
+    use tempfile::tempdir;
+
+    /// Blocks `0..4` with difficulty `10`, the last one post-merge, totalling `10`, `20`, `30` and
+    /// `30`.
+    fn blocks() -> Vec<ExportBlock<ConsensusHeader, BlockBody, EthReceipt>> {
+        (0..4u64)
+            .map(|number| ExportBlock {
+                header: ConsensusHeader {
+                    number,
+                    difficulty: U256::from(if number < 3 { 10 } else { 0 }),
+                    ..Default::default()
+                },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::from(number.min(2) * 10 + 10),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn refuses_blocks_only_profile() {
+        let config = ExportConfig { profile: ArchiveProfile::BlocksOnly, ..Default::default() };
+        let err = Era1::write_file(&config, &blocks()).unwrap_err().to_string();
+        assert!(err.contains("era1 files always carry receipts"), "{err}");
+    }
+
+    #[test]
+    fn cuts_file_at_merge_against_ttd() {
+        let dir = tempdir().unwrap();
+        let config = ExportConfig {
+            dir: dir.path().to_path_buf(),
+            terminal_total_difficulty: Some(U256::from(25)),
+            ..Default::default()
+        };
+        let blocks = blocks();
+
+        let path = Era1::write_file(&config, &blocks).unwrap();
+        let file = Era1Reader::open(&path, "mainnet").unwrap();
+        assert_eq!(file.group.blocks.len(), 3);
+
+        // A post-merge chunk can't be written as era1.
+        let err = Era1::write_file(&config, &blocks[3..]).unwrap_err().to_string();
+        assert!(err.contains("Block 3 is post-merge"), "{err}");
+
+        // The terminal block must reach the TTD...
+        let config = ExportConfig { terminal_total_difficulty: Some(U256::from(31)), ..config };
+        let err = Era1::write_file(&config, &blocks).unwrap_err().to_string();
+        assert!(err.contains("short of the terminal total difficulty 31"), "{err}");
+
+        // ...and no proof-of-work block may follow it.
+        let config = ExportConfig { terminal_total_difficulty: Some(U256::from(15)), ..config };
+        let err = Era1::write_file(&config, &blocks).unwrap_err().to_string();
+        assert!(err.contains("block 2 extends total difficulty 20"), "{err}");
     }
 }
@@ -1,13 +1,13 @@
 //! `.ere` block-history writer.
 
-use super::{ChunkAccumulator, EraBlockWriter, ExportBlock};
+use super::{ChunkAccumulator, EraBlockWriter, ExportBlock, ExportConfig};
 use crate::Ere;
 use alloy_consensus::{BlockHeader, TxType};
 use alloy_primitives::{B256, U256};
 use alloy_rlp::Encodable;
 use eyre::{eyre, Result};
 use reth_era::{
-    common::file_ops::{EraFileFormat, EraFileId, StreamWriter},
+    common::file_ops::{EraFileFormat, EraFileType, StreamWriter},
     e2s::types::Header,
     ere::{
         file::{EreFile, EreWriter},
@@ -21,18 +21,18 @@ use reth_era::{
     },
 };
 use reth_primitives_traits::Receipt;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 impl EraBlockWriter for Ere {
+    const FILE_TYPE: Option<EraFileType> = Some(EraFileType::Ere);
+
     fn write_file<H, B, R>(
-        network: &str,
-        max_blocks_per_file: u64,
+        config: &ExportConfig,
         blocks: &[ExportBlock<H, B, R>],
-        dir: &Path,
     ) -> Result<PathBuf>
     where
-        H: BlockHeader + Encodable,
-        B: Encodable,
+        H: BlockHeader + Encodable + Sync,
+        B: Encodable + Sync,
         R: Receipt,
     {
         // Total difficulty and the accumulator are pre-merge only: post-merge blocks have zero
@@ -44,20 +44,28 @@ impl EraBlockWriter for Ere {
         // block.
         let pre_merge_count = blocks.partition_point(|b| !b.header.difficulty().is_zero());
 
-        let tuples = blocks
-            .iter()
-            .map(|block| compress_block(block, pre_merge))
-            .collect::<Result<Vec<_>>>()?;
+        let has_receipts = config.profile.has_receipts();
+        let tuples = config
+            .tuning
+            .compress_all(blocks, |block| compress_block(block, has_receipts, pre_merge))?;
         let accumulator = pre_merge
             .then(|| super::accumulator::<Accumulator, _, _, _>(&blocks[..pre_merge_count]))
             .transpose()?;
-        let id = file_id(network, max_blocks_per_file, blocks)?;
+        if let Some(accumulator) = &accumulator {
+            super::mainnet::check_accumulator_root(
+                config,
+                blocks[0].header.number(),
+                pre_merge_count,
+                accumulator.root,
+            )?;
+        }
+        let id = file_id(config, blocks)?;
         let index = block_index(blocks[0].header.number(), &tuples, accumulator.as_ref());
 
-        let file_path = dir.join(id.to_file_name());
+        let file_path = config.dir.join(config.naming.file_name(&id));
         let group = EreGroup::new(tuples, accumulator, index);
 
-        EreWriter::new(std::fs::File::create(&file_path)?)
+        EreWriter::new(config.tuning.create_file(&file_path)?)
             .write_file(&EreFile::new(group, id))
             .map_err(|e| eyre!("Failed to write ERE file {file_path:?}: {e}"))?;
 
@@ -65,13 +73,14 @@ impl EraBlockWriter for Ere {
     }
 }
 
-/// Compresses one block into an `ere` [`BlockTuple`] (header, body, slim receipts, and, for
-/// pre-merge blocks, cumulative total difficulty).
+/// Compresses one block into an `ere` [`BlockTuple`] (header, body, slim receipts unless left
+/// out, and, for pre-merge blocks, cumulative total difficulty).
 ///
 /// The optional ERE `Proof` (a Portal Network header-inclusion proof) is not produced here, so the
 /// file carries the [`EreProfile::NoProofs`] profile.
 fn compress_block<H, B, R>(
     block: &ExportBlock<H, B, R>,
+    include_receipts: bool,
     include_total_difficulty: bool,
 ) -> Result<BlockTuple>
 where
@@ -81,6 +90,10 @@ where
 {
     let header = CompressedHeader::from_header(&block.header)?;
     let body = CompressedBody::from_body(&block.body)?;
+    let tuple = BlockTuple::new(header, body);
+    if !include_receipts {
+        return Ok(with_total_difficulty(tuple, block, include_total_difficulty))
+    }
 
     let slim_receipts = block
         .receipts
@@ -98,12 +111,20 @@ where
     let receipts = CompressedSlimReceipts::from_receipts(&slim_receipts)
         .map_err(|e| eyre!("Failed to compress receipts: {e}"))?;
 
-    let tuple = BlockTuple::new(header, body).with_receipts(receipts);
-    Ok(if include_total_difficulty {
+    Ok(with_total_difficulty(tuple.with_receipts(receipts), block, include_total_difficulty))
+}
+
+/// Adds the total difficulty of `block` to `tuple` if `include` is set.
+fn with_total_difficulty<H, B, R>(
+    tuple: BlockTuple,
+    block: &ExportBlock<H, B, R>,
+    include: bool,
+) -> BlockTuple {
+    if include {
         tuple.with_total_difficulty(TotalDifficulty::new(block.total_difficulty))
     } else {
         tuple
-    })
+    }
 }
 
 impl ChunkAccumulator for Accumulator {
@@ -119,36 +140,44 @@ impl ChunkAccumulator for Accumulator {
 /// Builds the file identifier.
 ///
 /// Per the [`EreId`] contract, the short hash is the first four bytes of the last block's hash.
+/// Blocks-only exports add the [`EreProfile::NoReceipts`] profile.
 fn file_id<H: BlockHeader, B, R>(
-    network: &str,
-    max_blocks_per_file: u64,
+    config: &ExportConfig,
     blocks: &[ExportBlock<H, B, R>],
 ) -> Result<EreId> {
     let last_block_hash =
         blocks.last().ok_or_else(|| eyre!("cannot build ERE file id from empty block range"))?;
     let file_hash = super::short_hash(last_block_hash.block_hash);
-    let id = EreId::new(network, blocks[0].header.number(), blocks.len() as u32)
+    let id = EreId::new(&config.network, blocks[0].header.number(), blocks.len() as u32)
         .with_hash(file_hash)
         .with_profile(EreProfile::NoProofs);
+    let id =
+        if config.profile.has_receipts() { id } else { id.with_profile(EreProfile::NoReceipts) };
     // Custom block-per-file exports tag the era count into the filename.
-    Ok(if max_blocks_per_file == MAX_BLOCKS_PER_ERE as u64 { id } else { id.with_era_count() })
+    Ok(if config.max_blocks_per_file == MAX_BLOCKS_PER_ERE as u64 {
+        id
+    } else {
+        id.with_era_count()
+    })
 }
 
 /// Builds the [`DynamicBlockIndex`] for the file's sectioned layout.
 ///
 /// `ere` groups records by type, so the file is laid out (after the version record) as: all
-/// headers, all bodies, all receipts, all total-difficulties and the accumulator (both pre-merge
-/// only), then the index. Offsets are negative `i64`s relative to the index record, per the spec's
-/// backward-pointing convention.
+/// headers, all bodies, all receipts (unless left out), all total-difficulties and the
+/// accumulator (both pre-merge only), then the index. Offsets are negative `i64`s relative to the
+/// index record, per the spec's backward-pointing convention.
 fn block_index(
     start_block: u64,
     tuples: &[BlockTuple],
     accumulator: Option<&Accumulator>,
 ) -> DynamicBlockIndex {
-    // Every block carries header + body + receipts, plus total-difficulty for pre-merge files
-    // (proofs are always omitted), so the index stores three or four offsets per block.
+    // Every block carries header + body, plus receipts unless left out and total-difficulty for
+    // pre-merge files (proofs are always omitted), so the index stores two to four offsets per
+    // block.
+    let has_receipts = tuples.first().is_some_and(|t| t.receipts.is_some());
     let has_total_difficulty = tuples.first().is_some_and(|t| t.total_difficulty.is_some());
-    let component_count = if has_total_difficulty { 4 } else { 3 };
+    let component_count = 2 + u64::from(has_receipts) + u64::from(has_total_difficulty);
 
     // Absolute position of each block's components, walked section by section, starting past the
     // leading version record.
@@ -164,8 +193,9 @@ fn block_index(
 
     let header_pos = section(&mut tuples.iter().map(|t| t.header.to_entry().size() as i64));
     let body_pos = section(&mut tuples.iter().map(|t| t.body.to_entry().size() as i64));
-    let receipts_pos =
-        section(&mut tuples.iter().map(|t| entry_size(t.receipts.as_ref().map(|r| r.to_entry()))));
+    let receipts_pos = has_receipts.then(|| {
+        section(&mut tuples.iter().map(|t| entry_size(t.receipts.as_ref().map(|r| r.to_entry()))))
+    });
     let difficulty_pos = has_total_difficulty.then(|| {
         section(
             &mut tuples
@@ -183,7 +213,9 @@ fn block_index(
     for i in 0..tuples.len() {
         offsets.push(header_pos[i] - index_position);
         offsets.push(body_pos[i] - index_position);
-        offsets.push(receipts_pos[i] - index_position);
+        if let Some(receipts_pos) = &receipts_pos {
+            offsets.push(receipts_pos[i] - index_position);
+        }
         if let Some(difficulty_pos) = &difficulty_pos {
             offsets.push(difficulty_pos[i] - index_position);
         }
@@ -200,6 +232,7 @@ fn entry_size(entry: Option<reth_era::e2s::types::Entry>) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ArchiveProfile;
     use alloy_consensus::Header;
     use reth_era::{common::file_ops::StreamReader, ere::file::EreReader};
     use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
@@ -227,8 +260,8 @@ mod tests {
 
     fn write_and_read(blocks: &[ExportBlock<Header, BlockBody, EthReceipt>]) -> EreFile {
         let dir = tempdir().unwrap();
-        let path =
-            Ere::write_file("mainnet", MAX_BLOCKS_PER_ERE as u64, blocks, dir.path()).unwrap();
+        let config = ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() };
+        let path = Ere::write_file(&config, blocks).unwrap();
         EreReader::new(std::fs::File::open(path).unwrap()).read("mainnet".to_string()).unwrap()
     }
 
@@ -250,6 +283,25 @@ mod tests {
         assert!(file.group.blocks.iter().all(|b| b.total_difficulty.is_none()));
     }
 
+    #[test]
+    fn blocks_only_file_omits_receipts() {
+        let dir = tempdir().unwrap();
+        let config = ExportConfig {
+            dir: dir.path().to_path_buf(),
+            profile: ArchiveProfile::BlocksOnly,
+            ..Default::default()
+        };
+        let path = Ere::write_file(&config, &export_blocks(3, U256::from(1))).unwrap();
+        assert!(path.to_str().unwrap().ends_with("-noproofs-noreceipts.ere"), "{path:?}");
+        assert_eq!(ArchiveProfile::of_file(&path), ArchiveProfile::BlocksOnly);
+
+        let file =
+            EreReader::new(std::fs::File::open(path).unwrap()).read("mainnet".to_string()).unwrap();
+        assert_eq!(file.group.index.component_count(), 3);
+        assert!(file.group.blocks.iter().all(|b| b.receipts.is_none()));
+        assert!(file.group.blocks.iter().all(|b| b.total_difficulty.is_some()));
+    }
+
     #[test]
     fn merge_spanning_file_excludes_post_merge_blocks_from_accumulator() {
         // Blocks 0-1 pre-merge (non-zero difficulty), blocks 2-3 post-merge (zero difficulty).
@@ -0,0 +1,131 @@
+//! `index.html` and `checksums.txt` for serving an export directory to other nodes.
+
+use alloy_primitives::{hex, B256};
+use eyre::Result;
+use reth_era::common::file_ops::EraFileType;
+use reth_fs_util as fs;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{self, Write},
+    io,
+    path::Path,
+};
+
+/// Name of the checksums file the downloader fetches alongside `.era1` and `.ere` files.
+pub(super) const CHECKSUMS_FILE: &str = "checksums.txt";
+
+/// Name of the directory listing the downloader parses for file names.
+pub(super) const INDEX_FILE: &str = "index.html";
+
+/// Writes an `index.html` listing and, for formats that ship one, a `checksums.txt` covering every
+/// `file_type` file in `dir`, so the directory can be served as-is to the era downloader.
+///
+/// Both files are rewritten from whatever is in `dir`, so files left by earlier exports are
+/// included. The downloader looks up a file's checksum by its era number, so `checksums.txt` is
+/// only usable when the files form a contiguous series starting at era `0`.
+pub(crate) fn write_listing(dir: &Path, file_type: EraFileType) -> Result<()> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(name) = name.to_str() &&
+            EraFileType::from_filename(name) == Some(file_type)
+        {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+
+    let mut listed = Vec::with_capacity(names.len() + 1);
+    if file_type.has_checksums() {
+        let mut checksums = String::new();
+        for name in &names {
+            writeln!(checksums, "{}", hex::encode_prefixed(file_checksum(&dir.join(name))?))?;
+        }
+        fs::write(dir.join(CHECKSUMS_FILE), checksums)?;
+        listed.push(CHECKSUMS_FILE);
+    }
+    listed.extend(names.iter().map(String::as_str));
+
+    let mut index = String::from(
+        "<html>\n<head><title>Index of /</title></head>\n<body>\n<h1>Index of /</h1><hr><pre>\n",
+    );
+    for name in listed {
+        let name = HtmlEscaped(name);
+        writeln!(index, "<a href=\"{name}\">{name}</a>")?;
+    }
+    index.push_str("</pre><hr></body>\n</html>\n");
+    fs::write(dir.join(INDEX_FILE), index)?;
+
+    Ok(())
+}
+
+/// Returns the SHA-256 of the file at `path`, as listed in `checksums.txt`.
+pub(crate) fn file_checksum(path: &Path) -> Result<B256> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut io::BufReader::new(fs::open(path)?), &mut hasher)?;
+    Ok(B256::from(<[u8; 32]>::from(hasher.finalize())))
+}
+
+/// Displays a string with the characters HTML gives a meaning escaped, so it can be placed in
+/// element content and quoted attribute values.
+#[derive(Debug)]
+struct HtmlEscaped<'a>(&'a str);
+
+impl fmt::Display for HtmlEscaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&#39;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn listing_is_accepted_by_downloader() {
+        let dir = tempdir().unwrap();
+        let names = ["mainnet-00000-00000000.era1", "mainnet-00001-00000000.era1"];
+        for (i, name) in names.iter().enumerate() {
+            fs::write(dir.path().join(name), [i as u8; 32]).unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "not an era file").unwrap();
+
+        write_listing(dir.path(), EraFileType::Era1).unwrap();
+
+        let index = fs::read_to_string(dir.path().join(INDEX_FILE)).unwrap();
+        assert!(index.contains("<a href=\"checksums.txt\">"));
+        assert!(names.iter().all(|name| index.contains(&format!("<a href=\"{name}\">"))));
+        assert!(!index.contains("notes.txt"));
+
+        let files = reth_era_downloader::read_dir(dir.path().to_path_buf(), 0)
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(files.len(), 2);
+        assert!(files.into_iter().all(|file| file.is_ok()));
+    }
+
+    #[test]
+    fn escapes_html_in_names() {
+        assert_eq!(
+            HtmlEscaped("a\"><script>&'").to_string(),
+            "a&quot;&gt;&lt;script&gt;&amp;&#39;"
+        );
+        assert_eq!(
+            HtmlEscaped("mainnet-00000-00000000.era1").to_string(),
+            "mainnet-00000-00000000.era1"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Cross-checks exported accumulators against the published mainnet epoch accumulators.
+
+use super::ExportConfig;
+use alloy_primitives::{hex, BlockNumber, B256};
+use eyre::{eyre, Result};
+use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
+use std::sync::LazyLock;
+
+/// First four bytes of each mainnet epoch accumulator root, indexed by era, as carried by the
+/// names of the canonical mainnet `.era1` files.
+static SHORT_HASHES: LazyLock<Vec<[u8; 4]>> = LazyLock::new(|| {
+    include_str!("mainnet_short_hashes.txt")
+        .lines()
+        .map(|line| {
+            hex::decode(line)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .expect("bundled short hashes are four hex bytes")
+        })
+        .collect()
+});
+
+/// Full mainnet epoch accumulator roots vetted in the bundled registry, by era.
+static REGISTRY: LazyLock<crate::ChecksumRegistry> = LazyLock::new(|| {
+    crate::ChecksumRegistry::bundled("mainnet").expect("mainnet registry is bundled")
+});
+
+/// Checks the accumulator `root` built from `records` header records starting at `first_block`
+/// against the known mainnet root of that epoch.
+///
+/// The root is compared in full where the bundled registry knows it, and by the first four bytes
+/// carried by canonical file names otherwise. Only a `mainnet` export covering a whole epoch, or
+/// the last pre-merge epoch up to [`ExportConfig::merge_block`], can be checked; anything else
+/// passes.
+pub(crate) fn check_accumulator_root(
+    config: &ExportConfig,
+    first_block: BlockNumber,
+    records: usize,
+    root: B256,
+) -> Result<()> {
+    let epoch_size = MAX_BLOCKS_PER_ERA1 as u64;
+    if config.network != "mainnet" || !first_block.is_multiple_of(epoch_size) {
+        return Ok(())
+    }
+    let last_block = first_block + records as u64;
+    if records as u64 != epoch_size && config.merge_block != Some(last_block) {
+        return Ok(())
+    }
+    let era = first_block / epoch_size;
+
+    if let Some(expected) = REGISTRY.get(era).and_then(|known| known.accumulator_root) {
+        if root != expected {
+            return Err(eyre!(
+                "Accumulator root {root} of mainnet era {era} does not match the published root \
+                 {expected}"
+            ));
+        }
+        return Ok(())
+    }
+
+    let Some(expected) = SHORT_HASHES.get(era as usize) else { return Ok(()) };
+    if super::short_hash(root) != *expected {
+        return Err(eyre!(
+            "Accumulator root {root} of mainnet era {era} does not match the published root \
+             starting with {}",
+            hex::encode_prefixed(expected)
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_whole_mainnet_epochs_only() {
+        let root = B256::repeat_byte(0xab);
+        let config = ExportConfig { merge_block: Some(15_537_394), ..Default::default() };
+
+        let err = check_accumulator_root(&config, 0, 8192, root).unwrap_err().to_string();
+        assert!(err.contains("mainnet era 0"), "{err}");
+        let err = check_accumulator_root(&config, 1896 * 8192, 5362, root).unwrap_err().to_string();
+        assert!(err.contains("mainnet era 1896"), "{err}");
+
+        let mut known = B256::ZERO;
+        known[..4].copy_from_slice(&hex::decode("5ec1ffb8").unwrap());
+        assert!(check_accumulator_root(&config, 0, 8192, known).is_ok());
+
+        // Without the merge block, the last pre-merge epoch isn't known to be whole.
+        let unknown_merge = ExportConfig::default();
+        assert!(check_accumulator_root(&unknown_merge, 1896 * 8192, 5362, root).is_ok());
+
+        assert!(check_accumulator_root(&config, 0, 250, root).is_ok());
+        assert!(check_accumulator_root(&config, 100, 8192, root).is_ok());
+        let sepolia = ExportConfig { network: "sepolia".to_string(), ..config };
+        assert!(check_accumulator_root(&sepolia, 0, 8192, root).is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+//! Manifest of the files an export has completed, so an interrupted export can be resumed.
+//!
+//! Files are written in place, so a killed export can leave a truncated file under its final name.
+//! Each file is listed once it is fully written, with its checksum, letting a later export tell
+//! complete files from partial ones.
+
+use super::{
+    checksum::{Checksum, ChecksumAlgorithm},
+    ArchiveProfile, ExportConfig,
+};
+use alloy_primitives::BlockNumber;
+use eyre::{eyre, Result};
+use reth_fs_util as fs;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+/// Name of the manifest in the export directory.
+const MANIFEST_FILE: &str = "export-manifest.txt";
+
+/// A file listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    /// Last block of the chunk the file was written from.
+    last_block: BlockNumber,
+    /// Checksum of the whole file.
+    checksum: Checksum,
+    /// File name within the export directory.
+    name: String,
+    /// What the file holds.
+    profile: ArchiveProfile,
+}
+
+/// The files completed in an export directory, keyed by the first block of the chunk each was
+/// written from.
+///
+/// Stored one file per line as `<checksum> <first>-<last> <name>`, where the checksum is a SHA-256
+/// or, prefixed with `xxh3:`, an XXH3. Files of an [`ArchiveProfile::BlocksOnly`] export are
+/// flagged with a trailing ` noreceipts`, so resuming a full export rewrites them.
+#[derive(Debug)]
+pub(crate) struct Manifest {
+    dir: PathBuf,
+    entries: BTreeMap<BlockNumber, Entry>,
+}
+
+impl Manifest {
+    /// Reads the manifest of `dir`, which is empty if there is none.
+    ///
+    /// Lines that don't parse are skipped with a warning, leaving their files to be rewritten.
+    pub(crate) fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let mut entries = BTreeMap::new();
+        if path.exists() {
+            for line in fs::read_to_string(&path)?.lines().filter(|line| !line.is_empty()) {
+                match parse_line(line) {
+                    Some((first_block, entry)) => {
+                        entries.insert(first_block, entry);
+                    }
+                    None => {
+                        warn!(target: "era::history::export", line, "Skipping malformed export manifest line")
+                    }
+                }
+            }
+        }
+        Ok(Self { dir: dir.to_path_buf(), entries })
+    }
+
+    /// Lists `file`, written from the chunk `blocks` with `profile`, with its checksum taken with
+    /// `algorithm`, replacing entries of chunks it overlaps. Returns the checksum.
+    pub(crate) fn record(
+        &mut self,
+        file: &Path,
+        blocks: &RangeInclusive<BlockNumber>,
+        algorithm: ChecksumAlgorithm,
+        profile: ArchiveProfile,
+    ) -> Result<Checksum> {
+        let name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| eyre!("Exported file {} has no file name", file.display()))?;
+        let checksum = Checksum::of_file(file, algorithm)?;
+
+        self.entries
+            .retain(|first, entry| entry.last_block < *blocks.start() || first > blocks.end());
+        self.entries.insert(
+            *blocks.start(),
+            Entry { last_block: *blocks.end(), checksum, name: name.to_owned(), profile },
+        );
+        self.save()?;
+        Ok(checksum)
+    }
+
+    /// Returns the first of `chunks` whose file is not complete, i.e. not listed for that exact
+    /// chunk and `profile`, missing or failing its checksum, or `None` if every file is complete.
+    pub(crate) fn first_incomplete(
+        &self,
+        chunks: impl IntoIterator<Item = RangeInclusive<BlockNumber>>,
+        profile: ArchiveProfile,
+    ) -> Result<Option<BlockNumber>> {
+        for chunk in chunks {
+            if !self.is_complete(&chunk, profile)? {
+                return Ok(Some(*chunk.start()))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Drops the entries of chunks starting at `first_block` or later, whose files are about to be
+    /// rewritten.
+    fn truncate(&mut self, first_block: BlockNumber) -> Result<()> {
+        self.entries.retain(|first, _| *first < first_block);
+        self.save()
+    }
+
+    fn is_complete(
+        &self,
+        chunk: &RangeInclusive<BlockNumber>,
+        profile: ArchiveProfile,
+    ) -> Result<bool> {
+        let Some(entry) = self.entries.get(chunk.start()) else { return Ok(false) };
+        if entry.last_block != *chunk.end() || entry.profile != profile {
+            return Ok(false)
+        }
+
+        let path = self.dir.join(&entry.name);
+        Ok(path.exists() && Checksum::of_file(&path, entry.checksum.algorithm())? == entry.checksum)
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut contents = String::new();
+        for (first_block, entry) in &self.entries {
+            contents.push_str(&format!(
+                "{} {first_block}-{} {}",
+                entry.checksum, entry.last_block, entry.name
+            ));
+            if let Some(flag) = entry.profile.manifest_flag() {
+                contents.push(' ');
+                contents.push_str(flag);
+            }
+            contents.push('\n');
+        }
+
+        // Replaced whole, so an interrupted save leaves the previous manifest in place.
+        fs::atomic_write_file(&self.dir.join(MANIFEST_FILE), |file| {
+            file.write_all(contents.as_bytes())
+        })?;
+        Ok(())
+    }
+}
+
+/// Parses a manifest line into the first block of its chunk and its entry.
+fn parse_line(line: &str) -> Option<(BlockNumber, Entry)> {
+    let mut fields = line.split(' ');
+    let checksum = fields.next()?.parse().ok()?;
+    let (first_block, last_block) = fields.next()?.split_once('-')?;
+    let name = fields.next()?.to_owned();
+    let profile = match fields.next() {
+        Some(flag) => ArchiveProfile::from_manifest_flag(flag)?,
+        None => ArchiveProfile::Full,
+    };
+    if fields.next().is_some() {
+        return None
+    }
+    Some((
+        first_block.parse().ok()?,
+        Entry { last_block: last_block.parse().ok()?, checksum, name, profile },
+    ))
+}
+
+/// Moves the start of `config` past the files `manifest` lists as complete, up to `last_block`.
+///
+/// Returns `None` if every file of the export is complete. Unless [`ExportConfig::resume`] is set,
+/// `config` is returned as is.
+pub(crate) fn resume(
+    config: &ExportConfig,
+    last_block: BlockNumber,
+    manifest: &mut Manifest,
+) -> Result<Option<ExportConfig>> {
+    if !config.resume {
+        return Ok(Some(config.clone()))
+    }
+
+    let first_block =
+        manifest.first_incomplete(super::chunk_ranges(config, last_block), config.profile)?;
+    if let Some(first_block) = first_block {
+        manifest.truncate(first_block)?;
+    }
+    match first_block {
+        Some(first_block) if first_block > config.first_block_number => {
+            info!(
+                target: "era::history::export",
+                skipped = first_block - config.first_block_number,
+                first_block,
+                "Resuming export past complete files"
+            );
+        }
+        Some(_) => {}
+        None => {
+            info!(target: "era::history::export", "Every file of the export is already complete");
+        }
+    }
+
+    Ok(first_block.map(|first_block_number| ExportConfig { first_block_number, ..config.clone() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn resumes_at_first_missing_or_partial_file() {
+        let dir = tempdir().unwrap();
+        let chunks = [0..=9, 10..=19, 20..=29];
+        let mut manifest = Manifest::open(dir.path()).unwrap();
+        // Entries taken with either hash are checked with the one they were listed with.
+        let algorithms =
+            [ChecksumAlgorithm::Sha256, ChecksumAlgorithm::Xxh3, ChecksumAlgorithm::Xxh3];
+        for (index, (chunk, algorithm)) in chunks.iter().zip(algorithms).enumerate() {
+            let file = dir.path().join(format!("chunk-{index}.era1"));
+            fs::write(&file, [index as u8; 16]).unwrap();
+            manifest.record(&file, chunk, algorithm, ArchiveProfile::Full).unwrap();
+        }
+
+        let config = ExportConfig {
+            dir: dir.path().to_path_buf(),
+            max_blocks_per_file: 10,
+            resume: true,
+            ..Default::default()
+        };
+        let mut manifest = Manifest::open(dir.path()).unwrap();
+        assert_eq!(manifest.first_incomplete(chunks.clone(), ArchiveProfile::Full).unwrap(), None);
+        assert!(resume(&config, 29, &mut manifest).unwrap().is_none());
+
+        // A file truncated by an interrupted rewrite no longer matches its checksum.
+        fs::write(dir.path().join("chunk-1.era1"), [1u8; 4]).unwrap();
+        assert_eq!(
+            manifest.first_incomplete(chunks.clone(), ArchiveProfile::Full).unwrap(),
+            Some(10)
+        );
+        assert_eq!(Manifest::open(dir.path()).unwrap().entries.len(), 3);
+
+        // Resuming drops the entries from the incomplete file on.
+        let resumed = resume(&config, 29, &mut manifest).unwrap().unwrap();
+        assert_eq!(resumed.first_block_number, 10);
+        let manifest = Manifest::open(dir.path()).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.first_incomplete(chunks, ArchiveProfile::Full).unwrap(), Some(10));
+    }
+
+    #[test]
+    fn flags_blocks_only_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("mainnet-00000-4bb7de2e-noproofs-noreceipts.ere");
+        fs::write(&file, [0u8; 16]).unwrap();
+        let mut manifest = Manifest::open(dir.path()).unwrap();
+        manifest
+            .record(&file, &(0..=9), ChecksumAlgorithm::Xxh3, ArchiveProfile::BlocksOnly)
+            .unwrap();
+
+        let contents = fs::read_to_string(dir.path().join(MANIFEST_FILE)).unwrap();
+        assert!(contents.ends_with("-noreceipts.ere noreceipts\n"), "{contents}");
+
+        // Blocks-only files don't complete a full export of the same chunk.
+        let manifest = Manifest::open(dir.path()).unwrap();
+        assert_eq!(manifest.first_incomplete([0..=9], ArchiveProfile::BlocksOnly).unwrap(), None);
+        assert_eq!(manifest.first_incomplete([0..=9], ArchiveProfile::Full).unwrap(), Some(0));
+    }
+}
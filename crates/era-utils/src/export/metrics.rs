@@ -0,0 +1,21 @@
+use reth_metrics::{
+    metrics::{Counter, Histogram},
+    Metrics,
+};
+
+/// Metrics of the files written by [`export`](super::export) and
+/// [`export_parallel`](super::export_parallel).
+///
+/// Reported to the node's metrics recorder under the `era.export` scope.
+#[derive(Clone, Metrics)]
+#[metrics(scope = "era.export")]
+pub(crate) struct ExportMetrics {
+    /// Number of files written
+    pub(crate) files_written: Counter,
+    /// Number of blocks written into files
+    pub(crate) blocks_exported: Counter,
+    /// Total size of the written files, in bytes
+    pub(crate) bytes_written: Counter,
+    /// Time taken to read each file's blocks from storage and write it
+    pub(crate) file_duration_seconds: Histogram,
+}
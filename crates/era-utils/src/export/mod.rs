@@ -3,27 +3,81 @@
 //! [`export`] is the format-agnostic driver: it resolves the export range, walks the requested
 //! blocks in `max_blocks_per_file` chunks, and hands each chunk to an [`EraBlockWriter`]. A writer
 //! turns one chunk of [`ExportBlock`]s into one on-disk file and owns every format-specific detail
-//! (receipt encoding, accumulator, block index, record layout, file naming).
+//! (receipt encoding, accumulator, block index, record layout, file naming). Accumulators of
+//! `mainnet` files spanning a whole pre-merge epoch are checked against the published roots.
 //!
-//! [`Era1`](crate::Era1) writes `.era1` files and [`Ere`](crate::Ere) writes `.ere` files.
-
+//! [`Era1`](crate::Era1) writes `.era1` files, [`Ere`](crate::Ere) writes `.ere` files and
+//! [`E2hs`] writes Portal Network `.e2hs` files.
+//! Alongside the files, [`export`] keeps a JSON report of each file's blocks, size, checksum and
+//! build time in `export-report.json`, and reports them to the node's metrics. With the
+//! `encryption` feature, files can also be encrypted at rest as they are completed.
+//! Consensus-layer `.era` files are written separately by [`export_consensus`], as their content
+//! comes from a [`BeaconSource`] rather than from storage.
+
+mod checksum;
+mod cid;
+mod e2hs;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod era;
 mod era1;
 mod ere;
-
-use crate::calculate_td_by_number;
+mod listing;
+mod mainnet;
+mod manifest;
+mod metrics;
+mod naming;
+mod profile;
+mod recover;
+mod repack;
+mod report;
+#[cfg(feature = "server")]
+mod server;
+mod timestamps;
+mod tuning;
+mod watch;
+
+pub use checksum::ChecksumAlgorithm;
+pub use cid::{file_cid, write_cids};
+pub use e2hs::{
+    header_with_proof, headers_with_proofs, inclusion_proofs, is_valid_merkle_branch, E2hs,
+    HeaderInclusionProof, HeaderWithProof,
+};
+#[cfg(feature = "encryption")]
+pub use encryption::{decrypt_file, EncryptionKey};
+pub use era::{export_consensus, BeaconSource, ConsensusExportConfig};
+pub use naming::FileNaming;
+pub use profile::ArchiveProfile;
+pub use recover::{recover_era1, Era1Recovery};
+pub use repack::{merge_era1, recompress_era1, split_era1};
+#[cfg(feature = "server")]
+pub use server::EraFileServer;
+pub use timestamps::blocks_in_time_range;
+pub use tuning::ExportTuning;
+pub use watch::FinalizedExporter;
+
+use crate::{calculate_td_by_number, shutdown::is_shutting_down, verify_pre_merge};
 use alloy_consensus::{BlockHeader, Sealable};
 use alloy_primitives::{BlockNumber, B256, U256};
 use alloy_rlp::Encodable;
 use eyre::{eyre, Result};
-use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
+pub(crate) use listing::{file_checksum, write_listing};
+use manifest::Manifest;
+use report::ExportReport;
+use reth_era::{common::file_ops::EraFileType, era1::types::execution::MAX_BLOCKS_PER_ERA1};
 use reth_fs_util as fs;
 use reth_primitives_traits::{Block, Receipt};
-use reth_storage_api::{BlockNumReader, BlockReader, HeaderProvider, ReceiptProvider};
+use reth_storage_api::{
+    BlockNumReader, BlockReader, DatabaseProviderFactory, HeaderProvider, ReceiptProvider,
+};
+use reth_tasks::shutdown::Shutdown;
 use std::{
-    path::{Path, PathBuf},
+    ops::RangeInclusive,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 use tracing::{info, warn};
+pub(crate) use tuning::map_parallel;
 
 /// Minimum delay between export progress log lines, so large exports report periodically without
 /// flooding the logs.
@@ -41,12 +95,86 @@ pub struct ExportConfig {
     pub first_block_number: BlockNumber,
     /// Last block to export
     pub last_block_number: BlockNumber,
+    /// Block timestamps to export, in seconds since the Unix epoch, both ends included.
+    ///
+    /// When set, [`ExportConfig::first_block_number`] and [`ExportConfig::last_block_number`] are
+    /// ignored, and the stored blocks whose timestamp falls within the range are exported instead,
+    /// as found by [`blocks_in_time_range`]. Ignored by [`FinalizedExporter`].
+    pub time_range: Option<RangeInclusive<u64>>,
     /// Number of blocks per ERA file.
     /// It can never be larger than `MAX_BLOCKS_PER_ERA1 = 8192`
     /// See also <`https://github.com/eth-clients/e2store-format-specs/blob/main/formats/era1.md`>
     pub max_blocks_per_file: u64,
     /// Network name.
     pub network: String,
+    /// Whether to also write an `index.html` listing and a `checksums.txt` for the files in
+    /// [`ExportConfig::dir`], so it can be served to other nodes' era downloaders as-is.
+    ///
+    /// Ignored for formats the era downloader doesn't fetch, see [`EraBlockWriter::FILE_TYPE`].
+    pub write_listing: bool,
+    /// Whether to also write a `cids.txt` mapping each file in [`ExportConfig::dir`] to its IPFS
+    /// CID, as [`write_cids`] does, so the directory can be published to IPFS.
+    ///
+    /// Ignored for formats without an [`EraBlockWriter::FILE_TYPE`].
+    pub write_cids: bool,
+    /// How exported files are named; spec-compliant by default.
+    pub naming: FileNaming,
+    /// What each file holds; blocks with their receipts by default.
+    ///
+    /// [`ArchiveProfile::BlocksOnly`] exports skip reading receipts altogether. Only `.ere` files
+    /// support it; `.era1` writers refuse it.
+    pub profile: ArchiveProfile,
+    /// Whether to only produce files laid out as the spec requires: whole `8192`-block eras
+    /// starting on an era boundary.
+    ///
+    /// [`ExportConfig::validate`] rejects ranges and chunk sizes that break this, and the export
+    /// stops at the last whole era if the node's head is short of
+    /// [`ExportConfig::last_block_number`]. Otherwise chunks are free-form.
+    pub spec_compliant: bool,
+    /// Compression and write-buffer tuning.
+    pub tuning: ExportTuning,
+    /// Total difficulty at which the chain merged into proof-of-stake, from the chain spec.
+    ///
+    /// When set, pre-merge formats refuse to hold anything past the merge: every block must
+    /// extend a chain short of it, the file holding the merge is cut at the terminal block, which
+    /// must reach it, and chunks starting past the merge are rejected. When `None`, `.era1` files
+    /// hold whatever blocks they are handed.
+    pub terminal_total_difficulty: Option<U256>,
+    /// First proof-of-stake block, the chain spec's Paris activation block.
+    ///
+    /// The last pre-merge epoch ends before it, so `mainnet` files cut there can still be checked
+    /// against the published root of their epoch. When `None`, only full epochs are checked.
+    pub merge_block: Option<BlockNumber>,
+    /// Whether to skip the files an earlier, interrupted export into [`ExportConfig::dir`]
+    /// completed, resuming at the first file that is missing or doesn't match the checksum it was
+    /// listed with.
+    ///
+    /// Every export lists the files it completes in an `export-manifest.txt` in the directory, so
+    /// it can be resumed later. Only files written from the same chunks as this export are
+    /// skipped.
+    pub resume: bool,
+    /// Hash the export manifest lists completed files with; SHA-256 by default.
+    ///
+    /// [`ChecksumAlgorithm::Xxh3`] makes listing and resuming much faster where files only move
+    /// between the operator's own nodes. Files published with [`ExportConfig::write_listing`] are
+    /// still listed with SHA-256 in their `checksums.txt`.
+    pub checksum: ChecksumAlgorithm,
+    /// Key to encrypt exported files with at rest, for archives kept on shared or third-party
+    /// storage. When `None`, files are written in the clear.
+    ///
+    /// Each file is replaced by its AES-256-GCM encryption under an `.enc` suffix, next to an
+    /// `.enc.json` key descriptor naming the key by [`EncryptionKey::id`], and decrypted with
+    /// [`decrypt_file`]. The export manifest and report list the encrypted files. Encrypted
+    /// files can't be served to era downloaders, so [`ExportConfig::write_listing`] and
+    /// [`ExportConfig::write_cids`] are refused with it.
+    #[cfg(feature = "encryption")]
+    pub encryption: Option<EncryptionKey>,
+    /// Shutdown that stops the export early once fired, such as the node's on Ctrl-C.
+    ///
+    /// Files already started are still finished and listed in the manifest, so an export with
+    /// [`ExportConfig::resume`] picks up after them. Dropping the
+    /// [`Signal`](reth_tasks::shutdown::Signal) counts as firing it.
+    pub shutdown: Option<Shutdown>,
 }
 
 impl Default for ExportConfig {
@@ -55,8 +183,22 @@ impl Default for ExportConfig {
             dir: PathBuf::new(),
             first_block_number: 0,
             last_block_number: (MAX_BLOCKS_PER_ERA1 - 1) as u64,
+            time_range: None,
             max_blocks_per_file: MAX_BLOCKS_PER_ERA1 as u64,
             network: "mainnet".to_string(),
+            write_listing: false,
+            write_cids: false,
+            naming: FileNaming::default(),
+            profile: ArchiveProfile::Full,
+            spec_compliant: false,
+            tuning: ExportTuning::default(),
+            terminal_total_difficulty: None,
+            merge_block: None,
+            resume: false,
+            checksum: ChecksumAlgorithm::Sha256,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+            shutdown: None,
         }
     }
 }
@@ -76,6 +218,53 @@ impl ExportConfig {
             return Err(eyre!("Max blocks per file cannot be zero"));
         }
 
+        if self.first_block_number > self.last_block_number {
+            return Err(eyre!(
+                "First block ({}) is after last block ({})",
+                self.first_block_number,
+                self.last_block_number
+            ));
+        }
+
+        if let Some(timestamps) = &self.time_range &&
+            timestamps.is_empty()
+        {
+            return Err(eyre!(
+                "First timestamp ({}) is after last timestamp ({})",
+                timestamps.start(),
+                timestamps.end()
+            ));
+        }
+
+        #[cfg(feature = "encryption")]
+        if self.encryption.is_some() && (self.write_listing || self.write_cids) {
+            return Err(eyre!(
+                "Encrypted files can't be listed for era downloaders or published to IPFS"
+            ));
+        }
+
+        if self.spec_compliant {
+            let era_size = MAX_BLOCKS_PER_ERA1 as u64;
+            if self.max_blocks_per_file != era_size {
+                return Err(eyre!(
+                    "Spec-compliant exports need {era_size} blocks per file, got {}",
+                    self.max_blocks_per_file
+                ));
+            }
+            if !self.first_block_number.is_multiple_of(era_size) {
+                return Err(eyre!(
+                    "Spec-compliant exports must start on an era boundary, got block {}",
+                    self.first_block_number
+                ));
+            }
+            if !(self.last_block_number + 1).is_multiple_of(era_size) {
+                return Err(eyre!(
+                    "Spec-compliant exports must end on an era boundary, got block {}",
+                    self.last_block_number
+                ));
+            }
+        }
+
         Ok(())
     }
 }
@@ -91,6 +280,8 @@ pub struct ExportBlock<H, B, R> {
     pub body: B,
     /// Block receipts in the provider's native, bloom-bearing form. A writer re-encodes them into
     /// its own form (`era1` keeps the bloom, `ere` stores the slim variant).
+    ///
+    /// Empty in [`ArchiveProfile::BlocksOnly`] exports.
     pub receipts: Vec<R>,
     /// Total difficulty up to and including this block.
     pub total_difficulty: U256,
@@ -112,19 +303,23 @@ impl<H, B, R> ExportBlock<H, B, R> {
 /// One implementor exists per ERA format. A chunk is ordered, non-empty, and at most
 /// [`ExportConfig::max_blocks_per_file`] blocks long.
 pub trait EraBlockWriter {
-    /// Writes `blocks` as a single ERA file in `dir`, returning the created file's path.
+    /// The format of the written files, `None` for formats the era downloader doesn't fetch, which
+    /// can't be listed for it.
+    const FILE_TYPE: Option<EraFileType>;
+
+    /// Writes `blocks` as a single ERA file in [`ExportConfig::dir`], returning the created file's
+    /// path.
     ///
-    /// `max_blocks_per_file` is the configured per-file ceiling; a writer compares it against its
-    /// own format limit to decide whether the filename carries an era-count segment.
+    /// A writer compares [`ExportConfig::max_blocks_per_file`] against its own format limit to
+    /// decide whether the filename carries an era-count segment, and renders the name through
+    /// [`ExportConfig::naming`].
     fn write_file<H, B, R>(
-        network: &str,
-        max_blocks_per_file: u64,
+        config: &ExportConfig,
         blocks: &[ExportBlock<H, B, R>],
-        dir: &Path,
     ) -> Result<PathBuf>
     where
-        H: BlockHeader + Encodable,
-        B: Encodable,
+        H: BlockHeader + Encodable + Sync,
+        B: Encodable + Sync,
         R: Receipt;
 }
 
@@ -148,6 +343,39 @@ where
     A::from_pairs(&records)
 }
 
+/// Returns how many leading blocks of `blocks` precede the merge, the rest carrying zero
+/// difficulty.
+///
+/// With [`ExportConfig::terminal_total_difficulty`] set, each of those blocks is checked with
+/// [`verify_pre_merge`], and if the chunk reaches the merge its last pre-merge block must be the
+/// terminal block.
+fn pre_merge_len<H: BlockHeader, B, R>(
+    config: &ExportConfig,
+    blocks: &[ExportBlock<H, B, R>],
+) -> Result<usize> {
+    let pre_merge = blocks.partition_point(|b| !b.header.difficulty().is_zero());
+    let Some(terminal_total_difficulty) = config.terminal_total_difficulty else {
+        return Ok(pre_merge)
+    };
+
+    for block in &blocks[..pre_merge] {
+        verify_pre_merge(&block.header, block.total_difficulty, terminal_total_difficulty)?;
+    }
+    if let Some(terminal) = pre_merge.checked_sub(1).filter(|_| pre_merge < blocks.len()) {
+        let terminal = &blocks[terminal];
+        if terminal.total_difficulty < terminal_total_difficulty {
+            return Err(eyre!(
+                "Block {} precedes the merge at total difficulty {}, short of the terminal total \
+                 difficulty {terminal_total_difficulty}",
+                terminal.header.number(),
+                terminal.total_difficulty
+            ));
+        }
+    }
+
+    Ok(pre_merge)
+}
+
 /// A chunk of [`ExportBlock`]s sourced from provider `P`.
 type Chunk<P> =
     Vec<ExportBlock<<P as HeaderProvider>::Header, BodyOf<P>, <P as ReceiptProvider>::Receipt>>;
@@ -158,7 +386,11 @@ type BodyOf<P> = <<P as BlockReader>::Block as Block>::Body;
 /// Fetches block history from `provider` and writes it to ERA files in the `W` format, chunked by
 /// [`ExportConfig::max_blocks_per_file`].
 ///
-/// Returns the paths of the files that were created.
+/// Each file is listed in the export manifest and the `export-report.json` report as it is
+/// completed. Once [`ExportConfig::shutdown`] fires, the export stops before the next file.
+///
+/// Returns the paths of the files that were created, leaving out those skipped by
+/// [`ExportConfig::resume`].
 pub fn export<W, P>(provider: &P, config: &ExportConfig) -> Result<Vec<PathBuf>>
 where
     W: EraBlockWriter,
@@ -166,12 +398,180 @@ where
     P::Header: BlockHeader + Sealable + Encodable,
     BodyOf<P>: Encodable,
     P::Receipt: Receipt,
+{
+    let config = &timestamps::resolve(provider, config)?;
+    let last_block = prepare_export(provider, config)?;
+    let mut manifest = Manifest::open(&config.dir)?;
+    let Some(config) = &manifest::resume(config, last_block, &mut manifest)? else {
+        return finish_export::<W>(config, Vec::new(), &ExportProgress::new(0))
+    };
+
+    let mut report = ExportReport::open(config)?;
+    let mut progress = ExportProgress::new(last_block - config.first_block_number + 1);
+    let mut total_difficulty = seed_total_difficulty(provider, config)?;
+    let mut created_files = Vec::new();
+
+    for range in chunk_ranges(config, last_block) {
+        if stops_on_shutdown(config, *range.start()) {
+            break;
+        }
+
+        let started = Instant::now();
+        let end_block = *range.end();
+        let mut blocks = gather_chunk(provider, range.clone(), config.profile)?;
+        accumulate_total_difficulty(&mut blocks, &mut total_difficulty);
+        progress.record(blocks.len() as u64, end_block, last_block);
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let file_path = write_file::<W, _, _, _>(config, &blocks)?;
+        let checksum = manifest.record(&file_path, &range, config.checksum, config.profile)?;
+        report.record(&file_path, &range, checksum, config.profile, started.elapsed())?;
+
+        info!(target: "era::history::export", "Wrote ERA file: {file_path:?} with {} blocks", blocks.len());
+        created_files.push(file_path);
+    }
+
+    finish_export::<W>(config, created_files, &progress)
+}
+
+/// Like [`export`], but builds up to [`ExportTuning::parallel_files`] files at once, each read
+/// from its own read-only provider opened from `provider_factory`.
+///
+/// Files are finalized in block order: if one fails, the files built after it in the same batch
+/// are removed, so the directory never holds a gap. Once [`ExportConfig::shutdown`] fires, the
+/// export stops before the next batch.
+///
+/// Returns the paths of the files that were created.
+pub fn export_parallel<W, PF>(provider_factory: &PF, config: &ExportConfig) -> Result<Vec<PathBuf>>
+where
+    W: EraBlockWriter,
+    PF: DatabaseProviderFactory<Provider: BlockReader>,
+    <PF::Provider as HeaderProvider>::Header: BlockHeader + Sealable + Encodable,
+    BodyOf<PF::Provider>: Encodable,
+    <PF::Provider as ReceiptProvider>::Receipt: Receipt,
+{
+    let provider = provider_factory.database_provider_ro()?;
+    let config = &timestamps::resolve(&provider, config)?;
+    let last_block = prepare_export(&provider, config)?;
+    let mut manifest = Manifest::open(&config.dir)?;
+    let Some(config) = &manifest::resume(config, last_block, &mut manifest)? else {
+        return finish_export::<W>(config, Vec::new(), &ExportProgress::new(0))
+    };
+    let mut total_difficulty = seed_total_difficulty(&provider, config)?;
+    drop(provider);
+
+    let mut report = ExportReport::open(config)?;
+    let mut progress = ExportProgress::new(last_block - config.first_block_number + 1);
+    let mut created_files = Vec::new();
+
+    let ranges = chunk_ranges(config, last_block).collect::<Vec<_>>();
+    for batch in ranges.chunks(config.tuning.parallel_files.get()) {
+        if stops_on_shutdown(config, *batch[0].start()) {
+            break;
+        }
+
+        let mut chunks = map_parallel(config.tuning.parallel_files, batch, |range| -> Result<_> {
+            let started = Instant::now();
+            let provider = provider_factory.database_provider_ro()?;
+            Ok((gather_chunk(&provider, range.clone(), config.profile)?, started.elapsed()))
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        // Total difficulty runs across files, so it is threaded through the batch in block order.
+        for ((blocks, _), range) in chunks.iter_mut().zip(batch) {
+            accumulate_total_difficulty(blocks, &mut total_difficulty);
+            progress.record(blocks.len() as u64, *range.end(), last_block);
+        }
+
+        let (written, durations): (Vec<_>, Vec<_>) =
+            map_parallel(config.tuning.parallel_files, &chunks, |(blocks, gathering)| {
+                let started = Instant::now();
+                let written =
+                    (!blocks.is_empty()).then(|| write_file::<W, _, _, _>(config, blocks));
+                (written.transpose(), *gathering + started.elapsed())
+            })
+            .into_iter()
+            .unzip();
+        let finalized = created_files.len();
+        let result = finalize_in_order(written, &mut created_files);
+        // Files kept before a failure are complete, so they are listed either way.
+        let written_ranges = chunks
+            .iter()
+            .zip(batch)
+            .zip(durations)
+            .filter(|(((blocks, _), _), _)| !blocks.is_empty())
+            .map(|((_, range), duration)| (range, duration));
+        for (file_path, (range, duration)) in created_files[finalized..].iter().zip(written_ranges)
+        {
+            let checksum = manifest.record(file_path, range, config.checksum, config.profile)?;
+            report.record(file_path, range, checksum, config.profile, duration)?;
+        }
+        result?;
+    }
+
+    finish_export::<W>(config, created_files, &progress)
+}
+
+/// Returns the block ranges of the files [`export`] would write with `config`, in order, without
+/// writing anything.
+///
+/// Files [`ExportConfig::resume`] would skip are left out. A range holding no blocks yields no
+/// file once exported.
+pub fn plan_export<P>(
+    provider: &P,
+    config: &ExportConfig,
+) -> Result<Vec<RangeInclusive<BlockNumber>>>
+where
+    P: HeaderProvider + BlockNumReader,
+{
+    let config = &timestamps::resolve(provider, config)?;
+    let last_block = last_export_block(provider, config)?;
+    let first_block = if config.resume {
+        Manifest::open(&config.dir)?
+            .first_incomplete(chunk_ranges(config, last_block), config.profile)?
+    } else {
+        Some(config.first_block_number)
+    };
+
+    Ok(first_block
+        .map(|first_block_number| {
+            chunk_ranges(&ExportConfig { first_block_number, ..config.clone() }, last_block)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Validates `config`, resolves the last block to export and creates the output directory.
+fn prepare_export<P>(provider: &P, config: &ExportConfig) -> Result<BlockNumber>
+where
+    P: HeaderProvider + BlockNumReader,
+{
+    let last_block = last_export_block(provider, config)?;
+
+    if !config.dir.exists() {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| eyre!("Failed to create output directory: {}", e))?;
+    }
+
+    Ok(last_block)
+}
+
+/// Validates `config` and resolves the last block to export.
+fn last_export_block<P>(provider: &P, config: &ExportConfig) -> Result<BlockNumber>
+where
+    P: HeaderProvider + BlockNumReader,
 {
     config.validate()?;
 
     // `best_block_number()` can be stale behind static files, so reconcile against what is actually
     // available.
-    let last_block = determine_export_range(provider, config)?;
+    let mut last_block = determine_export_range(provider, config)?;
+    if config.spec_compliant {
+        last_block = last_whole_era_block(config.first_block_number, last_block)?;
+    }
 
     info!(
         target: "era::history::export",
@@ -181,36 +581,25 @@ where
         "Preparing ERA export data"
     );
 
-    if !config.dir.exists() {
-        fs::create_dir_all(&config.dir)
-            .map_err(|e| eyre!("Failed to create output directory: {}", e))?;
-    }
-
-    let mut progress = ExportProgress::new(last_block - config.first_block_number + 1);
-    let mut total_difficulty = seed_total_difficulty(provider, config)?;
-    let mut created_files = Vec::new();
+    Ok(last_block)
+}
 
-    for start_block in
-        (config.first_block_number..=last_block).step_by(config.max_blocks_per_file as usize)
+/// Writes the directory listing and CIDs if requested, reports the finished export and returns
+/// `created_files`.
+fn finish_export<W: EraBlockWriter>(
+    config: &ExportConfig,
+    created_files: Vec<PathBuf>,
+    progress: &ExportProgress,
+) -> Result<Vec<PathBuf>> {
+    if config.write_listing &&
+        let Some(file_type) = W::FILE_TYPE
     {
-        let end_block = (start_block + config.max_blocks_per_file - 1).min(last_block);
-
-        let blocks = gather_chunk(
-            provider,
-            start_block..=end_block,
-            last_block,
-            &mut total_difficulty,
-            &mut progress,
-        )?;
-        if blocks.is_empty() {
-            continue;
-        }
-
-        let file_path =
-            W::write_file(&config.network, config.max_blocks_per_file, &blocks, &config.dir)?;
-
-        info!(target: "era::history::export", "Wrote ERA file: {file_path:?} with {} blocks", blocks.len());
-        created_files.push(file_path);
+        listing::write_listing(&config.dir, file_type)?;
+    }
+    if config.write_cids &&
+        let Some(file_type) = W::FILE_TYPE
+    {
+        write_cids(&config.dir, file_type)?;
     }
 
     info!(
@@ -223,6 +612,71 @@ where
     Ok(created_files)
 }
 
+/// Returns `true` if [`ExportConfig::shutdown`] has fired, in which case the export stops before
+/// `next_block`.
+fn stops_on_shutdown(config: &ExportConfig, next_block: BlockNumber) -> bool {
+    let stops = is_shutting_down(config.shutdown.as_ref());
+    if stops {
+        info!(target: "era::history::export", next_block, "Stopping ERA export on shutdown");
+    }
+    stops
+}
+
+/// The block ranges of the files making up the export, in order.
+fn chunk_ranges(
+    config: &ExportConfig,
+    last_block: BlockNumber,
+) -> impl Iterator<Item = RangeInclusive<BlockNumber>> + '_ {
+    (config.first_block_number..=last_block)
+        .step_by(config.max_blocks_per_file as usize)
+        .map(move |start| start..=(start + config.max_blocks_per_file - 1).min(last_block))
+}
+
+/// Writes `blocks` as a single file with `W`, then encrypts it if `ExportConfig::encryption` is
+/// set. Returns the path of the file as left in [`ExportConfig::dir`].
+fn write_file<W, H, B, R>(config: &ExportConfig, blocks: &[ExportBlock<H, B, R>]) -> Result<PathBuf>
+where
+    W: EraBlockWriter,
+    H: BlockHeader + Encodable + Sync,
+    B: Encodable + Sync,
+    R: Receipt,
+{
+    let file_path = W::write_file(config, blocks)?;
+    #[cfg(feature = "encryption")]
+    if let Some(key) = &config.encryption {
+        return encryption::encrypt_file(&file_path, key)
+    }
+    Ok(file_path)
+}
+
+/// Records files written concurrently into `created_files` in block order.
+///
+/// On the first failure, the files written after it are removed and the error is returned.
+fn finalize_in_order(
+    written: Vec<Result<Option<PathBuf>>>,
+    created_files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let mut written = written.into_iter();
+    while let Some(result) = written.next() {
+        match result {
+            Ok(Some(file_path)) => {
+                info!(target: "era::history::export", "Wrote ERA file: {file_path:?}");
+                created_files.push(file_path);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                for file_path in written.flatten().flatten() {
+                    if let Err(remove_err) = fs::remove_file(&file_path) {
+                        warn!(target: "era::history::export", %remove_err, "Failed to remove out-of-order ERA file {file_path:?}");
+                    }
+                }
+                return Err(err)
+            }
+        }
+    }
+    Ok(())
+}
+
 /// The four-byte short hash an ERA file name carries, taken from its accumulator root.
 fn short_hash(root: B256) -> [u8; 4] {
     root[..4].try_into().expect("root is 32 bytes")
@@ -238,14 +692,26 @@ fn seed_total_difficulty<P: BlockReader>(provider: &P, config: &ExportConfig) ->
     }
 }
 
-/// Loads the headers, bodies and receipts for `range` into a [`Chunk`], advancing
-/// `total_difficulty` by each header's difficulty.
+/// Sets each block's total difficulty, advancing the running `total_difficulty` by each header's
+/// difficulty.
+fn accumulate_total_difficulty<H: BlockHeader, B, R>(
+    blocks: &mut [ExportBlock<H, B, R>],
+    total_difficulty: &mut U256,
+) {
+    for block in blocks {
+        *total_difficulty += block.header.difficulty();
+        block.total_difficulty = *total_difficulty;
+    }
+}
+
+/// Loads the headers, bodies and, unless `profile` leaves them out, receipts for `range` into a
+/// [`Chunk`].
+///
+/// Total difficulty is left at zero, for [`accumulate_total_difficulty`] to fill in.
 fn gather_chunk<P>(
     provider: &P,
-    range: std::ops::RangeInclusive<BlockNumber>,
-    last_block: BlockNumber,
-    total_difficulty: &mut U256,
-    progress: &mut ExportProgress,
+    range: RangeInclusive<BlockNumber>,
+    profile: ArchiveProfile,
 ) -> Result<Chunk<P>>
 where
     P: BlockReader,
@@ -267,26 +733,47 @@ where
             .block_by_number(actual)?
             .ok_or_else(|| eyre!("Block not found for block {actual}"))?
             .into_body();
-        let receipts = provider
-            .receipts_by_block(actual.into())?
-            .ok_or_else(|| eyre!("Receipts not found for block {actual}"))?;
+        let receipts = if profile.has_receipts() {
+            provider
+                .receipts_by_block(actual.into())?
+                .ok_or_else(|| eyre!("Receipts not found for block {actual}"))?
+        } else {
+            Vec::new()
+        };
 
         let block_hash = header.hash_slow();
-        *total_difficulty += header.difficulty();
-
         blocks.push(ExportBlock {
             header,
             block_hash,
             body,
             receipts,
-            total_difficulty: *total_difficulty,
+            total_difficulty: U256::ZERO,
         });
-        progress.record(actual, last_block);
     }
 
     Ok(blocks)
 }
 
+/// Rounds `last_block` down to the end of the last whole era starting at `first_block`, an era
+/// boundary.
+fn last_whole_era_block(first_block: BlockNumber, last_block: BlockNumber) -> Result<BlockNumber> {
+    let era_size = MAX_BLOCKS_PER_ERA1 as u64;
+    let whole_eras = (last_block + 1 - first_block) / era_size;
+    if whole_eras == 0 {
+        return Err(eyre!(
+            "No whole era available from block {first_block}, the node's head is at {last_block}"
+        ));
+    }
+    let last_whole = first_block + whole_eras * era_size - 1;
+    if last_whole != last_block {
+        warn!(
+            target: "era::history::export",
+            "Head {last_block} is mid-era, stopping the spec-compliant export at block {last_whole}"
+        );
+    }
+    Ok(last_whole)
+}
+
 /// Determines the actual last block number that can be exported.
 ///
 /// Uses a `headers_range` fallback when `best_block_number` is stale due to static file storage.
@@ -346,9 +833,10 @@ impl ExportProgress {
         }
     }
 
-    /// Counts one processed block and logs progress at most once per [`REPORT_INTERVAL_SECS`].
-    fn record(&mut self, current_block: BlockNumber, last_block: BlockNumber) {
-        self.processed += 1;
+    /// Counts `blocks` processed blocks up to `current_block` and logs progress at most once per
+    /// [`REPORT_INTERVAL_SECS`].
+    fn record(&mut self, blocks: u64, current_block: BlockNumber, last_block: BlockNumber) {
+        self.processed += blocks;
         if self.last_report.elapsed() >= self.interval {
             info!(
                 target: "era::history::export",
@@ -367,7 +855,8 @@ impl ExportProgress {
 
 #[cfg(test)]
 mod tests {
-    use super::ExportConfig;
+    use super::{finalize_in_order, last_whole_era_block, ExportConfig};
+    use eyre::eyre;
     use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
     use tempfile::tempdir;
 
@@ -409,5 +898,98 @@ mod tests {
         let result = oversized_config.validate();
         assert!(result.is_err(), "Oversized blocks per file should fail validation");
         assert!(result.unwrap_err().to_string().contains("exceeds ERA1 limit"));
+
+        // Inverted ranges should fail
+        let inverted_config =
+            ExportConfig { first_block_number: 10, last_block_number: 9, ..Default::default() };
+        let result = inverted_config.validate();
+        assert!(result.is_err(), "Inverted range should fail validation");
+        assert!(result.unwrap_err().to_string().contains("is after last block"));
+
+        // Encrypted files can't be listed
+        #[cfg(feature = "encryption")]
+        {
+            let encrypted_config = ExportConfig {
+                encryption: Some(super::EncryptionKey::new([1; 32])),
+                write_listing: true,
+                ..Default::default()
+            };
+            let result = encrypted_config.validate();
+            assert!(result.unwrap_err().to_string().contains("Encrypted files can't be listed"));
+        }
+    }
+
+    #[test]
+    fn test_spec_compliant_export_config_validation() {
+        let era_size = MAX_BLOCKS_PER_ERA1 as u64;
+
+        // Whole eras should pass
+        let config = ExportConfig {
+            first_block_number: era_size,
+            last_block_number: 3 * era_size - 1,
+            spec_compliant: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok(), "Whole eras should pass validation");
+
+        // Partial chunks are fine unless spec compliance is requested
+        let free_form = ExportConfig {
+            first_block_number: 100,
+            last_block_number: 999,
+            max_blocks_per_file: 250,
+            ..Default::default()
+        };
+        assert!(free_form.validate().is_ok(), "Free-form chunks should pass validation");
+
+        let result = ExportConfig { spec_compliant: true, ..free_form.clone() }.validate();
+        assert!(result.unwrap_err().to_string().contains("blocks per file"));
+
+        let result =
+            ExportConfig { spec_compliant: true, max_blocks_per_file: era_size, ..free_form }
+                .validate();
+        assert!(result.unwrap_err().to_string().contains("start on an era boundary"));
+
+        let result = ExportConfig {
+            last_block_number: 2 * era_size,
+            spec_compliant: true,
+            ..Default::default()
+        }
+        .validate();
+        assert!(result.unwrap_err().to_string().contains("end on an era boundary"));
+    }
+
+    #[test]
+    fn test_last_whole_era_block() {
+        let era_size = MAX_BLOCKS_PER_ERA1 as u64;
+        assert_eq!(last_whole_era_block(0, era_size - 1).unwrap(), era_size - 1);
+        assert_eq!(last_whole_era_block(0, 2 * era_size + 5).unwrap(), 2 * era_size - 1);
+        assert_eq!(last_whole_era_block(era_size, 3 * era_size - 2).unwrap(), 2 * era_size - 1);
+        assert!(last_whole_era_block(era_size, 2 * era_size - 2).is_err());
+    }
+
+    #[test]
+    fn test_finalize_in_order_removes_files_after_failure() {
+        let dir = tempdir().unwrap();
+        let files = ["a.era1", "b.era1"].map(|name| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, []).unwrap();
+            path
+        });
+
+        let mut created = Vec::new();
+        let result = finalize_in_order(
+            vec![
+                Ok(Some(files[0].clone())),
+                Ok(None),
+                Err(eyre!("write failed")),
+                Ok(Some(files[1].clone())),
+            ],
+            &mut created,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(created, [files[0].clone()]);
+        assert!(files[0].exists());
+        assert!(!files[1].exists(), "files after the failure should be removed");
     }
 }
@@ -0,0 +1,88 @@
+//! File naming for exported ERA files.
+
+use reth_era::common::file_ops::{format_hash, EraFileId};
+
+/// Zero-padding width of the era number in spec-compliant file names.
+const SPEC_ERA_NUMBER_WIDTH: usize = 5;
+
+/// How exported files are named.
+///
+/// The default follows the e2store spec, `<network>-<era-number>-<short-hash>.<ext>`, which is
+/// what era downloaders expect for public networks. Rollups and private chains can swap the
+/// network name for their own prefix, widen the era number or leave the short hash out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileNaming {
+    /// Replaces the network name at the start of file names; `None` keeps
+    /// [`ExportConfig::network`](super::ExportConfig::network).
+    pub prefix: Option<String>,
+    /// Zero-padding width of the era number.
+    pub era_number_width: usize,
+    /// Whether file names carry the `-<short-hash>` segment.
+    pub short_hash: bool,
+}
+
+impl Default for FileNaming {
+    fn default() -> Self {
+        Self { prefix: None, era_number_width: SPEC_ERA_NUMBER_WIDTH, short_hash: true }
+    }
+}
+
+impl FileNaming {
+    /// Returns whether names follow the spec, leaving [`EraFileId::to_file_name`] untouched.
+    pub fn is_spec(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Renders the file name for `id`.
+    ///
+    /// Only the leading `<network>-<era-number>` and the short hash are affected; the era-count
+    /// segment and any format-specific postfixes are kept as the spec renders them.
+    pub fn file_name<I: EraFileId>(&self, id: &I) -> String {
+        let name = id.to_file_name();
+        if self.is_spec() {
+            return name
+        }
+
+        let head = format!("{}-{:05}", id.network_name(), id.era_number());
+        let mut rest = name.strip_prefix(&head).expect("spec name starts with its head").to_owned();
+        if !self.short_hash {
+            // The hash is the only 8-character segment, so it can't be confused with the 5-digit
+            // era count.
+            rest = rest.replacen(&format!("-{}", format_hash(id.hash())), "", 1);
+        }
+
+        let prefix = self.prefix.as_deref().unwrap_or(id.network_name());
+        format!("{prefix}-{:0width$}{rest}", id.era_number(), width = self.era_number_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_era::{era1::types::group::Era1Id, ere::types::group::EreId};
+
+    #[test]
+    fn default_keeps_spec_names() {
+        let id = Era1Id::new("mainnet", 8192, 8192).with_hash([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(FileNaming::default().file_name(&id), "mainnet-00001-deadbeef.era1");
+    }
+
+    #[test]
+    fn custom_naming_rewrites_prefix_width_and_hash() {
+        let naming = FileNaming {
+            prefix: Some("my-rollup".to_string()),
+            era_number_width: 8,
+            short_hash: false,
+        };
+
+        let id = Era1Id::new("mainnet", 8192, 100).with_hash([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(naming.file_name(&id), "my-rollup-00000001.era1");
+        assert_eq!(naming.file_name(&id.with_era_count()), "my-rollup-00000001-00001.era1");
+
+        let naming = FileNaming { short_hash: true, ..naming };
+        let id = EreId::new("mainnet", 0, 8192)
+            .with_hash([0x4b, 0xb7, 0xde, 0x2e])
+            .with_profile(reth_era::ere::types::group::EreProfile::NoProofs);
+        assert_eq!(naming.file_name(&id), "my-rollup-00000000-4bb7de2e-noproofs.ere");
+    }
+}
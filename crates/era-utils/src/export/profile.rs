@@ -0,0 +1,76 @@
+//! What exported archives hold, beyond the headers every format carries.
+
+use std::path::Path;
+
+/// Postfix the names of archives without receipts carry, as `ere` names its `noreceipts` profile.
+const NO_RECEIPTS: &str = "noreceipts";
+
+/// What an export writes for each block.
+///
+/// Only `.ere` files can leave receipts out, marking it in their name with the `noreceipts`
+/// profile postfix; `.era1` files always carry them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArchiveProfile {
+    /// Headers, bodies and receipts.
+    #[default]
+    Full,
+    /// Headers and bodies only, for consumers that only need blocks, such as a node backfilling
+    /// bodies. Receipts are most of an archive, so these are about half the size.
+    BlocksOnly,
+}
+
+impl ArchiveProfile {
+    /// Returns whether archives of this profile carry receipts.
+    pub const fn has_receipts(&self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    /// Returns the profile of the archive at `path`, from the postfix its name carries.
+    pub fn of_file(path: &Path) -> Self {
+        let blocks_only = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.split('-').skip(2).any(|segment| segment == NO_RECEIPTS));
+        if blocks_only {
+            Self::BlocksOnly
+        } else {
+            Self::Full
+        }
+    }
+
+    /// Returns the flag the export manifest lists files of this profile with, `None` for
+    /// [`ArchiveProfile::Full`].
+    pub(crate) const fn manifest_flag(&self) -> Option<&'static str> {
+        match self {
+            Self::Full => None,
+            Self::BlocksOnly => Some(NO_RECEIPTS),
+        }
+    }
+
+    /// Parses a flag written by [`ArchiveProfile::manifest_flag`].
+    pub(crate) fn from_manifest_flag(flag: &str) -> Option<Self> {
+        (flag == NO_RECEIPTS).then_some(Self::BlocksOnly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_profile_from_file_name() {
+        assert_eq!(
+            ArchiveProfile::of_file(Path::new("mainnet-00000-4bb7de2e-noproofs-noreceipts.ere")),
+            ArchiveProfile::BlocksOnly
+        );
+        assert_eq!(
+            ArchiveProfile::of_file(Path::new("dir/mainnet-00000-4bb7de2e-noproofs.ere")),
+            ArchiveProfile::Full
+        );
+        // A network named like the postfix doesn't make a file blocks-only.
+        assert_eq!(
+            ArchiveProfile::of_file(Path::new("noreceipts-00000-5ec1ffb8.era1")),
+            ArchiveProfile::Full
+        );
+    }
+}
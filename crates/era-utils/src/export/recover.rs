@@ -0,0 +1,274 @@
+//! Best-effort reading of damaged `.era1` files, keeping every block that can still be read whole.
+
+use super::{repack::write, ExportConfig};
+use alloy_primitives::BlockNumber;
+use eyre::{eyre, Result};
+use reth_era::{
+    e2s::types::{Entry, Header, IndexEntry, VERSION},
+    era1::types::{
+        execution::{
+            BlockTuple, CompressedBody, CompressedHeader, CompressedReceipts, TotalDifficulty,
+            ACCUMULATOR, COMPRESSED_BODY, COMPRESSED_HEADER, COMPRESSED_RECEIPTS, TOTAL_DIFFICULTY,
+        },
+        group::{BlockIndex, BLOCK_INDEX},
+    },
+};
+use reth_fs_util as fs;
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Types of the entries an `.era1` file is made of.
+const ENTRY_TYPES: [[u8; 2]; 7] = [
+    VERSION,
+    COMPRESSED_HEADER,
+    COMPRESSED_BODY,
+    COMPRESSED_RECEIPTS,
+    TOTAL_DIFFICULTY,
+    ACCUMULATOR,
+    BLOCK_INDEX,
+];
+
+/// What [`recover_era1`] read back of a damaged `.era1` file.
+#[derive(Debug)]
+pub struct Era1Recovery {
+    /// Runs of consecutive blocks read whole, each with the number of its first block, in
+    /// ascending order.
+    pub runs: Vec<(BlockNumber, Vec<BlockTuple>)>,
+    /// Blocks the file should hold that couldn't be read back whole, in ascending order.
+    pub lost: Vec<BlockNumber>,
+    /// Byte ranges of the file passed over while looking for the next entry.
+    pub skipped: Vec<Range<u64>>,
+}
+
+impl Era1Recovery {
+    /// Returns the number of blocks read back whole.
+    pub fn recovered(&self) -> usize {
+        self.runs.iter().map(|(_, tuples)| tuples.len()).sum()
+    }
+
+    /// Writes each run of recovered blocks to a file of its own in [`ExportConfig::dir`], with an
+    /// accumulator and block index rebuilt from its blocks, returning their paths.
+    pub fn write(&self, config: &ExportConfig) -> Result<Vec<PathBuf>> {
+        self.runs.iter().map(|(first_block, tuples)| write(config, *first_block, tuples)).collect()
+    }
+}
+
+/// Reads what is left of a damaged `.era1` file at `file`, such as one with flipped bits or a
+/// truncated transfer.
+///
+/// Entries are read one after the other without relying on the block index. An entry whose
+/// header is damaged is passed over by scanning ahead for the next well-formed entry header
+/// followed by another one. A block is kept only if its header, body, receipts and total
+/// difficulty are found in order and all decompress, and its header decodes; anything else about
+/// it is lost. The blocks the file should hold are taken from its block index if that is intact,
+/// and from the first and last block headers found otherwise, so a block lost at either end of a
+/// file without an index goes unreported.
+pub fn recover_era1(file: impl AsRef<Path>) -> Result<Era1Recovery> {
+    let file = file.as_ref();
+    let bytes = fs::read(file)?;
+
+    let mut blocks = BTreeMap::new();
+    let mut skipped = Vec::new();
+    let mut headers_seen: Option<(BlockNumber, BlockNumber)> = None;
+    let mut block: Option<PartialBlock> = None;
+    let mut index = None;
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let Some((entry_type, data)) = entry_at(&bytes, position) else {
+            let next = resync(&bytes, position).unwrap_or(bytes.len());
+            skipped.push(position as u64..next as u64);
+            block = None;
+            position = next;
+            continue
+        };
+        position += Header::SIZE + data.len();
+        let entry = Entry::new(entry_type, data.to_vec());
+
+        match entry_type {
+            COMPRESSED_HEADER => {
+                block = PartialBlock::new(&entry).ok();
+                if let Some(number) = block.as_ref().map(|block| block.number) {
+                    let (first, last) = headers_seen.get_or_insert((number, number));
+                    *first = number.min(*first);
+                    *last = number.max(*last);
+                }
+            }
+            COMPRESSED_BODY | COMPRESSED_RECEIPTS | TOTAL_DIFFICULTY => {
+                let Some(partial) = block.as_mut() else { continue };
+                match partial.add(&entry) {
+                    Ok(Some(tuple)) => {
+                        blocks.entry(partial.number).or_insert(tuple);
+                        block = None;
+                    }
+                    Ok(None) => {}
+                    Err(_) => block = None,
+                }
+            }
+            BLOCK_INDEX => index = BlockIndex::from_entry(&entry).ok(),
+            _ => {}
+        }
+    }
+
+    let expected = match index.filter(|index| !index.offsets().is_empty()) {
+        Some(index) => {
+            index.starting_number()..=index.starting_number() + index.offsets().len() as u64 - 1
+        }
+        None => {
+            let (first, last) = headers_seen
+                .ok_or_else(|| eyre!("No block headers left to recover in {}", file.display()))?;
+            first..=last
+        }
+    };
+    blocks.retain(|number, _| expected.contains(number));
+    let lost = expected.filter(|number| !blocks.contains_key(number)).collect();
+
+    let mut runs: Vec<(BlockNumber, Vec<BlockTuple>)> = Vec::new();
+    for (number, tuple) in blocks {
+        match runs.last_mut() {
+            Some((first, tuples)) if *first + tuples.len() as u64 == number => tuples.push(tuple),
+            _ => runs.push((number, vec![tuple])),
+        }
+    }
+
+    Ok(Era1Recovery { runs, lost, skipped })
+}
+
+/// A block whose header was read, waiting for the rest of its tuple.
+#[derive(Debug)]
+struct PartialBlock {
+    number: BlockNumber,
+    header: CompressedHeader,
+    body: Option<CompressedBody>,
+    receipts: Option<CompressedReceipts>,
+}
+
+impl PartialBlock {
+    /// Starts a block from its header `entry`, failing if the header doesn't decode.
+    fn new(entry: &Entry) -> Result<Self> {
+        let header = CompressedHeader::from_entry(entry)?;
+        let number = header.decode_header()?.number;
+        Ok(Self { number, header, body: None, receipts: None })
+    }
+
+    /// Adds `entry`, the next record of the block, returning the block's tuple once it is whole.
+    ///
+    /// Fails if the record is out of order or doesn't decompress.
+    fn add(&mut self, entry: &Entry) -> Result<Option<BlockTuple>> {
+        match entry.entry_type {
+            COMPRESSED_BODY if self.body.is_none() => {
+                let body = CompressedBody::from_entry(entry)?;
+                body.decompress()?;
+                self.body = Some(body);
+            }
+            COMPRESSED_RECEIPTS if self.body.is_some() && self.receipts.is_none() => {
+                let receipts = CompressedReceipts::from_entry(entry)?;
+                receipts.decompress()?;
+                self.receipts = Some(receipts);
+            }
+            TOTAL_DIFFICULTY if self.receipts.is_some() => {
+                return Ok(Some(BlockTuple::new(
+                    self.header.clone(),
+                    self.body.clone().expect("body is read before receipts"),
+                    self.receipts.clone().expect("receipts were checked"),
+                    TotalDifficulty::from_entry(entry)?,
+                )))
+            }
+            _ => return Err(eyre!("Record of block {} is out of order", self.number)),
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the type and data of the entry starting at `position` of `bytes`, or `None` if no
+/// well-formed `.era1` entry starts there.
+fn entry_at(bytes: &[u8], position: usize) -> Option<([u8; 2], &[u8])> {
+    let header = bytes.get(position..position + Header::SIZE)?;
+    let entry_type = [header[0], header[1]];
+    let len = u32::from_le_bytes(header[2..6].try_into().expect("4 bytes")) as usize;
+    let data = bytes.get(position + Header::SIZE..position + Header::SIZE + len)?;
+    (ENTRY_TYPES.contains(&entry_type) && header[6..] == [0, 0]).then_some((entry_type, data))
+}
+
+/// Returns the position of the first entry after `position` that is followed by another entry or
+/// ends the file, so that compressed data happening to look like an entry header is passed over.
+fn resync(bytes: &[u8], position: usize) -> Option<usize> {
+    (position + 1..bytes.len()).find(|&start| {
+        entry_at(bytes, start).is_some_and(|(_, data)| {
+            let next = start + Header::SIZE + data.len();
+            next == bytes.len() || entry_at(bytes, next).is_some()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock};
+    use alloy_consensus::Header;
+    use alloy_primitives::{B256, U256};
+    use reth_ethereum_primitives::{BlockBody, Receipt};
+    use tempfile::tempdir;
+
+    #[test]
+    fn recovers_blocks_around_damaged_entries() {
+        let dir = tempdir().unwrap();
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..4u64)
+            .map(|number| {
+                let header = Header { number, parent_hash, ..Default::default() };
+                parent_hash = header.hash_slow();
+                ExportBlock::<_, BlockBody, Receipt> {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody::default(),
+                    receipts: Vec::new(),
+                    total_difficulty: U256::ZERO,
+                }
+            })
+            .collect::<Vec<_>>();
+        let file = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        // Finds the header entry of each block through the block index, which follows them.
+        let mut bytes = fs::read(&file).unwrap();
+        let index_position = bytes.len() - (8 + 16 + 4 * 8);
+        let header_position = |bytes: &[u8], number: usize| {
+            let offset = 8 + 8 + number * 8;
+            let offset = &bytes[index_position + offset..index_position + offset + 8];
+            (index_position as i64 + i64::from_le_bytes(offset.try_into().unwrap())) as usize
+        };
+        let next_entry = |bytes: &[u8], position: usize| {
+            let len = u32::from_le_bytes(bytes[position + 2..position + 6].try_into().unwrap());
+            position + 8 + len as usize
+        };
+
+        // Breaks the snappy stream of block 1's body and the entry header of block 2's header.
+        let body = next_entry(&bytes, header_position(&bytes, 1));
+        bytes[body + 8] = 0;
+        let header = header_position(&bytes, 2);
+        let body_of_block_2 = next_entry(&bytes, header) as u64;
+        bytes[header..header + 8].fill(0xff);
+        fs::write(&file, &bytes).unwrap();
+
+        let recovery = recover_era1(&file).unwrap();
+        assert_eq!(recovery.lost, vec![1, 2]);
+        assert_eq!(recovery.recovered(), 2);
+        assert_eq!(
+            recovery.runs.iter().map(|(first, tuples)| (*first, tuples.len())).collect::<Vec<_>>(),
+            vec![(0, 1), (3, 1)]
+        );
+        assert_eq!(recovery.skipped, vec![header as u64..body_of_block_2]);
+
+        let out = dir.path().join("out");
+        fs::create_dir_all(&out).unwrap();
+        let written = recovery.write(&ExportConfig { dir: out, ..Default::default() }).unwrap();
+        assert_eq!(written.len(), 2);
+    }
+}
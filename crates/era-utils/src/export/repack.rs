@@ -0,0 +1,272 @@
+//! Splitting, merging and recompression of `.era1` files, for repairing archives or repackaging
+//! custom ranges.
+
+use super::{era1::write_tuples, ChunkAccumulator, ExportConfig};
+use alloy_primitives::{keccak256, BlockNumber, B256, U256};
+use eyre::{eyre, Result};
+use reth_era::{
+    common::file_ops::FileReader,
+    era1::{
+        file::{Era1File, Era1Reader},
+        types::execution::{
+            Accumulator, BlockTuple, CompressedBody, CompressedHeader, CompressedReceipts,
+            MAX_BLOCKS_PER_ERA1,
+        },
+    },
+};
+use reth_fs_util as fs;
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Writes the blocks of each of `ranges` held by the `.era1` file at `file` to a file of their own
+/// in [`ExportConfig::dir`], returning their paths in the order of `ranges`.
+///
+/// Blocks are copied as they are stored, without being decoded. Each new file gets an accumulator
+/// and block index of its own blocks and is named like an export with
+/// [`ExportConfig::network`] and [`ExportConfig::naming`]. Fails before writing anything if a
+/// range reaches outside the file.
+pub fn split_era1(
+    file: impl AsRef<Path>,
+    ranges: &[RangeInclusive<BlockNumber>],
+    config: &ExportConfig,
+) -> Result<Vec<PathBuf>> {
+    let file = file.as_ref();
+    let era1 = Era1Reader::open(file, &config.network)?;
+    let held = era1.block_range();
+    if let Some(range) = ranges.iter().find(|range| {
+        range.is_empty() || !held.contains(range.start()) || !held.contains(range.end())
+    }) {
+        return Err(eyre!(
+            "{} holds blocks {held:?}, so can't be split into blocks {range:?}",
+            file.display()
+        ))
+    }
+
+    ranges
+        .iter()
+        .map(|range| {
+            let first = (range.start() - held.start()) as usize;
+            let last = (range.end() - held.start()) as usize;
+            write(config, *range.start(), &era1.group.blocks[first..=last])
+        })
+        .collect()
+}
+
+/// Writes the blocks of the `.era1` files at `files`, which must follow each other, to a single
+/// file in [`ExportConfig::dir`], returning its path.
+///
+/// Blocks are copied as they are stored, and the accumulator and block index are rebuilt over all
+/// of them. Fails if a file's first block isn't the child of the previous file's last one, or if
+/// the files hold more than the `8192` blocks one accumulator covers together.
+pub fn merge_era1(files: &[impl AsRef<Path>], config: &ExportConfig) -> Result<PathBuf> {
+    let mut tuples = Vec::new();
+    let mut first_block = None;
+    let mut last_hash = None;
+
+    for file in files {
+        let file = file.as_ref();
+        let era1 = Era1Reader::open(file, &config.network)?;
+        let held = era1.block_range();
+        let first =
+            era1.group.blocks.first().ok_or_else(|| eyre!("{} is empty", file.display()))?;
+
+        if let Some(last_hash) = last_hash {
+            let expected = first_block.unwrap_or_default() + tuples.len() as u64;
+            let parent_hash = first.header.decode_header()?.parent_hash;
+            if *held.start() != expected || parent_hash != last_hash {
+                return Err(eyre!(
+                    "{} holds blocks {held:?}, which don't continue the chain of the files before \
+                     it at block {expected}",
+                    file.display()
+                ))
+            }
+        }
+        first_block.get_or_insert(*held.start());
+
+        if tuples.len() + era1.group.blocks.len() > MAX_BLOCKS_PER_ERA1 {
+            return Err(eyre!(
+                "Files hold more than the {MAX_BLOCKS_PER_ERA1} blocks an era1 file can hold"
+            ))
+        }
+        let last = era1.group.blocks.last().expect("file is not empty");
+        last_hash = Some(keccak256(last.header.decompress()?));
+        tuples.extend(era1.group.blocks);
+    }
+
+    let first_block = first_block.ok_or_else(|| eyre!("No era1 files to merge"))?;
+    write(config, first_block, &tuples)
+}
+
+/// Rewrites the `.era1` file at `file` into [`ExportConfig::dir`] with every record decompressed
+/// and compressed again, returning the new file's path.
+///
+/// Normalizes files written by other tools or by older versions of the codec, such as to the
+/// frame sizes it writes now; the block index and accumulator are rebuilt along the way. The
+/// accumulator stored in `file` is checked against its headers before anything is written, and
+/// the new file is read back and checked to hold the same content, being removed if it doesn't.
+/// The original is left in place, so [`ExportConfig::dir`] must not be the directory holding it.
+pub fn recompress_era1(file: impl AsRef<Path>, config: &ExportConfig) -> Result<PathBuf> {
+    let file = file.as_ref();
+    if std::fs::canonicalize(file)?.parent() == std::fs::canonicalize(&config.dir).ok().as_deref() {
+        return Err(eyre!(
+            "Recompressing {} into its own directory would replace it",
+            file.display()
+        ))
+    }
+
+    let era1 = Era1Reader::open(file, &config.network)?;
+    let first_block = *era1.block_range().start();
+    if accumulator(&era1.group.blocks)?.root != era1.group.accumulator.root {
+        return Err(eyre!("{} holds an accumulator that doesn't match its headers", file.display()))
+    }
+
+    let tuples = config.tuning.compress_all(&era1.group.blocks, |tuple| {
+        Ok(BlockTuple::new(
+            CompressedHeader::from_rlp(&tuple.header.decompress()?)?,
+            CompressedBody::from_rlp(&tuple.body.decompress()?)?,
+            CompressedReceipts::from_rlp(&tuple.receipts.decompress()?)?,
+            tuple.total_difficulty.clone(),
+        ))
+    })?;
+    let path = write(config, first_block, &tuples)?;
+
+    if let Err(error) = same_content(&era1, &Era1Reader::open(&path, &config.network)?) {
+        fs::remove_file(&path)?;
+        return Err(error.wrap_err(format!("Recompressed {} differs from it", file.display())))
+    }
+    Ok(path)
+}
+
+/// Checks that `rewritten` holds the same blocks, decompressed, and accumulator as `original`.
+fn same_content(original: &Era1File, rewritten: &Era1File) -> Result<()> {
+    if original.group.blocks.len() != rewritten.group.blocks.len() ||
+        original.group.accumulator.root != rewritten.group.accumulator.root
+    {
+        return Err(eyre!("Block count or accumulator changed"))
+    }
+
+    for (number, (a, b)) in
+        original.block_range().zip(original.group.blocks.iter().zip(&rewritten.group.blocks))
+    {
+        if a.header.decompress()? != b.header.decompress()? ||
+            a.body.decompress()? != b.body.decompress()? ||
+            a.receipts.decompress()? != b.receipts.decompress()? ||
+            a.total_difficulty.value != b.total_difficulty.value
+        {
+            return Err(eyre!("Block {number} changed"))
+        }
+    }
+    Ok(())
+}
+
+/// Builds the accumulator of `tuples` from their headers and total difficulties.
+fn accumulator(tuples: &[BlockTuple]) -> Result<Accumulator> {
+    let records = tuples
+        .iter()
+        .map(|tuple| Ok((keccak256(tuple.header.decompress()?), tuple.total_difficulty.value)))
+        .collect::<Result<Vec<(B256, U256)>>>()?;
+    Accumulator::from_pairs(&records)
+}
+
+/// Writes `tuples`, the blocks from `first_block` on, to a new file with an accumulator and block
+/// index rebuilt from their headers and total difficulties.
+///
+/// Files that don't cover a whole era carry the era count in their name, as custom exports do.
+pub(super) fn write(
+    config: &ExportConfig,
+    first_block: BlockNumber,
+    tuples: &[BlockTuple],
+) -> Result<PathBuf> {
+    let accumulator = accumulator(tuples)?;
+    super::mainnet::check_accumulator_root(
+        &config.network,
+        first_block,
+        tuples.len(),
+        accumulator.root,
+    )?;
+
+    let whole_era = tuples.len() == MAX_BLOCKS_PER_ERA1 &&
+        first_block.is_multiple_of(MAX_BLOCKS_PER_ERA1 as u64);
+    let max_blocks_per_file = if whole_era { MAX_BLOCKS_PER_ERA1 } else { tuples.len() };
+    let config = ExportConfig { max_blocks_per_file: max_blocks_per_file as u64, ..config.clone() };
+    write_tuples(&config, first_block, tuples, &accumulator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock};
+    use alloy_consensus::Header;
+    use reth_ethereum_primitives::{BlockBody, Receipt};
+    use tempfile::tempdir;
+
+    /// Writes blocks `0..4`, linked by parent hash, to an `era1` file in `dir`.
+    fn write_blocks(dir: &Path) -> PathBuf {
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..4u64)
+            .map(|number| {
+                let header = Header {
+                    number,
+                    parent_hash,
+                    difficulty: U256::from(10),
+                    ..Default::default()
+                };
+                parent_hash = header.hash_slow();
+                ExportBlock::<_, BlockBody, Receipt> {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody::default(),
+                    receipts: Vec::new(),
+                    total_difficulty: U256::from((number + 1) * 10),
+                }
+            })
+            .collect::<Vec<_>>();
+        Era1::write_file(&ExportConfig { dir: dir.to_path_buf(), ..Default::default() }, &blocks)
+            .unwrap()
+    }
+
+    fn config(dir: &Path) -> ExportConfig {
+        std::fs::create_dir_all(dir).unwrap();
+        ExportConfig { dir: dir.to_path_buf(), ..Default::default() }
+    }
+
+    #[test]
+    fn splits_and_merges_back() {
+        let dir = tempdir().unwrap();
+        let original = write_blocks(dir.path());
+
+        let split = config(&dir.path().join("split"));
+        let parts = split_era1(&original, &[0..=1, 2..=3], &split).unwrap();
+        assert_eq!(parts.len(), 2);
+        let second = Era1Reader::open(&parts[1], "mainnet").unwrap();
+        assert_eq!(second.block_range(), 2..=3);
+        assert!(split_era1(&original, &[3..=4], &split).is_err());
+
+        let merged = merge_era1(&parts, &config(&dir.path().join("merged"))).unwrap();
+        let merged = Era1Reader::open(merged, "mainnet").unwrap();
+        let original = Era1Reader::open(original, "mainnet").unwrap();
+        assert_eq!(merged.group.accumulator.root, original.group.accumulator.root);
+        assert_eq!(merged.group.blocks.len(), 4);
+
+        let err = merge_era1(&[&parts[1], &parts[0]], &config(&dir.path().join("bad")))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("don't continue the chain"), "{err}");
+    }
+
+    #[test]
+    fn recompresses_into_another_directory() {
+        let dir = tempdir().unwrap();
+        let original = write_blocks(dir.path());
+
+        let err = recompress_era1(&original, &config(dir.path())).unwrap_err().to_string();
+        assert!(err.contains("would replace it"), "{err}");
+
+        let path = recompress_era1(&original, &config(&dir.path().join("out"))).unwrap();
+        let original = Era1Reader::open(original, "mainnet").unwrap();
+        let rewritten = Era1Reader::open(path, "mainnet").unwrap();
+        same_content(&original, &rewritten).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Machine-readable report of the files an export wrote, so consumers of the archives can audit
+//! where each file came from.
+//!
+//! The report is a JSON document in the export directory, updated as each file is completed.
+//! Files listed by the reports of earlier exports into the same directory are kept, unless a later
+//! export rewrites their blocks:
+//!
+//! ```json
+//! {
+//!   "network": "mainnet",
+//!   "files": [
+//!     {
+//!       "name": "mainnet-00000-5ec1ffb8.era1",
+//!       "first_block": 0,
+//!       "last_block": 8191,
+//!       "size": 1203520,
+//!       "checksum": "0x…",
+//!       "receipts": true,
+//!       "duration_secs": 1.25,
+//!       "version": "1.9.3"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `checksum` is taken as for the export manifest, with [`ExportConfig::checksum`].
+
+use super::{checksum::Checksum, metrics::ExportMetrics, ArchiveProfile, ExportConfig};
+use alloy_primitives::BlockNumber;
+use eyre::{eyre, Result};
+use reth_fs_util as fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::warn;
+
+/// Name of the report in the export directory.
+const REPORT_FILE: &str = "export-report.json";
+
+/// Contents of the report.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Contents {
+    /// Network the files hold the history of.
+    network: String,
+    /// Files in block order.
+    files: Vec<ReportedFile>,
+}
+
+/// A file listed in the report.
+#[derive(Debug, Serialize, Deserialize)]
+struct ReportedFile {
+    /// File name within the export directory.
+    name: String,
+    /// First block of the chunk the file was written from.
+    first_block: BlockNumber,
+    /// Last block of the chunk the file was written from.
+    last_block: BlockNumber,
+    /// Size of the file, in bytes.
+    size: u64,
+    /// Checksum of the whole file, as listed in the export manifest.
+    checksum: String,
+    /// Whether the file carries receipts.
+    receipts: bool,
+    /// Time taken to read the file's blocks from storage and write it.
+    duration_secs: f64,
+    /// Version of the reth crates that wrote the file.
+    version: String,
+}
+
+/// The report of an export directory, recording the files an export completes and reporting them
+/// to the export metrics.
+pub(crate) struct ExportReport {
+    path: PathBuf,
+    contents: Contents,
+    metrics: ExportMetrics,
+}
+
+impl ExportReport {
+    /// Reads the report of the directory `config` exports into, which is empty if there is none.
+    ///
+    /// A report that doesn't parse is replaced, with a warning.
+    pub(crate) fn open(config: &ExportConfig) -> Result<Self> {
+        let path = config.dir.join(REPORT_FILE);
+        let mut contents = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?).unwrap_or_else(|err| {
+                warn!(target: "era::history::export", %err, path = %path.display(), "Replacing malformed export report");
+                Contents::default()
+            })
+        } else {
+            Contents::default()
+        };
+        contents.network.clone_from(&config.network);
+        Ok(Self { path, contents, metrics: ExportMetrics::default() })
+    }
+
+    /// Lists `file`, written from the chunk `blocks` with `profile` in `duration`, replacing
+    /// entries of chunks it overlaps.
+    pub(crate) fn record(
+        &mut self,
+        file: &Path,
+        blocks: &RangeInclusive<BlockNumber>,
+        checksum: Checksum,
+        profile: ArchiveProfile,
+        duration: Duration,
+    ) -> Result<()> {
+        let name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| eyre!("Exported file {} has no file name", file.display()))?;
+        let size = fs::metadata(file)?.len();
+
+        let files = &mut self.contents.files;
+        files.retain(|entry| {
+            entry.last_block < *blocks.start() || entry.first_block > *blocks.end()
+        });
+        let index = files.partition_point(|entry| entry.first_block < *blocks.start());
+        files.insert(
+            index,
+            ReportedFile {
+                name: name.to_owned(),
+                first_block: *blocks.start(),
+                last_block: *blocks.end(),
+                size,
+                checksum: checksum.to_string(),
+                receipts: profile.has_receipts(),
+                duration_secs: duration.as_secs_f64(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+            },
+        );
+
+        self.metrics.files_written.increment(1);
+        self.metrics.blocks_exported.increment(blocks.end() - blocks.start() + 1);
+        self.metrics.bytes_written.increment(size);
+        self.metrics.file_duration_seconds.record(duration);
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let contents = serde_json::to_vec_pretty(&self.contents)?;
+        // Replaced whole, so an interrupted save leaves the previous report in place.
+        fs::atomic_write_file(&self.path, |file| file.write_all(&contents))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChecksumAlgorithm;
+    use tempfile::tempdir;
+
+    #[test]
+    fn lists_files_in_block_order() {
+        let dir = tempdir().unwrap();
+        let config = ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() };
+        let mut report = ExportReport::open(&config).unwrap();
+        for (name, blocks) in [("b.era1", 10..=19), ("a.era1", 0..=9), ("c.ere", 20..=29)] {
+            let file = dir.path().join(name);
+            fs::write(&file, [0; 16]).unwrap();
+            let checksum = Checksum::of_file(&file, ChecksumAlgorithm::Sha256).unwrap();
+            report
+                .record(&file, &blocks, checksum, ArchiveProfile::Full, Duration::from_secs(1))
+                .unwrap();
+        }
+
+        // A later export rewriting blocks drops the files it overlaps.
+        let file = dir.path().join("c.ere");
+        let checksum = Checksum::of_file(&file, ChecksumAlgorithm::Xxh3).unwrap();
+        let mut report = ExportReport::open(&config).unwrap();
+        report
+            .record(&file, &(15..=29), checksum, ArchiveProfile::BlocksOnly, Duration::ZERO)
+            .unwrap();
+
+        let contents: Contents =
+            serde_json::from_str(&fs::read_to_string(dir.path().join(REPORT_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(contents.network, "mainnet");
+        let files = contents
+            .files
+            .iter()
+            .map(|file| (file.name.as_str(), file.first_block, file.size, file.receipts))
+            .collect::<Vec<_>>();
+        assert_eq!(files, [("a.era1", 0, 16, true), ("c.ere", 15, 16, false)]);
+        assert!(contents.files[1].checksum.starts_with("xxh3:"));
+        assert_eq!(contents.files[0].version, env!("CARGO_PKG_VERSION"));
+    }
+}
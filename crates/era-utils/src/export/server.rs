@@ -0,0 +1,416 @@
+//! HTTP server making an export directory a mirror other nodes' era downloaders fetch from.
+
+use super::listing::{write_listing, CHECKSUMS_FILE, INDEX_FILE};
+use bytes::Bytes;
+use eyre::Result;
+use futures_util::TryStreamExt;
+use http_body_util::{combinators::BoxBody, BodyExt, Empty, StreamBody};
+use hyper::{
+    body::{Frame, Incoming},
+    header::{self, HeaderValue},
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::{TokioIo, TokioTimer};
+use reth_era::common::file_ops::EraFileType;
+use reth_tasks::{shutdown::Shutdown, TaskExecutor};
+use std::{
+    convert::Infallible,
+    future::Future,
+    io::{self, SeekFrom},
+    ops::RangeInclusive,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, ReadBuf},
+    net::TcpListener,
+    sync::Semaphore,
+    time::{Instant, Sleep},
+};
+use tokio_util::io::ReaderStream;
+use tracing::{debug, info};
+
+/// Body of the server's responses, streamed from disk.
+type ResponseBody = BoxBody<Bytes, io::Error>;
+
+/// Most connections served at once. Further connections wait to be accepted until one closes.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Time a client has to send the headers of a request.
+const HEADER_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time a connection may go without reading or writing a byte before it is closed.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Serves the files of an export directory over HTTP, laid out as the era downloader expects, so
+/// a node can act as a mirror of its own archive.
+///
+/// `GET` and `HEAD` requests are answered for the `index.html` listing, also served at `/`, for
+/// `checksums.txt` and for the ERA files of the served type. A single byte range may be requested
+/// with a `Range` header. Anything else is not found.
+///
+/// At most 64 connections are served at once. A connection is closed if its client takes longer
+/// than 30 seconds to send the headers of a request, or if no bytes go either way for a minute.
+///
+/// The listing is written with the directory's files when the server starts. Exports that keep
+/// adding files, such as [`FinalizedExporter`](super::FinalizedExporter), should set
+/// [`ExportConfig::write_listing`](super::ExportConfig::write_listing) to keep it current.
+#[derive(Debug, Clone)]
+pub struct EraFileServer {
+    inner: Arc<EraFileServerInner>,
+}
+
+#[derive(Debug)]
+struct EraFileServerInner {
+    dir: PathBuf,
+    file_type: EraFileType,
+}
+
+impl EraFileServer {
+    /// Creates a server of the `file_type` files in `dir`.
+    pub fn new(dir: impl Into<PathBuf>, file_type: EraFileType) -> Self {
+        Self { inner: Arc::new(EraFileServerInner { dir: dir.into(), file_type }) }
+    }
+
+    /// Writes the directory's listing, then serves connections accepted on `listener` until
+    /// `shutdown` fires. Each connection is served by a task spawned on `executor`.
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        executor: TaskExecutor,
+        mut shutdown: Shutdown,
+    ) -> Result<()> {
+        write_listing(&self.inner.dir, self.inner.file_type)?;
+        info!(
+            target: "era::server",
+            addr = %listener.local_addr()?,
+            dir = %self.inner.dir.display(),
+            "Serving ERA files"
+        );
+
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+        loop {
+            let permit = tokio::select! {
+                permit = connections.clone().acquire_owned() => permit?,
+                _ = &mut shutdown => return Ok(()),
+            };
+            let (stream, peer) = tokio::select! {
+                accepted = listener.accept() => accepted?,
+                _ = &mut shutdown => return Ok(()),
+            };
+
+            let server = self.clone();
+            executor.spawn_task(async move {
+                // Frees the connection's slot once it closes
+                let _permit = permit;
+                let service = service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.respond(&request).await) }
+                });
+                let io = TokioIo::new(IdleTimeout::new(stream, IDLE_TIMEOUT));
+                if let Err(error) = http1::Builder::new()
+                    .timer(TokioTimer::new())
+                    .header_read_timeout(HEADER_READ_TIMEOUT)
+                    .serve_connection(io, service)
+                    .await
+                {
+                    debug!(target: "era::server", %peer, %error, "Connection ended with an error");
+                }
+            });
+        }
+    }
+
+    /// Answers `request`, turning failures to read the file into error responses.
+    async fn respond(&self, request: &Request<Incoming>) -> Response<ResponseBody> {
+        if !matches!(*request.method(), Method::GET | Method::HEAD) {
+            return with_status(StatusCode::METHOD_NOT_ALLOWED)
+        }
+        let Some(name) = self.file_name(request.uri().path()) else {
+            return with_status(StatusCode::NOT_FOUND)
+        };
+
+        match self.read(request, name).await {
+            Ok(response) => response,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                with_status(StatusCode::NOT_FOUND)
+            }
+            Err(error) => {
+                debug!(target: "era::server", name, %error, "Failed to read served file");
+                with_status(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Returns the name of the file `path` asks for, or `None` if it is not one that is served.
+    fn file_name<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let name = match path.strip_prefix('/')? {
+            "" => INDEX_FILE,
+            name => name,
+        };
+        let served = name == INDEX_FILE ||
+            (name == CHECKSUMS_FILE && self.inner.file_type.has_checksums()) ||
+            (!name.contains(['/', '\\']) &&
+                EraFileType::from_filename(name) == Some(self.inner.file_type));
+        served.then_some(name)
+    }
+
+    /// Streams the file `name`, or the byte range of it the request asks for.
+    async fn read(
+        &self,
+        request: &Request<Incoming>,
+        name: &str,
+    ) -> io::Result<Response<ResponseBody>> {
+        let mut file = File::open(self.inner.dir.join(name)).await?;
+        let len = file.metadata().await?.len();
+
+        let range = request
+            .headers()
+            .get(header::RANGE)
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| byte_range(range, len));
+        let (status, bytes) = match range {
+            Some(Ok(bytes)) => (StatusCode::PARTIAL_CONTENT, bytes),
+            Some(Err(())) => {
+                let mut response = with_status(StatusCode::RANGE_NOT_SATISFIABLE);
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes */{len}")).expect("valid header"),
+                );
+                return Ok(response)
+            }
+            None if len == 0 => return Ok(content(StatusCode::OK, empty(), 0, name)),
+            None => (StatusCode::OK, 0..=len - 1),
+        };
+
+        let content_len = bytes.end() - bytes.start() + 1;
+        let body = if *request.method() == Method::HEAD {
+            empty()
+        } else {
+            file.seek(SeekFrom::Start(*bytes.start())).await?;
+            StreamBody::new(ReaderStream::new(file.take(content_len)).map_ok(Frame::data)).boxed()
+        };
+
+        let mut response = content(status, body, content_len, name);
+        if status == StatusCode::PARTIAL_CONTENT {
+            response.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {}-{}/{len}", bytes.start(), bytes.end()))
+                    .expect("valid header"),
+            );
+        }
+        Ok(response)
+    }
+}
+
+/// A connection that fails with [`io::ErrorKind::TimedOut`] once no bytes were read or written
+/// for its timeout.
+#[derive(Debug)]
+struct IdleTimeout<T> {
+    io: T,
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
+}
+
+impl<T> IdleTimeout<T> {
+    fn new(io: T, timeout: Duration) -> Self {
+        Self { io, timeout, deadline: Box::pin(tokio::time::sleep(timeout)) }
+    }
+
+    /// Pushes the deadline back if `poll` is ready, otherwise fails if the deadline has passed.
+    fn check<R>(&mut self, cx: &mut Context<'_>, poll: Poll<io::Result<R>>) -> Poll<io::Result<R>> {
+        if poll.is_ready() {
+            self.deadline.as_mut().reset(Instant::now() + self.timeout);
+            return poll
+        }
+        match self.deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(io::ErrorKind::TimedOut.into())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for IdleTimeout<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.io).poll_read(cx, buf);
+        this.check(cx, poll)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for IdleTimeout<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.io).poll_write(cx, buf);
+        this.check(cx, poll)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.io).poll_flush(cx);
+        this.check(cx, poll)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.io).poll_shutdown(cx);
+        this.check(cx, poll)
+    }
+}
+
+/// Parses a `Range` header for a file of `len` bytes into the bytes it asks for.
+///
+/// Returns `None` if the header should be ignored and the whole file served, as for multiple
+/// ranges or units other than bytes, and `Some(Err(()))` if the range lies past the end of the
+/// file.
+fn byte_range(range: &str, len: u64) -> Option<Result<RangeInclusive<u64>, ()>> {
+    let (start, end) = range.strip_prefix("bytes=")?.trim().split_once('-')?;
+    if end.contains(',') {
+        return None
+    }
+
+    let bytes = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        // The last `suffix` bytes.
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            if suffix == 0 || len == 0 {
+                return Some(Err(()))
+            }
+            len.saturating_sub(suffix)..=len - 1
+        }
+        (start, end) => {
+            let start = start.parse::<u64>().ok()?;
+            let end = match end {
+                "" => u64::MAX,
+                end => end.parse::<u64>().ok()?,
+            };
+            if start > end {
+                return None
+            }
+            if start >= len {
+                return Some(Err(()))
+            }
+            start..=end.min(len - 1)
+        }
+    };
+    Some(Ok(bytes))
+}
+
+/// Returns an empty response with `status`.
+fn with_status(status: StatusCode) -> Response<ResponseBody> {
+    let mut response = Response::new(empty());
+    *response.status_mut() = status;
+    response
+}
+
+/// Returns a response carrying `len` bytes of the file `name` in `body`.
+fn content(status: StatusCode, body: ResponseBody, len: u64, name: &str) -> Response<ResponseBody> {
+    let content_type = if name.ends_with(".html") {
+        "text/html; charset=utf-8"
+    } else if name.ends_with(".txt") {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    };
+
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    response
+}
+
+fn empty() -> ResponseBody {
+    Empty::new().map_err(|never| match never {}).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_fs_util as fs;
+    use reth_tasks::shutdown::signal;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(byte_range("bytes=0-9", 100), Some(Ok(0..=9)));
+        assert_eq!(byte_range("bytes=90-", 100), Some(Ok(90..=99)));
+        assert_eq!(byte_range("bytes=90-200", 100), Some(Ok(90..=99)));
+        assert_eq!(byte_range("bytes=-10", 100), Some(Ok(90..=99)));
+        assert_eq!(byte_range("bytes=-200", 100), Some(Ok(0..=99)));
+        assert_eq!(byte_range("bytes=100-", 100), Some(Err(())));
+        assert_eq!(byte_range("bytes=-0", 100), Some(Err(())));
+        assert_eq!(byte_range("bytes=0-1,5-9", 100), None);
+        assert_eq!(byte_range("bytes=9-0", 100), None);
+        assert_eq!(byte_range("items=0-9", 100), None);
+    }
+
+    #[tokio::test]
+    async fn times_out_idle_connections() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut client = IdleTimeout::new(client, Duration::from_millis(10));
+
+        server.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let err = client.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn serves_listing_and_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let name = "mainnet-00000-00000000.era1";
+        let contents = (0..=255).collect::<Vec<u8>>();
+        fs::write(dir.path().join(name), &contents).unwrap();
+        fs::write(dir.path().join("notes.txt"), "not served").unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (_signal, shutdown) = signal();
+        let server = EraFileServer::new(dir.path(), EraFileType::Era1);
+        tokio::spawn(server.serve(listener, TaskExecutor::test(), shutdown));
+
+        let client = reqwest::Client::new();
+        let index = client.get(format!("{url}/")).send().await.unwrap().text().await.unwrap();
+        assert!(index.contains(&format!("<a href=\"{name}\">")));
+        assert!(index.contains("<a href=\"checksums.txt\">"));
+
+        let whole = client.get(format!("{url}/{name}")).send().await.unwrap();
+        assert_eq!(whole.status(), StatusCode::OK);
+        assert_eq!(whole.bytes().await.unwrap().as_ref(), contents.as_slice());
+
+        let part = client
+            .get(format!("{url}/{name}"))
+            .header(header::RANGE, "bytes=16-31")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(part.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(part.headers()[header::CONTENT_RANGE], "bytes 16-31/256");
+        assert_eq!(part.bytes().await.unwrap().as_ref(), &contents[16..32]);
+
+        for path in ["notes.txt", "..%2Fnotes.txt", "missing-00001-00000000.era1"] {
+            let response = client.get(format!("{url}/{path}")).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{path}");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Resolution of export ranges given as block timestamps into block numbers.
+
+use super::ExportConfig;
+use alloy_consensus::BlockHeader;
+use alloy_primitives::BlockNumber;
+use eyre::{eyre, Result};
+use reth_storage_api::{BlockNumReader, HeaderProvider};
+use std::{borrow::Cow, ops::RangeInclusive};
+use tracing::info;
+
+/// Returns the stored blocks whose timestamp falls within `timestamps`, in seconds since the Unix
+/// epoch.
+///
+/// Block timestamps only ever increase, so each end is found by a binary search over the stored
+/// headers. Fails if no stored block falls within `timestamps`.
+pub fn blocks_in_time_range<P>(
+    provider: &P,
+    timestamps: RangeInclusive<u64>,
+) -> Result<RangeInclusive<BlockNumber>>
+where
+    P: HeaderProvider + BlockNumReader,
+{
+    let tip = provider.last_block_number()?;
+    let first = first_block_at(provider, tip, *timestamps.start())?;
+    let end = match timestamps.end().checked_add(1) {
+        Some(after) => first_block_at(provider, tip, after)?,
+        None => tip + 1,
+    };
+
+    if first >= end {
+        return Err(eyre!(
+            "No stored block up to block {tip} has a timestamp within {}..={}",
+            timestamps.start(),
+            timestamps.end()
+        ));
+    }
+
+    Ok(first..=end - 1)
+}
+
+/// Returns `config` with [`ExportConfig::time_range`] resolved into its block range, or `config`
+/// itself if it has none.
+pub(super) fn resolve<'a, P>(
+    provider: &P,
+    config: &'a ExportConfig,
+) -> Result<Cow<'a, ExportConfig>>
+where
+    P: HeaderProvider + BlockNumReader,
+{
+    let Some(timestamps) = config.time_range.clone() else { return Ok(Cow::Borrowed(config)) };
+
+    let blocks = blocks_in_time_range(provider, timestamps.clone())?;
+    info!(
+        target: "era::history::export",
+        ?timestamps,
+        ?blocks,
+        "Resolved ERA export time range"
+    );
+
+    Ok(Cow::Owned(ExportConfig {
+        first_block_number: *blocks.start(),
+        last_block_number: *blocks.end(),
+        time_range: None,
+        ..config.clone()
+    }))
+}
+
+/// Returns the first block up to `tip` with a timestamp of at least `timestamp`, or `tip + 1` if
+/// there is none.
+fn first_block_at<P>(provider: &P, tip: BlockNumber, timestamp: u64) -> Result<BlockNumber>
+where
+    P: HeaderProvider,
+{
+    let (mut low, mut high) = (0, tip + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let header = provider
+            .header_by_number(mid)?
+            .ok_or_else(|| eyre!("Missing header of block {mid}"))?;
+        if header.timestamp() < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+    use alloy_primitives::B256;
+    use reth_provider::test_utils::MockEthProvider;
+
+    /// Blocks `0..=4`, each twelve seconds after the previous one, starting at `100`.
+    fn provider() -> MockEthProvider {
+        let provider = MockEthProvider::default();
+        for number in 0..=4 {
+            let header = Header { number, timestamp: 100 + 12 * number, ..Default::default() };
+            provider.add_header(B256::with_last_byte(number as u8), header);
+        }
+        provider
+    }
+
+    #[test]
+    fn resolves_blocks_within_timestamps() {
+        let provider = provider();
+
+        assert_eq!(blocks_in_time_range(&provider, 100..=148).unwrap(), 0..=4);
+        assert_eq!(blocks_in_time_range(&provider, 101..=135).unwrap(), 1..=2);
+        assert_eq!(blocks_in_time_range(&provider, 124..=124).unwrap(), 2..=2);
+        assert_eq!(blocks_in_time_range(&provider, 0..=u64::MAX).unwrap(), 0..=4);
+
+        let err = blocks_in_time_range(&provider, 125..=135).unwrap_err().to_string();
+        assert!(err.contains("No stored block"), "{err}");
+        assert!(blocks_in_time_range(&provider, 149..=200).is_err());
+    }
+
+    #[test]
+    fn resolves_config_time_range() {
+        let provider = provider();
+        let config = ExportConfig { time_range: Some(112..=136), ..Default::default() };
+
+        let resolved = resolve(&provider, &config).unwrap();
+        assert_eq!(
+            (resolved.first_block_number, resolved.last_block_number, resolved.time_range.clone()),
+            (1, 3, None)
+        );
+
+        let config = ExportConfig::default();
+        assert!(matches!(resolve(&provider, &config).unwrap(), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,113 @@
+//! Compression and I/O tuning for exports.
+
+use eyre::Result;
+use std::{fs::File, io::BufWriter, num::NonZeroUsize, path::Path};
+
+/// Default capacity of the buffer in front of each exported file, that of [`BufWriter::new`].
+const DEFAULT_WRITE_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Performance knobs for [`export`](super::export), none of which change the written bytes.
+///
+/// Records are snappy-compressed with the framing format, which fixes frames at 64 KiB, so frame
+/// size is not tunable. Static files hold data in their own compression, never snappy, so every
+/// record is compressed afresh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExportTuning {
+    /// Threads compressing a file's blocks in parallel; `1` compresses on the exporting thread.
+    pub compression_threads: NonZeroUsize,
+    /// Capacity in bytes of the write buffer in front of each exported file.
+    pub write_buffer_size: usize,
+    /// Files built at once by [`export_parallel`](super::export_parallel), each from its own
+    /// read-only provider. [`export`](super::export) builds one at a time.
+    pub parallel_files: NonZeroUsize,
+}
+
+impl Default for ExportTuning {
+    fn default() -> Self {
+        Self {
+            compression_threads: NonZeroUsize::MIN,
+            write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
+            parallel_files: NonZeroUsize::MIN,
+        }
+    }
+}
+
+impl ExportTuning {
+    /// Creates the file at `path` behind a [`ExportTuning::write_buffer_size`] buffer.
+    pub(crate) fn create_file(&self, path: &Path) -> Result<BufWriter<File>> {
+        Ok(BufWriter::with_capacity(self.write_buffer_size, File::create(path)?))
+    }
+
+    /// Maps `compress` over `items`, spread over [`ExportTuning::compression_threads`] scoped
+    /// threads, keeping the order of `items`.
+    pub(crate) fn compress_all<T, U>(
+        &self,
+        items: &[T],
+        compress: impl Fn(&T) -> Result<U> + Sync,
+    ) -> Result<Vec<U>>
+    where
+        T: Sync,
+        U: Send,
+    {
+        map_parallel(self.compression_threads, items, compress).into_iter().collect()
+    }
+}
+
+/// Maps `f` over `items`, spread over up to `threads` scoped threads, keeping the order of
+/// `items`.
+///
+/// Panics in `f` are resumed on the calling thread.
+pub(crate) fn map_parallel<T, U>(
+    threads: NonZeroUsize,
+    items: &[T],
+    f: impl Fn(&T) -> U + Sync,
+) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+{
+    let threads = threads.get();
+    if threads == 1 || items.len() < 2 {
+        return items.iter().map(f).collect()
+    }
+
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles = items
+            .chunks(items.len().div_ceil(threads))
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        let mut mapped = Vec::with_capacity(items.len());
+        for handle in handles {
+            mapped.extend(handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)));
+        }
+        mapped
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::eyre;
+
+    #[test]
+    fn compress_all_keeps_order_across_threads() {
+        let items = (0..1_000u64).collect::<Vec<_>>();
+        let tuning = ExportTuning {
+            compression_threads: NonZeroUsize::new(7).unwrap(),
+            ..Default::default()
+        };
+
+        let doubled = tuning.compress_all(&items, |i| Ok(i * 2)).unwrap();
+        assert_eq!(doubled, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+
+        let failed = tuning.compress_all(&items, |&i| {
+            if i == 500 {
+                return Err(eyre!("bad block {i}"))
+            }
+            Ok(i)
+        });
+        assert!(failed.unwrap_err().to_string().contains("bad block 500"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Incremental export of eras as they finalize.
+
+use super::{export, BodyOf, EraBlockWriter, ExportConfig};
+use alloy_consensus::{BlockHeader, Sealable};
+use alloy_primitives::BlockNumber;
+use alloy_rlp::Encodable;
+use eyre::{eyre, Result};
+use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
+use reth_primitives_traits::Receipt;
+use reth_storage_api::{BlockReader, ChainStateBlockReader, HeaderProvider};
+use std::{marker::PhantomData, ops::RangeInclusive, path::PathBuf};
+use tracing::debug;
+
+/// Keeps an export directory up to date with the finalized chain, one whole era at a time.
+///
+/// Each [`FinalizedExporter::poll`] writes a file for every `8192`-block era that finalized since
+/// the previous poll, so a long-running loop or an ExEx reacting to finalization can keep a hosted
+/// archive current without re-exporting what is already there. Files are always laid out as the
+/// spec requires; see [`ExportConfig::spec_compliant`].
+#[derive(Debug)]
+pub struct FinalizedExporter<W> {
+    config: ExportConfig,
+    /// First block of the next era to export.
+    next_block: BlockNumber,
+    /// Last block written to a file, short of the end of the last exported era if a pre-merge
+    /// format cut it at the merge.
+    last_exported: Option<BlockNumber>,
+    _writer: PhantomData<W>,
+}
+
+impl<W: EraBlockWriter> FinalizedExporter<W> {
+    /// Creates an exporter starting at [`ExportConfig::first_block_number`], which must be an era
+    /// boundary.
+    ///
+    /// [`ExportConfig::last_block_number`] and [`ExportConfig::max_blocks_per_file`] are ignored,
+    /// files span whole eras up to the finalized head.
+    pub fn new(config: ExportConfig) -> Result<Self> {
+        let next_block = config.first_block_number;
+        if !next_block.is_multiple_of(MAX_BLOCKS_PER_ERA1 as u64) {
+            return Err(eyre!(
+                "Watched exports must start on an era boundary, got block {next_block}"
+            ));
+        }
+        Ok(Self { config, next_block, last_exported: None, _writer: PhantomData })
+    }
+
+    /// Returns the first block of the next era to be exported.
+    pub const fn next_block(&self) -> BlockNumber {
+        self.next_block
+    }
+
+    /// Returns the blocks written to files so far, `None` before the first file.
+    pub fn exported_blocks(&self) -> Option<RangeInclusive<BlockNumber>> {
+        self.last_exported.map(|last| self.config.first_block_number..=last)
+    }
+
+    /// Exports every whole era finalized in `provider` past the last exported one.
+    ///
+    /// Returns the paths of the files that were created, empty if no further era has finalized.
+    pub fn poll<P>(&mut self, provider: &P) -> Result<Vec<PathBuf>>
+    where
+        P: BlockReader + ChainStateBlockReader,
+        P::Header: BlockHeader + Sealable + Encodable,
+        BodyOf<P>: Encodable,
+        P::Receipt: Receipt,
+    {
+        let era_size = MAX_BLOCKS_PER_ERA1 as u64;
+        let Some(finalized) = provider.last_finalized_block_number()? else {
+            debug!(target: "era::history::export", "No finalized block yet");
+            return Ok(Vec::new())
+        };
+
+        let whole_eras = (finalized + 1).saturating_sub(self.next_block) / era_size;
+        if whole_eras == 0 {
+            debug!(
+                target: "era::history::export",
+                finalized,
+                next_block = self.next_block,
+                "Next era not finalized yet"
+            );
+            return Ok(Vec::new())
+        }
+
+        let last_block_number = self.next_block + whole_eras * era_size - 1;
+        let config = ExportConfig {
+            first_block_number: self.next_block,
+            last_block_number,
+            time_range: None,
+            max_blocks_per_file: era_size,
+            spec_compliant: true,
+            // Eras are counted off the returned files, so none may be skipped.
+            resume: false,
+            ..self.config.clone()
+        };
+        let files = export::<W, _>(provider, &config)?;
+        // The export stops short if the provider lags behind the finalized head.
+        self.next_block += files.len() as u64 * era_size;
+
+        if !files.is_empty() {
+            let last_era = self.next_block - era_size..=self.next_block - 1;
+            self.last_exported = Some(match self.config.terminal_total_difficulty {
+                Some(_) => last_pre_merge_block(provider, last_era)?,
+                None => *last_era.end(),
+            });
+        }
+
+        Ok(files)
+    }
+}
+
+/// Returns the last proof-of-work block of `blocks`, whose first block is one.
+fn last_pre_merge_block<P>(provider: &P, blocks: RangeInclusive<BlockNumber>) -> Result<BlockNumber>
+where
+    P: HeaderProvider<Header: BlockHeader>,
+{
+    let (mut low, mut high) = blocks.into_inner();
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        let header = provider
+            .header_by_number(mid)?
+            .ok_or_else(|| eyre!("Missing header of block {mid}"))?;
+        if header.difficulty().is_zero() {
+            high = mid - 1;
+        } else {
+            low = mid;
+        }
+    }
+    Ok(low)
+}
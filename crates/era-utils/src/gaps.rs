@@ -0,0 +1,100 @@
+//! Detection of the history already stored, so an import only writes what is missing.
+
+use alloy_primitives::{BlockHash, BlockNumber};
+use reth_db_api::{cursor::DbCursorRO, tables, transaction::DbTx};
+use reth_provider::{StaticFileProviderFactory, StaticFileSegment};
+use reth_storage_api::{
+    errors::{ProviderError, ProviderResult},
+    BlockHashReader, DBProvider,
+};
+use std::{fmt, ops::RangeInclusive};
+
+/// Stored headers are read this many at a time when checking imported blocks against them.
+const HASHES_WINDOW: u64 = 8192;
+
+/// How far each part of the block history is stored, as found by [`stored_history`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoredHistory {
+    /// Last block whose header is stored.
+    pub headers: BlockNumber,
+    /// Last block whose body is stored.
+    pub bodies: BlockNumber,
+}
+
+impl StoredHistory {
+    /// Returns the blocks whose header is stored but not their body, as left by an import in
+    /// [`ImportMode::HeadersOnly`](crate::ImportMode::HeadersOnly) or an interrupted sync.
+    pub fn missing_bodies(&self) -> Option<RangeInclusive<BlockNumber>> {
+        (self.bodies < self.headers).then(|| self.bodies + 1..=self.headers)
+    }
+}
+
+impl fmt::Display for StoredHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "headers up to block {}, bodies up to block {}", self.headers, self.bodies)?;
+        if let Some(missing) = self.missing_bodies() {
+            write!(f, ", bodies of blocks {}..={} missing", missing.start(), missing.end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds how far headers and bodies are stored, each with a single lookup of its tip.
+pub fn stored_history<P>(provider: &P) -> ProviderResult<StoredHistory>
+where
+    P: DBProvider + StaticFileProviderFactory,
+{
+    let headers = provider
+        .static_file_provider()
+        .get_highest_static_file_block(StaticFileSegment::Headers)
+        .unwrap_or_default();
+    let bodies = provider
+        .tx_ref()
+        .cursor_read::<tables::BlockBodyIndices>()?
+        .last()?
+        .map_or(0, |(number, _)| number);
+
+    Ok(StoredHistory { headers, bodies })
+}
+
+/// Hashes of the stored headers up to `tip`, read in windows of [`HASHES_WINDOW`] as imported
+/// blocks are checked against them.
+#[derive(Debug)]
+pub(crate) struct StoredHashes {
+    tip: BlockNumber,
+    /// Block of the first hash in `hashes`.
+    first: BlockNumber,
+    hashes: Vec<BlockHash>,
+}
+
+impl StoredHashes {
+    pub(crate) const fn new(tip: BlockNumber) -> Self {
+        Self { tip, first: 0, hashes: Vec::new() }
+    }
+
+    /// Returns the hash of the stored header of block `number`, or `None` if it is past the tip.
+    ///
+    /// Reads the window starting at `number` when it isn't already read, so asking for blocks in
+    /// order reads each header once.
+    pub(crate) fn get<P: BlockHashReader>(
+        &mut self,
+        provider: &P,
+        number: BlockNumber,
+    ) -> ProviderResult<Option<BlockHash>> {
+        if number > self.tip {
+            return Ok(None)
+        }
+
+        if !(self.first..self.first + self.hashes.len() as u64).contains(&number) {
+            let end = number.saturating_add(HASHES_WINDOW).min(self.tip + 1);
+            self.hashes = provider.canonical_hashes_range(number, end)?;
+            self.first = number;
+        }
+
+        self.hashes
+            .get((number - self.first) as usize)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| ProviderError::HeaderNotFound(number.into()))
+    }
+}
@@ -1,5 +1,21 @@
-use alloy_consensus::BlockHeader;
-use alloy_primitives::{BlockHash, BlockNumber, U256};
+use crate::{
+    decode::DecodeAhead,
+    export::ArchiveProfile,
+    gaps::StoredHashes,
+    hooks::{ImportHooks, ImportedFile},
+    lookup::TransactionLookup,
+    senders::SenderRecovery,
+    shutdown::is_shutting_down,
+    storage::{HistoryWriter, HistoryWriterFactory},
+    summary::ImportSummary,
+    throttle::ImportThrottle,
+    verify::{
+        verify_body_roots, verify_network, verify_pre_merge, verify_receipts_root, VerificationMode,
+    },
+};
+use alloy_consensus::{BlockHeader, ReceiptEnvelope, ReceiptWithBloom, TxReceipt};
+use alloy_eips::BlockNumHash;
+use alloy_primitives::{BlockHash, BlockNumber, Bloom, U256};
 use futures_util::{Stream, StreamExt};
 use reth_db_api::{
     cursor::{DbCursorRO, DbCursorRW},
@@ -15,23 +31,34 @@ use reth_era::{
     era1::{file::Era1Reader, types::execution::BlockTuple},
     ere::{file::EreReader, types::execution::BlockTuple as EreBlockTuple},
 };
-use reth_era_downloader::EraMeta;
+use reth_era_downloader::{EraMeta, EraRead};
 use reth_etl::Collector;
 use reth_fs_util as fs;
-use reth_primitives_traits::{Block, BlockBody, FullBlockBody, FullBlockHeader, NodePrimitives};
-use reth_provider::{
-    providers::StaticFileProviderRWRefMut, BlockReader, BlockWriter, StaticFileProviderFactory,
-    StaticFileSegment, StaticFileWriter,
+use reth_primitives_traits::{
+    Block, BlockBody, FullBlockBody, FullBlockHeader, FullReceipt, NodePrimitives, Receipt,
 };
+use reth_provider::BlockReader;
 use reth_stages_types::{
     CheckpointBlockRange, EntitiesCheckpoint, HeadersCheckpoint, StageCheckpoint, StageId,
 };
 use reth_storage_api::{
-    errors::ProviderResult, DBProvider, DatabaseProviderFactory, NodePrimitivesProvider,
-    StageCheckpointWriter,
+    errors::ProviderResult, DBProvider, NodePrimitivesProvider, StageCheckpointWriter,
+};
+use reth_tasks::shutdown::Shutdown;
+use std::{
+    collections::{BTreeMap, Bound},
+    error::Error,
+    num::NonZeroUsize,
+    ops::RangeBounds,
+    sync::mpsc::{self, TryRecvError},
+    time::{Duration, Instant},
 };
-use std::{collections::Bound, error::Error, ops::RangeBounds, sync::mpsc};
-use tracing::info;
+use tracing::{debug, info, warn};
+
+#[cfg(feature = "ethash")]
+use crate::ethash::{verify_seal, SealVerification};
+#[cfg(feature = "ethash")]
+use std::hash::RandomState;
 
 /// Reads execution `(header, body)` pairs out of an ERA file.
 ///
@@ -41,6 +68,67 @@ pub trait EraBlockReader<BH, BB> {
     fn blocks<M: EraMeta + ?Sized>(
         meta: &M,
     ) -> eyre::Result<impl Iterator<Item = eyre::Result<(BH, BB)>>>;
+
+    /// Opens the ERA file at `meta` and iterates its execution blocks without touching their
+    /// receipts, with [`DecodedBlock::receipts`] left out.
+    ///
+    /// The default decodes [`EraBlockReader::blocks`]; formats that store receipts override it to
+    /// skip decompressing and checking them.
+    fn blocks_without_receipts<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        Ok(Self::blocks(meta)?.map(|block| block.map(DecodedBlock::from)))
+    }
+
+    /// Opens the ERA file at `meta` and iterates its execution blocks along with their receipts.
+    ///
+    /// Formats whose receipts can't be turned into the node's receipt type yield
+    /// [`DecodedBlock::receipts`] as `None`, which is the default.
+    fn blocks_with_receipts<M: EraMeta + ?Sized, R: Receipt>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        Ok(Self::blocks(meta)?.map(|block| block.map(DecodedBlock::from)))
+    }
+
+    /// Opens the ERA file at `meta` and iterates the headers of its execution blocks, with
+    /// [`DecodedBlock::body`] and [`DecodedBlock::receipts`] left out.
+    ///
+    /// The default decodes whole blocks and drops their bodies; formats that store headers
+    /// separately override it to skip decompressing the rest.
+    fn headers<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        Ok(Self::blocks(meta)?.map(|block| {
+            block.map(|(header, _)| DecodedBlock {
+                header,
+                body: None,
+                receipts: None,
+                blooms: None,
+                total_difficulty: None,
+            })
+        }))
+    }
+}
+
+/// An execution block decoded from an ERA file, as appended by [`process_iter`].
+#[derive(Debug)]
+pub struct DecodedBlock<BH, BB, R> {
+    /// Block header.
+    pub header: BH,
+    /// Block body, unless only the header was decoded.
+    pub body: Option<BB>,
+    /// Block receipts, if the file carries them and they were decoded.
+    pub receipts: Option<Vec<R>>,
+    /// Log blooms the file encodes for [`DecodedBlock::receipts`], if it carries them.
+    pub blooms: Option<Vec<Bloom>>,
+    /// Total difficulty up to and including this block, if the file carries it.
+    pub total_difficulty: Option<U256>,
+}
+
+impl<BH, BB, R> From<(BH, BB)> for DecodedBlock<BH, BB, R> {
+    fn from((header, body): (BH, BB)) -> Self {
+        Self { header, body: Some(body), receipts: None, blooms: None, total_difficulty: None }
+    }
 }
 
 /// [`EraBlockReader`] for `.era1` files.
@@ -55,9 +143,38 @@ where
     fn blocks<M: EraMeta + ?Sized>(
         meta: &M,
     ) -> eyre::Result<impl Iterator<Item = eyre::Result<(BH, BB)>>> {
-        let reader: Era1Reader<std::fs::File> = open(meta)?;
+        let reader: Era1Reader<Box<dyn EraRead>> = open(meta)?;
         Ok(reader.iter().map(decode::<BH, BB, E2sError>))
     }
+
+    fn blocks_without_receipts<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        let reader: Era1Reader<Box<dyn EraRead>> = open(meta)?;
+        Ok(reader.iter().map(|block| {
+            let block = block?;
+            let total_difficulty = block.total_difficulty.value;
+            let decoded = decode_without_receipts::<BH, BB, E2sError>(Ok(block))?;
+            Ok(DecodedBlock {
+                total_difficulty: Some(total_difficulty),
+                ..DecodedBlock::from(decoded)
+            })
+        }))
+    }
+
+    fn blocks_with_receipts<M: EraMeta + ?Sized, R: Receipt>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        let reader: Era1Reader<Box<dyn EraRead>> = open(meta)?;
+        Ok(reader.iter().map(decode_with_receipts::<BH, BB, R, E2sError>))
+    }
+
+    fn headers<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        let reader: Era1Reader<Box<dyn EraRead>> = open(meta)?;
+        Ok(reader.iter().map(decode_header::<BH, BB, R, E2sError>))
+    }
 }
 
 impl<BH, BB> EraBlockReader<BH, BB> for Ere
@@ -68,9 +185,29 @@ where
     fn blocks<M: EraMeta + ?Sized>(
         meta: &M,
     ) -> eyre::Result<impl Iterator<Item = eyre::Result<(BH, BB)>>> {
-        let reader: EreReader<std::fs::File> = open(meta)?;
+        let reader: EreReader<Box<dyn EraRead>> = open(meta)?;
         Ok(reader.iter().map(Self::decode))
     }
+
+    fn blocks_without_receipts<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        let reader: EreReader<Box<dyn EraRead>> = open(meta)?;
+        Ok(reader.iter().map(Self::decode_with_total_difficulty))
+    }
+
+    fn blocks_with_receipts<M: EraMeta + ?Sized, R: Receipt>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        Self::blocks_without_receipts(meta)
+    }
+
+    fn headers<M: EraMeta + ?Sized, R>(
+        meta: &M,
+    ) -> eyre::Result<impl Iterator<Item = eyre::Result<DecodedBlock<BH, BB, R>>>> {
+        Ok(Self::blocks_without_receipts(meta)?
+            .map(|block| block.map(|block| DecodedBlock { body: None, ..block })))
+    }
 }
 
 /// [`EraBlockReader`] for `.ere`/`.erae` files.
@@ -91,6 +228,25 @@ impl Ere {
         let body: BB = block.body.decode()?;
         Ok((header, body))
     }
+
+    /// Like [`Ere::decode`], along with the total difficulty the block tuple carries for a
+    /// proof-of-work block.
+    ///
+    /// Merge-spanning files also carry it for the blocks past the merge, where it no longer
+    /// advances, so it is left out for them.
+    fn decode_with_total_difficulty<BH, BB, R>(
+        block: Result<EreBlockTuple, E2sError>,
+    ) -> eyre::Result<DecodedBlock<BH, BB, R>>
+    where
+        BH: FullBlockHeader + Value,
+        BB: FullBlockBody<OmmerHeader = BH>,
+    {
+        let block = block?;
+        let total_difficulty = block.total_difficulty.as_ref().map(|td| td.value);
+        let decoded = DecodedBlock::from(Self::decode::<BH, BB, E2sError>(Ok(block))?);
+        let proof_of_work = !decoded.header.difficulty().is_zero();
+        Ok(DecodedBlock { total_difficulty: total_difficulty.filter(|_| proof_of_work), ..decoded })
+    }
 }
 
 /// [`EraBlockReader`] for consensus-layer `.era` files.
@@ -110,7 +266,7 @@ where
     fn blocks<M: EraMeta + ?Sized>(
         meta: &M,
     ) -> eyre::Result<impl Iterator<Item = eyre::Result<(BH, BB)>>> {
-        let reader: EraReader<std::fs::File> = open(meta)?;
+        let reader: EraReader<Box<dyn EraRead>> = open(meta)?;
         let mut buf = Vec::new();
         Ok(reader.iter().filter_map(move |block| Self::decode(block, &mut buf).transpose()))
     }
@@ -154,42 +310,237 @@ where
     Ok(<U as alloy_rlp::Decodable>::decode(&mut buf.as_slice())?)
 }
 
-/// Opens the ERA file at `meta` with the format's [`StreamReader`].
+/// Opens the ERA file at `meta` with the format's [`StreamReader`], reading it through
+/// [`EraMeta::open`].
 pub fn open<Reader>(meta: &(impl EraMeta + ?Sized)) -> eyre::Result<Reader>
 where
-    Reader: StreamReader<std::fs::File>,
+    Reader: StreamReader<Box<dyn EraRead>>,
 {
-    Ok(Reader::new(fs::open(meta.path())?))
+    Ok(Reader::new(meta.open()?))
+}
+
+/// What an ERA import decodes and where it writes it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Headers and transactions are written through the [`HistoryWriter`] to the static files of
+    /// a node's provider, and only the body indices, ommers and withdrawals to its database.
+    /// Receipts the file carries are checked against their header, subject to
+    /// [`ImportConfig::verification`], but left for the execution stage to produce.
+    ///
+    /// Blocks-only archives, see [`ArchiveProfile::BlocksOnly`], import in this mode, as in
+    /// [`ImportMode::SkipReceipts`] and [`ImportMode::HeadersOnly`], such as to backfill the
+    /// bodies of stored headers.
+    #[default]
+    Database,
+    /// Like [`ImportMode::Database`], but receipts are also decoded and appended straight to the
+    /// receipts static file segment, where historical receipts ultimately live.
+    ///
+    /// Only formats that carry full receipts (`.era1`) support this mode, and blocks-only
+    /// archives are refused before they are opened. The receipts segment
+    /// runs ahead of the execution stage checkpoint afterwards, so it suits nodes that serve
+    /// history rather than re-execute it.
+    StaticFiles,
+    /// Like [`ImportMode::Database`], but `.era1` receipts are neither decompressed nor checked
+    /// against the header's receipts root, even with [`ImportConfig::recompute_blooms`] set.
+    ///
+    /// For operators who don't serve historical receipts; pair it with receipts pruning to also
+    /// keep the receipts produced by execution off disk.
+    SkipReceipts,
+    /// Only headers, and the total difficulty where the format carries it, are decoded and written
+    /// to static files. Bodies and receipts are skipped without being decompressed, and only the
+    /// headers stage checkpoint is advanced, leaving bodies to be downloaded later.
+    ///
+    /// Only `.era1` avoids decoding bodies; other formats decode whole blocks and drop them.
+    HeadersOnly,
+}
+
+/// Default [`ImportConfig::memory_budget`], small enough for an import to run alongside a node on a
+/// host with 8 GB of memory.
+pub const DEFAULT_IMPORT_MEMORY_BUDGET: usize = 512 * 1024 * 1024;
+
+/// Size of the ETL buffer an import collects block hashes into, in bytes, unless configured
+/// otherwise.
+pub const DEFAULT_ETL_FILE_SIZE: usize = 500 * 1024 * 1024;
+
+/// Configuration to import block history from ERA files.
+#[derive(Clone, Debug, Default)]
+pub struct ImportConfig {
+    /// Block height to stop the import at.
+    ///
+    /// The file containing it is imported up to and including this height. When `None`, the
+    /// import continues until the source has no more files.
+    pub to_block: Option<BlockNumber>,
+    /// How blocks whose transactions, ommers or receipts don't match their header are handled.
+    pub verification: VerificationMode,
+    /// What is decoded and where it is written.
+    pub mode: ImportMode,
+    /// Limit on how fast files are imported. When `None`, files are imported as fast as they can
+    /// be written.
+    pub throttle: Option<ImportThrottle>,
+    /// Total difficulty at which the chain merged into proof-of-stake, from the chain spec.
+    ///
+    /// When set, every block whose file carries its total difficulty, as `.era1` files do, and
+    /// every proof-of-work block is checked with [`verify_pre_merge`] in every [`ImportMode`], so
+    /// an archive holding post-merge blocks or proof-of-work blocks past the terminal one is
+    /// refused. Such blocks are rejected regardless of [`ImportConfig::verification`].
+    pub terminal_total_difficulty: Option<U256>,
+    /// Which imported proof-of-work headers have their ethash seal checked with [`verify_seal`],
+    /// none by default.
+    ///
+    /// Checking seals costs about a millisecond per header, plus a few seconds to build the cache
+    /// of each 30000-block epoch, so [`SealVerification::Sampled`] suits whole-chain imports. A
+    /// header with an invalid seal is rejected regardless of [`ImportConfig::verification`].
+    #[cfg(feature = "ethash")]
+    pub seal_verification: SealVerification,
+    /// Network ERA file names must start with, as `mainnet` in `mainnet-00000-5ec1ffb8.era1`.
+    ///
+    /// When set, files named for another network are refused with [`verify_network`] before any
+    /// of their blocks are read.
+    pub network: Option<String>,
+    /// Hashes the chain being imported into is known to have at some heights, such as its genesis
+    /// hash.
+    ///
+    /// A file holding a block at one of these heights with another hash is from another network
+    /// and is refused, even if that block is already stored, before any of its blocks are
+    /// committed.
+    pub known_block_hashes: BTreeMap<BlockNumber, BlockHash>,
+    /// Number of the chain's genesis block, from the chain spec. Non-zero for rollups whose
+    /// history starts at a genesis offset.
+    ///
+    /// Files holding blocks below it are for a chain whose history starts earlier and are refused
+    /// before any of their blocks are committed. When [`process_iter`] is given no start bound,
+    /// it imports from the block after it.
+    pub genesis_block: BlockNumber,
+    /// Block the consensus layer last reported finalized through the engine API, when importing
+    /// into a live node.
+    ///
+    /// The imported history must lead up to it, so archives of a stale fork are refused. A file
+    /// holding a block at its height with another hash is refused before any of its blocks are
+    /// committed, as with [`ImportConfig::known_block_hashes`]. When it is below the stored
+    /// headers, [`import`] first checks that they hold it, as files are checked against those
+    /// headers instead. History imported past the stored headers and short of the finalized block
+    /// can't be linked to it yet, which is left to the headers synced in between.
+    pub finalized_block: Option<BlockNumHash>,
+    /// Whether the log bloom of every decoded receipt is recomputed from its logs rather than
+    /// taken from the file, for operators who put the correctness of stored history before import
+    /// speed.
+    ///
+    /// Receipts are checked against their header's receipts root with the recomputed blooms, and
+    /// blooms the file encodes differently are logged and counted in
+    /// [`ImportSummary::bloom_mismatches`]. Ignored in [`ImportMode::SkipReceipts`] and
+    /// [`ImportMode::HeadersOnly`].
+    pub recompute_blooms: bool,
+    /// Threads recovering the senders of imported transactions, which are then written alongside
+    /// their bodies so the sender recovery stage has nothing left to do. When `None`, senders are
+    /// left to that stage.
+    ///
+    /// Ignored in [`ImportMode::HeadersOnly`]. [`import`] requires the sender recovery stage to
+    /// have caught up with the stored headers, so recovered senders extend those already stored.
+    pub sender_recovery_threads: Option<NonZeroUsize>,
+    /// Whether imported blocks are made queryable by hash as each file is committed.
+    ///
+    /// Block hashes are written to [`tables::HeaderNumbers`] with each file rather than once
+    /// [`import`] ends, and the hashes of imported transactions to
+    /// [`tables::TransactionHashNumbers`], so the transaction lookup stage has nothing left to
+    /// do. Transaction hashes are ignored in [`ImportMode::HeadersOnly`]. [`import`] requires the
+    /// transaction lookup stage to have caught up with the stored headers.
+    pub index_hashes: bool,
+    /// Bytes of decoded block data the import buffers in memory at once. When `None`,
+    /// [`DEFAULT_IMPORT_MEMORY_BUDGET`].
+    ///
+    /// The budget is split evenly between the import's buffers: bodies queued for
+    /// [`ImportConfig::sender_recovery_threads`] and transaction hashes queued for
+    /// [`ImportConfig::index_hashes`] are each written once they fill their share. A larger
+    /// budget writes in fewer, larger batches.
+    pub memory_budget: Option<NonZeroUsize>,
+    /// Threads decoding files ahead of the one being written, so decompressing later files
+    /// overlaps with writing earlier ones. When `None`, each file is decoded as it is written.
+    ///
+    /// Files are still written and committed one at a time, in order. Each thread holds one
+    /// whole decoded file until it is written, on top of [`ImportConfig::memory_budget`]. Only
+    /// [`import`] decodes ahead.
+    pub decode_threads: Option<NonZeroUsize>,
+    /// Shutdown that stops [`import`] early once fired, such as the node's on Ctrl-C.
+    ///
+    /// The file being written is still committed along with its stage checkpoints, so the import
+    /// stops at a file boundary and a later one picks up after it. Dropping the
+    /// [`Signal`](reth_tasks::shutdown::Signal) counts as firing it. Only [`import`] stops early.
+    pub shutdown: Option<Shutdown>,
+}
+
+impl ImportConfig {
+    /// Returns the threads to recover senders with, if they are recovered at all.
+    const fn recovers_senders(&self) -> Option<NonZeroUsize> {
+        match self.mode {
+            ImportMode::HeadersOnly => None,
+            _ => self.sender_recovery_threads,
+        }
+    }
+
+    /// Returns the hash the chain is known to have at `number`, and whether that is the hash of
+    /// [`ImportConfig::finalized_block`].
+    fn expected_hash(&self, number: BlockNumber) -> Option<(BlockHash, bool)> {
+        match self.finalized_block {
+            Some(finalized) if finalized.number == number => Some((finalized.hash, true)),
+            _ => self.known_block_hashes.get(&number).map(|&hash| (hash, false)),
+        }
+    }
+
+    /// Returns `true` if the hashes of imported transactions are indexed.
+    const fn indexes_transactions(&self) -> bool {
+        self.index_hashes && !matches!(self.mode, ImportMode::HeadersOnly)
+    }
+
+    /// Returns the bytes each buffer of the import may hold, its share of
+    /// [`ImportConfig::memory_budget`].
+    fn buffer_budget(&self) -> usize {
+        let budget = self.memory_budget.map_or(DEFAULT_IMPORT_MEMORY_BUDGET, NonZeroUsize::get);
+        let buffers = usize::from(self.recovers_senders().is_some()) +
+            usize::from(self.indexes_transactions());
+        budget / buffers.max(1)
+    }
 }
 
 /// Imports blocks from `downloader`, decoding each file with the [`EraBlockReader`] `S`.
 ///
-/// When `to_block` is set, the import stops after reaching that block height; otherwise it
-/// continues until the source has no more files.
+/// Files are written and committed one at a time, in order. With
+/// [`ImportConfig::decode_threads`] set, the files after the one being written are decoded
+/// concurrently on that many threads.
+///
+/// `hooks` are told about every appended block and, once committed, every imported file.
 ///
-/// Returns current block height.
+/// The import starts after the last block whose body is stored, so bodies missing behind stored
+/// headers are filled in; see [`process_iter`] for how those headers are checked. What is stored
+/// is logged up front, and bodies still missing once the import ends are logged as a warning.
+///
+/// Once [`ImportConfig::shutdown`] fires, the import stops after committing the file at hand.
+///
+/// With [`ImportConfig::finalized_block`] set, the stored headers are checked to hold it before
+/// any file is read, when they reach its height.
+///
+/// Returns an [`ImportSummary`] of every file imported or skipped.
 pub fn import<S, Downloader, Era, PF, B, BB, BH>(
     mut downloader: Downloader,
     provider_factory: &PF,
     hash_collector: &mut Collector<BlockHash, BlockNumber>,
-    to_block: Option<BlockNumber>,
-) -> eyre::Result<BlockNumber>
+    config: &ImportConfig,
+    hooks: &mut impl ImportHooks<BH, BB>,
+) -> eyre::Result<ImportSummary>
 where
     S: EraBlockReader<BH, BB>,
     B: Block<Header = BH, Body = BB>,
     BH: FullBlockHeader + Value,
     BB: FullBlockBody<
-        Transaction = <<<PF as DatabaseProviderFactory>::ProviderRW as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
+        Transaction = <<<PF as HistoryWriterFactory>::Writer as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
         OmmerHeader = BH,
     >,
     Downloader: Stream<Item = eyre::Result<Era>> + Send + 'static + Unpin,
     Era: EraMeta + Send + 'static,
-    PF: DatabaseProviderFactory<
-        ProviderRW: BlockWriter<Block = B>
-            + DBProvider
-            + StaticFileProviderFactory<Primitives: NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB>>
-            + StageCheckpointWriter,
-    > + StaticFileProviderFactory<Primitives = <<PF as DatabaseProviderFactory>::ProviderRW as NodePrimitivesProvider>::Primitives>,
+    PF: HistoryWriterFactory<
+        Writer: HistoryWriter<
+            Primitives: NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
+        >,
+    >,
 {
     let (tx, rx) = mpsc::channel();
 
@@ -201,47 +552,221 @@ where
         tx.send(None)
     });
 
-    let static_file_provider = provider_factory.static_file_provider();
-
-    // Consistency check of expected headers in static files vs DB is done on provider::sync_gap
-    // when poll_execute_ready is polled.
-    let mut height = static_file_provider
-        .get_highest_static_file_block(StaticFileSegment::Headers)
-        .unwrap_or_default();
-
-    let end = to_block.map_or(Bound::Unbounded, Bound::Included);
-
-    while let Some(meta) = rx.recv()? {
-        let meta = meta?;
-        let from = height;
-        let provider = provider_factory.database_provider_rw()?;
+    let stored = provider_factory.history_writer()?.stored_history()?;
+    // Bodies missing behind the stored headers are imported too, checking the files against those
+    // headers instead of writing them again.
+    let mut height = match config.mode {
+        ImportMode::HeadersOnly => stored.headers,
+        _ => stored.bodies,
+    };
+    info!(target: "era::history::import", %stored, first = height + 1, "Importing ERA files");
+    if let Some(finalized) = config.finalized_block {
+        check_stored_finalized(&provider_factory.history_writer()?, finalized, stored.headers)?;
+    }
+    let mut summary = ImportSummary::new(height);
 
-        height = process::<S, _, _, _, _>(
-            &meta,
-            &mut static_file_provider.latest_writer(StaticFileSegment::Headers)?,
-            &provider,
-            hash_collector,
-            (Bound::Included(height), end),
-        )?;
+    let end = config.to_block.map_or(Bound::Unbounded, Bound::Included);
 
-        save_stage_checkpoints(&provider, from, height, height, height)?;
+    // Senders and transaction hashes written by the import extend those of their stages.
+    let stages = config
+        .recovers_senders()
+        .map(|_| StageId::SenderRecovery)
+        .into_iter()
+        .chain(config.indexes_transactions().then_some(StageId::TransactionLookup))
+        .collect::<Vec<_>>();
+    {
+        let provider = provider_factory.history_writer()?;
+        for &stage in &stages {
+            let checkpoint = provider.get_stage_checkpoint(stage)?.unwrap_or_default().block_number;
+            if checkpoint != height {
+                eyre::bail!(
+                    "the {stage} stage has only reached block {checkpoint}, but bodies are \
+                     stored up to block {height}; run it up to the tip before this import"
+                );
+            }
+        }
+    }
 
-        provider.commit()?;
+    // Decodes a whole file on a worker of `config.decode_threads`.
+    let decode = |meta: &Era| {
+        decode_file::<
+            S,
+            _,
+            BH,
+            BB,
+            <<<PF as HistoryWriterFactory>::Writer as NodePrimitivesProvider>::Primitives as NodePrimitives>::Receipt,
+        >(meta, config)
+    };
+    std::thread::scope(|scope| -> eyre::Result<()> {
+        let mut ahead =
+            config.decode_threads.map(|threads| DecodeAhead::spawn(scope, threads, &decode));
+        let mut source_ended = false;
+
+        loop {
+            let (meta, decoded, waited) = match &mut ahead {
+                Some(ahead) => {
+                    // Queue files as they arrive, only waiting for one when none is queued.
+                    while !source_ended && !ahead.is_full() {
+                        let file = if ahead.is_empty() {
+                            rx.recv()?
+                        } else {
+                            match rx.try_recv() {
+                                Ok(file) => file,
+                                Err(TryRecvError::Empty) => break,
+                                Err(err) => return Err(err.into()),
+                            }
+                        };
+                        match file {
+                            Some(meta) => ahead.push(meta?),
+                            None => source_ended = true,
+                        }
+                    }
+
+                    let waiting = Instant::now();
+                    let Some(decoded) = ahead.pop() else { break };
+                    let (meta, blocks) = decoded?;
+                    (meta, Some(blocks?), waiting.elapsed())
+                }
+                None => match rx.recv()? {
+                    Some(meta) => (meta?, None, Duration::ZERO),
+                    None => break,
+                },
+            };
+
+            let from = height;
+            let started = Instant::now();
+            // Sized up front, as processing may remove the file. Sources not backed by a file on
+            // disk count as empty.
+            let size = fs::metadata(meta.path()).map_or(0, |metadata| metadata.len());
+            let provider = provider_factory.history_writer()?;
+
+            let mut file = match decoded {
+                Some(blocks) => process_iter(
+                    blocks
+                        .into_iter()
+                        .map(|block| Some(Ok(block)))
+                        .chain(std::iter::once_with(|| mark_as_processed(&meta)))
+                        .flatten(),
+                    &provider,
+                    hash_collector,
+                    (Bound::Included(height), end),
+                    config,
+                    hooks,
+                )?,
+                None => process::<S, _, _, _, _>(
+                    &meta,
+                    &provider,
+                    hash_collector,
+                    (Bound::Included(height), end),
+                    config,
+                    hooks,
+                )?,
+            };
+            height = file.last_block;
+            file.bytes_read = size;
+            file.timings.decode += waited;
+            if file.blocks_written > 0 {
+                file.files_imported = 1;
+            } else {
+                file.files_skipped = 1;
+            }
+
+            let committing = Instant::now();
+            if config.mode == ImportMode::HeadersOnly {
+                save_headers_checkpoint(&provider, from, height, height, height)?;
+            } else if height < stored.headers {
+                // Only bodies were imported so far, behind headers stored before the import.
+                save_bodies_checkpoint(&provider, height, height, height)?;
+            } else {
+                save_stage_checkpoints(&provider, from, height, height, height)?;
+            }
+            for &stage in &stages {
+                provider.save_stage_checkpoint(stage, StageCheckpoint::new(height))?;
+            }
+            if config.index_hashes {
+                let indexing = Instant::now();
+                provider.insert_block_hashes(hash_collector)?;
+                hash_collector.clear();
+                file.timings.index = indexing.elapsed();
+            }
+
+            provider.commit()?;
+            file.timings.commit = committing.elapsed().saturating_sub(file.timings.index);
+            summary.add(&file);
+
+            info!(target: "era::history::import", first = from, last = height, file = %meta.path().display(), "Imported ERA file");
+
+            hooks.on_file(&ImportedFile {
+                path: meta.path(),
+                blocks: from + 1..=height,
+                elapsed: started.elapsed(),
+            })?;
+
+            if config.to_block.is_some_and(|to| height >= to) {
+                break;
+            }
+
+            if is_shutting_down(config.shutdown.as_ref()) {
+                info!(target: "era::history::import", last = height, "Stopping ERA import on shutdown");
+                summary.interrupted = true;
+                break;
+            }
+
+            if let Some(throttle) = config.throttle {
+                let delay = throttle.delay(started.elapsed(), size, height - from);
+                if !delay.is_zero() {
+                    debug!(target: "era::history::import", ?delay, "Throttling ERA import");
+                    std::thread::sleep(delay);
+                }
+            }
+        }
 
-        info!(target: "era::history::import", first = from, last = height, file = %meta.path().display(), "Imported ERA file");
+        Ok(())
+    })?;
 
-        if to_block.is_some_and(|to| height >= to) {
-            break;
-        }
+    if config.mode != ImportMode::HeadersOnly && height < stored.headers {
+        warn!(target: "era::history::import", missing = ?(height + 1..=stored.headers), "Bodies of stored headers are still missing after the import");
+    }
+    if let Some(finalized) = config.finalized_block &&
+        height > stored.headers &&
+        height < finalized.number
+    {
+        debug!(target: "era::history::import", last = height, finalized = finalized.number, "Imported history is linked to the finalized block once the headers in between are synced");
     }
 
-    let provider = provider_factory.database_provider_rw()?;
+    let provider = provider_factory.history_writer()?;
 
-    build_index(&provider, hash_collector)?;
+    let indexing = Instant::now();
+    provider.insert_block_hashes(hash_collector)?;
+    summary.timings.index += indexing.elapsed();
 
+    let committing = Instant::now();
     provider.commit()?;
+    summary.timings.commit += committing.elapsed();
+
+    Ok(summary)
+}
 
-    Ok(height)
+/// Checks that the headers stored up to `stored_headers` hold `finalized` if they reach its height,
+/// as imported blocks below them are only checked against those headers.
+fn check_stored_finalized(
+    provider: &impl HistoryWriter,
+    finalized: BlockNumHash,
+    stored_headers: BlockNumber,
+) -> eyre::Result<()> {
+    if finalized.number > stored_headers {
+        return Ok(())
+    }
+    let stored = provider.block_hash(finalized.number)?;
+    if stored != Some(finalized.hash) {
+        eyre::bail!(
+            "the finalized block {} is {}, but the stored chain has {stored:?} at that height; \
+             the local history is on a stale fork, so unwind it before importing",
+            finalized.number,
+            finalized.hash
+        );
+    }
+    Ok(())
 }
 
 /// Saves progress of ERA import into stages sync.
@@ -256,6 +781,39 @@ pub fn save_stage_checkpoints<P>(
     processed: u64,
     total: u64,
 ) -> ProviderResult<()>
+where
+    P: StageCheckpointWriter,
+{
+    save_headers_checkpoint(&provider, from, to, processed, total)?;
+    save_bodies_checkpoint(&provider, to, processed, total)
+}
+
+/// Saves the stage checkpoint of [`StageId::Bodies`] alone, for imports filling in the bodies of
+/// headers stored before.
+pub fn save_bodies_checkpoint<P>(
+    provider: P,
+    to: BlockNumber,
+    processed: u64,
+    total: u64,
+) -> ProviderResult<()>
+where
+    P: StageCheckpointWriter,
+{
+    provider.save_stage_checkpoint(
+        StageId::Bodies,
+        StageCheckpoint::new(to)
+            .with_entities_stage_checkpoint(EntitiesCheckpoint { processed, total }),
+    )
+}
+
+/// Saves the stage checkpoint of [`StageId::Headers`] alone, for imports that leave bodies out.
+pub fn save_headers_checkpoint<P>(
+    provider: P,
+    from: BlockNumber,
+    to: BlockNumber,
+    processed: u64,
+    total: u64,
+) -> ProviderResult<()>
 where
     P: StageCheckpointWriter,
 {
@@ -265,24 +823,25 @@ where
             block_range: CheckpointBlockRange { from, to },
             progress: EntitiesCheckpoint { processed, total },
         }),
-    )?;
-    provider.save_stage_checkpoint(
-        StageId::Bodies,
-        StageCheckpoint::new(to)
-            .with_entities_stage_checkpoint(EntitiesCheckpoint { processed, total }),
-    )?;
-    Ok(())
+    )
 }
 
 /// Reads `meta` with the [`EraBlockReader`] `S`, appends its blocks within `block_numbers`, and
-/// marks `meta` processed if the file was fully consumed. Returns last block height.
+/// marks `meta` processed if the file was fully consumed. Returns an [`ImportSummary`] of the
+/// appended blocks.
+///
+/// A file not named for [`ImportConfig::network`], or named as a blocks-only archive in
+/// [`ImportMode::StaticFiles`], is refused before it is opened.
+///
+/// See [`process_iter`] for how `config` and `hooks` apply.
 pub fn process<S, P, B, BB, BH>(
     meta: &(impl EraMeta + ?Sized),
-    writer: &mut StaticFileProviderRWRefMut<'_, <P as NodePrimitivesProvider>::Primitives>,
     provider: &P,
     hash_collector: &mut Collector<BlockHash, BlockNumber>,
     block_numbers: impl RangeBounds<BlockNumber>,
-) -> eyre::Result<BlockNumber>
+    config: &ImportConfig,
+    hooks: &mut impl ImportHooks<BH, BB>,
+) -> eyre::Result<ImportSummary>
 where
     S: EraBlockReader<BH, BB>,
     B: Block<Header = BH, Body = BB>,
@@ -291,22 +850,101 @@ where
         Transaction = <<P as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
         OmmerHeader = BH,
     >,
-    P: DBProvider<Tx: DbTxMut> + NodePrimitivesProvider + BlockWriter<Block = B>,
-    <P as NodePrimitivesProvider>::Primitives: NodePrimitives<BlockHeader = BH, BlockBody = BB>,
+    P: HistoryWriter,
+    <P as NodePrimitivesProvider>::Primitives:
+        NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
 {
-    let iter = S::blocks(meta)?
-        .map(Some)
-        .chain(std::iter::once_with(|| match meta.mark_as_processed() {
-            Ok(()) => None,
-            Err(error) => Some(Err(error)),
-        }))
-        .flatten();
+    check_file_name(meta, config)?;
+
+    match config.mode {
+        ImportMode::SkipReceipts => {
+            let iter = S::blocks_without_receipts(meta)?
+                .map(Some)
+                .chain(std::iter::once_with(|| mark_as_processed(meta)))
+                .flatten();
+            process_iter(iter, provider, hash_collector, block_numbers, config, hooks)
+        }
+        ImportMode::Database | ImportMode::StaticFiles => {
+            let iter = S::blocks_with_receipts(meta)?
+                .map(Some)
+                .chain(std::iter::once_with(|| mark_as_processed(meta)))
+                .flatten();
+            process_iter(iter, provider, hash_collector, block_numbers, config, hooks)
+        }
+        ImportMode::HeadersOnly => {
+            let iter = S::headers(meta)?
+                .map(Some)
+                .chain(std::iter::once_with(|| mark_as_processed(meta)))
+                .flatten();
+            process_iter(iter, provider, hash_collector, block_numbers, config, hooks)
+        }
+    }
+}
+
+/// Decodes every block of `meta` as [`process`] would in [`ImportConfig::mode`], refusing the
+/// files [`process`] refuses first.
+fn decode_file<S, M, BH, BB, R>(
+    meta: &M,
+    config: &ImportConfig,
+) -> eyre::Result<Vec<DecodedBlock<BH, BB, R>>>
+where
+    S: EraBlockReader<BH, BB>,
+    M: EraMeta + ?Sized,
+    R: Receipt,
+{
+    check_file_name(meta, config)?;
+
+    match config.mode {
+        ImportMode::SkipReceipts => S::blocks_without_receipts(meta)?.collect(),
+        ImportMode::Database | ImportMode::StaticFiles => S::blocks_with_receipts(meta)?.collect(),
+        ImportMode::HeadersOnly => S::headers(meta)?.collect(),
+    }
+}
+
+/// Refuses `meta` if it isn't named for [`ImportConfig::network`], or is named as a blocks-only
+/// archive while [`ImportConfig::mode`] imports receipts.
+fn check_file_name(meta: &(impl EraMeta + ?Sized), config: &ImportConfig) -> eyre::Result<()> {
+    let path = meta.path();
+    if let Some(network) = &config.network {
+        verify_network(path, network)?;
+    }
+    if config.mode == ImportMode::StaticFiles && !ArchiveProfile::of_file(path).has_receipts() {
+        eyre::bail!(
+            "ERA file {} is a blocks-only archive without receipts, which can't be imported into \
+             static files; import it with `ImportMode::Database` or `ImportMode::SkipReceipts`",
+            path.display()
+        );
+    }
+    Ok(())
+}
 
-    process_iter(iter, writer, provider, hash_collector, block_numbers)
+/// Marks `meta` processed, yielding the error as a final iterator item if that fails.
+fn mark_as_processed<T>(meta: &(impl EraMeta + ?Sized)) -> Option<eyre::Result<T>> {
+    meta.mark_as_processed().err().map(Err)
 }
 
 /// Extracts a pair of [`FullBlockHeader`] and [`FullBlockBody`] from [`BlockTuple`].
+///
+/// The tuple's receipts are not returned, but they are checked against the header's receipts root
+/// so that an archive with corrupt receipts is rejected.
 pub fn decode<BH, BB, E>(block: Result<BlockTuple, E>) -> eyre::Result<(BH, BB)>
+where
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<OmmerHeader = BH>,
+    E: From<E2sError> + Error + Send + Sync + 'static,
+{
+    let block = block?;
+    let header: BH = block.header.decode()?;
+    let receipts: Vec<ReceiptEnvelope> = block.receipts.decode()?;
+    verify_receipts_root(&header, &receipts)?;
+    let body: BB = block.body.decode()?;
+
+    Ok((header, body))
+}
+
+/// Extracts a pair of [`FullBlockHeader`] and [`FullBlockBody`] from [`BlockTuple`], leaving the
+/// tuple's receipts compressed and unchecked.
+pub fn decode_without_receipts<BH, BB, E>(block: Result<BlockTuple, E>) -> eyre::Result<(BH, BB)>
 where
     BH: FullBlockHeader + Value,
     BB: FullBlockBody<OmmerHeader = BH>,
@@ -319,24 +957,121 @@ where
     Ok((header, body))
 }
 
-/// Extracts block headers and bodies from `iter` and appends them using `writer` and `provider`.
+/// Extracts a [`DecodedBlock`] with its receipts, in the node's receipt type `R`, from
+/// [`BlockTuple`].
+///
+/// Unlike [`decode`], the receipts are not checked here but by [`process_iter`], subject to
+/// [`ImportConfig::verification`].
+pub fn decode_with_receipts<BH, BB, R, E>(
+    block: Result<BlockTuple, E>,
+) -> eyre::Result<DecodedBlock<BH, BB, R>>
+where
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<OmmerHeader = BH>,
+    R: Receipt,
+    E: From<E2sError> + Error + Send + Sync + 'static,
+{
+    let block = block?;
+    let header: BH = block.header.decode()?;
+    let body: BB = block.body.decode()?;
+    let receipts: Vec<ReceiptWithBloom<R>> = block.receipts.decode()?;
+    let (receipts, blooms) =
+        receipts.into_iter().map(|receipt| (receipt.receipt, receipt.logs_bloom)).unzip();
+
+    Ok(DecodedBlock {
+        header,
+        body: Some(body),
+        receipts: Some(receipts),
+        blooms: Some(blooms),
+        total_difficulty: Some(block.total_difficulty.value),
+    })
+}
+
+/// Extracts a header-only [`DecodedBlock`] from [`BlockTuple`], carrying the tuple's total
+/// difficulty. The body and receipts are left compressed.
+pub fn decode_header<BH, BB, R, E>(
+    block: Result<BlockTuple, E>,
+) -> eyre::Result<DecodedBlock<BH, BB, R>>
+where
+    BH: FullBlockHeader + Value,
+    E: From<E2sError> + Error + Send + Sync + 'static,
+{
+    let block = block?;
+    let header: BH = block.header.decode()?;
+
+    Ok(DecodedBlock {
+        header,
+        body: None,
+        receipts: None,
+        blooms: None,
+        total_difficulty: Some(block.total_difficulty.value),
+    })
+}
+
+/// Extracts block headers and bodies from `iter` and appends them to `provider`.
 ///
 /// Collects hash to height using `hash_collector`.
 ///
 /// Skips all blocks below the [`start_bound`] of `block_numbers` and stops when reaching past the
 /// [`end_bound`] or the end of the file.
 ///
-/// Returns last block height.
+/// Every appended header must link to its predecessor by parent hash, starting from the block
+/// stored at the start bound, so a file that does not extend the stored chain is rejected with the
+/// offending block number before that block is written. A first block forking off the stored block
+/// is reported as such, and a start bound past the stored headers is rejected, as nothing tells
+/// whether the file extends them. Blocks below [`ImportConfig::genesis_block`] are rejected.
+/// Blocks carrying their total difficulty are checked against
+/// [`ImportConfig::terminal_total_difficulty`] when it is set, with the `ethash` feature appended
+/// blocks' seals according to `ImportConfig::seal_verification`, and every block, skipped or not,
+/// against [`ImportConfig::known_block_hashes`] and [`ImportConfig::finalized_block`].
+///
+/// Blocks past the start bound whose header is already stored, as when bodies are imported after
+/// an [`ImportMode::HeadersOnly`] import, must match that header by hash. Their header is left as
+/// stored and only the rest of the block is written.
+///
+/// Each body is checked against the transactions root and ommers hash of its header, with
+/// mismatches handled according to [`ImportConfig::verification`]. Receipts, decoded in
+/// [`ImportMode::Database`] and [`ImportMode::StaticFiles`], are checked against its receipts root
+/// the same way, with the blooms the file encodes unless those are recomputed.
+/// In [`ImportMode::StaticFiles`] they are then appended to the receipts segment; blocks without
+/// receipts are rejected in that mode. In
+/// [`ImportMode::HeadersOnly`] only headers are appended. Whatever the mode, headers are appended
+/// with the total difficulty the file carries, or for proof-of-work blocks one derived from the
+/// previous block in the file, which is rejected when that block's isn't known either. Post-merge
+/// blocks of formats without it are appended with zero. The range is bounded
+/// by `block_numbers` alone; [`ImportConfig::to_block`] is only consulted by [`import`].
+///
+/// With [`ImportConfig::sender_recovery_threads`] set, the senders of appended transactions are
+/// recovered in parallel and written after those already stored, in batches bounded by
+/// [`ImportConfig::memory_budget`] and once more before returning. With
+/// [`ImportConfig::index_hashes`] set, their hashes are written to
+/// [`tables::TransactionHashNumbers`] the same way.
+///
+/// [`ImportHooks::on_block`] is called on `hooks` after each block is appended.
+///
+/// Returns an [`ImportSummary`] of the appended blocks, whose [`ImportSummary::last_block`] is the
+/// last block height. Time spent pulling blocks out of `iter` counts as decoding.
 ///
 /// [`start_bound`]: RangeBounds::start_bound
 /// [`end_bound`]: RangeBounds::end_bound
 pub fn process_iter<P, B, BB, BH>(
-    mut iter: impl Iterator<Item = eyre::Result<(BH, BB)>>,
-    writer: &mut StaticFileProviderRWRefMut<'_, <P as NodePrimitivesProvider>::Primitives>,
+    mut iter: impl Iterator<
+        Item = eyre::Result<
+            impl Into<
+                DecodedBlock<
+                    BH,
+                    BB,
+                    <<P as NodePrimitivesProvider>::Primitives as NodePrimitives>::Receipt,
+                >,
+            >,
+        >,
+    >,
     provider: &P,
     hash_collector: &mut Collector<BlockHash, BlockNumber>,
     block_numbers: impl RangeBounds<BlockNumber>,
-) -> eyre::Result<BlockNumber>
+    config: &ImportConfig,
+    hooks: &mut impl ImportHooks<BH, BB>,
+) -> eyre::Result<ImportSummary>
 where
     B: Block<Header = BH, Body = BB>,
     BH: FullBlockHeader + Value,
@@ -344,13 +1079,14 @@ where
         Transaction = <<P as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
         OmmerHeader = BH,
     >,
-    P: DBProvider<Tx: DbTxMut> + NodePrimitivesProvider + BlockWriter<Block = B>,
-    <P as NodePrimitivesProvider>::Primitives: NodePrimitives<BlockHeader = BH, BlockBody = BB>,
+    P: HistoryWriter,
+    <P as NodePrimitivesProvider>::Primitives:
+        NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
 {
     let mut last_header_number = match block_numbers.start_bound() {
         Bound::Included(&number) => number,
         Bound::Excluded(&number) => number.saturating_add(1),
-        Bound::Unbounded => 0,
+        Bound::Unbounded => config.genesis_block,
     };
     let target = match block_numbers.end_bound() {
         Bound::Included(&number) => Some(number),
@@ -358,43 +1094,246 @@ where
         Bound::Unbounded => None,
     };
 
-    for block in &mut iter {
-        let (header, body) = block?;
-        let number = header.number();
-
-        if number <= last_header_number {
-            continue;
-        }
-        if let Some(target) = target &&
-            number > target
-        {
-            break;
-        }
+    // The first appended header must extend the stored tip; afterwards each header must extend the
+    // one appended before it. `None` only when the tip itself is not stored, e.g. an empty
+    // database.
+    let stored_tip = last_header_number;
+    let mut parent_hash = provider.block_hash(stored_tip)?;
+
+    // Headers already stored past the start, as left by a headers-only import, are checked against
+    // the file rather than appended again.
+    let mut stored_hashes = StoredHashes::new(provider.stored_history()?.headers);
+
+    // Total difficulty of the previous block in the file, to derive that of blocks it carries none
+    // for.
+    let mut parent_total_difficulty: Option<U256> = None;
+
+    // Seeded anew for every call, so the sampled seals can't be predicted.
+    #[cfg(feature = "ethash")]
+    let seal_picker = RandomState::new();
+
+    let buffer_budget = config.buffer_budget();
+    let mut senders =
+        config.recovers_senders().map(|threads| SenderRecovery::new(threads, buffer_budget));
+    let mut lookup = config.indexes_transactions().then(|| TransactionLookup::new(buffer_budget));
+
+    let started = Instant::now();
+    let mut summary = ImportSummary::new(last_header_number);
+    let mut decoding = Duration::ZERO;
+    let blocks = std::iter::from_fn(|| {
+        let decode_started = Instant::now();
+        let block = iter.next();
+        decoding += decode_started.elapsed();
+        block
+    });
 
-        // Reject gaps: the import marks the Headers/Bodies stages complete up to `height`, so a
-        // non-contiguous append would leave earlier blocks missing while the stages report done.
-        if number != last_header_number + 1 {
-            eyre::bail!(
-                "non-contiguous ERA import: expected block {}, got {number}; the execution \
-                 database must be synced up to block {} before importing this file",
-                last_header_number + 1,
-                number - 1,
-            );
+    // Static file writers are looked up once for the file rather than for every block.
+    provider.with_appender(|appender| {
+        for block in blocks {
+            let DecodedBlock { header, body, receipts, blooms, total_difficulty } = block?.into();
+            let number = header.number();
+            let hash = header.hash_slow();
+
+            // Proof-of-work blocks need a total difficulty, so one the file doesn't carry is
+            // derived from the previous block in the file.
+            let total_difficulty = total_difficulty.or_else(|| {
+                let difficulty = header.difficulty();
+                (!difficulty.is_zero()).then_some(parent_total_difficulty? + difficulty)
+            });
+            parent_total_difficulty = total_difficulty;
+
+            if number < config.genesis_block {
+                eyre::bail!(
+                    "block {number} is below the genesis block {} of the chain; the ERA files are \
+                     for a chain whose history starts earlier",
+                    config.genesis_block
+                );
+            }
+
+            if let Some((expected, finalized)) = config.expected_hash(number) {
+                if hash != expected && finalized {
+                    eyre::bail!(
+                        "block {number} has hash {hash}, but the finalized block at that height is \
+                         {expected}; the ERA files are from a stale fork"
+                    );
+                }
+                if hash != expected {
+                    eyre::bail!(
+                        "block {number} has hash {hash}, but the chain has {expected} at that \
+                         height; the ERA files are for another network"
+                    );
+                }
+            }
+
+            if number <= last_header_number {
+                continue;
+            }
+            if let Some(target) = target &&
+                number > target
+            {
+                break;
+            }
+
+            // Reject gaps: the import marks the Headers/Bodies stages complete up to `height`, so
+            // a non-contiguous append would leave earlier blocks missing while the stages report
+            // done.
+            if number != last_header_number + 1 {
+                eyre::bail!(
+                    "non-contiguous ERA import: expected block {}, got {number}; the execution \
+                     database must be synced up to block {} before importing this file",
+                    last_header_number + 1,
+                    number - 1,
+                );
+            }
+
+            match parent_hash {
+                Some(expected) if header.parent_hash() != expected && number == stored_tip + 1 => {
+                    eyre::bail!(
+                        "block {number} doesn't extend the local chain: its parent hash {} is not \
+                         the hash {expected} of stored block {stored_tip}; importing it would fork \
+                         the local history, so unwind the database below block {stored_tip} or \
+                         import ERA files of the local chain",
+                        header.parent_hash(),
+                    );
+                }
+                Some(expected) if header.parent_hash() != expected => eyre::bail!(
+                    "broken header chain at block {number}: parent hash {} does not match hash \
+                     {expected} of block {}",
+                    header.parent_hash(),
+                    number - 1,
+                ),
+                None if stored_tip > config.genesis_block => eyre::bail!(
+                    "block {stored_tip} is not stored, so block {number} can't be checked to \
+                     extend the local chain"
+                ),
+                _ => {}
+            }
+
+            if total_difficulty.is_none() && !header.difficulty().is_zero() {
+                eyre::bail!(
+                    "block {number} is a proof-of-work block, but its ERA file carries no total \
+                     difficulty and the one of block {} isn't known to derive it from; import \
+                     pre-merge history from `.era1` files",
+                    number - 1
+                );
+            }
+
+            if let (Some(terminal_total_difficulty), Some(total_difficulty)) =
+                (config.terminal_total_difficulty, total_difficulty)
+            {
+                verify_pre_merge(&header, total_difficulty, terminal_total_difficulty)?;
+            }
+
+            #[cfg(feature = "ethash")]
+            if config.seal_verification.checks(&seal_picker, number) {
+                verify_seal(&header)?;
+            }
+
+            let header_stored = match stored_hashes.get(provider, number)? {
+                Some(stored) if stored != hash => eyre::bail!(
+                    "block {number} has hash {hash}, but the stored header has {stored}; the ERA \
+                     files don't extend the stored chain"
+                ),
+                stored => stored.is_some(),
+            };
+
+            let body = match (config.mode, body) {
+                (ImportMode::HeadersOnly, _) => None,
+                (_, Some(body)) => {
+                    let outcome = verify_body_roots(&header, &body);
+                    summary.validation_warnings += u64::from(outcome.is_err());
+                    config.verification.apply(outcome)?;
+                    Some(body)
+                }
+                (_, None) => eyre::bail!("block {number} carries no body to import"),
+            };
+            let receipts = match (config.mode, receipts) {
+                (ImportMode::SkipReceipts | ImportMode::HeadersOnly, _) |
+                (ImportMode::Database, None) => None,
+                (mode, Some(receipts)) => {
+                    // Blooms are only recomputed from logs when the file has none or they aren't
+                    // trusted, as that hashes every logged address and topic.
+                    let with_bloom: Vec<_> = match &blooms {
+                        Some(blooms) if !config.recompute_blooms => receipts
+                            .iter()
+                            .zip(blooms)
+                            .map(|(receipt, &bloom)| ReceiptWithBloom::new(receipt, bloom))
+                            .collect(),
+                        _ => receipts.iter().map(TxReceipt::with_bloom_ref).collect(),
+                    };
+                    if config.recompute_blooms &&
+                        let Some(blooms) = &blooms
+                    {
+                        let mismatches = with_bloom
+                            .iter()
+                            .zip(blooms)
+                            .filter(|(receipt, bloom)| receipt.logs_bloom != **bloom)
+                            .count() as u64;
+                        if mismatches > 0 {
+                            warn!(target: "era::history::import", block = number, mismatches, "Receipt log blooms in the ERA file don't match their logs, using the recomputed ones");
+                        }
+                        summary.bloom_mismatches += mismatches;
+                    }
+                    let outcome = verify_receipts_root(&header, &with_bloom);
+                    summary.validation_warnings += u64::from(outcome.is_err());
+                    config.verification.apply(outcome)?;
+                    (mode == ImportMode::StaticFiles).then_some(receipts)
+                }
+                (ImportMode::StaticFiles, None) => {
+                    eyre::bail!(
+                        "block {number} carries no receipts to import into static files; this ERA \
+                         format only supports `ImportMode::Database`"
+                    )
+                }
+            };
+
+            last_header_number = number;
+            parent_hash = Some(hash);
+
+            // Append to Headers segment. Post-merge headers without a total difficulty are written
+            // with none, as the headers stage writes them.
+            if !header_stored {
+                appender.append_header(&header, total_difficulty.unwrap_or_default(), &hash)?;
+            }
+
+            // Write bodies to database.
+            if let Some(body) = &body {
+                appender.append_body(number, body)?;
+            }
+
+            if let Some(receipts) = receipts {
+                appender.append_receipts(number, &receipts)?;
+                summary.receipts_written += receipts.len() as u64;
+            }
+
+            if !header_stored {
+                hash_collector.insert(hash, number)?;
+            }
+
+            hooks.on_block(&header, hash, body.as_ref())?;
+
+            if let (Some(lookup), Some(body)) = (&mut lookup, &body) {
+                lookup.push(provider, number, body)?;
+            }
+            if let (Some(senders), Some(body)) = (&mut senders, body) {
+                senders.push(provider, number, body)?;
+            }
+            summary.blocks_written += 1;
         }
+        eyre::Ok(())
+    })?;
 
-        let hash = header.hash_slow();
-        last_header_number = number;
-
-        // Append to Headers segment
-        writer.append_header(&header, &hash)?;
-
-        // Write bodies to database.
-        provider.append_block_bodies(vec![(header.number(), Some(&body))])?;
-
-        hash_collector.insert(hash, number)?;
+    if let Some(senders) = &mut senders {
+        senders.flush(provider)?;
+    }
+    if let Some(lookup) = &mut lookup {
+        lookup.flush(provider)?;
     }
 
-    Ok(last_header_number)
+    summary.last_block = last_header_number;
+    summary.timings.decode = decoding;
+    summary.timings.write = started.elapsed().saturating_sub(decoding);
+    Ok(summary)
 }
 
 /// Dumps the contents of `hash_collector` into [`tables::HeaderNumbers`].
@@ -412,12 +1351,12 @@ where
     let mut cursor_header_numbers =
         provider.tx_ref().cursor_write::<RawTable<tables::HeaderNumbers>>()?;
     // If we only have the genesis block hash, then we are at first sync, and we can remove it,
-    // add it to the collector and use tx.append on all hashes.
+    // add it to the collector and use tx.append on all hashes. The genesis block isn't block 0 on
+    // chains whose history starts at a genesis offset.
     let first_sync = if provider.tx_ref().entries::<RawTable<tables::HeaderNumbers>>()? == 1 &&
-        let Some((hash, block_number)) = cursor_header_numbers.last()? &&
-        block_number.value()? == 0
+        let Some((hash, block_number)) = cursor_header_numbers.last()?
     {
-        hash_collector.insert(hash.key()?, 0)?;
+        hash_collector.insert(hash.key()?, block_number.value()?)?;
         cursor_header_numbers.delete_current()?;
         true
     } else {
@@ -475,14 +1414,32 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_consensus::Header;
+    use crate::{stored_history, EraBlockWriter, ExportBlock, ExportConfig, StoredHistory};
+    use alloy_consensus::{
+        proofs::{calculate_receipt_root, calculate_transaction_root},
+        transaction::TxHashRef,
+        Header, TxLegacy,
+    };
+    use alloy_primitives::{Address, Log, Signature, B256};
+    use reth_chainspec::MAINNET;
+    use reth_db::static_file::TotalDifficultyMask;
+    use reth_db_api::models::StoredBlockBodyIndices;
     use reth_db_common::init::init_genesis;
-    use reth_ethereum_primitives::{Block, BlockBody};
+    use reth_era::era1::types::execution::{
+        CompressedBody, CompressedHeader, CompressedReceipts, TotalDifficulty,
+    };
+    use reth_ethereum_primitives::{Block, BlockBody, Receipt, Transaction, TransactionSigned};
+    use reth_primitives_traits::SignerRecoverable;
     use reth_provider::{
         test_utils::create_test_provider_factory, DatabaseProviderFactory,
-        StaticFileProviderFactory, StaticFileSegment, StaticFileWriter,
+        StaticFileProviderFactory, StaticFileSegment, StaticFileWriter, TransactionsProvider,
+    };
+    use reth_storage_api::{BlockBodyIndicesProvider, StageCheckpointReader};
+    use std::{
+        cell::Cell,
+        ops::RangeInclusive,
+        path::{Path, PathBuf},
     };
-    use std::{cell::Cell, path::Path};
     use tempfile::tempdir;
 
     struct TestEra;
@@ -491,12 +1448,50 @@ mod tests {
         fn blocks<M: EraMeta + ?Sized>(
             _meta: &M,
         ) -> eyre::Result<impl Iterator<Item = eyre::Result<(Header, BlockBody)>>> {
-            Ok([1, 2]
-                .into_iter()
-                .map(|number| Ok((Header { number, ..Default::default() }, BlockBody::default()))))
+            Ok(linked_blocks(2).into_iter().map(Ok))
         }
     }
 
+    /// Empty blocks `1..=count`, each linked by parent hash to its predecessor, starting from the
+    /// mainnet genesis written by `init_genesis`.
+    fn linked_blocks(count: u64) -> Vec<(Header, BlockBody)> {
+        let mut parent_hash = MAINNET.genesis_hash();
+        (1..=count)
+            .map(|number| {
+                let header = Header { number, parent_hash, ..Default::default() };
+                parent_hash = header.hash_slow();
+                (header, BlockBody::default())
+            })
+            .collect()
+    }
+
+    /// Blocks `1..=3` linked to the mainnet genesis, where blocks 1 and 3 carry a transaction each
+    /// and block 2 none.
+    fn blocks_with_transactions() -> Vec<(Header, BlockBody)> {
+        let mut parent_hash = MAINNET.genesis_hash();
+        (1..=3u64)
+            .map(|number| {
+                let transactions = (number != 2)
+                    .then(|| {
+                        TransactionSigned::new_unhashed(
+                            Transaction::Legacy(TxLegacy { nonce: number, ..Default::default() }),
+                            Signature::test_signature(),
+                        )
+                    })
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let header = Header {
+                    number,
+                    parent_hash,
+                    transactions_root: calculate_transaction_root(&transactions),
+                    ..Default::default()
+                };
+                parent_hash = header.hash_slow();
+                (header, BlockBody { transactions, ..Default::default() })
+            })
+            .collect()
+    }
+
     #[derive(Debug)]
     struct TestMeta {
         marked: Cell<bool>,
@@ -513,6 +1508,36 @@ mod tests {
         }
     }
 
+    /// An ERA file on disk.
+    #[derive(Debug)]
+    struct FileMeta(PathBuf);
+
+    impl EraMeta for FileMeta {
+        fn mark_as_processed(&self) -> eyre::Result<()> {
+            Ok(())
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    /// Writes `blocks`, each with its receipts and total difficulty, to an `.era1` file in `dir`.
+    fn write_era1(dir: &Path, blocks: Vec<(Header, BlockBody, Vec<Receipt>, U256)>) -> FileMeta {
+        let blocks = blocks
+            .into_iter()
+            .map(|(header, body, receipts, total_difficulty)| ExportBlock {
+                block_hash: header.hash_slow(),
+                header,
+                body,
+                receipts,
+                total_difficulty,
+            })
+            .collect::<Vec<_>>();
+        let config = ExportConfig { dir: dir.to_path_buf(), ..Default::default() };
+        FileMeta(Era1::write_file(&config, &blocks).unwrap())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn import_stops_at_to_block() {
         let pf = create_test_provider_factory();
@@ -527,9 +1552,16 @@ mod tests {
             Ok(TestMeta { marked: Cell::new(false) }),
         ]);
 
-        let height =
-            import::<TestEra, _, _, _, Block, _, _>(stream, &pf, &mut hash_collector, Some(1))
-                .unwrap();
+        let config = ImportConfig { to_block: Some(1), ..Default::default() };
+        let height = import::<TestEra, _, _, _, Block, _, _>(
+            stream,
+            &pf,
+            &mut hash_collector,
+            &config,
+            &mut (),
+        )
+        .unwrap()
+        .last_block;
 
         assert_eq!(height, 1);
     }
@@ -539,8 +1571,6 @@ mod tests {
         let pf = create_test_provider_factory();
         init_genesis(&pf).unwrap();
 
-        let static_file_provider = pf.static_file_provider();
-        let mut writer = static_file_provider.latest_writer(StaticFileSegment::Headers).unwrap();
         let provider = pf.database_provider_rw().unwrap();
         let folder = tempdir().unwrap();
         let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
@@ -548,12 +1578,14 @@ mod tests {
 
         let height = process::<TestEra, _, Block, _, _>(
             &meta,
-            &mut writer,
             &provider,
             &mut hash_collector,
             0..=1,
+            &ImportConfig::default(),
+            &mut (),
         )
-        .unwrap();
+        .unwrap()
+        .last_block;
 
         assert_eq!(height, 1);
         assert!(!meta.marked.get());
@@ -564,8 +1596,6 @@ mod tests {
         let pf = create_test_provider_factory();
         init_genesis(&pf).unwrap();
 
-        let static_file_provider = pf.static_file_provider();
-        let mut writer = static_file_provider.latest_writer(StaticFileSegment::Headers).unwrap();
         let provider = pf.database_provider_rw().unwrap();
         let folder = tempdir().unwrap();
         let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
@@ -578,12 +1608,924 @@ mod tests {
 
         let result = process_iter::<_, Block, _, _>(
             blocks,
-            &mut writer,
             &provider,
             &mut hash_collector,
             0..,
+            &ImportConfig::default(),
+            &mut (),
         );
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn refuses_blocks_only_archives_in_static_files_mode() {
+        struct Named(&'static Path);
+
+        impl EraMeta for Named {
+            fn mark_as_processed(&self) -> eyre::Result<()> {
+                Ok(())
+            }
+
+            fn path(&self) -> &Path {
+                self.0
+            }
+        }
+
+        let meta = Named(Path::new("mainnet-00000-4bb7de2e-noproofs-noreceipts.ere"));
+        let static_files = ImportConfig { mode: ImportMode::StaticFiles, ..Default::default() };
+        let err = check_file_name(&meta, &static_files).unwrap_err().to_string();
+        assert!(err.contains("is a blocks-only archive"), "{err}");
+
+        // Blocks-only archives are accepted for everything but receipts.
+        for mode in [ImportMode::Database, ImportMode::SkipReceipts, ImportMode::HeadersOnly] {
+            check_file_name(&meta, &ImportConfig { mode, ..Default::default() }).unwrap();
+        }
+    }
+
+    #[test]
+    fn process_refuses_another_networks_files() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+        let meta = TestMeta { marked: Cell::new(false) };
+
+        let mut process_with = |config: &ImportConfig| {
+            process::<TestEra, _, Block, _, _>(
+                &meta,
+                &provider,
+                &mut hash_collector,
+                0..=1,
+                config,
+                &mut (),
+            )
+        };
+
+        let named = ImportConfig { network: Some("sepolia".to_string()), ..Default::default() };
+        let err = process_with(&named).unwrap_err().to_string();
+        assert!(err.contains("not named for network sepolia"), "{err}");
+
+        let known_block_hashes = BTreeMap::from([(1, B256::repeat_byte(0xab))]);
+        let hashed = ImportConfig { known_block_hashes, ..Default::default() };
+        let err = process_with(&hashed).unwrap_err().to_string();
+        assert!(err.contains("block 1 has hash"), "{err}");
+
+        let known_block_hashes = BTreeMap::from([(1, linked_blocks(1)[0].0.hash_slow())]);
+        let hashed = ImportConfig { known_block_hashes, ..Default::default() };
+        assert_eq!(process_with(&hashed).unwrap().last_block, 1);
+    }
+
+    #[test]
+    fn process_iter_refuses_blocks_below_the_genesis_block() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        // A rollup whose history starts at block 5 has no blocks 1 and 2.
+        let config = ImportConfig { genesis_block: 5, ..Default::default() };
+        let err = process_iter::<_, Block, _, _>(
+            linked_blocks(2).into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            ..,
+            &config,
+            &mut (),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("block 1 is below the genesis block 5"), "{err}");
+        assert_eq!(stored_history(&provider).unwrap(), StoredHistory::default());
+    }
+
+    #[test]
+    fn refuses_history_off_the_finalized_chain() {
+        let pf = create_test_provider_factory();
+        let genesis = init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+        let blocks = linked_blocks(2);
+
+        let stale = BlockNumHash::new(1, B256::repeat_byte(0xab));
+        let config = ImportConfig { finalized_block: Some(stale), ..Default::default() };
+        let err = process_iter::<_, Block, _, _>(
+            blocks.clone().into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("the ERA files are from a stale fork"), "{err}");
+
+        let finalized = BlockNumHash::new(1, blocks[0].0.hash_slow());
+        let config = ImportConfig { finalized_block: Some(finalized), ..Default::default() };
+        let summary = process_iter::<_, Block, _, _>(
+            blocks.into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        assert_eq!(summary.last_block, 2);
+
+        // Only the genesis header is stored past which files are checked, so a finalized block
+        // ahead of it is left to the headers in between.
+        check_stored_finalized(&provider, finalized, 0).unwrap();
+        check_stored_finalized(&provider, BlockNumHash::new(0, genesis), 0).unwrap();
+        let err = check_stored_finalized(&provider, BlockNumHash::new(0, B256::ZERO), 0)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("the local history is on a stale fork"), "{err}");
+    }
+
+    #[test]
+    fn process_iter_rejects_broken_parent_hash() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        // Block 2 does not point at block 1, so the chain breaks there.
+        let mut blocks = linked_blocks(3);
+        blocks[1].0.parent_hash = B256::repeat_byte(0xab);
+
+        let result = process_iter::<_, Block, _, _>(
+            blocks.into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &ImportConfig::default(),
+            &mut (),
+        );
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("broken header chain at block 2"), "{err}");
+    }
+
+    #[test]
+    fn process_iter_rejects_chain_not_extending_stored_tip() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        // Internally consistent, but block 1 does not extend the stored genesis.
+        let blocks = [Header { number: 1, ..Default::default() }]
+            .into_iter()
+            .map(|header| Ok((header, BlockBody::default())));
+
+        let result = process_iter::<_, Block, _, _>(
+            blocks,
+            &provider,
+            &mut hash_collector,
+            0..,
+            &ImportConfig::default(),
+            &mut (),
+        );
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("block 1 doesn't extend the local chain"), "{err}");
+        assert!(err.contains(&MAINNET.genesis_hash().to_string()), "{err}");
+        assert!(provider.block_body_indices(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn process_iter_rejects_start_past_stored_tip() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        // Block 5 isn't stored, so nothing tells whether block 6 extends it.
+        let blocks = [Header { number: 6, ..Default::default() }]
+            .into_iter()
+            .map(|header| Ok((header, BlockBody::default())));
+
+        let err = process_iter::<_, Block, _, _>(
+            blocks,
+            &provider,
+            &mut hash_collector,
+            5..,
+            &ImportConfig::default(),
+            &mut (),
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("block 5 is not stored"), "{err}");
+    }
+
+    #[test]
+    fn process_iter_appends_receipts_in_static_files_mode() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = linked_blocks(2).into_iter().map(|(header, body)| {
+            Ok(DecodedBlock {
+                header,
+                body: Some(body),
+                receipts: Some(Vec::<Receipt>::new()),
+                blooms: None,
+                total_difficulty: None,
+            })
+        });
+
+        let config = ImportConfig { mode: ImportMode::StaticFiles, ..Default::default() };
+        let height = process_iter::<_, Block, _, _>(
+            blocks,
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap()
+        .last_block;
+
+        assert_eq!(height, 2);
+    }
+
+    #[test]
+    fn process_iter_writes_transactions_to_static_files() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = blocks_with_transactions();
+        let expected =
+            blocks.iter().flat_map(|(_, body)| body.transactions.clone()).collect::<Vec<_>>();
+        let blocks = blocks.into_iter().map(|(header, body)| {
+            Ok(DecodedBlock {
+                receipts: Some(vec![Receipt::default(); body.transactions.len()]),
+                header,
+                body: Some(body),
+                blooms: None,
+                total_difficulty: None,
+            })
+        });
+
+        // The receipts don't match the headers' receipts roots, which only matters when strict.
+        let config = ImportConfig {
+            mode: ImportMode::StaticFiles,
+            verification: VerificationMode::Warn,
+            ..Default::default()
+        };
+        process_iter::<_, Block, _, _>(
+            blocks,
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        provider.commit().unwrap();
+
+        assert_eq!(
+            pf.static_file_provider().get_highest_static_file_tx(StaticFileSegment::Transactions),
+            Some(1)
+        );
+        let provider = pf.provider().unwrap();
+        assert_eq!(provider.transactions_by_tx_range(0..2).unwrap(), expected);
+        assert_eq!(
+            provider.block_body_indices(3).unwrap(),
+            Some(StoredBlockBodyIndices { first_tx_num: 1, tx_count: 1 })
+        );
+    }
+
+    #[test]
+    fn process_iter_recomputes_receipt_blooms() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let (mut header, body) = blocks_with_transactions().remove(0);
+        let receipt = Receipt {
+            success: true,
+            cumulative_gas_used: 21_000,
+            logs: vec![Log::new_unchecked(
+                Address::repeat_byte(1),
+                vec![B256::repeat_byte(2)],
+                Default::default(),
+            )],
+            ..Default::default()
+        };
+        header.receipts_root = calculate_receipt_root(&[receipt.with_bloom_ref()]);
+
+        // The file encodes an empty bloom for a receipt with a log.
+        let mut import_with = |recompute_blooms| {
+            let block = DecodedBlock {
+                header: header.clone(),
+                body: Some(body.clone()),
+                receipts: Some(vec![receipt.clone()]),
+                blooms: Some(vec![Bloom::ZERO]),
+                total_difficulty: None,
+            };
+            let config = ImportConfig {
+                mode: ImportMode::StaticFiles,
+                recompute_blooms,
+                ..Default::default()
+            };
+            process_iter::<_, Block, _, _>(
+                std::iter::once(Ok(block)),
+                &provider,
+                &mut hash_collector,
+                0..,
+                &config,
+                &mut (),
+            )
+        };
+
+        let err = import_with(false).unwrap_err().to_string();
+        assert!(err.contains("receipts root mismatch at block 1"), "{err}");
+
+        let summary = import_with(true).unwrap();
+        assert_eq!(
+            (summary.last_block, summary.receipts_written, summary.bloom_mismatches),
+            (1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn process_checks_era1_receipts_subject_to_verification() {
+        let dir = tempdir().unwrap();
+        // Block 1 has no transactions, so any receipt breaks its receipts root.
+        let receipt = Receipt { cumulative_gas_used: 21_000, ..Default::default() };
+        let blocks = linked_blocks(2)
+            .into_iter()
+            .map(|(header, body)| {
+                let receipts = if header.number == 1 { vec![receipt.clone()] } else { Vec::new() };
+                (header, body, receipts, U256::ZERO)
+            })
+            .collect();
+        let meta = write_era1(dir.path(), blocks);
+
+        let import_with = |verification| {
+            let pf = create_test_provider_factory();
+            init_genesis(&pf).unwrap();
+            let provider = pf.database_provider_rw().unwrap();
+            let folder = tempdir().unwrap();
+            let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+            process::<Era1, _, Block, _, _>(
+                &meta,
+                &provider,
+                &mut hash_collector,
+                0..,
+                &ImportConfig { verification, ..Default::default() },
+                &mut (),
+            )
+        };
+
+        let err = import_with(VerificationMode::Strict).unwrap_err().to_string();
+        assert!(err.contains("receipts root mismatch at block 1"), "{err}");
+
+        // Recovering the archive imports the block, without its receipts.
+        let summary = import_with(VerificationMode::Warn).unwrap();
+        assert_eq!(
+            (summary.last_block, summary.validation_warnings, summary.receipts_written),
+            (2, 1, 0)
+        );
+    }
+
+    #[test]
+    fn process_iter_rejects_missing_receipts_in_static_files_mode() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let config = ImportConfig { mode: ImportMode::StaticFiles, ..Default::default() };
+        let result = process_iter::<_, Block, _, _>(
+            linked_blocks(1).into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        );
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("block 1 carries no receipts"), "{err}");
+    }
+
+    #[test]
+    fn process_iter_skips_bodies_in_headers_only_mode() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = linked_blocks(2).into_iter().map(|(header, _)| {
+            Ok(DecodedBlock::<_, BlockBody, Receipt> {
+                header,
+                body: None,
+                receipts: None,
+                blooms: None,
+                total_difficulty: Some(U256::from(17)),
+            })
+        });
+
+        let config = ImportConfig { mode: ImportMode::HeadersOnly, ..Default::default() };
+        let height = process_iter::<_, Block, _, _>(
+            blocks,
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap()
+        .last_block;
+
+        assert_eq!(height, 2);
+        assert!(provider.block_body_indices(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn process_iter_derives_missing_proof_of_work_total_difficulty() {
+        let mut parent_hash = MAINNET.genesis_hash();
+        let blocks: Vec<_> = (1..=2u64)
+            .map(|number| {
+                let header =
+                    Header { number, parent_hash, difficulty: U256::from(5), ..Default::default() };
+                parent_hash = header.hash_slow();
+                header
+            })
+            .collect();
+        let process_with = |total_difficulty: [Option<u64>; 2], terminal_total_difficulty| {
+            let pf = create_test_provider_factory();
+            init_genesis(&pf).unwrap();
+            let provider = pf.database_provider_rw().unwrap();
+            let folder = tempdir().unwrap();
+            let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+            let blocks = blocks.iter().zip(total_difficulty).map(|(header, total_difficulty)| {
+                Ok(DecodedBlock::<_, BlockBody, Receipt> {
+                    header: header.clone(),
+                    body: Some(BlockBody::default()),
+                    receipts: None,
+                    blooms: None,
+                    total_difficulty: total_difficulty.map(U256::from),
+                })
+            });
+            let config = ImportConfig {
+                terminal_total_difficulty: Some(U256::from(terminal_total_difficulty)),
+                ..Default::default()
+            };
+            process_iter::<_, Block, _, _>(
+                blocks,
+                &provider,
+                &mut hash_collector,
+                0..,
+                &config,
+                &mut (),
+            )
+        };
+
+        // Block 2 is derived to total difficulty 10, extending 5.
+        process_with([Some(5), None], 10).unwrap();
+        let err = process_with([Some(5), None], 5).unwrap_err().to_string();
+        assert!(err.contains("block 2 extends total difficulty 5"), "{err}");
+
+        let err = process_with([None, None], 10).unwrap_err().to_string();
+        assert!(err.contains("block 1 is a proof-of-work block"), "{err}");
+    }
+
+    #[test]
+    fn process_enforces_the_terminal_total_difficulty_in_every_mode() {
+        let dir = tempdir().unwrap();
+        let mut parent_hash = MAINNET.genesis_hash();
+        let blocks = (1..=2u64)
+            .map(|number| {
+                let header =
+                    Header { number, parent_hash, difficulty: U256::from(1), ..Default::default() };
+                parent_hash = header.hash_slow();
+                (header, BlockBody::default(), Vec::new(), U256::from(number))
+            })
+            .collect();
+        let meta = write_era1(dir.path(), blocks);
+
+        let process_with = |mode, terminal_total_difficulty| {
+            let pf = create_test_provider_factory();
+            init_genesis(&pf).unwrap();
+            let provider = pf.database_provider_rw().unwrap();
+            let folder = tempdir().unwrap();
+            let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+            let config = ImportConfig {
+                mode,
+                terminal_total_difficulty: Some(U256::from(terminal_total_difficulty)),
+                ..Default::default()
+            };
+            process::<Era1, _, Block, _, _>(
+                &meta,
+                &provider,
+                &mut hash_collector,
+                0..,
+                &config,
+                &mut (),
+            )
+        };
+
+        // The default mode first, as `import-era` runs it.
+        for mode in [
+            ImportMode::default(),
+            ImportMode::StaticFiles,
+            ImportMode::SkipReceipts,
+            ImportMode::HeadersOnly,
+        ] {
+            assert_eq!(process_with(mode, 2).unwrap().last_block, 2, "{mode:?}");
+
+            // Block 2 extends a chain that already merged.
+            let err = process_with(mode, 1).unwrap_err().to_string();
+            assert!(err.contains("block 2 extends total difficulty 1"), "{mode:?}: {err}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn import_writes_the_archived_total_difficulty_in_every_mode() {
+        let dir = tempdir().unwrap();
+        let blocks = linked_blocks(2)
+            .into_iter()
+            .map(|(header, body)| {
+                let total_difficulty = U256::from(header.number * 10);
+                (header, body, Vec::new(), total_difficulty)
+            })
+            .collect();
+        let meta = write_era1(dir.path(), blocks);
+
+        for mode in [
+            ImportMode::Database,
+            ImportMode::StaticFiles,
+            ImportMode::SkipReceipts,
+            ImportMode::HeadersOnly,
+        ] {
+            let pf = create_test_provider_factory();
+            init_genesis(&pf).unwrap();
+            let folder = tempdir().unwrap();
+            let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+            let stream = futures_util::stream::iter(vec![Ok(FileMeta(meta.0.clone()))]);
+            import::<Era1, _, _, _, Block, _, _>(
+                stream,
+                &pf,
+                &mut hash_collector,
+                &ImportConfig { mode, ..Default::default() },
+                &mut (),
+            )
+            .unwrap();
+
+            let static_files = pf.static_file_provider();
+            let headers = static_files
+                .get_segment_provider_for_block(StaticFileSegment::Headers, 2, None)
+                .unwrap();
+            let total_difficulty: Option<U256> = headers
+                .cursor()
+                .unwrap()
+                .get_one::<TotalDifficultyMask>(2.into())
+                .unwrap()
+                .map(Into::into);
+            assert_eq!(total_difficulty, Some(U256::from(20)), "{mode:?}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn import_fills_in_bodies_behind_stored_headers() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+        let stream = || futures_util::stream::iter(vec![Ok(TestMeta { marked: Cell::new(false) })]);
+
+        let config = ImportConfig { mode: ImportMode::HeadersOnly, ..Default::default() };
+        import::<TestEra, _, _, _, Block, _, _>(
+            stream(),
+            &pf,
+            &mut hash_collector,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        let stored = stored_history(&pf.provider().unwrap()).unwrap();
+        assert_eq!(stored.missing_bodies(), Some(1..=2));
+
+        // The same file again only writes the missing bodies, leaving the headers as stored.
+        let mut hooks = RecordingHooks::default();
+        let height = import::<TestEra, _, _, _, Block, _, _>(
+            stream(),
+            &pf,
+            &mut hash_collector,
+            &ImportConfig::default(),
+            &mut hooks,
+        )
+        .unwrap()
+        .last_block;
+
+        assert_eq!(height, 2);
+        assert_eq!(hooks.blocks, [1, 2]);
+        let provider = pf.provider().unwrap();
+        assert_eq!(stored_history(&provider).unwrap(), StoredHistory { headers: 2, bodies: 2 });
+        assert!(provider.block_body_indices(2).unwrap().is_some());
+        assert_eq!(
+            provider.get_stage_checkpoint(StageId::Bodies).unwrap().unwrap().block_number,
+            2
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn import_stops_after_the_file_at_hand_on_shutdown() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+        let stream = futures_util::stream::iter(vec![
+            Ok(TestMeta { marked: Cell::new(false) }),
+            Ok(TestMeta { marked: Cell::new(false) }),
+        ]);
+
+        let (signal, shutdown) = reth_tasks::shutdown::signal();
+        signal.fire();
+        let config = ImportConfig { shutdown: Some(shutdown), ..Default::default() };
+        let summary = import::<TestEra, _, _, _, Block, _, _>(
+            stream,
+            &pf,
+            &mut hash_collector,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+
+        assert!(summary.interrupted);
+        assert_eq!((summary.last_block, summary.files_imported, summary.files_skipped), (2, 1, 0));
+        assert_eq!(
+            pf.provider()
+                .unwrap()
+                .get_stage_checkpoint(StageId::Bodies)
+                .unwrap()
+                .unwrap()
+                .block_number,
+            2
+        );
+    }
+
+    #[test]
+    fn process_iter_rejects_blocks_not_matching_stored_headers() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let static_file_provider = pf.static_file_provider();
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let config = ImportConfig { mode: ImportMode::HeadersOnly, ..Default::default() };
+        process_iter::<_, Block, _, _>(
+            linked_blocks(2).into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        static_file_provider.commit().unwrap();
+
+        // Extends genesis like the stored block 1, but with another hash.
+        let header = Header {
+            number: 1,
+            parent_hash: MAINNET.genesis_hash(),
+            gas_limit: 1,
+            ..Default::default()
+        };
+        let err = process_iter::<_, Block, _, _>(
+            std::iter::once(Ok((header, BlockBody::default()))),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &ImportConfig::default(),
+            &mut (),
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("but the stored header has"), "{err}");
+        assert!(provider.block_body_indices(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn process_iter_writes_recovered_senders() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = blocks_with_transactions();
+        let expected = blocks
+            .iter()
+            .flat_map(|(_, body)| &body.transactions)
+            .map(|transaction| transaction.recover_signer_unchecked().unwrap())
+            .collect::<Vec<_>>();
+
+        let config =
+            ImportConfig { sender_recovery_threads: NonZeroUsize::new(2), ..Default::default() };
+        let height = process_iter::<_, Block, _, _>(
+            blocks.into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap()
+        .last_block;
+        provider.commit().unwrap();
+
+        assert_eq!(height, 3);
+        assert_eq!(pf.provider().unwrap().senders_by_tx_range(0..2).unwrap(), expected);
+    }
+
+    #[test]
+    fn process_iter_indexes_transaction_hashes() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = blocks_with_transactions();
+        let hashes = blocks
+            .iter()
+            .flat_map(|(_, body)| &body.transactions)
+            .map(|transaction| *transaction.tx_hash())
+            .collect::<Vec<_>>();
+
+        let config = ImportConfig { index_hashes: true, ..Default::default() };
+        process_iter::<_, Block, _, _>(
+            blocks.into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        provider.commit().unwrap();
+
+        let provider = pf.provider().unwrap();
+        for (tx_number, hash) in hashes.into_iter().enumerate() {
+            assert_eq!(provider.transaction_id(hash).unwrap(), Some(tx_number as u64));
+        }
+    }
+
+    #[test]
+    fn process_iter_flushes_buffers_filling_memory_budget() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let provider = pf.database_provider_rw().unwrap();
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+
+        let blocks = blocks_with_transactions();
+        let transactions =
+            blocks.iter().flat_map(|(_, body)| body.transactions.clone()).collect::<Vec<_>>();
+
+        // A budget this small flushes both buffers after every block.
+        let config = ImportConfig {
+            sender_recovery_threads: NonZeroUsize::new(2),
+            index_hashes: true,
+            memory_budget: NonZeroUsize::new(1),
+            ..Default::default()
+        };
+        process_iter::<_, Block, _, _>(
+            blocks.into_iter().map(Ok),
+            &provider,
+            &mut hash_collector,
+            0..,
+            &config,
+            &mut (),
+        )
+        .unwrap();
+        provider.commit().unwrap();
+
+        let provider = pf.provider().unwrap();
+        let senders = provider.senders_by_tx_range(0..2).unwrap();
+        for (tx_number, transaction) in transactions.iter().enumerate() {
+            assert_eq!(senders[tx_number], transaction.recover_signer_unchecked().unwrap());
+            assert_eq!(
+                provider.transaction_id(*transaction.tx_hash()).unwrap(),
+                Some(tx_number as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn decode_without_receipts_ignores_corrupt_receipts() {
+        let (header, body) = linked_blocks(1).remove(0);
+        // Block 1 has no transactions, so any receipt breaks its receipts root.
+        let receipts = vec![ReceiptEnvelope::Legacy(ReceiptWithBloom::new(
+            alloy_consensus::Receipt {
+                status: true.into(),
+                cumulative_gas_used: 21_000,
+                logs: vec![],
+            },
+            Default::default(),
+        ))];
+        let tuple = || {
+            Ok::<_, E2sError>(BlockTuple::new(
+                CompressedHeader::from_header(&header).unwrap(),
+                CompressedBody::from_body(&body).unwrap(),
+                CompressedReceipts::from_encodable_list(&receipts).unwrap(),
+                TotalDifficulty::new(U256::ZERO),
+            ))
+        };
+
+        assert!(decode::<Header, BlockBody, _>(tuple()).is_err());
+        let (decoded, _) = decode_without_receipts::<Header, BlockBody, _>(tuple()).unwrap();
+        assert_eq!(decoded, header);
+    }
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        blocks: Vec<BlockNumber>,
+        files: Vec<RangeInclusive<BlockNumber>>,
+    }
+
+    impl ImportHooks<Header, BlockBody> for RecordingHooks {
+        fn on_block(
+            &mut self,
+            header: &Header,
+            hash: BlockHash,
+            body: Option<&BlockBody>,
+        ) -> eyre::Result<()> {
+            assert_eq!(hash, header.hash_slow());
+            assert!(body.is_some());
+            self.blocks.push(header.number);
+            Ok(())
+        }
+
+        fn on_file(&mut self, file: &ImportedFile<'_>) -> eyre::Result<()> {
+            self.files.push(file.blocks.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn import_calls_hooks_per_block_and_file() {
+        let pf = create_test_provider_factory();
+        init_genesis(&pf).unwrap();
+
+        let folder = tempdir().unwrap();
+        let mut hash_collector = Collector::new(4096, Some(folder.path().to_owned()));
+        let stream = futures_util::stream::iter(vec![Ok(TestMeta { marked: Cell::new(false) })]);
+        let mut hooks = RecordingHooks::default();
+
+        let summary = import::<TestEra, _, _, _, Block, _, _>(
+            stream,
+            &pf,
+            &mut hash_collector,
+            &ImportConfig::default(),
+            &mut hooks,
+        )
+        .unwrap();
+
+        assert_eq!(summary.last_block, 2);
+        assert_eq!(summary.blocks_written, 2);
+        assert_eq!((summary.files_imported, summary.files_skipped), (1, 0));
+        assert_eq!(hooks.blocks, [1, 2]);
+        assert_eq!(hooks.files, [1..=2]);
+    }
 }
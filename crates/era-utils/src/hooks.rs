@@ -0,0 +1,39 @@
+//! Callbacks into an ERA import as it progresses.
+
+use alloy_primitives::{BlockHash, BlockNumber};
+use std::{ops::RangeInclusive, path::Path, time::Duration};
+
+/// Summary of an ERA file whose blocks have been committed.
+#[derive(Clone, Debug)]
+pub struct ImportedFile<'a> {
+    /// Path the file was read from.
+    pub path: &'a Path,
+    /// Blocks imported from the file; empty if it held none past the stored tip.
+    pub blocks: RangeInclusive<BlockNumber>,
+    /// Time spent decoding, writing and committing the file.
+    pub elapsed: Duration,
+}
+
+/// Hooks invoked as an import appends blocks, so ExExes and indexers can follow historical data
+/// as it lands instead of re-scanning it afterwards.
+///
+/// Both methods default to doing nothing. An error aborts the import, leaving the current file
+/// uncommitted if raised from [`ImportHooks::on_block`].
+pub trait ImportHooks<BH, BB> {
+    /// Called after each block is appended, before the file it belongs to is committed.
+    ///
+    /// `body` is `None` when the import leaves bodies out.
+    fn on_block(&mut self, header: &BH, hash: BlockHash, body: Option<&BB>) -> eyre::Result<()> {
+        let _ = (header, hash, body);
+        Ok(())
+    }
+
+    /// Called after the blocks of an ERA file have been committed.
+    fn on_file(&mut self, file: &ImportedFile<'_>) -> eyre::Result<()> {
+        let _ = file;
+        Ok(())
+    }
+}
+
+/// No hooks.
+impl<BH, BB> ImportHooks<BH, BB> for () {}
@@ -0,0 +1,218 @@
+//! Listing of the records of e2store files, the container of every ERA format, so unknown or
+//! corrupt files can be looked into.
+
+use reth_era::{
+    common::file_ops::EraFileType,
+    e2s::{
+        error::E2sError,
+        types::{Header, EMPTY, SLOT_INDEX, VERSION},
+    },
+    era::types::consensus::{COMPRESSED_BEACON_STATE, COMPRESSED_SIGNED_BEACON_BLOCK},
+    era1::types::{execution as era1, group::BLOCK_INDEX},
+    ere::types::{execution as ere, group::DYNAMIC_BLOCK_INDEX},
+};
+use reth_fs_util as fs;
+use std::{
+    io::{self, BufReader},
+    iter,
+    path::Path,
+};
+
+/// A record type and its name in the format defining it.
+type RecordType = ([u8; 2], &'static str);
+
+/// Record types of every e2store file.
+const COMMON: &[RecordType] = &[(VERSION, "Version"), (EMPTY, "Empty")];
+
+/// Record types of `.era1` files.
+const ERA1: &[RecordType] = &[
+    (era1::COMPRESSED_HEADER, "CompressedHeader"),
+    (era1::COMPRESSED_BODY, "CompressedBody"),
+    (era1::COMPRESSED_RECEIPTS, "CompressedReceipts"),
+    (era1::TOTAL_DIFFICULTY, "TotalDifficulty"),
+    (era1::ACCUMULATOR, "Accumulator"),
+    (BLOCK_INDEX, "BlockIndex"),
+];
+
+/// Record types of `.ere` files.
+const ERE: &[RecordType] = &[
+    (ere::COMPRESSED_HEADER, "CompressedHeader"),
+    (ere::COMPRESSED_BODY, "CompressedBody"),
+    (ere::COMPRESSED_SLIM_RECEIPTS, "CompressedSlimReceipts"),
+    (ere::PROOF, "Proof"),
+    (ere::TOTAL_DIFFICULTY, "TotalDifficulty"),
+    (ere::ACCUMULATOR, "Accumulator"),
+    (DYNAMIC_BLOCK_INDEX, "DynamicBlockIndex"),
+];
+
+/// Record types of consensus-layer `.era` files.
+const ERA: &[RecordType] = &[
+    (COMPRESSED_SIGNED_BEACON_BLOCK, "CompressedSignedBeaconBlock"),
+    (COMPRESSED_BEACON_STATE, "CompressedBeaconState"),
+    (SLOT_INDEX, "SlotIndex"),
+];
+
+/// Record types of Portal Network `.e2hs` files.
+const E2HS: &[RecordType] = &[
+    (era1::COMPRESSED_HEADER, "CompressedHeaderWithProof"),
+    (era1::COMPRESSED_BODY, "CompressedBody"),
+    (era1::COMPRESSED_RECEIPTS, "CompressedReceipts"),
+    (BLOCK_INDEX, "BlockIndex"),
+];
+
+/// One record of an e2store file, as listed by [`inspect`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct E2sRecord {
+    /// Offset of the record's header from the start of the file, in bytes.
+    pub offset: u64,
+    /// Record type identifier.
+    pub entry_type: [u8; 2],
+    /// Length of the record's data, in bytes, not counting its 8-byte header.
+    pub length: u32,
+    /// Name of the record type in the file's format, `None` if the format defines no such type.
+    pub name: Option<&'static str>,
+}
+
+/// The records of an e2store file, as listed by [`inspect`].
+#[derive(Debug)]
+pub struct E2sInspection {
+    /// Size of the file, in bytes.
+    pub size: u64,
+    /// Every record whose header could be read, in file order.
+    pub records: Vec<E2sRecord>,
+    /// Offset at which the file stopped making sense and why, `None` if every byte belongs to a
+    /// whole record.
+    ///
+    /// A record whose data runs past the end of the file is still listed, with its error at its
+    /// own offset.
+    pub error: Option<(u64, E2sError)>,
+}
+
+/// Lists the records of the e2store file at `path`, reading only their headers.
+///
+/// Record types are named as in the format the file's extension stands for: `.era1`, `.ere`,
+/// `.era` or `.e2hs`. Files with another extension have them named as in the first of these
+/// formats that defines them.
+///
+/// A corrupt file is listed up to the first record that can't be read, see
+/// [`E2sInspection::error`], so only failing to open the file is an error.
+pub fn inspect(path: impl AsRef<Path>) -> eyre::Result<E2sInspection> {
+    let path = path.as_ref();
+    let formats = formats(path.file_name().and_then(|name| name.to_str()).unwrap_or_default());
+
+    let file = fs::open(path)?;
+    let size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+    let error = loop {
+        let header = match Header::read(&mut reader) {
+            Ok(Some(header)) => header,
+            Ok(None) if offset < size => {
+                break Some(truncated(format!(
+                    "{} trailing bytes are too short for a record header",
+                    size - offset
+                )))
+            }
+            Ok(None) => break None,
+            Err(err) => break Some(err),
+        };
+
+        records.push(E2sRecord {
+            offset,
+            entry_type: header.header_type,
+            length: header.length,
+            name: record_name(formats, header.header_type),
+        });
+
+        let end = offset + Header::SIZE as u64 + u64::from(header.length);
+        if end > size {
+            break Some(truncated(format!(
+                "record holds {} bytes, but the file ends {} bytes after its header",
+                header.length,
+                size - offset - Header::SIZE as u64
+            )))
+        }
+        reader.seek_relative(i64::from(header.length))?;
+        offset = end;
+    };
+
+    Ok(E2sInspection { size, records, error: error.map(|error| (offset, error)) })
+}
+
+/// Returns the record types of the formats a file named `file_name` may be in.
+fn formats(file_name: &str) -> &'static [&'static [RecordType]] {
+    if file_name.ends_with(".e2hs") {
+        return &[E2HS]
+    }
+    match EraFileType::from_filename(file_name) {
+        Some(EraFileType::Era1) => &[ERA1],
+        Some(EraFileType::Ere) => &[ERE],
+        Some(EraFileType::Era) => &[ERA],
+        None => &[ERA1, ERE, ERA, E2HS],
+    }
+}
+
+/// Returns the name of `entry_type` in the first of `formats` defining it.
+fn record_name(formats: &[&'static [RecordType]], entry_type: [u8; 2]) -> Option<&'static str> {
+    iter::once(COMMON)
+        .chain(formats.iter().copied())
+        .flatten()
+        .find(|(ty, _)| *ty == entry_type)
+        .map(|(_, name)| *name)
+}
+
+/// An error for a file ending partway through a record.
+fn truncated(reason: String) -> E2sError {
+    E2sError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_era::e2s::types::{Entry, Version};
+    use tempfile::tempdir;
+
+    #[test]
+    fn lists_records_up_to_truncation() {
+        let mut contents = Vec::new();
+        Version.encode(&mut contents).unwrap();
+        Entry::new(era1::COMPRESSED_HEADER, vec![0; 3]).write(&mut contents).unwrap();
+        Entry::new([0x99, 0x99], vec![0]).write(&mut contents).unwrap();
+        // Claims 100 bytes of data, but only 2 follow.
+        Header::new(era1::COMPRESSED_RECEIPTS, 100).write(&mut contents).unwrap();
+        contents.extend([0; 2]);
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mainnet-00000-5ec1ffb8.era1");
+        fs::write(&path, &contents).unwrap();
+
+        let inspection = inspect(&path).unwrap();
+        assert_eq!(inspection.size, 38);
+        let records = inspection
+            .records
+            .iter()
+            .map(|record| (record.offset, record.length, record.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                (0, 0, Some("Version")),
+                (8, 3, Some("CompressedHeader")),
+                (19, 1, None),
+                (28, 100, Some("CompressedReceipts")),
+            ]
+        );
+        let (offset, error) = inspection.error.unwrap();
+        assert_eq!(offset, 28);
+        assert!(error.to_string().contains("the file ends 2 bytes after its header"), "{error}");
+
+        // The same record type is named after the format of the file.
+        let path = dir.path().join("mainnet-00000-5ec1ffb8.e2hs");
+        fs::write(&path, &contents[..28]).unwrap();
+        let inspection = inspect(&path).unwrap();
+        assert_eq!(inspection.records[1].name, Some("CompressedHeaderWithProof"));
+        assert!(inspection.error.is_none());
+    }
+}
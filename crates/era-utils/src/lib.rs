@@ -2,13 +2,101 @@
 //!
 //! Each ERA format plugs into a shared pipeline through a per-format seam ([`EraBlockReader`]).
 
+#[cfg(feature = "arrow")]
+mod batches;
+mod decode;
+#[cfg(feature = "ethash")]
+mod ethash;
+#[cfg(feature = "exex")]
+mod exex;
+mod expiry;
+mod export;
+mod gaps;
 mod history;
+mod hooks;
+mod inspect;
+mod lookup;
+mod provider;
+pub mod query;
+mod registry;
+mod roundtrip;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod senders;
+mod shutdown;
+mod storage;
+mod summary;
+#[cfg(feature = "sync")]
+mod sync;
+mod tables;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+mod throttle;
+mod verify;
+mod watch;
 
-mod export;
+pub use export::{
+    blocks_in_time_range, export, export_consensus, export_parallel, file_cid, header_with_proof,
+    headers_with_proofs, inclusion_proofs, is_valid_merkle_branch, merge_era1, plan_export,
+    recompress_era1, recover_era1, split_era1, write_cids, ArchiveProfile, BeaconSource,
+    ChecksumAlgorithm, ConsensusExportConfig, E2hs, Era1Recovery, EraBlockWriter, ExportBlock,
+    ExportConfig, ExportTuning, FileNaming, FinalizedExporter, HeaderInclusionProof,
+    HeaderWithProof,
+};
+
+#[cfg(feature = "encryption")]
+pub use export::{decrypt_file, EncryptionKey};
+
+#[cfg(feature = "server")]
+pub use export::EraFileServer;
+
+#[cfg(feature = "ethash")]
+pub use ethash::{verify_seal, SealVerification};
 
-pub use export::{export, EraBlockWriter, ExportBlock, ExportConfig};
+pub use expiry::save_archived_blocks;
+
+#[cfg(feature = "exex")]
+pub use exex::EraArchiveExEx;
+
+pub use gaps::{stored_history, StoredHistory};
 
 pub use history::{
-    build_index, calculate_td_by_number, decode, import, open, process, process_iter,
-    save_stage_checkpoints, Era, Era1, EraBlockReader, Ere,
+    build_index, calculate_td_by_number, decode, decode_header, decode_with_receipts,
+    decode_without_receipts, import, open, process, process_iter, save_bodies_checkpoint,
+    save_headers_checkpoint, save_stage_checkpoints, DecodedBlock, Era, Era1, EraBlockReader, Ere,
+    ImportConfig, ImportMode, DEFAULT_ETL_FILE_SIZE, DEFAULT_IMPORT_MEMORY_BUDGET,
 };
+
+pub use hooks::{ImportHooks, ImportedFile};
+
+pub use inspect::{inspect, E2sInspection, E2sRecord};
+
+pub use provider::EraHistoryProvider;
+
+pub use registry::{ChecksumRegistry, KnownEra};
+
+pub use roundtrip::{compare_era1, verify_roundtrip, RoundtripFile, RoundtripOutcome};
+
+#[cfg(feature = "rpc")]
+pub use rpc::EraHistoryRpc;
+
+pub use storage::{BlockAppender, HistoryWriter, HistoryWriterFactory};
+
+pub use summary::{ImportSummary, ImportTimings};
+
+#[cfg(feature = "sync")]
+pub use sync::{sync_history_from_era, EraSyncConfig};
+
+#[cfg(feature = "arrow")]
+pub use batches::RecordBatches;
+
+pub use tables::{export_tables, Table, TableExportConfig, TableRows};
+
+pub use throttle::ImportThrottle;
+
+pub use verify::{
+    verify_body_roots, verify_era1_file, verify_era1_files, verify_network, verify_pre_merge,
+    verify_receipts_root, VerificationMode,
+};
+
+pub use watch::DirectoryImporter;
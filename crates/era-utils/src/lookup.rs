@@ -0,0 +1,56 @@
+//! Transaction hash index of imported blocks.
+
+use crate::storage::HistoryWriter;
+use alloy_consensus::transaction::TxHashRef;
+use alloy_primitives::{BlockNumber, TxHash, TxNumber};
+use reth_primitives_traits::BlockBody;
+use reth_storage_api::errors::ProviderError;
+
+/// Queues the hashes of appended transactions and writes them to
+/// [`TransactionHashNumbers`](reth_db_api::tables::TransactionHashNumbers) in sorted batches.
+#[derive(Debug)]
+pub(crate) struct TransactionLookup {
+    /// Hashes queued before they are flushed.
+    capacity: usize,
+    entries: Vec<(TxHash, TxNumber)>,
+}
+
+impl TransactionLookup {
+    /// Creates a lookup queueing up to `budget` bytes of hashes at once.
+    pub(crate) fn new(budget: usize) -> Self {
+        Self { capacity: (budget / size_of::<(TxHash, TxNumber)>()).max(1), entries: Vec::new() }
+    }
+
+    /// Queues the transaction hashes of block `number`, whose body indices must already be
+    /// written, flushing once the queued hashes fill the budget.
+    pub(crate) fn push(
+        &mut self,
+        provider: &impl HistoryWriter,
+        number: BlockNumber,
+        body: &impl BlockBody,
+    ) -> eyre::Result<()> {
+        let first_tx = provider
+            .block_body_indices(number)?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?
+            .first_tx_num();
+        self.entries.extend(
+            body.transactions().iter().map(|transaction| *transaction.tx_hash()).zip(first_tx..),
+        );
+
+        if self.entries.len() >= self.capacity {
+            self.flush(provider)?;
+        }
+        Ok(())
+    }
+
+    /// Writes every queued hash, sorted so the table is walked once.
+    pub(crate) fn flush(&mut self, provider: &impl HistoryWriter) -> eyre::Result<()> {
+        if self.entries.is_empty() {
+            return Ok(())
+        }
+
+        let mut entries = std::mem::take(&mut self.entries);
+        entries.sort_unstable();
+        provider.insert_transaction_hashes(entries)
+    }
+}
@@ -0,0 +1,535 @@
+//! Block history read from `.era1` files on demand.
+
+use alloy_consensus::ReceiptWithBloom;
+use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::{BlockHash, BlockNumber, TxHash, TxNumber, B256};
+use eyre::eyre;
+use memmap2::Mmap;
+use parking_lot::Mutex;
+use reth_era::{
+    common::{decode::DecodeCompressedRlp, file_ops::EraFileType},
+    e2s::{
+        error::E2sError,
+        types::{Entry, Header, IndexEntry},
+    },
+    era1::types::{
+        execution::{
+            BlockTuple, CompressedBody, CompressedHeader, CompressedReceipts, TotalDifficulty,
+            MAX_BLOCKS_PER_ERA1,
+        },
+        group::BlockIndex,
+    },
+};
+use reth_fs_util as fs;
+use reth_primitives_traits::{
+    Block, FullBlockBody, FullBlockHeader, Receipt, RecoveredBlock, SealedHeader,
+};
+use reth_storage_api::{
+    errors::{ProviderError, ProviderResult},
+    BlockHashReader, HeaderProvider, ReceiptProvider,
+};
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds, RangeInclusive},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Default number of mapped files [`EraHistoryProvider`] keeps open.
+const DEFAULT_CACHE_CAPACITY: u32 = 4;
+
+/// Serves headers, blocks and receipts of pre-merge history from a directory of `.era1` files.
+///
+/// Meant for nodes that expired old bodies and receipts from storage: they can keep answering
+/// `eth_getBlockByNumber` and `eth_getBlockReceipts` for those blocks by reading the file of the
+/// block's era when asked. Files are found by the era number in their names, each holding the
+/// `8192` blocks of its era as the spec lays them out.
+///
+/// Only the read traits `era1` data can back are implemented: [`HeaderProvider`],
+/// [`BlockHashReader`] and [`ReceiptProvider`], with whole blocks read by
+/// [`EraHistoryProvider::block`] and [`EraHistoryProvider::recovered_block`]. `era1` files neither
+/// index blocks by hash nor number transactions across the chain, so lookups by block hash or by
+/// transaction fail with [`ProviderError::UnsupportedProvider`] rather than finding nothing;
+/// resolve block numbers from the node's headers instead.
+///
+/// Files are mapped into memory and only the requested blocks are read, found through the block
+/// index at the end of each file. The most recently read files are kept mapped, see
+/// [`EraHistoryProvider::with_cache_capacity`], so repeated queries into the same eras don't reopen
+/// them and reread their index.
+pub struct EraHistoryProvider<H, B, R> {
+    /// The `.era1` file of each era number.
+    files: BTreeMap<u64, PathBuf>,
+    /// Mapped files by era number, least recently read evicted first.
+    cache: Mutex<LruMap<u64, Arc<MappedEra1>, ByLength>>,
+    _types: PhantomData<fn() -> (H, B, R)>,
+}
+
+impl<H, B, R> std::fmt::Debug for EraHistoryProvider<H, B, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EraHistoryProvider")
+            .field("files", &self.files)
+            .field("cached", &self.cache.lock().len())
+            .finish()
+    }
+}
+
+impl<H, B, R> EraHistoryProvider<H, B, R> {
+    /// Catalogs the `.era1` files in `dir`, which are only opened once their blocks are read.
+    pub fn new(dir: impl AsRef<Path>) -> eyre::Result<Self> {
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+            if EraFileType::from_filename(name) != Some(EraFileType::Era1) {
+                continue
+            }
+
+            let era = name
+                .split('-')
+                .nth(1)
+                .and_then(|era| u64::from_str(era).ok())
+                .ok_or_else(|| eyre!("No era number in the name of {}", path.display()))?;
+            if let Some(other) = files.insert(era, path.clone()) {
+                return Err(eyre!("Both {} and {} hold era {era}", other.display(), path.display()))
+            }
+        }
+
+        Ok(Self {
+            files,
+            cache: Mutex::new(LruMap::new(ByLength::new(DEFAULT_CACHE_CAPACITY))),
+            _types: PhantomData,
+        })
+    }
+
+    /// Sets how many mapped files are kept open, `4` by default; `0` reopens a file on every read.
+    ///
+    /// Mapped files take address space rather than memory: only the pages of the blocks read are
+    /// loaded, and the OS may drop them again.
+    pub fn with_cache_capacity(self, capacity: u32) -> Self {
+        Self { cache: Mutex::new(LruMap::new(ByLength::new(capacity))), ..self }
+    }
+
+    /// Returns the range of blocks covered by contiguous files from the first era on, if any.
+    ///
+    /// A block of this range may still be missing if the last file ends short of its era.
+    pub fn block_range(&self) -> Option<RangeInclusive<BlockNumber>> {
+        let (&first, _) = self.files.first_key_value()?;
+        let last = (first..).take_while(|era| self.files.contains_key(era)).last()?;
+        Some(era_start(first)..=era_start(last + 1) - 1)
+    }
+
+    /// Returns the mapped file of the era holding `number`, if there is one, opening it unless it
+    /// is cached.
+    fn read_era(&self, number: BlockNumber) -> ProviderResult<Option<Arc<MappedEra1>>> {
+        let era = number / MAX_BLOCKS_PER_ERA1 as u64;
+        if let Some(file) = self.cache.lock().get(&era) {
+            return Ok(Some(Arc::clone(file)))
+        }
+        let Some(path) = self.files.get(&era) else { return Ok(None) };
+
+        // Opened outside the lock, so reads of other eras aren't held up.
+        let file = Arc::new(MappedEra1::open(path)?);
+        self.cache.lock().insert(era, Arc::clone(&file));
+        Ok(Some(file))
+    }
+
+    /// Reads the tuple of block `number`, if its file holds it.
+    fn read_block(&self, number: BlockNumber) -> ProviderResult<Option<BlockTuple>> {
+        match self.read_era(number)? {
+            Some(file) => file.block(number),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the tuples of the blocks in `range`, stopping at the first one no file holds.
+    ///
+    /// Each file is opened once.
+    fn read_blocks(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<BlockTuple>> {
+        let mut next = match range.start_bound() {
+            Bound::Included(&number) => number,
+            Bound::Excluded(&number) => number + 1,
+            Bound::Unbounded => 0,
+        };
+        let last = match range.end_bound() {
+            Bound::Included(&number) => number,
+            Bound::Excluded(&number) => match number.checked_sub(1) {
+                Some(number) => number,
+                None => return Ok(Vec::new()),
+            },
+            Bound::Unbounded => BlockNumber::MAX,
+        };
+
+        let mut tuples = Vec::new();
+        while next <= last {
+            let Some(file) = self.read_era(next)? else { break };
+            let Some(file_end) =
+                file.last_block().filter(|end| file.first_block <= next && next <= *end)
+            else {
+                break
+            };
+
+            let end = last.min(file_end);
+            for number in next..=end {
+                tuples.extend(file.block(number)?);
+            }
+            next = end + 1;
+            if end < era_start(end / MAX_BLOCKS_PER_ERA1 as u64 + 1) - 1 {
+                // The file ends short of its era, so no later block is held.
+                break
+            }
+        }
+
+        Ok(tuples)
+    }
+}
+
+impl<H, B, R> EraHistoryProvider<H, B, R>
+where
+    H: FullBlockHeader,
+    B: FullBlockBody<OmmerHeader = H>,
+{
+    /// Reads block `number` from its file, if there is one.
+    pub fn block<BK>(&self, number: BlockNumber) -> ProviderResult<Option<BK>>
+    where
+        BK: Block<Header = H, Body = B>,
+    {
+        let Some(tuple) = self.read_block(number)? else { return Ok(None) };
+        let header = tuple.header.decode().map_err(ProviderError::other)?;
+        let body = tuple.body.decode().map_err(ProviderError::other)?;
+        Ok(Some(BK::new(header, body)))
+    }
+
+    /// Reads block `number` from its file with the senders of its transactions recovered, if
+    /// there is one.
+    pub fn recovered_block<BK>(
+        &self,
+        number: BlockNumber,
+    ) -> ProviderResult<Option<RecoveredBlock<BK>>>
+    where
+        BK: Block<Header = H, Body = B>,
+    {
+        let Some(tuple) = self.read_block(number)? else { return Ok(None) };
+        tuple.to_recovered_block().map(Some).map_err(ProviderError::other)
+    }
+}
+
+impl<H, B, R> HeaderProvider for EraHistoryProvider<H, B, R>
+where
+    H: FullBlockHeader,
+{
+    type Header = H;
+
+    fn header(&self, _block_hash: BlockHash) -> ProviderResult<Option<H>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn header_by_number(&self, num: u64) -> ProviderResult<Option<H>> {
+        self.read_block(num)?.map(|tuple| decode_header(&tuple)).transpose()
+    }
+
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<H>> {
+        self.read_blocks(range)?.iter().map(decode_header).collect()
+    }
+
+    fn sealed_header(&self, number: BlockNumber) -> ProviderResult<Option<SealedHeader<H>>> {
+        Ok(self.header_by_number(number)?.map(SealedHeader::seal_slow))
+    }
+
+    fn sealed_headers_while(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        mut predicate: impl FnMut(&SealedHeader<H>) -> bool,
+    ) -> ProviderResult<Vec<SealedHeader<H>>> {
+        let mut headers = Vec::new();
+        for tuple in self.read_blocks(range)? {
+            let header = SealedHeader::seal_slow(decode_header(&tuple)?);
+            if !predicate(&header) {
+                break
+            }
+            headers.push(header);
+        }
+        Ok(headers)
+    }
+}
+
+impl<H, B, R> BlockHashReader for EraHistoryProvider<H, B, R>
+where
+    H: FullBlockHeader,
+{
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        Ok(self.sealed_header(number)?.map(|header| header.hash()))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        Ok(self.sealed_headers_range(start..end)?.into_iter().map(|header| header.hash()).collect())
+    }
+}
+
+impl<H, B, R> ReceiptProvider for EraHistoryProvider<H, B, R>
+where
+    R: Receipt,
+{
+    type Receipt = R;
+
+    fn receipt(&self, _id: TxNumber) -> ProviderResult<Option<R>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn receipt_by_hash(&self, _hash: TxHash) -> ProviderResult<Option<R>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> ProviderResult<Option<Vec<R>>> {
+        let BlockHashOrNumber::Number(number) = block else {
+            return Err(ProviderError::UnsupportedProvider)
+        };
+        self.read_block(number)?.map(|tuple| decode_receipts(&tuple)).transpose()
+    }
+
+    fn receipts_by_tx_range(&self, _range: impl RangeBounds<TxNumber>) -> ProviderResult<Vec<R>> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn receipts_by_block_range(
+        &self,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<Vec<R>>> {
+        self.read_blocks(block_range)?.iter().map(decode_receipts).collect()
+    }
+}
+
+/// First block of `era`.
+const fn era_start(era: u64) -> BlockNumber {
+    era * MAX_BLOCKS_PER_ERA1 as u64
+}
+
+fn decode_header<H: FullBlockHeader>(tuple: &BlockTuple) -> ProviderResult<H> {
+    tuple.header.decode().map_err(ProviderError::other)
+}
+
+/// Decodes the receipts of `tuple`, dropping the blooms `era1` stores with them.
+fn decode_receipts<R: Receipt>(tuple: &BlockTuple) -> ProviderResult<Vec<R>> {
+    let receipts: Vec<ReceiptWithBloom<R>> =
+        tuple.receipts.decode().map_err(ProviderError::other)?;
+    Ok(receipts.into_iter().map(|receipt| receipt.receipt).collect())
+}
+
+/// An `.era1` file mapped into memory, with the positions of its blocks read from its block index.
+#[derive(Debug)]
+struct MappedEra1 {
+    /// The whole file.
+    mmap: Mmap,
+    /// Number of the first block the file holds.
+    first_block: BlockNumber,
+    /// Position in `mmap` of the tuple of each block the file holds, in block order.
+    positions: Vec<usize>,
+}
+
+impl MappedEra1 {
+    /// Maps the file at `path` and reads its block index.
+    fn open(path: &Path) -> ProviderResult<Self> {
+        let file = fs::open(path).map_err(ProviderError::other)?;
+        // SAFETY: File is read-only and its descriptor is kept alive as long as the mmap handle.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(ProviderError::other)?;
+        let (first_block, positions) = block_positions(&mmap).map_err(|err| {
+            ProviderError::other(E2sError::Ssz(format!("{}: {err}", path.display())))
+        })?;
+        Ok(Self { mmap, first_block, positions })
+    }
+
+    /// Returns the number of the last block the file holds, if it holds any.
+    fn last_block(&self) -> Option<BlockNumber> {
+        (self.positions.len() as u64).checked_sub(1).map(|last| self.first_block + last)
+    }
+
+    /// Reads the tuple of block `number`, if the file holds it.
+    fn block(&self, number: BlockNumber) -> ProviderResult<Option<BlockTuple>> {
+        let Some(&position) = number
+            .checked_sub(self.first_block)
+            .and_then(|index| self.positions.get(index as usize))
+        else {
+            return Ok(None)
+        };
+
+        read_tuple(&self.mmap[position..]).map(Some).map_err(ProviderError::other)
+    }
+}
+
+/// Reads the four entries of the block tuple `bytes` start with.
+fn read_tuple(mut bytes: &[u8]) -> Result<BlockTuple, E2sError> {
+    let mut next = || {
+        Entry::read(&mut bytes)?
+            .ok_or_else(|| E2sError::Ssz("Block tuple ends past the end of its file".to_string()))
+    };
+    let header = CompressedHeader::from_entry(&next()?)?;
+    let body = CompressedBody::from_entry(&next()?)?;
+    let receipts = CompressedReceipts::from_entry(&next()?)?;
+    let total_difficulty = TotalDifficulty::from_entry(&next()?)?;
+    Ok(BlockTuple::new(header, body, receipts, total_difficulty))
+}
+
+/// Reads the block index that ends an `.era1` file, returning its first block and the position of
+/// each block's tuple in `bytes`.
+///
+/// The index ends with the number of blocks it covers, which gives where the index starts; block
+/// offsets are counted from there.
+fn block_positions(bytes: &[u8]) -> Result<(BlockNumber, Vec<usize>), E2sError> {
+    let count = bytes
+        .last_chunk::<8>()
+        .map(|count| u64::from_le_bytes(*count))
+        .ok_or_else(|| E2sError::Ssz("File too short to hold a block index".to_string()))?;
+    let index_start = count
+        .checked_mul(8)
+        .and_then(|offsets| offsets.checked_add((Header::SIZE + 16) as u64))
+        .and_then(|index_len| (bytes.len() as u64).checked_sub(index_len))
+        .ok_or_else(|| E2sError::Ssz(format!("File too short to index {count} blocks")))?
+        as usize;
+
+    let mut reader = &bytes[index_start..];
+    let entry = Entry::read(&mut reader)?
+        .ok_or_else(|| E2sError::Ssz("File too short to hold a block index".to_string()))?;
+    let index = BlockIndex::from_entry(&entry)?;
+
+    let positions = index
+        .offsets()
+        .iter()
+        .map(|&offset| {
+            (index_start as i64)
+                .checked_add(offset)
+                .and_then(|position| usize::try_from(position).ok())
+                .filter(|&position| position < index_start)
+                .ok_or_else(|| E2sError::Ssz(format!("Block offset {offset} out of the file")))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((index.starting_number(), positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock, ExportConfig};
+    use alloy_consensus::Header;
+    use alloy_primitives::U256;
+    use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
+    use tempfile::tempdir;
+
+    type Provider = EraHistoryProvider<Header, BlockBody, EthReceipt>;
+
+    #[test]
+    fn reads_blocks_and_receipts_from_era1_files() {
+        let blocks = (0..4u64)
+            .map(|number| ExportBlock {
+                header: Header { number, gas_limit: number * 10, ..Default::default() },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: vec![EthReceipt { cumulative_gas_used: number, ..Default::default() }],
+                total_difficulty: U256::ZERO,
+            })
+            .collect::<Vec<_>>();
+        let dir = tempdir().unwrap();
+        Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        let provider = Provider::new(dir.path()).unwrap();
+        assert_eq!(provider.block_range(), Some(0..=8191));
+
+        let header = provider.header_by_number(2).unwrap().unwrap();
+        assert_eq!(header.gas_limit, 20);
+        assert!(provider.header_by_number(4).unwrap().is_none());
+        assert!(provider.header_by_number(8192).unwrap().is_none());
+        assert_eq!(provider.block_hash(2).unwrap(), Some(header.hash_slow()));
+
+        // Ranges stop where the file ends.
+        let numbers =
+            provider.headers_range(1..).unwrap().iter().map(|h| h.number).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![1, 2, 3]);
+
+        let block = provider.block::<reth_ethereum_primitives::Block>(3).unwrap().unwrap();
+        assert_eq!(block.header.number, 3);
+        let recovered =
+            provider.recovered_block::<reth_ethereum_primitives::Block>(3).unwrap().unwrap();
+        assert_eq!(recovered.hash(), block.header.hash_slow());
+        assert!(recovered.senders().is_empty());
+
+        let receipts = provider.receipts_by_block(BlockHashOrNumber::Number(3)).unwrap().unwrap();
+        assert_eq!(receipts[0].cumulative_gas_used, 3);
+        assert_eq!(provider.receipts_by_block_range(0..=1).unwrap().len(), 2);
+
+        let hash = header.hash_slow();
+        assert!(matches!(provider.header(hash), Err(ProviderError::UnsupportedProvider)));
+        assert!(matches!(
+            provider.receipts_by_block(BlockHashOrNumber::Hash(hash)),
+            Err(ProviderError::UnsupportedProvider)
+        ));
+        assert!(matches!(provider.receipt(0), Err(ProviderError::UnsupportedProvider)));
+    }
+
+    #[test]
+    fn reads_only_the_requested_blocks() {
+        let blocks = (0..4u64)
+            .map(|number| ExportBlock::<_, _, EthReceipt> {
+                header: Header { number, ..Default::default() },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::ZERO,
+            })
+            .collect::<Vec<_>>();
+        let dir = tempdir().unwrap();
+        let path = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        // Damage the header entry of block 2, leaving the rest of the file as it was.
+        let mut bytes = std::fs::read(&path).unwrap();
+        let (first_block, positions) = block_positions(&bytes).unwrap();
+        assert_eq!((first_block, positions.len()), (0, 4));
+        bytes[positions[2]] ^= 0xff;
+        std::fs::write(&path, bytes).unwrap();
+
+        let provider = Provider::new(dir.path()).unwrap();
+        assert_eq!(provider.header_by_number(1).unwrap().unwrap().number, 1);
+        assert_eq!(provider.header_by_number(3).unwrap().unwrap().number, 3);
+        assert!(provider.header_by_number(2).is_err());
+    }
+
+    #[test]
+    fn serves_cached_files_without_rereading() {
+        let blocks = (0..2u64)
+            .map(|number| ExportBlock::<_, _, EthReceipt> {
+                header: Header { number, ..Default::default() },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::ZERO,
+            })
+            .collect::<Vec<_>>();
+        let dir = tempdir().unwrap();
+        let path = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        let cached = Provider::new(dir.path()).unwrap();
+        let uncached = Provider::new(dir.path()).unwrap().with_cache_capacity(0);
+        assert!(cached.header_by_number(0).unwrap().is_some());
+        assert!(uncached.header_by_number(0).unwrap().is_some());
+
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(cached.header_by_number(1).unwrap().unwrap().number, 1);
+        assert!(uncached.header_by_number(1).is_err());
+    }
+}
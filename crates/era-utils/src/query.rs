@@ -0,0 +1,544 @@
+//! Reads of single blocks, transactions, receipts and logs out of a set of ERA files, without
+//! importing them.
+//!
+//! Each file's block index is read from its end to find the file holding a block and where the
+//! block's records sit in it, so only those records are read and decompressed. Meant for tooling
+//! that inspects archived history, such as checking a transaction against an archive.
+//!
+//! `.era1` and `.ere` files are supported; other files in the set are ignored. `.ere` files store
+//! slim receipts, so only `.era1` files serve [`receipts`], while [`logs`] scans both.
+
+use alloy_consensus::{Header, ReceiptEnvelope, ReceiptWithBloom, TxReceipt};
+use alloy_primitives::{Address, BlockHash, BlockNumber, Bloom, BloomInput, Log, B256};
+use eyre::{eyre, Result};
+use reth_era::{
+    common::{decode::DecodeCompressedRlp, file_ops::EraFileType},
+    e2s::types::{Entry, IndexEntry},
+    era1::types::{execution as era1, group::BlockIndex},
+    ere::types::{
+        execution::{self as ere, SlimReceipt},
+        group::DynamicBlockIndex,
+    },
+};
+use reth_fs_util as fs;
+use reth_primitives_traits::{Block, BlockBody, FullBlockBody, FullBlockHeader, Receipt};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+};
+
+/// Reads block `number` from the file of `files` holding it, or `None` if none does.
+pub fn block<B>(files: &[impl AsRef<Path>], number: BlockNumber) -> Result<Option<B>>
+where
+    B: Block,
+    B::Header: FullBlockHeader,
+    B::Body: FullBlockBody<OmmerHeader = B::Header>,
+{
+    let Some(mut file) = locate(files, number)? else { return Ok(None) };
+    let header = file.read(number, Component::Header)?;
+    let body = file.read(number, Component::Body)?;
+    Ok(Some(B::new(header, body)))
+}
+
+/// Reads the transaction at `index` in block `number` from the file of `files` holding the block,
+/// or `None` if none does or the block has fewer transactions.
+pub fn transaction<BB>(
+    files: &[impl AsRef<Path>],
+    number: BlockNumber,
+    index: usize,
+) -> Result<Option<BB::Transaction>>
+where
+    BB: FullBlockBody,
+{
+    let Some(mut file) = locate(files, number)? else { return Ok(None) };
+    let body: BB = file.read(number, Component::Body)?;
+    Ok(body.transactions().get(index).cloned())
+}
+
+/// Reads the receipts of block `number` from the `.era1` file of `files` holding it, or `None` if
+/// none does.
+///
+/// Fails if the block is held by an `.ere` file, whose slim receipts can't be read as full ones.
+pub fn receipts<R>(files: &[impl AsRef<Path>], number: BlockNumber) -> Result<Option<Vec<R>>>
+where
+    R: Receipt,
+{
+    let Some(mut file) = locate(files, number)? else { return Ok(None) };
+    if file.file_type != EraFileType::Era1 {
+        return Err(eyre!(
+            "Block {number} is held by {}, whose slim receipts can't be read as full receipts",
+            file.path.display()
+        ))
+    }
+
+    // `era1` stores receipts with their blooms, which are dropped.
+    let receipts: Vec<ReceiptWithBloom<R>> = file.read(number, Component::Receipts)?;
+    Ok(Some(receipts.into_iter().map(|receipt| receipt.receipt).collect()))
+}
+
+/// Scans the logs of the blocks in `blocks` held by `files`, yielding those `filter` matches in
+/// block order, as `eth_getLogs` would.
+///
+/// Receipts are only read for blocks whose header bloom may hold a match, so selective filters
+/// skip most of an archive's receipts. Blocks no file holds are skipped. The scan ends after the
+/// first error, such as a file that fails to read.
+pub fn logs(
+    files: &[impl AsRef<Path>],
+    blocks: RangeInclusive<BlockNumber>,
+    filter: LogFilter,
+) -> LogScan {
+    LogScan {
+        uncataloged: Some(files.iter().map(|path| path.as_ref().to_path_buf()).collect()),
+        files: VecDeque::new(),
+        next: (!blocks.is_empty()).then(|| *blocks.start()),
+        last: *blocks.end(),
+        filter,
+        current: None,
+        matched: VecDeque::new(),
+    }
+}
+
+/// Which logs a [`logs`] scan yields.
+///
+/// A log matches if it was emitted by one of [`LogFilter::addresses`] and, at each position, has
+/// one of the [`LogFilter::topics`] for that position. An empty list matches anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Addresses a matching log was emitted by.
+    pub addresses: Vec<Address>,
+    /// Topics a matching log has at each of the four positions.
+    pub topics: [Vec<B256>; 4],
+}
+
+impl LogFilter {
+    /// Returns `false` if no log of a block with `bloom` can match, `true` if one may.
+    pub fn may_match(&self, bloom: &Bloom) -> bool {
+        let contains = |input: &[u8]| bloom.contains_input(BloomInput::Raw(input));
+        (self.addresses.is_empty() ||
+            self.addresses.iter().any(|address| contains(address.as_slice()))) &&
+            self.topics.iter().all(|topics| {
+                topics.is_empty() || topics.iter().any(|topic| contains(topic.as_slice()))
+            })
+    }
+
+    /// Returns `true` if `log` matches.
+    pub fn matches(&self, log: &Log) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&log.address)) &&
+            self.topics.iter().enumerate().all(|(position, topics)| {
+                topics.is_empty() ||
+                    log.topics().get(position).is_some_and(|topic| topics.contains(topic))
+            })
+    }
+}
+
+/// A log yielded by a [`logs`] scan, with where it was emitted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchedLog {
+    /// Number of the block the log was emitted in.
+    pub block_number: BlockNumber,
+    /// Hash of the block the log was emitted in.
+    pub block_hash: BlockHash,
+    /// Index of the emitting transaction in its block.
+    pub transaction_index: u64,
+    /// Index of the log among all logs of its block.
+    pub log_index: u64,
+    /// The log.
+    pub log: Log,
+}
+
+/// Iterator over the logs matched by a [`logs`] scan.
+#[derive(Debug)]
+pub struct LogScan {
+    /// Files to scan, cataloged by the blocks they hold on the first call to `next`.
+    uncataloged: Option<Vec<PathBuf>>,
+    /// Files left to scan and the blocks they hold, in block order.
+    files: VecDeque<(PathBuf, Range<BlockNumber>)>,
+    /// Next block to scan, `None` once the scan is over.
+    next: Option<BlockNumber>,
+    /// Last block to scan.
+    last: BlockNumber,
+    filter: LogFilter,
+    /// File holding the blocks being scanned.
+    current: Option<IndexedFile>,
+    /// Matched logs of the last scanned block not yet yielded.
+    matched: VecDeque<MatchedLog>,
+}
+
+impl LogScan {
+    /// Scans the next block held by a file, returning `false` once no block is left.
+    fn scan_next(&mut self) -> Result<bool> {
+        if let Some(files) = self.uncataloged.take() {
+            self.files = catalog(&files, self.next.unwrap_or_default()..=self.last)?.into();
+        }
+
+        loop {
+            let Some(number) = self.next.filter(|number| *number <= self.last) else {
+                return Ok(false)
+            };
+
+            if !self.current.as_ref().is_some_and(|file| file.blocks.contains(&number)) {
+                while self.files.front().is_some_and(|(_, blocks)| blocks.end <= number) {
+                    self.files.pop_front();
+                }
+                let Some((path, blocks)) = self.files.pop_front() else { return Ok(false) };
+                if blocks.start > number {
+                    // No file holds the blocks up to the next one's.
+                    self.next = Some(blocks.start);
+                    self.files.push_front((path, blocks));
+                    continue
+                }
+                self.current = IndexedFile::open(&path)?;
+            }
+            self.next = number.checked_add(1);
+
+            let Some(file) = self.current.as_mut() else { return Ok(false) };
+            let header: Header = file.read(number, Component::Header)?;
+            if !self.filter.may_match(&header.logs_bloom) {
+                return Ok(true)
+            }
+
+            let block_hash = header.hash_slow();
+            let mut log_index = 0;
+            for (transaction_index, logs) in file.logs(number)?.into_iter().enumerate() {
+                for log in logs {
+                    if self.filter.matches(&log) {
+                        self.matched.push_back(MatchedLog {
+                            block_number: number,
+                            block_hash,
+                            transaction_index: transaction_index as u64,
+                            log_index,
+                            log,
+                        });
+                    }
+                    log_index += 1;
+                }
+            }
+            return Ok(true)
+        }
+    }
+}
+
+impl Iterator for LogScan {
+    type Item = Result<MatchedLog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(log) = self.matched.pop_front() {
+                return Some(Ok(log))
+            }
+            match self.scan_next() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(error) => {
+                    self.next = None;
+                    return Some(Err(error))
+                }
+            }
+        }
+    }
+}
+
+/// Record of a block read out of its file.
+#[derive(Clone, Copy, Debug)]
+enum Component {
+    Header,
+    Body,
+    Receipts,
+}
+
+/// Block index of an ERA file.
+#[derive(Debug)]
+enum Index {
+    Era1(BlockIndex),
+    Ere(DynamicBlockIndex),
+}
+
+/// An ERA file whose block index was read.
+#[derive(Debug)]
+struct IndexedFile {
+    path: PathBuf,
+    file_type: EraFileType,
+    reader: BufReader<File>,
+    /// Blocks the index covers.
+    blocks: Range<BlockNumber>,
+    /// Position of the index, which its offsets are relative to.
+    index_position: u64,
+    index: Index,
+}
+
+impl IndexedFile {
+    /// Opens the file at `path` and reads its block index, or returns `None` if it is not an
+    /// `.era1` or `.ere` file.
+    fn open(path: &Path) -> Result<Option<Self>> {
+        let Some(file_type @ (EraFileType::Era1 | EraFileType::Ere)) =
+            path.file_name().and_then(|name| name.to_str()).and_then(EraFileType::from_filename)
+        else {
+            return Ok(None)
+        };
+
+        let mut reader = BufReader::new(fs::open(path)?);
+        let index_position = index_position(&mut reader, file_type)?;
+        reader.seek(SeekFrom::Start(index_position))?;
+        let entry = Entry::read(&mut reader)?
+            .ok_or_else(|| eyre!("{} has no block index", path.display()))?;
+
+        let (index, first, count) = match file_type {
+            EraFileType::Era1 => {
+                let index = BlockIndex::from_entry(&entry)?;
+                let (first, count) = (index.starting_number(), index.offsets().len());
+                (Index::Era1(index), first, count)
+            }
+            _ => {
+                let index = DynamicBlockIndex::from_entry(&entry)?;
+                let (first, count) = (index.starting_number(), index.block_count());
+                (Index::Ere(index), first, count)
+            }
+        };
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            file_type,
+            reader,
+            blocks: first..first.saturating_add(count as u64),
+            index_position,
+            index,
+        }))
+    }
+
+    /// Reads and decodes the `component` record of block `number`.
+    fn read<T: alloy_rlp::Decodable>(
+        &mut self,
+        number: BlockNumber,
+        component: Component,
+    ) -> Result<T> {
+        let offset = match &self.index {
+            Index::Era1(index) => index.offset_for_block(number),
+            Index::Ere(index) => index
+                .offsets_for_block(number)
+                .and_then(|offsets| offsets.get(component as usize).copied()),
+        }
+        .ok_or_else(|| {
+            eyre!("{} does not index the {component:?} of block {number}", self.path.display())
+        })?;
+        let position = self.index_position.checked_add_signed(offset).ok_or_else(|| {
+            eyre!("{} indexes block {number} before its start", self.path.display())
+        })?;
+        self.reader.seek(SeekFrom::Start(position))?;
+
+        // `era1` blocks are stored as a tuple of consecutive records, of which only the first is
+        // indexed, so the records before the component are skipped.
+        if self.file_type == EraFileType::Era1 {
+            for _ in 0..component as usize {
+                self.read_entry(number)?;
+            }
+        }
+
+        let entry = self.read_entry(number)?;
+        let value = match (self.file_type, component) {
+            (EraFileType::Era1, Component::Header) => {
+                era1::CompressedHeader::from_entry(&entry)?.decode()
+            }
+            (EraFileType::Era1, Component::Body) => {
+                era1::CompressedBody::from_entry(&entry)?.decode()
+            }
+            (EraFileType::Era1, Component::Receipts) => {
+                era1::CompressedReceipts::from_entry(&entry)?.decode()
+            }
+            (_, Component::Header) => ere::CompressedHeader::from_entry(&entry)?.decode(),
+            (_, Component::Body) => ere::CompressedBody::from_entry(&entry)?.decode(),
+            (_, Component::Receipts) => ere::CompressedSlimReceipts::from_entry(&entry)?.decode(),
+        };
+        Ok(value?)
+    }
+
+    /// Reads the logs of each transaction of block `number`.
+    fn logs(&mut self, number: BlockNumber) -> Result<Vec<Vec<Log>>> {
+        Ok(match self.file_type {
+            EraFileType::Era1 => self
+                .read::<Vec<ReceiptEnvelope>>(number, Component::Receipts)?
+                .iter()
+                .map(|receipt| receipt.logs().to_vec())
+                .collect(),
+            _ => self
+                .read::<Vec<SlimReceipt>>(number, Component::Receipts)?
+                .into_iter()
+                .map(|receipt| receipt.logs)
+                .collect(),
+        })
+    }
+
+    fn read_entry(&mut self, number: BlockNumber) -> Result<Entry> {
+        Entry::read(&mut self.reader)?.ok_or_else(|| {
+            eyre!("{} ends before the records of block {number}", self.path.display())
+        })
+    }
+}
+
+/// Finds the file of `files` whose block index covers `number`.
+fn locate(files: &[impl AsRef<Path>], number: BlockNumber) -> Result<Option<IndexedFile>> {
+    for path in files {
+        if let Some(file) = IndexedFile::open(path.as_ref())? &&
+            file.blocks.contains(&number)
+        {
+            return Ok(Some(file))
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the files of `files` holding any of `blocks`, with the blocks they hold, in block
+/// order.
+///
+/// Files are closed once cataloged, so scans over many files don't keep them all open.
+fn catalog(
+    files: &[PathBuf],
+    blocks: RangeInclusive<BlockNumber>,
+) -> Result<Vec<(PathBuf, Range<BlockNumber>)>> {
+    let mut cataloged = Vec::new();
+    for path in files {
+        if let Some(file) = IndexedFile::open(path)? &&
+            file.blocks.start <= *blocks.end() &&
+            file.blocks.end > *blocks.start()
+        {
+            cataloged.push((file.path, file.blocks));
+        }
+    }
+    cataloged.sort_by_key(|(_, blocks)| blocks.start);
+    Ok(cataloged)
+}
+
+/// Returns the position of the block index closing the file, worked out from the block and
+/// component counts it ends with.
+fn index_position(reader: &mut BufReader<File>, file_type: EraFileType) -> Result<u64> {
+    let mut read_u64 = |from_end: i64| -> Result<u64> {
+        let mut bytes = [0; 8];
+        reader.seek(SeekFrom::End(-from_end))?;
+        reader.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    };
+
+    let count = read_u64(8)?;
+    // Record header, starting number and count, plus the component count `ere` adds.
+    let (fixed, offsets) = match file_type {
+        EraFileType::Era1 => (24, count),
+        _ => (32, count.saturating_mul(read_u64(16)?)),
+    };
+    let len = reader.seek(SeekFrom::End(0))?;
+    offsets
+        .checked_mul(8)
+        .and_then(|size| size.checked_add(fixed))
+        .and_then(|size| len.checked_sub(size))
+        .ok_or_else(|| eyre!("Block index of {count} blocks is larger than its file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, Ere, ExportBlock, ExportConfig};
+    use alloy_consensus::TxLegacy;
+    use alloy_primitives::{logs_bloom, Bytes, Signature, U256};
+    use reth_ethereum_primitives::{
+        Block, BlockBody, Receipt as EthReceipt, Transaction, TransactionSigned,
+    };
+    use tempfile::tempdir;
+
+    const CONTRACT: Address = Address::repeat_byte(0xc0);
+
+    /// Blocks `0..4`, each with one transaction whose receipt carries a log of [`CONTRACT`] with
+    /// the block number as topic, and a difficulty so `ere` files keep them pre-merge.
+    fn blocks() -> Vec<ExportBlock<Header, BlockBody, EthReceipt>> {
+        (0..4u64)
+            .map(|number| {
+                let logs =
+                    vec![Log::new_unchecked(CONTRACT, vec![topic(number)], Bytes::default())];
+                ExportBlock {
+                    header: Header {
+                        number,
+                        difficulty: U256::from(1),
+                        logs_bloom: logs_bloom(&logs),
+                        ..Default::default()
+                    },
+                    block_hash: B256::repeat_byte(number as u8 + 1),
+                    body: BlockBody {
+                        transactions: vec![TransactionSigned::new_unhashed(
+                            Transaction::Legacy(TxLegacy { nonce: number, ..Default::default() }),
+                            Signature::test_signature(),
+                        )],
+                        ..Default::default()
+                    },
+                    receipts: vec![EthReceipt {
+                        cumulative_gas_used: number,
+                        logs,
+                        ..Default::default()
+                    }],
+                    total_difficulty: U256::from(number + 1),
+                }
+            })
+            .collect()
+    }
+
+    fn topic(number: u64) -> B256 {
+        B256::with_last_byte(number as u8)
+    }
+
+    /// Writes blocks `0..2` to an `era1` file and `2..4` to an `ere` one, returning their paths
+    /// along with that of a file that isn't an ERA file.
+    fn write_files(dir: &Path) -> [PathBuf; 3] {
+        let config = ExportConfig { dir: dir.to_path_buf(), ..Default::default() };
+        let blocks = blocks();
+        let era1 = Era1::write_file(&config, &blocks[..2]).unwrap();
+        let ere = Ere::write_file(&config, &blocks[2..]).unwrap();
+        [dir.join("notes.txt"), ere, era1]
+    }
+
+    #[test]
+    fn reads_single_blocks_through_the_block_index() {
+        let dir = tempdir().unwrap();
+        let files = write_files(dir.path());
+        let blocks = blocks();
+
+        for number in 0..4 {
+            let block = block::<Block>(&files, number).unwrap().unwrap();
+            assert_eq!(block.header.number, number);
+
+            let transaction = transaction::<BlockBody>(&files, number, 0).unwrap().unwrap();
+            assert_eq!(transaction, blocks[number as usize].body.transactions[0]);
+            assert!(transaction::<BlockBody>(&files, number, 1).unwrap().is_none());
+        }
+        assert!(block::<Block>(&files, 4).unwrap().is_none());
+
+        let receipts = receipts::<EthReceipt>(&files, 1).unwrap().unwrap();
+        assert_eq!(receipts[0].cumulative_gas_used, 1);
+        assert!(receipts::<EthReceipt>(&files, 2).is_err());
+    }
+
+    #[test]
+    fn scans_logs_across_files() {
+        let dir = tempdir().unwrap();
+        let files = write_files(dir.path());
+
+        let scan = |blocks, filter| {
+            logs(&files, blocks, filter).map(|log| log.unwrap().block_number).collect::<Vec<_>>()
+        };
+        let by_contract = LogFilter { addresses: vec![CONTRACT], ..Default::default() };
+        assert_eq!(scan(0..=10, by_contract.clone()), vec![0, 1, 2, 3]);
+        assert_eq!(scan(1..=2, by_contract), vec![1, 2]);
+
+        let by_topic = |numbers: &[u64]| LogFilter {
+            topics: [numbers.iter().copied().map(topic).collect(), vec![], vec![], vec![]],
+            ..Default::default()
+        };
+        assert_eq!(scan(0..=3, by_topic(&[0, 3])), vec![0, 3]);
+        assert!(scan(0..=3, by_topic(&[9])).is_empty());
+
+        let other = LogFilter { addresses: vec![Address::ZERO], ..Default::default() };
+        assert!(scan(0..=3, other).is_empty());
+
+        let log = logs(&files, 2..=2, LogFilter::default()).next().unwrap().unwrap();
+        assert_eq!((log.transaction_index, log.log_index), (0, 0));
+        assert_eq!(log.block_hash, blocks()[2].header.hash_slow());
+    }
+}
@@ -0,0 +1,190 @@
+//! Known-good SHA-256 sums and accumulator roots of `.era1` files, for verifying archives offline
+//! against vetted values rather than whatever a mirror's `checksums.txt` says.
+//!
+//! Registries for `mainnet` and `sepolia` are bundled with the crate, and can be extended at
+//! runtime from a file in the same format as the bundled ones:
+//!
+//! ```text
+//! # era sha256 accumulator-root
+//! 0 <sha256> <accumulator-root>
+//! ```
+//!
+//! Either value may be `-` when not vetted. Both are compared in full.
+
+use crate::export::file_checksum;
+use alloy_primitives::B256;
+use eyre::{eyre, Result, WrapErr};
+use reth_era::{common::file_ops::FileReader, era1::file::Era1Reader};
+use reth_fs_util as fs;
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// What a [`ChecksumRegistry`] knows of the canonical file of one era.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KnownEra {
+    /// SHA-256 of the file.
+    pub sha256: Option<B256>,
+    /// The era's accumulator root.
+    pub accumulator_root: Option<B256>,
+}
+
+/// Known-good values of the `.era1` files of one network, by era number.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChecksumRegistry {
+    eras: BTreeMap<u64, KnownEra>,
+}
+
+impl ChecksumRegistry {
+    /// Returns the registry bundled with the crate for `network`, `mainnet` or `sepolia`.
+    pub fn bundled(network: &str) -> Option<Self> {
+        let contents = match network {
+            "mainnet" => include_str!("registry/mainnet.txt"),
+            "sepolia" => include_str!("registry/sepolia.txt"),
+            _ => return None,
+        };
+        Some(Self::parse(contents).expect("bundled registry is well-formed"))
+    }
+
+    /// Parses a registry, one era per line as the module documentation describes. Blank lines
+    /// and lines starting with `#` are skipped.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut eras = BTreeMap::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let known = parse_line(line).wrap_err_with(|| format!("Line {}", number + 1))?;
+            eras.insert(known.0, known.1);
+        }
+        Ok(Self { eras })
+    }
+
+    /// Reads a registry from the file at `path`, as [`ChecksumRegistry::parse`] does.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Adds the values of `other`, replacing those known already for the same eras, such as to
+    /// update a bundled registry with values vetted since.
+    pub fn extend(&mut self, other: Self) {
+        for (era, known) in other.eras {
+            let entry = self.eras.entry(era).or_default();
+            if known.sha256.is_some() {
+                entry.sha256 = known.sha256;
+            }
+            if known.accumulator_root.is_some() {
+                entry.accumulator_root = known.accumulator_root;
+            }
+        }
+    }
+
+    /// Returns what is known of the file of `era`.
+    pub fn get(&self, era: u64) -> Option<&KnownEra> {
+        self.eras.get(&era)
+    }
+
+    /// Checks the `.era1` file at `path` against the values known for the era its name carries,
+    /// returning whether any were known.
+    ///
+    /// Fails if the file's SHA-256 or accumulator root differs from a known value.
+    pub fn verify(&self, path: impl AsRef<Path>) -> Result<bool> {
+        let path = path.as_ref();
+        let era = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('-').nth(1))
+            .and_then(|era| u64::from_str(era).ok())
+            .ok_or_else(|| eyre!("No era number in the name of {}", path.display()))?;
+        let Some(known) = self.get(era) else { return Ok(false) };
+
+        if let Some(expected) = known.sha256 {
+            let actual = file_checksum(path)?;
+            if actual != expected {
+                return Err(eyre!(
+                    "{} has SHA-256 {actual}, but era {era} is known to have {expected}",
+                    path.display()
+                ))
+            }
+        }
+        if let Some(expected) = known.accumulator_root {
+            let actual = Era1Reader::open(path, "")?.group.accumulator.root;
+            if actual != expected {
+                return Err(eyre!(
+                    "{} holds accumulator {actual}, but era {era} is known to have {expected}",
+                    path.display()
+                ))
+            }
+        }
+        Ok(known.sha256.is_some() || known.accumulator_root.is_some())
+    }
+}
+
+/// Parses an `era sha256 accumulator-root` line.
+fn parse_line(line: &str) -> Result<(u64, KnownEra)> {
+    let mut fields = line.split_whitespace();
+    let mut field = |name| fields.next().ok_or_else(|| eyre!("Missing {name}"));
+    let era = u64::from_str(field("era number")?)?;
+    let sha256 = match field("SHA-256")? {
+        "-" => None,
+        sha256 => Some(B256::from_str(sha256)?),
+    };
+    let accumulator_root = match field("accumulator root")? {
+        "-" => None,
+        root => Some(B256::from_str(root)?),
+    };
+    Ok((era, KnownEra { sha256, accumulator_root }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock, ExportConfig};
+    use alloy_consensus::Header;
+    use alloy_primitives::U256;
+    use reth_ethereum_primitives::{BlockBody, Receipt};
+    use tempfile::tempdir;
+
+    #[test]
+    fn verifies_against_known_values() {
+        assert!(ChecksumRegistry::bundled("mainnet").is_some());
+        assert!(ChecksumRegistry::bundled("sepolia").is_some());
+        assert!(ChecksumRegistry::bundled("holesky").is_none());
+
+        let dir = tempdir().unwrap();
+        let blocks = (0..2u64)
+            .map(|number| ExportBlock::<_, BlockBody, Receipt> {
+                header: Header { number, ..Default::default() },
+                block_hash: B256::ZERO,
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::ZERO,
+            })
+            .collect::<Vec<_>>();
+        let file = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+        let sha256 = file_checksum(&file).unwrap();
+        let root = Era1Reader::open(&file, "").unwrap().group.accumulator.root;
+
+        let other = ChecksumRegistry::parse(&format!("0 - {}", B256::repeat_byte(1))).unwrap();
+        let err = other.verify(&file).unwrap_err().to_string();
+        assert!(err.contains("holds accumulator"), "{err}");
+
+        let mut registry = ChecksumRegistry::default();
+        assert!(!registry.verify(&file).unwrap());
+        registry
+            .extend(ChecksumRegistry::parse(&format!("# vetted\n\n0 {sha256} {root}\n")).unwrap());
+        assert!(registry.verify(&file).unwrap());
+
+        registry.extend(ChecksumRegistry::parse(&format!("0 {} -", B256::ZERO)).unwrap());
+        assert_eq!(registry.get(0).unwrap().accumulator_root, Some(root));
+        let err = registry.verify(&file).unwrap_err().to_string();
+        assert!(err.contains("era 0 is known to have"), "{err}");
+
+        assert!(ChecksumRegistry::parse("0 -").is_err());
+        // Roots are compared in full, so their leading bytes alone aren't accepted.
+        assert!(ChecksumRegistry::parse("0 - 0x5ec1ffb8").is_err());
+    }
+}
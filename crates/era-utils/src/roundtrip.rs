@@ -0,0 +1,262 @@
+//! Checks that importing `.era1` files and exporting the same blocks again loses nothing.
+
+use crate::{
+    export, import, Era1, ExportConfig, HistoryWriter, HistoryWriterFactory, ImportConfig,
+};
+use alloy_consensus::{BlockHeader, Sealable};
+use alloy_primitives::{BlockHash, BlockNumber};
+use alloy_rlp::Encodable;
+use eyre::eyre;
+use reth_db_api::table::Value;
+use reth_era::{
+    common::file_ops::FileReader,
+    era1::{
+        file::{Era1File, Era1Reader},
+        types::execution::{BlockTuple, MAX_BLOCKS_PER_ERA1},
+    },
+};
+use reth_era_downloader::EraMeta;
+use reth_etl::Collector;
+use reth_fs_util as fs;
+use reth_primitives_traits::{
+    Block, FullBlockBody, FullBlockHeader, FullReceipt, NodePrimitives, Receipt,
+};
+use reth_storage_api::{
+    BlockReader, DatabaseProviderFactory, HeaderProvider, NodePrimitivesProvider, ReceiptProvider,
+};
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+/// How an exported `.era1` file compares with the source file its blocks were imported from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RoundtripOutcome {
+    /// The files are byte-for-byte identical.
+    Identical,
+    /// The files hold the same blocks, receipts, total difficulties and accumulator, but their
+    /// bytes differ, as when the source was compressed by another snappy encoder.
+    Equivalent,
+    /// The files hold different history; describes the first difference found.
+    Mismatch(String),
+}
+
+/// A source file checked by [`verify_roundtrip`].
+#[derive(Clone, Debug)]
+pub struct RoundtripFile {
+    /// The imported file.
+    pub source: PathBuf,
+    /// The file its blocks were exported to.
+    pub exported: PathBuf,
+    /// Blocks held by the source file.
+    pub blocks: RangeInclusive<BlockNumber>,
+    /// How the two compare.
+    pub outcome: RoundtripOutcome,
+}
+
+impl RoundtripFile {
+    /// Returns `true` if the exported file holds the same history as the source.
+    pub const fn is_lossless(&self) -> bool {
+        !matches!(self.outcome, RoundtripOutcome::Mismatch(_))
+    }
+}
+
+/// Imports the `.era1` files at `sources` into `provider_factory`, exports the blocks of each one
+/// into its own file under [`ExportConfig::dir`] and compares the two with [`compare_era1`].
+///
+/// `sources` must be in block order, starting right after the blocks `provider_factory` already
+/// holds, as for [`import`]. Every other [`ExportConfig`] field than the directory applies to each
+/// export, so a [`ExportConfig::terminal_total_difficulty`] that cuts a file short surfaces as a
+/// mismatch. The directory must not hold any of `sources`, which exports could overwrite. Like
+/// [`import`], this must run within a tokio runtime.
+///
+/// Returns one [`RoundtripFile`] per source, in order.
+pub fn verify_roundtrip<PF, B, BB, BH>(
+    sources: &[PathBuf],
+    provider_factory: &PF,
+    hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    import_config: &ImportConfig,
+    export_config: &ExportConfig,
+) -> eyre::Result<Vec<RoundtripFile>>
+where
+    B: Block<Header = BH, Body = BB>,
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<
+        Transaction = <<<PF as HistoryWriterFactory>::Writer as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
+        OmmerHeader = BH,
+    >,
+    PF: DatabaseProviderFactory<Provider: BlockReader>
+        + HistoryWriterFactory<
+            Writer: HistoryWriter<
+                Primitives: NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
+            >,
+        >,
+    <PF::Provider as HeaderProvider>::Header: BlockHeader + Sealable + Encodable,
+    <<PF::Provider as BlockReader>::Block as Block>::Body: Encodable,
+    <PF::Provider as ReceiptProvider>::Receipt: Receipt,
+{
+    if let Some(source) =
+        sources.iter().find(|source| source.parent() == Some(export_config.dir.as_path()))
+    {
+        return Err(eyre!("Source {} is in the export directory", source.display()))
+    }
+
+    let stream = futures_util::stream::iter(
+        sources.iter().cloned().map(|path| Ok(SourceFile(path))).collect::<Vec<_>>(),
+    );
+    import::<Era1, _, _, _, _, _, _>(
+        stream,
+        provider_factory,
+        hash_collector,
+        import_config,
+        &mut (),
+    )?;
+
+    let mut checked = Vec::with_capacity(sources.len());
+    for source in sources {
+        let source_file = Era1Reader::open(source, "")?;
+        let blocks = source_file.block_range();
+        let config = ExportConfig {
+            first_block_number: *blocks.start(),
+            last_block_number: *blocks.end(),
+            max_blocks_per_file: MAX_BLOCKS_PER_ERA1 as u64,
+            ..export_config.clone()
+        };
+
+        let provider = provider_factory.database_provider_ro()?;
+        let [exported] =
+            <[PathBuf; 1]>::try_from(export::<Era1, _>(&provider, &config)?).map_err(|files| {
+                eyre!("Blocks of {} exported to {} files", source.display(), files.len())
+            })?;
+
+        let outcome = compare_files(source, &source_file, &exported)?;
+        match &outcome {
+            RoundtripOutcome::Mismatch(difference) => {
+                warn!(target: "era::history::roundtrip", source = %source.display(), exported = %exported.display(), %difference, "Round trip lost history")
+            }
+            outcome => {
+                info!(target: "era::history::roundtrip", source = %source.display(), ?outcome, "Round trip is lossless")
+            }
+        }
+        checked.push(RoundtripFile { source: source.clone(), exported, blocks, outcome });
+    }
+
+    Ok(checked)
+}
+
+/// Compares the `.era1` files at `source` and `exported`, byte-for-byte and failing that block by
+/// block.
+///
+/// Blocks are compared on their decompressed header, body and receipts and their total
+/// difficulty, so files written by different snappy encoders are still found
+/// [`RoundtripOutcome::Equivalent`].
+pub fn compare_era1(source: &Path, exported: &Path) -> eyre::Result<RoundtripOutcome> {
+    compare_files(source, &Era1Reader::open(source, "")?, exported)
+}
+
+fn compare_files(
+    source_path: &Path,
+    source: &Era1File,
+    exported_path: &Path,
+) -> eyre::Result<RoundtripOutcome> {
+    if fs::read(source_path)? == fs::read(exported_path)? {
+        return Ok(RoundtripOutcome::Identical)
+    }
+
+    let exported = Era1Reader::open(exported_path, "")?;
+    if source.block_range() != exported.block_range() {
+        return Ok(RoundtripOutcome::Mismatch(format!(
+            "source holds blocks {:?}, export holds {:?}",
+            source.block_range(),
+            exported.block_range()
+        )))
+    }
+
+    for (number, (source_block, exported_block)) in
+        source.block_range().zip(source.group.blocks.iter().zip(&exported.group.blocks))
+    {
+        if let Some(record) = first_difference(source_block, exported_block)? {
+            return Ok(RoundtripOutcome::Mismatch(format!(
+                "block {number} has a different {record}"
+            )))
+        }
+    }
+
+    if source.group.accumulator.root != exported.group.accumulator.root {
+        return Ok(RoundtripOutcome::Mismatch("the accumulator root differs".to_string()))
+    }
+
+    Ok(RoundtripOutcome::Equivalent)
+}
+
+/// Names the first record of two block tuples that decompresses to different bytes.
+fn first_difference(
+    source: &BlockTuple,
+    exported: &BlockTuple,
+) -> eyre::Result<Option<&'static str>> {
+    Ok(if source.header.decompress()? != exported.header.decompress()? {
+        Some("header")
+    } else if source.body.decompress()? != exported.body.decompress()? {
+        Some("body")
+    } else if source.receipts.decompress()? != exported.receipts.decompress()? {
+        Some("receipts")
+    } else if source.total_difficulty.value != exported.total_difficulty.value {
+        Some("total difficulty")
+    } else {
+        None
+    })
+}
+
+/// A source file, left in place once imported.
+#[derive(Debug)]
+struct SourceFile(PathBuf);
+
+impl EraMeta for SourceFile {
+    fn mark_as_processed(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EraBlockWriter, ExportBlock};
+    use alloy_consensus::Header;
+    use alloy_primitives::{B256, U256};
+    use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
+    use tempfile::tempdir;
+
+    fn write_blocks(dir: &Path, gas_limit: u64) -> PathBuf {
+        let blocks = (0..3u64)
+            .map(|number| ExportBlock::<_, _, EthReceipt> {
+                header: Header { number, gas_limit, ..Default::default() },
+                block_hash: B256::repeat_byte(number as u8 + 1),
+                body: BlockBody::default(),
+                receipts: Vec::new(),
+                total_difficulty: U256::from(number),
+            })
+            .collect::<Vec<_>>();
+        Era1::write_file(&ExportConfig { dir: dir.to_path_buf(), ..Default::default() }, &blocks)
+            .unwrap()
+    }
+
+    #[test]
+    fn compares_files_by_bytes_then_blocks() {
+        let (first, second, third) = (tempdir().unwrap(), tempdir().unwrap(), tempdir().unwrap());
+        let source = write_blocks(first.path(), 1);
+
+        assert_eq!(
+            compare_era1(&source, &write_blocks(second.path(), 1)).unwrap(),
+            RoundtripOutcome::Identical
+        );
+        assert_eq!(
+            compare_era1(&source, &write_blocks(third.path(), 2)).unwrap(),
+            RoundtripOutcome::Mismatch("block 0 has a different header".to_string())
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Admin RPC starting and monitoring ERA imports and exports.
+
+use crate::{
+    export_parallel, import, stored_history, E2hs, Era, Era1, Ere, ExportConfig, ImportConfig,
+    ImportHooks, ImportedFile, DEFAULT_ETL_FILE_SIZE,
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use parking_lot::Mutex;
+use reth_db_api::database::Database;
+use reth_era::common::file_ops::EraFileType;
+use reth_era_downloader::{read_dir, read_era_dir};
+use reth_etl::Collector;
+use reth_provider::{
+    providers::ProviderNodeTypes, DatabaseProvider, DatabaseProviderFactory, DatabaseProviderRO,
+};
+use reth_rpc_api::{
+    EraExportFormat, EraExportRequest, EraImportRequest, EraJob, EraStatus, RethEraApiServer,
+};
+use reth_rpc_server_types::result::invalid_params_rpc_err;
+use std::{path::PathBuf, sync::Arc};
+use tracing::{error, info};
+
+/// Serves the `reth_eraStatus`, `reth_startEraExport` and `reth_startEraImport` admin methods
+/// against the node's database, so history can be archived and restored without shell access.
+///
+/// One job runs at a time, on a blocking thread: [`export_parallel`] for exports and [`import`]
+/// for imports, configured by the base [`ExportConfig`] and [`ImportConfig`] with the range or
+/// directory of each request applied on top. Give the base configs the node's shutdown so jobs
+/// stop at a file boundary along with it.
+///
+/// It reads and writes through `P`, a [`ProviderFactory`](reth_provider::ProviderFactory) or the
+/// node's [`BlockchainProvider`](reth_provider::providers::BlockchainProvider). The `reth` node
+/// serves it as the opt-in `reth_era` RPC module, see
+/// [`RethRpcModule::RethEra`](reth_rpc_server_types::RethRpcModule::RethEra).
+pub struct EraHistoryRpc<P> {
+    provider_factory: P,
+    import_config: ImportConfig,
+    export_config: ExportConfig,
+    etl_file_size: usize,
+    etl_dir: Option<PathBuf>,
+    status: Arc<Mutex<EraStatus>>,
+}
+
+impl<N, P> EraHistoryRpc<P>
+where
+    N: ProviderNodeTypes,
+    P: DatabaseProviderFactory<
+            Provider = DatabaseProviderRO<N::DB, N>,
+            ProviderRW = DatabaseProvider<<N::DB as Database>::TXMut, N>,
+        > + Clone
+        + 'static,
+{
+    /// Creates the RPC handler with default import and export configs.
+    pub fn new(provider_factory: P) -> Self {
+        Self {
+            provider_factory,
+            import_config: ImportConfig::default(),
+            export_config: ExportConfig::default(),
+            etl_file_size: DEFAULT_ETL_FILE_SIZE,
+            etl_dir: None,
+            status: Arc::default(),
+        }
+    }
+
+    /// Sets the config imports start from. [`ImportConfig::to_block`] is replaced by each
+    /// request's.
+    pub fn with_import_config(mut self, config: ImportConfig) -> Self {
+        self.import_config = config;
+        self
+    }
+
+    /// Sets the config exports start from. The directory and block range are replaced by each
+    /// request's.
+    pub fn with_export_config(mut self, config: ExportConfig) -> Self {
+        self.export_config = config;
+        self
+    }
+
+    /// Sets the size and directory of the ETL buffer block hashes are collected into on import.
+    pub fn with_etl(mut self, file_size: usize, dir: Option<PathBuf>) -> Self {
+        self.etl_file_size = file_size;
+        self.etl_dir = dir;
+        self
+    }
+
+    /// Marks `job` as running, failing if a job already is.
+    fn begin(&self, job: EraJob) -> RpcResult<()> {
+        let mut status = self.status.lock();
+        if status.running {
+            return Err(invalid_params_rpc_err(format!(
+                "an ERA {} is already running",
+                job_name(status.job)
+            )))
+        }
+        *status = EraStatus { job: Some(job), running: true, ..Default::default() };
+        Ok(())
+    }
+
+    /// Imports the files of `dir`, whose format is `era_type`.
+    fn import_dir(
+        &self,
+        dir: PathBuf,
+        era_type: EraFileType,
+        config: &ImportConfig,
+    ) -> eyre::Result<()> {
+        let mut hash_collector = Collector::new(self.etl_file_size, self.etl_dir.clone());
+        let mut hooks = StatusHooks(Arc::clone(&self.status));
+        let provider_factory = &self.provider_factory;
+
+        let next_block = stored_history(&provider_factory.database_provider_ro()?)?.bodies + 1;
+        let summary = match era_type {
+            EraFileType::Era => import::<Era, _, _, _, _, _, _>(
+                read_era_dir(dir)?,
+                provider_factory,
+                &mut hash_collector,
+                config,
+                &mut hooks,
+            )?,
+            EraFileType::Era1 => import::<Era1, _, _, _, _, _, _>(
+                read_dir(dir, next_block)?,
+                provider_factory,
+                &mut hash_collector,
+                config,
+                &mut hooks,
+            )?,
+            EraFileType::Ere => import::<Ere, _, _, _, _, _, _>(
+                read_dir(dir, next_block)?,
+                provider_factory,
+                &mut hash_collector,
+                config,
+                &mut hooks,
+            )?,
+        };
+
+        info!(
+            target: "era::history",
+            last_block = summary.last_block,
+            blocks_written = summary.blocks_written,
+            files_imported = summary.files_imported,
+            interrupted = summary.interrupted,
+            "ERA import finished"
+        );
+        Ok(())
+    }
+}
+
+impl<P: Clone> Clone for EraHistoryRpc<P> {
+    fn clone(&self) -> Self {
+        Self {
+            provider_factory: self.provider_factory.clone(),
+            import_config: self.import_config.clone(),
+            export_config: self.export_config.clone(),
+            etl_file_size: self.etl_file_size,
+            etl_dir: self.etl_dir.clone(),
+            status: Arc::clone(&self.status),
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for EraHistoryRpc<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EraHistoryRpc").field("status", &self.status).finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<N, P> RethEraApiServer for EraHistoryRpc<P>
+where
+    N: ProviderNodeTypes,
+    P: DatabaseProviderFactory<
+            Provider = DatabaseProviderRO<N::DB, N>,
+            ProviderRW = DatabaseProvider<<N::DB as Database>::TXMut, N>,
+        > + Clone
+        + 'static,
+{
+    /// Handler for `reth_eraStatus`
+    async fn reth_era_status(&self) -> RpcResult<EraStatus> {
+        Ok(self.status.lock().clone())
+    }
+
+    /// Handler for `reth_startEraExport`
+    async fn reth_start_era_export(&self, request: EraExportRequest) -> RpcResult<()> {
+        let EraExportRequest { dir, first_block, last_block, format } = request;
+        let config = ExportConfig {
+            dir,
+            first_block_number: first_block,
+            last_block_number: last_block,
+            time_range: None,
+            ..self.export_config.clone()
+        };
+        config.validate().map_err(|err| invalid_params_rpc_err(err.to_string()))?;
+
+        self.begin(EraJob::Export)?;
+        info!(target: "era::history", ?format, first_block, last_block, dir = %config.dir.display(), "Starting ERA export");
+
+        let provider_factory = self.provider_factory.clone();
+        let status = Arc::clone(&self.status);
+        tokio::task::spawn_blocking(move || {
+            let files = match format {
+                EraExportFormat::Era1 => export_parallel::<Era1, _>(&provider_factory, &config),
+                EraExportFormat::Ere => export_parallel::<Ere, _>(&provider_factory, &config),
+                EraExportFormat::E2hs => export_parallel::<E2hs, _>(&provider_factory, &config),
+            };
+            finish(&status, files.map(Some));
+        });
+
+        Ok(())
+    }
+
+    /// Handler for `reth_startEraImport`
+    async fn reth_start_era_import(&self, request: EraImportRequest) -> RpcResult<()> {
+        let EraImportRequest { dir, to_block } = request;
+        let era_type = EraFileType::from_dir(&dir)
+            .map_err(|err| invalid_params_rpc_err(err.to_string()))?
+            .ok_or_else(|| {
+                invalid_params_rpc_err(format!("no ERA files found in {}", dir.display()))
+            })?;
+        let config = ImportConfig { to_block, ..self.import_config.clone() };
+
+        self.begin(EraJob::Import)?;
+        info!(target: "era::history", ?era_type, dir = %dir.display(), ?to_block, "Starting ERA import");
+
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let imported = this.import_dir(dir, era_type, &config);
+            finish(&this.status, imported.map(|()| None));
+        });
+
+        Ok(())
+    }
+}
+
+/// Records the outcome of the job running.
+fn finish(status: &Mutex<EraStatus>, result: eyre::Result<Option<Vec<PathBuf>>>) {
+    let mut status = status.lock();
+    status.running = false;
+    match result {
+        Ok(Some(files)) => status.files = files,
+        Ok(None) => {}
+        Err(err) => {
+            error!(target: "era::history", job = job_name(status.job), %err, "ERA job failed");
+            status.error = Some(err.to_string());
+        }
+    }
+}
+
+/// Name of `job` in messages.
+const fn job_name(job: Option<EraJob>) -> &'static str {
+    match job {
+        Some(EraJob::Import) => "import",
+        Some(EraJob::Export) => "export",
+        None => "job",
+    }
+}
+
+/// Records each committed file of an import in its [`EraStatus`].
+struct StatusHooks(Arc<Mutex<EraStatus>>);
+
+impl<BH, BB> ImportHooks<BH, BB> for StatusHooks {
+    fn on_file(&mut self, file: &ImportedFile<'_>) -> eyre::Result<()> {
+        let mut status = self.0.lock();
+        status.files.push(file.path.to_path_buf());
+        if !file.blocks.is_empty() {
+            status.last_block = Some(*file.blocks.end());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    #[tokio::test]
+    async fn rejects_a_second_job_while_one_runs() {
+        let rpc = EraHistoryRpc::new(create_test_provider_factory());
+        assert_eq!(rpc.reth_era_status().await.unwrap(), EraStatus::default());
+
+        rpc.begin(EraJob::Export).unwrap();
+        let err = rpc.begin(EraJob::Import).unwrap_err();
+        assert!(err.message().contains("an ERA export is already running"), "{err:?}");
+
+        finish(&rpc.status, Err(eyre::eyre!("disk full")));
+        let status = rpc.reth_era_status().await.unwrap();
+        assert_eq!(
+            (status.job, status.running, status.error.as_deref()),
+            (Some(EraJob::Export), false, Some("disk full"))
+        );
+        rpc.begin(EraJob::Import).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_imports_of_directories_without_era_files() {
+        let rpc = EraHistoryRpc::new(create_test_provider_factory());
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = EraImportRequest { dir: dir.path().to_path_buf(), to_block: None };
+        let err = rpc.reth_start_era_import(request).await.unwrap_err();
+        assert!(err.message().contains("no ERA files found"), "{err:?}");
+        assert!(!rpc.reth_era_status().await.unwrap().running);
+    }
+}
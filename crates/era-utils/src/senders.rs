@@ -0,0 +1,82 @@
+//! Parallel recovery of the senders of imported transactions.
+
+use crate::{export::map_parallel, storage::HistoryWriter};
+use alloy_consensus::transaction::TxHashRef;
+use alloy_primitives::{Address, BlockNumber};
+use eyre::eyre;
+use reth_primitives_traits::{BlockBody, InMemorySize, SignerRecoverable};
+use std::num::NonZeroUsize;
+
+/// Queues the bodies of appended blocks and recovers the senders of their transactions in
+/// batches, spread over worker threads one block at a time.
+#[derive(Debug)]
+pub(crate) struct SenderRecovery<BB> {
+    threads: NonZeroUsize,
+    /// Bytes of bodies queued before they are flushed.
+    budget: usize,
+    blocks: Vec<(BlockNumber, BB)>,
+    /// In-memory size of the queued bodies.
+    bytes: usize,
+}
+
+impl<BB: BlockBody> SenderRecovery<BB> {
+    pub(crate) const fn new(threads: NonZeroUsize, budget: usize) -> Self {
+        Self { threads, budget, blocks: Vec::new(), bytes: 0 }
+    }
+
+    /// Queues the body of block `number`, whose body indices must already be written, flushing
+    /// once the queued bodies fill the budget.
+    pub(crate) fn push(
+        &mut self,
+        provider: &impl HistoryWriter,
+        number: BlockNumber,
+        body: BB,
+    ) -> eyre::Result<()> {
+        self.bytes += body.size();
+        self.blocks.push((number, body));
+        if self.bytes >= self.budget {
+            self.flush(provider)?;
+        }
+        Ok(())
+    }
+
+    /// Recovers the senders of every queued block and appends them after the senders already
+    /// stored, numbered by each block's body indices.
+    pub(crate) fn flush(&mut self, provider: &impl HistoryWriter) -> eyre::Result<()> {
+        if self.blocks.is_empty() {
+            return Ok(())
+        }
+
+        let senders = map_parallel(self.threads, &self.blocks, |(number, body)| {
+            recover_senders(*number, body)
+        });
+        let blocks = self
+            .blocks
+            .iter()
+            .zip(senders)
+            .map(|((number, _), senders)| Ok((*number, senders?)))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        provider.append_senders(blocks)?;
+
+        self.blocks.clear();
+        self.bytes = 0;
+        Ok(())
+    }
+}
+
+/// Recovers the sender of each transaction in the body of block `number`.
+///
+/// Signatures are not checked for low `s` values, which pre-homestead transactions may have.
+fn recover_senders<BB: BlockBody>(number: BlockNumber, body: &BB) -> eyre::Result<Vec<Address>> {
+    body.transactions()
+        .iter()
+        .map(|transaction| {
+            transaction.recover_signer_unchecked().map_err(|_| {
+                eyre!(
+                    "failed to recover the sender of transaction {} in block {number}",
+                    transaction.tx_hash()
+                )
+            })
+        })
+        .collect()
+}
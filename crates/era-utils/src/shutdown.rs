@@ -0,0 +1,30 @@
+//! Early stop of long imports and exports on node shutdown.
+
+use futures_util::FutureExt;
+use reth_tasks::shutdown::Shutdown;
+
+/// Returns `true` if `shutdown` has been fired, or its [`Signal`](reth_tasks::shutdown::Signal)
+/// dropped, without waiting for it.
+pub(crate) fn is_shutting_down(shutdown: Option<&Shutdown>) -> bool {
+    shutdown.is_some_and(|shutdown| shutdown.clone().now_or_never().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_tasks::shutdown::signal;
+
+    #[test]
+    fn reports_fired_and_dropped_signals() {
+        assert!(!is_shutting_down(None));
+
+        let (fired, shutdown) = signal();
+        assert!(!is_shutting_down(Some(&shutdown)));
+        fired.fire();
+        assert!(is_shutting_down(Some(&shutdown)));
+
+        let (dropped, shutdown) = signal();
+        drop(dropped);
+        assert!(is_shutting_down(Some(&shutdown)));
+    }
+}
@@ -0,0 +1,270 @@
+//! Storage an import writes block history to, so the importer runs against node storage or any
+//! other backend that can hold what it writes.
+
+use crate::{gaps::StoredHistory, history::build_index, stored_history};
+use alloy_primitives::{Address, BlockHash, BlockNumber, TxHash, TxNumber, U256};
+use eyre::eyre;
+use reth_db_api::transaction::DbTxMut;
+use reth_etl::Collector;
+use reth_primitives_traits::{
+    Block, BlockBody, BodyTy, FullReceipt, HeaderTy, NodePrimitives, ReceiptTy,
+};
+use reth_provider::{
+    providers::{StaticFileProvider, StaticFileProviderRWRefMut},
+    EitherWriter, RocksDBProviderFactory, StaticFileProviderFactory, StaticFileSegment,
+    StaticFileWriter,
+};
+use reth_storage_api::{
+    errors::{ProviderError, ProviderResult},
+    BlockBodyIndicesProvider, BlockHashReader, BlockWriter, DBProvider, DatabaseProviderFactory,
+    NodePrimitivesProvider, StageCheckpointReader, StageCheckpointWriter, StorageSettingsCache,
+};
+
+/// Writes imported block history, in a transaction committed once per imported file.
+///
+/// Besides the writes, an import reads back what it needs through the storage-api reader traits:
+/// stored hashes to check imported headers against, body indices to number transactions and stage
+/// checkpoints to resume from.
+///
+/// Implemented for every database provider that can write the history, and by
+/// [`InMemoryHistory`](crate::test_utils::InMemoryHistory) for tests.
+pub trait HistoryWriter:
+    NodePrimitivesProvider
+    + BlockHashReader
+    + BlockBodyIndicesProvider
+    + StageCheckpointReader
+    + StageCheckpointWriter
+    + Sized
+{
+    /// Returns how far headers and bodies are stored.
+    fn stored_history(&self) -> ProviderResult<StoredHistory>;
+
+    /// Calls `f` with the appender the blocks of one file are written with.
+    ///
+    /// Whatever the appender writes through, such as static file segment writers, is looked up
+    /// when first written to and held until `f` returns, rather than looked up for every block.
+    fn with_appender<R>(&self, f: impl FnOnce(&mut dyn BlockAppender<Self::Primitives>) -> R) -> R;
+
+    /// Appends the senders of the transactions of each of `blocks`, in block order, after the
+    /// stored ones.
+    ///
+    /// Every block advances the senders, so empty blocks are accounted for.
+    fn append_senders(&self, blocks: Vec<(BlockNumber, Vec<Address>)>) -> eyre::Result<()>;
+
+    /// Indexes transactions by hash, from `hashes` sorted by hash.
+    fn insert_transaction_hashes(&self, hashes: Vec<(TxHash, TxNumber)>) -> eyre::Result<()>;
+
+    /// Indexes blocks by hash, from the hashes collected in `hash_collector`.
+    fn insert_block_hashes(
+        &self,
+        hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    ) -> eyre::Result<()>;
+
+    /// Commits everything written.
+    fn commit(self) -> ProviderResult<()>;
+}
+
+/// Appends the blocks of one file, see [`HistoryWriter::with_appender`].
+pub trait BlockAppender<N: NodePrimitives> {
+    /// Appends the header of the block after the last stored one, with the total difficulty of
+    /// the chain up to it.
+    fn append_header(
+        &mut self,
+        header: &N::BlockHeader,
+        total_difficulty: U256,
+        hash: &BlockHash,
+    ) -> ProviderResult<()>;
+
+    /// Appends the body of block `number`, numbering its transactions after the stored ones.
+    fn append_body(&mut self, number: BlockNumber, body: &N::BlockBody) -> ProviderResult<()>;
+
+    /// Appends the `receipts` of block `number`, one for each transaction of its stored body.
+    fn append_receipts(&mut self, number: BlockNumber, receipts: &[N::Receipt])
+        -> eyre::Result<()>;
+}
+
+/// Opens the [`HistoryWriter`]s an import writes each file with.
+///
+/// Implemented for every [`DatabaseProviderFactory`] whose read-write provider is a
+/// [`HistoryWriter`].
+pub trait HistoryWriterFactory {
+    /// The writer, holding a transaction until committed.
+    type Writer: HistoryWriter;
+
+    /// Returns a writer, starting a transaction.
+    fn history_writer(&self) -> ProviderResult<Self::Writer>;
+}
+
+impl<PF> HistoryWriterFactory for PF
+where
+    PF: DatabaseProviderFactory<ProviderRW: HistoryWriter>,
+{
+    type Writer = PF::ProviderRW;
+
+    fn history_writer(&self) -> ProviderResult<Self::Writer> {
+        self.database_provider_rw()
+    }
+}
+
+impl<P, B> HistoryWriter for P
+where
+    B: Block,
+    P: DBProvider<Tx: DbTxMut>
+        + StaticFileProviderFactory
+        + StorageSettingsCache
+        + RocksDBProviderFactory
+        + BlockWriter<Block = B>
+        + BlockHashReader
+        + BlockBodyIndicesProvider
+        + StageCheckpointReader
+        + StageCheckpointWriter,
+    <P as NodePrimitivesProvider>::Primitives:
+        NodePrimitives<Block = B, BlockBody = B::Body, Receipt: FullReceipt>,
+{
+    fn stored_history(&self) -> ProviderResult<StoredHistory> {
+        stored_history(self)
+    }
+
+    fn with_appender<R>(&self, f: impl FnOnce(&mut dyn BlockAppender<Self::Primitives>) -> R) -> R {
+        let static_file_provider = self.static_file_provider();
+        f(&mut StaticFileAppender {
+            provider: self,
+            static_file_provider: &static_file_provider,
+            headers: None,
+            transactions: None,
+            receipts: None,
+            next_tx_num: None,
+        })
+    }
+
+    fn append_senders(&self, blocks: Vec<(BlockNumber, Vec<Address>)>) -> eyre::Result<()> {
+        let Some(&(first, _)) = blocks.first() else { return Ok(()) };
+
+        let mut writer = EitherWriter::new_senders(self, first)?;
+        for (number, senders) in blocks {
+            let first_tx = self
+                .block_body_indices(number)?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?
+                .first_tx_num();
+
+            writer.ensure_at_block(number)?;
+            writer.append_senders((first_tx..).zip(senders))?;
+        }
+        Ok(())
+    }
+
+    fn insert_transaction_hashes(&self, hashes: Vec<(TxHash, TxNumber)>) -> eyre::Result<()> {
+        self.with_rocksdb_batch(|batch| {
+            let mut writer = EitherWriter::new_transaction_hash_numbers(self, batch)?;
+            writer.put_transaction_hash_numbers_batch(hashes, false)?;
+            Ok(((), writer.into_raw_rocksdb_batch()))
+        })?;
+        Ok(())
+    }
+
+    fn insert_block_hashes(
+        &self,
+        hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    ) -> eyre::Result<()> {
+        build_index(self, hash_collector)
+    }
+
+    fn commit(self) -> ProviderResult<()> {
+        DBProvider::commit(self)
+    }
+}
+
+/// [`BlockAppender`] of a database provider, holding the static file writers it looked up.
+struct StaticFileAppender<'a, P: NodePrimitivesProvider> {
+    provider: &'a P,
+    static_file_provider: &'a StaticFileProvider<P::Primitives>,
+    headers: Option<StaticFileProviderRWRefMut<'a, P::Primitives>>,
+    transactions: Option<StaticFileProviderRWRefMut<'a, P::Primitives>>,
+    receipts: Option<StaticFileProviderRWRefMut<'a, P::Primitives>>,
+    /// Number of the next appended transaction, once a body was appended.
+    next_tx_num: Option<TxNumber>,
+}
+
+/// Returns the writer of `segment` held in `writer`, looking it up if it isn't held yet.
+fn held_writer<'a, 'w, N: NodePrimitives>(
+    static_file_provider: &'a StaticFileProvider<N>,
+    writer: &'w mut Option<StaticFileProviderRWRefMut<'a, N>>,
+    segment: StaticFileSegment,
+) -> ProviderResult<&'w mut StaticFileProviderRWRefMut<'a, N>> {
+    if writer.is_none() {
+        *writer = Some(static_file_provider.latest_writer(segment)?);
+    }
+    Ok(writer.as_mut().expect("writer was just looked up"))
+}
+
+impl<P, B> BlockAppender<P::Primitives> for StaticFileAppender<'_, P>
+where
+    B: Block,
+    P: BlockWriter<Block = B> + BlockBodyIndicesProvider + NodePrimitivesProvider,
+    <P as NodePrimitivesProvider>::Primitives:
+        NodePrimitives<Block = B, BlockBody = B::Body, Receipt: FullReceipt>,
+{
+    fn append_header(
+        &mut self,
+        header: &HeaderTy<P::Primitives>,
+        total_difficulty: U256,
+        hash: &BlockHash,
+    ) -> ProviderResult<()> {
+        held_writer(self.static_file_provider, &mut self.headers, StaticFileSegment::Headers)?
+            .append_header_with_td(header, total_difficulty, hash)
+    }
+
+    fn append_body(
+        &mut self,
+        number: BlockNumber,
+        body: &BodyTy<P::Primitives>,
+    ) -> ProviderResult<()> {
+        let first_tx_num = self.next_tx_num.unwrap_or_else(|| {
+            self.static_file_provider
+                .get_highest_static_file_tx(StaticFileSegment::Transactions)
+                .map_or(0, |last| last + 1)
+        });
+
+        // Transactions go to their static file segment, only their numbering to the database.
+        let writer = held_writer(
+            self.static_file_provider,
+            &mut self.transactions,
+            StaticFileSegment::Transactions,
+        )?;
+        writer.increment_block(number)?;
+        for (tx_num, transaction) in (first_tx_num..).zip(body.transactions()) {
+            writer.append_transaction(tx_num, transaction)?;
+        }
+        self.provider.append_block_body_indices(number, body, first_tx_num)?;
+
+        self.next_tx_num = Some(first_tx_num + body.transactions().len() as u64);
+        Ok(())
+    }
+
+    fn append_receipts(
+        &mut self,
+        number: BlockNumber,
+        receipts: &[ReceiptTy<P::Primitives>],
+    ) -> eyre::Result<()> {
+        let indices = self
+            .provider
+            .block_body_indices(number)?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+        if indices.tx_count != receipts.len() as u64 {
+            return Err(eyre!(
+                "block {number} has {} transactions but {} receipts",
+                indices.tx_count,
+                receipts.len()
+            ))
+        }
+
+        let writer = held_writer(
+            self.static_file_provider,
+            &mut self.receipts,
+            StaticFileSegment::Receipts,
+        )?;
+        writer.increment_block(number)?;
+        writer.append_receipts(indices.tx_num_range().zip(receipts).map(Ok))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Outcome of an ERA import.
+
+use alloy_primitives::BlockNumber;
+use std::time::Duration;
+
+/// What an ERA import wrote and how long it spent on each part of the work, as returned by
+/// [`import`](crate::import), [`process`](crate::process) and
+/// [`process_iter`](crate::process_iter).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Last block written, or the block the import started after if it wrote none.
+    pub last_block: BlockNumber,
+    /// Blocks written, counting blocks whose header was already stored and only had their body
+    /// written.
+    pub blocks_written: u64,
+    /// Receipts appended to static files, only written in
+    /// [`ImportMode::StaticFiles`](crate::ImportMode::StaticFiles).
+    pub receipts_written: u64,
+    /// Size of the files imported or skipped, in bytes, counting files not found on disk as empty.
+    /// Only counted by [`import`](crate::import).
+    pub bytes_read: u64,
+    /// Transactions, ommers or receipts found not to match their block's header, imported anyway
+    /// under [`VerificationMode::Warn`](crate::VerificationMode::Warn).
+    pub validation_warnings: u64,
+    /// Receipts whose log bloom in the file differs from the one recomputed from their logs, only
+    /// counted with [`ImportConfig::recompute_blooms`](crate::ImportConfig::recompute_blooms).
+    pub bloom_mismatches: u64,
+    /// Files at least one block was written from.
+    pub files_imported: u64,
+    /// Files holding no block past the ones already stored, so nothing was written from them.
+    pub files_skipped: u64,
+    /// Time spent on each part of the import.
+    pub timings: ImportTimings,
+    /// Whether [`import`](crate::import) stopped early on
+    /// [`ImportConfig::shutdown`](crate::ImportConfig::shutdown), before running out of files or
+    /// reaching [`ImportConfig::to_block`](crate::ImportConfig::to_block).
+    pub interrupted: bool,
+}
+
+impl ImportSummary {
+    /// Creates an empty summary of an import starting after `last_block`.
+    pub fn new(last_block: BlockNumber) -> Self {
+        Self { last_block, ..Default::default() }
+    }
+
+    /// Adds the counts and timings of `file` to `self`, whose import continued up to
+    /// [`ImportSummary::last_block`] of `file`.
+    pub(crate) fn add(&mut self, file: &Self) {
+        self.last_block = file.last_block;
+        self.blocks_written += file.blocks_written;
+        self.receipts_written += file.receipts_written;
+        self.bytes_read += file.bytes_read;
+        self.validation_warnings += file.validation_warnings;
+        self.bloom_mismatches += file.bloom_mismatches;
+        self.files_imported += file.files_imported;
+        self.files_skipped += file.files_skipped;
+        self.timings.decode += file.timings.decode;
+        self.timings.write += file.timings.write;
+        self.timings.index += file.timings.index;
+        self.timings.commit += file.timings.commit;
+    }
+}
+
+/// Time an ERA import spent on each part of the work.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportTimings {
+    /// Reading and decoding files, or waiting for them to be decoded when
+    /// [`ImportConfig::decode_threads`](crate::ImportConfig::decode_threads) is set.
+    pub decode: Duration,
+    /// Checking decoded blocks and writing them, their senders and transaction hashes.
+    pub write: Duration,
+    /// Writing block hashes to [`HeaderNumbers`](reth_db_api::tables::HeaderNumbers).
+    pub index: Duration,
+    /// Saving stage checkpoints and committing.
+    pub commit: Duration,
+}
@@ -0,0 +1,154 @@
+//! Download, verification and import of history from hosted ERA files in a single call.
+
+use crate::{
+    import, Era, Era1, Ere, HistoryWriter, HistoryWriterFactory, ImportConfig, ImportMode,
+    ImportSummary, DEFAULT_ETL_FILE_SIZE,
+};
+use alloy_primitives::{BlockHash, BlockNumber};
+use eyre::eyre;
+use reqwest::{Client, Url};
+use reth_db_api::table::Value;
+use reth_era::common::file_ops::EraFileType;
+use reth_era_downloader::{EraClient, EraStream, EraStreamConfig};
+use reth_etl::Collector;
+use reth_primitives_traits::{Block, FullBlockBody, FullBlockHeader, FullReceipt, NodePrimitives};
+use reth_storage_api::NodePrimitivesProvider;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+/// Configures [`sync_history_from_era`].
+#[derive(Clone, Debug)]
+pub struct EraSyncConfig {
+    /// Directory files are downloaded into, where up to [`EraStreamConfig::with_max_files`] of
+    /// them are kept until imported.
+    pub download_dir: PathBuf,
+    /// Format of the hosted files, detected from each host's URL if `None`.
+    pub era_type: Option<EraFileType>,
+    /// How many files are downloaded at once and kept on disk. The first file is picked after the
+    /// stored history, so any start set here is replaced.
+    pub stream: EraStreamConfig,
+    /// How downloaded files are checked and written.
+    pub import: ImportConfig,
+    /// Size of the ETL buffer block hashes are collected into, in bytes.
+    pub etl_file_size: usize,
+    /// Directory the ETL buffer spills into, the system's temporary directory if `None`.
+    pub etl_dir: Option<PathBuf>,
+}
+
+impl Default for EraSyncConfig {
+    fn default() -> Self {
+        Self {
+            download_dir: std::env::temp_dir().join("reth-era"),
+            era_type: None,
+            stream: EraStreamConfig::default(),
+            import: ImportConfig::default(),
+            etl_file_size: DEFAULT_ETL_FILE_SIZE,
+            etl_dir: None,
+        }
+    }
+}
+
+/// Downloads ERA files from the first of `urls` that serves a file listing and imports them,
+/// starting after the stored history.
+///
+/// The other URLs are mirrors, only tried while the ones before them can't be reached; a host
+/// failing once the import has started fails the sync. Files of formats that ship a
+/// `checksums.txt` are checked against it as they are downloaded, and their blocks are then
+/// checked as set by [`ImportConfig::verification`], before [`import`] writes them. Downloaded
+/// files are deleted once imported.
+///
+/// Must be called on a multi-threaded tokio runtime, whose worker runs the import.
+pub async fn sync_history_from_era<PF, B, BB, BH>(
+    urls: impl IntoIterator<Item = Url>,
+    provider_factory: &PF,
+    config: &EraSyncConfig,
+) -> eyre::Result<ImportSummary>
+where
+    B: Block<Header = BH, Body = BB>,
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<
+        Transaction = <<<PF as HistoryWriterFactory>::Writer as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
+        OmmerHeader = BH,
+    >,
+    PF: HistoryWriterFactory<
+        Writer: HistoryWriter<
+            Primitives: NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
+        >,
+    >,
+{
+    reth_fs_util::create_dir_all(&config.download_dir)?;
+
+    let stored = provider_factory.history_writer()?.stored_history()?;
+    let next_block = match config.import.mode {
+        ImportMode::HeadersOnly => stored.headers,
+        _ => stored.bodies,
+    } + 1;
+
+    let mut last_error = None;
+    for url in urls {
+        let era_type = config.era_type.unwrap_or_else(|| EraFileType::from_url(url.as_str()));
+        let client = EraClient::new(Client::new(), url.clone(), config.download_dir.clone())
+            .with_era_type(era_type);
+
+        if let Err(error) = client.fetch_file_list().await {
+            warn!(target: "era::history::import", %url, %error, "Skipping unreachable ERA host");
+            last_error = Some(error);
+            continue
+        }
+        info!(target: "era::history::import", %url, ?era_type, next_block, "Syncing history from ERA host");
+
+        // Consensus `.era` files are slot-indexed, so they are streamed from the start and the
+        // import skips the blocks already stored.
+        let stream_config = match era_type {
+            EraFileType::Era => config.stream.clone(),
+            _ => config.stream.clone().start_from(next_block),
+        };
+        let stream = EraStream::new(client, stream_config);
+        let mut hash_collector: Collector<BlockHash, BlockNumber> =
+            Collector::new(config.etl_file_size, config.etl_dir.clone());
+
+        return tokio::task::block_in_place(|| match era_type {
+            EraFileType::Era => import::<Era, _, _, _, _, _, _>(
+                stream,
+                provider_factory,
+                &mut hash_collector,
+                &config.import,
+                &mut (),
+            ),
+            EraFileType::Era1 => import::<Era1, _, _, _, _, _, _>(
+                stream,
+                provider_factory,
+                &mut hash_collector,
+                &config.import,
+                &mut (),
+            ),
+            EraFileType::Ere => import::<Ere, _, _, _, _, _, _>(
+                stream,
+                provider_factory,
+                &mut hash_collector,
+                &config.import,
+                &mut (),
+            ),
+        })
+    }
+
+    Err(last_error.unwrap_or_else(|| eyre!("No ERA host to sync history from")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::create_test_provider_factory;
+
+    #[tokio::test]
+    async fn fails_without_hosts() {
+        let download_dir = tempfile::tempdir().unwrap();
+        let config =
+            EraSyncConfig { download_dir: download_dir.path().to_path_buf(), ..Default::default() };
+
+        let err = sync_history_from_era(Vec::new(), &create_test_provider_factory(), &config)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "No ERA host to sync history from");
+    }
+}
@@ -0,0 +1,467 @@
+//! Export of `.era1` file content into CSV tables, so analytics tools such as `DuckDB` or Spark can
+//! load archived history without going through a node's RPC.
+//!
+//! Each [`Table`] is written to a CSV file of its own with a header row. Hashes, addresses and
+//! byte strings are written as `0x`-prefixed lowercase hex and quantities as decimals, so no field
+//! needs quoting; absent values, such as the recipient of a contract creation, are left empty.
+
+use crate::shutdown::is_shutting_down;
+use alloy_consensus::{
+    transaction::TxHashRef, Header, ReceiptEnvelope, Transaction, TxReceipt, Typed2718,
+};
+use alloy_primitives::{hex, Address, B256, U256};
+use eyre::Result;
+use reth_era::{
+    common::{decode::DecodeCompressedRlp, file_ops::FileReader},
+    era1::{file::Era1Reader, types::execution::BlockTuple},
+};
+use reth_fs_util as fs;
+use reth_primitives_traits::{FullBlockBody, SignerRecoverable};
+use reth_tasks::shutdown::Shutdown;
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// A table [`export_tables`] writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Table {
+    /// One row per block header, with its total difficulty and transaction count.
+    Headers,
+    /// One row per transaction.
+    Transactions,
+    /// One row per receipt, with the gas its transaction used.
+    Receipts,
+    /// One row per log, with its index among the logs of its block.
+    Logs,
+}
+
+impl Table {
+    /// Every table, in the order they are written.
+    pub const ALL: [Self; 4] = [Self::Headers, Self::Transactions, Self::Receipts, Self::Logs];
+
+    /// Returns the name of the file the table is written to.
+    pub const fn file_name(&self) -> &'static str {
+        match self {
+            Self::Headers => "headers.csv",
+            Self::Transactions => "transactions.csv",
+            Self::Receipts => "receipts.csv",
+            Self::Logs => "logs.csv",
+        }
+    }
+
+    /// Returns the columns of the table, in the order of its rows' fields.
+    pub(crate) const fn columns(&self) -> &'static [Column] {
+        match self {
+            Self::Headers => &[
+                ("number", Kind::Number, false),
+                ("hash", Kind::Hash, false),
+                ("parent_hash", Kind::Hash, false),
+                ("timestamp", Kind::Number, false),
+                ("miner", Kind::Address, false),
+                ("state_root", Kind::Hash, false),
+                ("transactions_root", Kind::Hash, false),
+                ("receipts_root", Kind::Hash, false),
+                ("gas_limit", Kind::Number, false),
+                ("gas_used", Kind::Number, false),
+                ("base_fee_per_gas", Kind::Number, true),
+                ("difficulty", Kind::Quantity, false),
+                ("total_difficulty", Kind::Quantity, false),
+                ("transaction_count", Kind::Number, false),
+            ],
+            Self::Transactions => &[
+                ("block_number", Kind::Number, false),
+                ("transaction_index", Kind::Number, false),
+                ("hash", Kind::Hash, false),
+                ("type", Kind::Number, false),
+                ("from", Kind::Address, true),
+                ("to", Kind::Address, true),
+                ("nonce", Kind::Number, false),
+                ("value", Kind::Quantity, false),
+                ("gas_limit", Kind::Number, false),
+                ("gas_price", Kind::Quantity, false),
+                ("input", Kind::Bytes, false),
+            ],
+            Self::Receipts => &[
+                ("block_number", Kind::Number, false),
+                ("transaction_index", Kind::Number, false),
+                ("transaction_hash", Kind::Hash, true),
+                ("type", Kind::Number, false),
+                ("status", Kind::Flag, false),
+                ("gas_used", Kind::Number, false),
+                ("cumulative_gas_used", Kind::Number, false),
+                ("log_count", Kind::Number, false),
+            ],
+            Self::Logs => &[
+                ("block_number", Kind::Number, false),
+                ("transaction_index", Kind::Number, false),
+                ("log_index", Kind::Number, false),
+                ("transaction_hash", Kind::Hash, true),
+                ("address", Kind::Address, false),
+                ("topic0", Kind::Hash, true),
+                ("topic1", Kind::Hash, true),
+                ("topic2", Kind::Hash, true),
+                ("topic3", Kind::Hash, true),
+                ("data", Kind::Bytes, false),
+            ],
+        }
+    }
+}
+
+/// A column of a [`Table`]: its name, the kind of its values and whether they may be absent.
+pub(crate) type Column = (&'static str, Kind, bool);
+
+/// Kind of the values of a table column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// An integer that fits in 64 bits.
+    Number,
+    /// A 256-bit integer, such as an amount of wei.
+    Quantity,
+    /// A boolean.
+    Flag,
+    /// A 32-byte hash.
+    Hash,
+    /// A 20-byte address.
+    Address,
+    /// A byte string of any length.
+    Bytes,
+}
+
+/// A field of a table row, of the [`Kind`] of its column.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Value<'a> {
+    Number(u64),
+    Quantity(U256),
+    Flag(bool),
+    Hash(B256),
+    Address(Address),
+    Bytes(&'a [u8]),
+    /// An absent value.
+    Null,
+}
+
+impl Value<'_> {
+    fn hash(hash: Option<&B256>) -> Self {
+        hash.map_or(Self::Null, |hash| Self::Hash(*hash))
+    }
+
+    fn address(address: Option<Address>) -> Self {
+        address.map_or(Self::Null, Self::Address)
+    }
+}
+
+/// Formats the value as a CSV field.
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Quantity(value) => write!(f, "{value}"),
+            Self::Flag(value) => write!(f, "{}", u8::from(*value)),
+            Self::Hash(hash) => write!(f, "{hash}"),
+            Self::Address(address) => write!(f, "{address:#x}"),
+            Self::Bytes(bytes) => f.write_str(&hex::encode_prefixed(bytes)),
+            Self::Null => Ok(()),
+        }
+    }
+}
+
+/// Configuration of an [`export_tables`] run.
+#[derive(Clone, Debug)]
+pub struct TableExportConfig {
+    /// Directory the table files are written to, replacing files of the same name.
+    pub dir: PathBuf,
+    /// Tables to write.
+    pub tables: Vec<Table>,
+    /// Whether to recover the sender of each transaction for the `from` column, which takes most
+    /// of the time of a transactions export. The column is left empty otherwise.
+    pub recover_senders: bool,
+    /// Stops the export after the file being read once it fires.
+    pub shutdown: Option<Shutdown>,
+}
+
+impl Default for TableExportConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("tables"),
+            tables: Table::ALL.to_vec(),
+            recover_senders: true,
+            shutdown: None,
+        }
+    }
+}
+
+/// Rows written by an [`export_tables`] run to each table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TableRows {
+    /// Block headers.
+    pub headers: u64,
+    /// Transactions.
+    pub transactions: u64,
+    /// Receipts.
+    pub receipts: u64,
+    /// Logs.
+    pub logs: u64,
+}
+
+impl TableRows {
+    /// Counts a row written to `table`.
+    const fn count(&mut self, table: Table) {
+        match table {
+            Table::Headers => self.headers += 1,
+            Table::Transactions => self.transactions += 1,
+            Table::Receipts => self.receipts += 1,
+            Table::Logs => self.logs += 1,
+        }
+    }
+}
+
+/// Writes the content of the `.era1` files at `files`, in their order, into the tables of
+/// `config` as CSV files in [`TableExportConfig::dir`].
+///
+/// Block bodies are decoded as `BB`, whose transactions fill the transactions table. Rows are
+/// written as they are, without checking blocks against their headers; check the files with
+/// [`verify_era1_files`](crate::verify_era1_files) first if they aren't trusted.
+pub fn export_tables<BB>(
+    files: &[impl AsRef<Path>],
+    config: &TableExportConfig,
+) -> Result<TableRows>
+where
+    BB: FullBlockBody,
+{
+    fs::create_dir_all(&config.dir)?;
+    let mut writers = Writers::create(config)?;
+    let mut rows = TableRows::default();
+
+    for path in files {
+        if is_shutting_down(config.shutdown.as_ref()) {
+            break
+        }
+
+        let file = Era1Reader::open(path.as_ref(), "")?;
+        for block in &file.group.blocks {
+            block_rows::<BB>(block, &config.tables, config.recover_senders, |table, row| {
+                rows.count(table);
+                writers.row(table, row)
+            })?;
+        }
+    }
+
+    writers.flush()?;
+    Ok(rows)
+}
+
+/// Decodes `block`, with its body as `BB`, and passes each row it adds to one of `tables` to
+/// `row`, in table order.
+///
+/// Senders are only recovered if `recover_senders` is set, leaving the `from` column empty
+/// otherwise.
+pub(crate) fn block_rows<BB>(
+    block: &BlockTuple,
+    tables: &[Table],
+    recover_senders: bool,
+    mut row: impl FnMut(Table, &[Value<'_>]) -> Result<()>,
+) -> Result<()>
+where
+    BB: FullBlockBody,
+{
+    let header: Header = block.header.decode()?;
+    let body: BB = block.body.decode()?;
+    let number = header.number;
+    let transactions = body.transactions();
+
+    if tables.contains(&Table::Headers) {
+        row(
+            Table::Headers,
+            &[
+                Value::Number(number),
+                Value::Hash(header.hash_slow()),
+                Value::Hash(header.parent_hash),
+                Value::Number(header.timestamp),
+                Value::Address(header.beneficiary),
+                Value::Hash(header.state_root),
+                Value::Hash(header.transactions_root),
+                Value::Hash(header.receipts_root),
+                Value::Number(header.gas_limit),
+                Value::Number(header.gas_used),
+                header.base_fee_per_gas.map_or(Value::Null, Value::Number),
+                Value::Quantity(header.difficulty),
+                Value::Quantity(block.total_difficulty.value),
+                Value::Number(transactions.len() as u64),
+            ],
+        )?;
+    }
+
+    if tables.contains(&Table::Transactions) {
+        for (index, transaction) in transactions.iter().enumerate() {
+            let from =
+                recover_senders.then(|| transaction.recover_signer_unchecked().ok()).flatten();
+            let gas_price = transaction.effective_gas_price(header.base_fee_per_gas);
+            row(
+                Table::Transactions,
+                &[
+                    Value::Number(number),
+                    Value::Number(index as u64),
+                    Value::Hash(*transaction.tx_hash()),
+                    Value::Number(transaction.ty().into()),
+                    Value::address(from),
+                    Value::address(transaction.to()),
+                    Value::Number(transaction.nonce()),
+                    Value::Quantity(transaction.value()),
+                    Value::Number(transaction.gas_limit()),
+                    Value::Quantity(U256::from(gas_price)),
+                    Value::Bytes(transaction.input()),
+                ],
+            )?;
+        }
+    }
+
+    if !tables.contains(&Table::Receipts) && !tables.contains(&Table::Logs) {
+        return Ok(())
+    }
+    let receipts: Vec<ReceiptEnvelope> = block.receipts.decode()?;
+    let mut log_index = 0u64;
+    let mut cumulative_gas_used = 0;
+    for (index, receipt) in receipts.iter().enumerate() {
+        let transaction_hash = transactions.get(index).map(|transaction| transaction.tx_hash());
+        let gas_used = receipt.cumulative_gas_used().saturating_sub(cumulative_gas_used);
+        cumulative_gas_used = receipt.cumulative_gas_used();
+
+        if tables.contains(&Table::Receipts) {
+            row(
+                Table::Receipts,
+                &[
+                    Value::Number(number),
+                    Value::Number(index as u64),
+                    Value::hash(transaction_hash),
+                    Value::Number(u8::from(receipt.tx_type()).into()),
+                    Value::Flag(receipt.status()),
+                    Value::Number(gas_used),
+                    Value::Number(cumulative_gas_used),
+                    Value::Number(receipt.logs().len() as u64),
+                ],
+            )?;
+        }
+
+        for log in receipt.logs() {
+            if tables.contains(&Table::Logs) {
+                let topic = |position| Value::hash(log.topics().get(position));
+                row(
+                    Table::Logs,
+                    &[
+                        Value::Number(number),
+                        Value::Number(index as u64),
+                        Value::Number(log_index),
+                        Value::hash(transaction_hash),
+                        Value::Address(log.address),
+                        topic(0),
+                        topic(1),
+                        topic(2),
+                        topic(3),
+                        Value::Bytes(&log.data.data),
+                    ],
+                )?;
+            }
+            log_index += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Writers of the tables being exported, indexed by [`Table`].
+#[derive(Debug)]
+struct Writers([Option<BufWriter<File>>; 4]);
+
+impl Writers {
+    /// Creates the files of the tables of `config`, starting each with its header row.
+    fn create(config: &TableExportConfig) -> Result<Self> {
+        let mut writers = Self(Default::default());
+        for table in &config.tables {
+            let mut writer = BufWriter::new(fs::create_file(config.dir.join(table.file_name()))?);
+            let names = table.columns().iter().map(|(name, ..)| *name).collect::<Vec<_>>();
+            writeln!(writer, "{}", names.join(","))?;
+            writers.0[*table as usize] = Some(writer);
+        }
+        Ok(writers)
+    }
+
+    /// Writes `row` to `table`, if it is being written.
+    fn row(&mut self, table: Table, row: &[Value<'_>]) -> Result<()> {
+        let Some(writer) = &mut self.0[table as usize] else { return Ok(()) };
+        for (position, field) in row.iter().enumerate() {
+            if position > 0 {
+                writer.write_all(b",")?;
+            }
+            write!(writer, "{field}")?;
+        }
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        for writer in self.0.iter_mut().flatten() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock, ExportConfig};
+    use alloy_primitives::Log;
+    use reth_ethereum_primitives::{BlockBody, Receipt, TxType};
+
+    #[test]
+    fn writes_tables_of_era1_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let log =
+            Log::new_unchecked(Address::repeat_byte(1), vec![B256::repeat_byte(2)], [3].into());
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..2u64)
+            .map(|number| {
+                let header = Header { number, parent_hash, ..Default::default() };
+                parent_hash = header.hash_slow();
+                ExportBlock::<_, BlockBody, Receipt> {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody::default(),
+                    receipts: vec![Receipt {
+                        tx_type: TxType::Legacy,
+                        success: true,
+                        cumulative_gas_used: 21_000,
+                        logs: vec![log.clone()],
+                    }],
+                    total_difficulty: U256::ZERO,
+                }
+            })
+            .collect::<Vec<_>>();
+        let file = Era1::write_file(
+            &ExportConfig { dir: dir.path().to_path_buf(), ..Default::default() },
+            &blocks,
+        )
+        .unwrap();
+
+        let config = TableExportConfig {
+            dir: dir.path().join("tables"),
+            tables: vec![Table::Headers, Table::Logs],
+            ..Default::default()
+        };
+        let rows = export_tables::<BlockBody>(&[file], &config).unwrap();
+        assert_eq!(rows, TableRows { headers: 2, transactions: 0, receipts: 0, logs: 2 });
+
+        let headers = fs::read_to_string(config.dir.join("headers.csv")).unwrap();
+        assert_eq!(headers.lines().count(), 3);
+        assert!(headers.lines().nth(2).unwrap().starts_with(&format!("1,{parent_hash},")));
+
+        let logs = fs::read_to_string(config.dir.join("logs.csv")).unwrap();
+        assert_eq!(
+            logs.lines().nth(1).unwrap(),
+            format!("0,0,0,,0x{},{},,,,0x03", "01".repeat(20), B256::repeat_byte(2))
+        );
+        assert!(!config.dir.join("receipts.csv").exists());
+    }
+}
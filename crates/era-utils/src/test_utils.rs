@@ -0,0 +1,669 @@
+//! Synthetic block history, for benchmarks and tests that need `.era1` files of a chosen size
+//! without a synced node or mainnet fixtures.
+
+use crate::{
+    export::write_listing, BlockAppender, Era1, EraBlockWriter, ExportBlock, ExportConfig,
+    HistoryWriter, HistoryWriterFactory, StoredHistory,
+};
+use alloy_consensus::{
+    proofs::calculate_transaction_root, BlockHeader, Header, Transaction as _, TxEip1559,
+    TxEip2930, TxLegacy, EMPTY_OMMER_ROOT_HASH,
+};
+use alloy_eips::eip2930::{AccessList, AccessListItem};
+use alloy_primitives::{
+    logs_bloom, Address, BlockHash, BlockNumber, Bytes, TxHash, TxKind, TxNumber, B256, U256,
+};
+use eyre::{eyre, Result};
+use parking_lot::Mutex;
+use reth_chainspec::EthChainSpec;
+use reth_db_api::{models::StoredBlockBodyIndices, RawKey, RawValue};
+use reth_era::common::file_ops::EraFileType;
+use reth_ethereum_primitives::{
+    calculate_receipt_root_no_memo, BlockBody, EthPrimitives, Receipt, Transaction,
+    TransactionSigned, TxType,
+};
+use reth_etl::Collector;
+use reth_primitives_traits::{BlockBody as _, BodyTy, HeaderTy, NodePrimitives, ReceiptTy};
+use reth_provider::StaticFileSegment;
+use reth_stages_types::{StageCheckpoint, StageId};
+use reth_storage_api::{
+    errors::{ProviderError, ProviderResult},
+    BlockBodyIndicesProvider, BlockHashReader, NodePrimitivesProvider, StageCheckpointReader,
+    StageCheckpointWriter,
+};
+use reth_testing_utils::generators::{self, generate_key, random_log, sign_tx_with_key_pair, Rng};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Seconds between the timestamps of consecutive blocks.
+const BLOCK_TIME: u64 = 12;
+
+/// Shape of the pre-merge chain [`SyntheticHistory::generate`] makes up.
+#[derive(Clone, Debug)]
+pub struct SyntheticHistory {
+    /// Hash the header of the first block links to, such as the genesis hash of the database the
+    /// blocks are imported into.
+    pub parent_hash: B256,
+    /// Total difficulty of the chain up to and including the parent of the first block.
+    pub parent_total_difficulty: U256,
+    /// Number of the first block.
+    pub first_block: BlockNumber,
+    /// Timestamp of the first block, each later block being `12` seconds younger.
+    pub first_timestamp: u64,
+    /// Chain id transactions are signed for.
+    pub chain_id: u64,
+    /// Number of blocks.
+    pub blocks: u64,
+    /// Signed transactions in each block, each with a receipt.
+    pub transactions_per_block: u8,
+    /// Types the transactions of a block cycle through, in order.
+    ///
+    /// Only the types a pre-merge block can hold are supported: [`TxType::Legacy`],
+    /// [`TxType::Eip2930`] and [`TxType::Eip1559`].
+    pub transaction_types: Vec<TxType>,
+    /// Logs in each successful receipt.
+    pub logs_per_receipt: u8,
+    /// Percentage of transactions whose receipt reports a failure, without logs.
+    pub failed_percent: u8,
+}
+
+impl Default for SyntheticHistory {
+    fn default() -> Self {
+        Self {
+            parent_hash: B256::ZERO,
+            parent_total_difficulty: U256::ZERO,
+            first_block: 0,
+            first_timestamp: 0,
+            chain_id: 1,
+            blocks: 1024,
+            transactions_per_block: 16,
+            transaction_types: vec![TxType::Legacy],
+            logs_per_receipt: 2,
+            failed_percent: 10,
+        }
+    }
+}
+
+impl SyntheticHistory {
+    /// Returns the default history extending the genesis block of `chain_spec`, such as a dev or
+    /// test chain, so that its files import into a database initialized with that chain spec.
+    pub fn for_chain(chain_spec: &impl EthChainSpec<Header = Header>) -> Self {
+        let genesis = chain_spec.genesis_header();
+        Self {
+            parent_hash: chain_spec.genesis_hash(),
+            parent_total_difficulty: genesis.difficulty,
+            first_block: genesis.number + 1,
+            first_timestamp: genesis.timestamp + BLOCK_TIME,
+            chain_id: chain_spec.chain_id(),
+            ..Default::default()
+        }
+    }
+
+    /// Makes up the blocks, linked by parent hash, each with a difficulty of `1`.
+    ///
+    /// Transaction, ommers and receipts roots match the blocks' contents, so they pass the checks
+    /// of an import. Every transaction is signed with a fresh key, so its sender can be recovered,
+    /// and uses up its gas limit. The rest is random, seeded from the `SEED` environment variable
+    /// if set.
+    ///
+    /// # Panics
+    ///
+    /// If [`SyntheticHistory::transaction_types`] is empty while blocks hold transactions, or holds
+    /// a type a pre-merge block can't.
+    pub fn generate(&self) -> Vec<ExportBlock<Header, BlockBody, Receipt>> {
+        let mut rng = generators::rng();
+        let mut parent_hash = self.parent_hash;
+        let mut total_difficulty = self.parent_total_difficulty;
+
+        (0..self.blocks)
+            .map(|index| {
+                let transactions = self
+                    .transaction_types
+                    .iter()
+                    .cycle()
+                    .take(self.transactions_per_block.into())
+                    .map(|tx_type| self.random_transaction(&mut rng, *tx_type))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    transactions.len(),
+                    usize::from(self.transactions_per_block),
+                    "no transaction types to pick from"
+                );
+
+                let mut gas_used = 0;
+                let receipts = transactions
+                    .iter()
+                    .map(|tx| {
+                        gas_used += tx.gas_limit();
+                        let success = rng.random_range(0..100) >= self.failed_percent;
+                        Receipt {
+                            tx_type: tx.tx_type(),
+                            success,
+                            cumulative_gas_used: gas_used,
+                            logs: if success {
+                                (0..self.logs_per_receipt)
+                                    .map(|_| random_log(&mut rng, None, Some(2)))
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            },
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let header = Header {
+                    parent_hash,
+                    ommers_hash: EMPTY_OMMER_ROOT_HASH,
+                    transactions_root: calculate_transaction_root(&transactions),
+                    receipts_root: calculate_receipt_root_no_memo(&receipts),
+                    logs_bloom: logs_bloom(receipts.iter().flat_map(|receipt| &receipt.logs)),
+                    difficulty: U256::from(1),
+                    number: self.first_block + index,
+                    gas_limit: gas_used.max(30_000_000),
+                    gas_used,
+                    timestamp: self.first_timestamp + index * BLOCK_TIME,
+                    ..Default::default()
+                };
+                total_difficulty += header.difficulty;
+                parent_hash = header.hash_slow();
+
+                ExportBlock {
+                    header,
+                    block_hash: parent_hash,
+                    body: BlockBody { transactions, ommers: Vec::new(), withdrawals: None },
+                    receipts,
+                    total_difficulty,
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the blocks [`SyntheticHistory::generate`] makes up to `.era1` files in
+    /// [`ExportConfig::dir`], [`ExportConfig::max_blocks_per_file`] blocks each, returning their
+    /// paths in block order.
+    ///
+    /// With [`ExportConfig::write_listing`], the directory also gets the `checksums.txt` the era
+    /// downloader's [`read_dir`](reth_era_downloader::read_dir) needs to import them.
+    pub fn write_era1(&self, config: &ExportConfig) -> Result<Vec<PathBuf>> {
+        let files = self
+            .generate()
+            .chunks(config.max_blocks_per_file.max(1) as usize)
+            .map(|blocks| Era1::write_file(config, blocks))
+            .collect::<Result<Vec<_>>>()?;
+        if config.write_listing {
+            write_listing(&config.dir, EraFileType::Era1)?;
+        }
+        Ok(files)
+    }
+
+    /// Returns a random transaction of `tx_type` to a random address, signed with a fresh key.
+    fn random_transaction(&self, rng: &mut impl Rng, tx_type: TxType) -> TransactionSigned {
+        let nonce = rng.random_range(0..1_000);
+        let gas_limit = rng.random_range(21_000..200_000);
+        let gas_price = rng.random_range(1..100) * 1_000_000_000;
+        let to = TxKind::Call(Address::from(rng.random::<[u8; 20]>()));
+        let value = U256::from(rng.random::<u64>());
+        let input = Bytes::copy_from_slice(&rng.random::<[u8; 32]>());
+
+        let tx = match tx_type {
+            TxType::Legacy => Transaction::Legacy(TxLegacy {
+                chain_id: Some(self.chain_id),
+                nonce,
+                gas_price,
+                gas_limit,
+                to,
+                value,
+                input,
+            }),
+            TxType::Eip2930 => Transaction::Eip2930(TxEip2930 {
+                chain_id: self.chain_id,
+                nonce,
+                gas_price,
+                gas_limit,
+                to,
+                value,
+                access_list: AccessList(vec![AccessListItem {
+                    address: Address::from(rng.random::<[u8; 20]>()),
+                    storage_keys: vec![B256::from(rng.random::<[u8; 32]>())],
+                }]),
+                input,
+            }),
+            TxType::Eip1559 => Transaction::Eip1559(TxEip1559 {
+                chain_id: self.chain_id,
+                nonce,
+                gas_limit,
+                max_fee_per_gas: gas_price,
+                max_priority_fee_per_gas: gas_price / 10,
+                to,
+                value,
+                access_list: AccessList::default(),
+                input,
+            }),
+            other => panic!("{other:?} transactions can't be in a pre-merge block"),
+        };
+        sign_tx_with_key_pair(generate_key(rng), tx)
+    }
+}
+
+/// Block history held in memory, for testing imports without a database.
+///
+/// Writers work on a copy of the history, which replaces it when committed, so an import that
+/// fails mid-file leaves the history as the last commit did.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryHistory<N: NodePrimitives = EthPrimitives> {
+    committed: Arc<Mutex<HistoryState<N>>>,
+}
+
+impl<N: NodePrimitives> InMemoryHistory<N> {
+    /// Returns a history holding the `genesis` header, with an empty body, as a database is after
+    /// being initialized with the genesis block.
+    pub fn with_genesis(genesis: N::BlockHeader) -> Self {
+        let mut state = HistoryState::default();
+        let hash = genesis.hash_slow();
+        state.block_numbers.insert(hash, genesis.number());
+        state.body_indices.insert(genesis.number(), StoredBlockBodyIndices::default());
+        state.headers.insert(genesis.number(), (hash, genesis));
+        Self { committed: Arc::new(Mutex::new(state)) }
+    }
+
+    /// Returns the committed header of block `number`.
+    pub fn header(&self, number: BlockNumber) -> Option<N::BlockHeader> {
+        self.committed.lock().headers.get(&number).map(|(_, header)| header.clone())
+    }
+
+    /// Returns the committed body of block `number`.
+    pub fn body(&self, number: BlockNumber) -> Option<N::BlockBody> {
+        self.committed.lock().bodies.get(&number).cloned()
+    }
+
+    /// Returns the committed receipt of transaction `tx_number`.
+    pub fn receipt(&self, tx_number: TxNumber) -> Option<N::Receipt> {
+        self.committed.lock().receipts.get(&tx_number).cloned()
+    }
+
+    /// Returns the committed sender of transaction `tx_number`.
+    pub fn sender(&self, tx_number: TxNumber) -> Option<Address> {
+        self.committed.lock().senders.get(&tx_number).copied()
+    }
+
+    /// Returns the number of the transaction with `hash`, if indexed.
+    pub fn transaction_number(&self, hash: TxHash) -> Option<TxNumber> {
+        self.committed.lock().transaction_numbers.get(&hash).copied()
+    }
+
+    /// Returns the number of the block with `hash`, if indexed.
+    pub fn block_number(&self, hash: BlockHash) -> Option<BlockNumber> {
+        self.committed.lock().block_numbers.get(&hash).copied()
+    }
+}
+
+impl<N: NodePrimitives> HistoryWriterFactory for InMemoryHistory<N> {
+    type Writer = InMemoryHistoryWriter<N>;
+
+    fn history_writer(&self) -> ProviderResult<Self::Writer> {
+        Ok(InMemoryHistoryWriter {
+            committed: self.committed.clone(),
+            state: Mutex::new(self.committed.lock().clone()),
+        })
+    }
+}
+
+/// [`HistoryWriter`] of an [`InMemoryHistory`].
+#[derive(Debug)]
+pub struct InMemoryHistoryWriter<N: NodePrimitives = EthPrimitives> {
+    committed: Arc<Mutex<HistoryState<N>>>,
+    state: Mutex<HistoryState<N>>,
+}
+
+/// Everything an [`InMemoryHistory`] holds.
+#[derive(Clone, Debug)]
+struct HistoryState<N: NodePrimitives> {
+    headers: BTreeMap<BlockNumber, (BlockHash, N::BlockHeader)>,
+    body_indices: BTreeMap<BlockNumber, StoredBlockBodyIndices>,
+    bodies: BTreeMap<BlockNumber, N::BlockBody>,
+    receipts: BTreeMap<TxNumber, N::Receipt>,
+    senders: BTreeMap<TxNumber, Address>,
+    transaction_numbers: HashMap<TxHash, TxNumber>,
+    block_numbers: HashMap<BlockHash, BlockNumber>,
+    checkpoints: HashMap<StageId, StageCheckpoint>,
+    checkpoint_progress: HashMap<StageId, Vec<u8>>,
+}
+
+impl<N: NodePrimitives> Default for HistoryState<N> {
+    fn default() -> Self {
+        Self {
+            headers: BTreeMap::new(),
+            body_indices: BTreeMap::new(),
+            bodies: BTreeMap::new(),
+            receipts: BTreeMap::new(),
+            senders: BTreeMap::new(),
+            transaction_numbers: HashMap::new(),
+            block_numbers: HashMap::new(),
+            checkpoints: HashMap::new(),
+            checkpoint_progress: HashMap::new(),
+        }
+    }
+}
+
+impl<N: NodePrimitives> HistoryState<N> {
+    fn body_indices(&self, number: BlockNumber) -> ProviderResult<StoredBlockBodyIndices> {
+        self.body_indices
+            .get(&number)
+            .copied()
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(number))
+    }
+}
+
+impl<N: NodePrimitives> NodePrimitivesProvider for InMemoryHistoryWriter<N> {
+    type Primitives = N;
+}
+
+impl<N: NodePrimitives> BlockHashReader for InMemoryHistoryWriter<N> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        Ok(self.state.lock().headers.get(&number).map(|(hash, _)| *hash))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        Ok(self.state.lock().headers.range(start..end).map(|(_, (hash, _))| *hash).collect())
+    }
+}
+
+impl<N: NodePrimitives> BlockBodyIndicesProvider for InMemoryHistoryWriter<N> {
+    fn block_body_indices(&self, num: u64) -> ProviderResult<Option<StoredBlockBodyIndices>> {
+        Ok(self.state.lock().body_indices.get(&num).copied())
+    }
+
+    fn block_body_indices_range(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<StoredBlockBodyIndices>> {
+        Ok(self.state.lock().body_indices.range(range).map(|(_, indices)| *indices).collect())
+    }
+}
+
+impl<N: NodePrimitives> StageCheckpointReader for InMemoryHistoryWriter<N> {
+    fn get_stage_checkpoint(&self, id: StageId) -> ProviderResult<Option<StageCheckpoint>> {
+        Ok(self.state.lock().checkpoints.get(&id).copied())
+    }
+
+    fn get_stage_checkpoint_progress(&self, id: StageId) -> ProviderResult<Option<Vec<u8>>> {
+        Ok(self.state.lock().checkpoint_progress.get(&id).cloned())
+    }
+
+    fn get_all_checkpoints(&self) -> ProviderResult<Vec<(String, StageCheckpoint)>> {
+        Ok(self
+            .state
+            .lock()
+            .checkpoints
+            .iter()
+            .map(|(id, checkpoint)| (id.to_string(), *checkpoint))
+            .collect())
+    }
+}
+
+impl<N: NodePrimitives> StageCheckpointWriter for InMemoryHistoryWriter<N> {
+    fn save_stage_checkpoint(
+        &self,
+        id: StageId,
+        checkpoint: StageCheckpoint,
+    ) -> ProviderResult<()> {
+        self.state.lock().checkpoints.insert(id, checkpoint);
+        Ok(())
+    }
+
+    fn save_stage_checkpoint_progress(
+        &self,
+        id: StageId,
+        checkpoint: Vec<u8>,
+    ) -> ProviderResult<()> {
+        self.state.lock().checkpoint_progress.insert(id, checkpoint);
+        Ok(())
+    }
+
+    fn update_pipeline_stages(
+        &self,
+        block_number: BlockNumber,
+        drop_stage_checkpoint: bool,
+    ) -> ProviderResult<()> {
+        let mut state = self.state.lock();
+        for id in StageId::ALL {
+            let checkpoint = state.checkpoints.entry(id).or_default();
+            checkpoint.block_number = block_number;
+            if drop_stage_checkpoint {
+                checkpoint.stage_checkpoint = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N: NodePrimitives> HistoryWriter for InMemoryHistoryWriter<N> {
+    fn stored_history(&self) -> ProviderResult<StoredHistory> {
+        let state = self.state.lock();
+        Ok(StoredHistory {
+            headers: state.headers.last_key_value().map_or(0, |(number, _)| *number),
+            bodies: state.body_indices.last_key_value().map_or(0, |(number, _)| *number),
+        })
+    }
+
+    fn with_appender<R>(&self, f: impl FnOnce(&mut dyn BlockAppender<N>) -> R) -> R {
+        let mut appender = self;
+        f(&mut appender)
+    }
+
+    fn append_senders(&self, blocks: Vec<(BlockNumber, Vec<Address>)>) -> Result<()> {
+        let mut state = self.state.lock();
+        for (number, senders) in blocks {
+            let first_tx = state.body_indices(number)?.first_tx_num();
+            state.senders.extend((first_tx..).zip(senders));
+        }
+        Ok(())
+    }
+
+    fn insert_transaction_hashes(&self, hashes: Vec<(TxHash, TxNumber)>) -> Result<()> {
+        self.state.lock().transaction_numbers.extend(hashes);
+        Ok(())
+    }
+
+    fn insert_block_hashes(
+        &self,
+        hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    ) -> Result<()> {
+        let mut state = self.state.lock();
+        for entry in hash_collector.iter()? {
+            let (hash, number) = entry?;
+            state.block_numbers.insert(
+                RawKey::<BlockHash>::from_vec(hash).key()?,
+                RawValue::<BlockNumber>::from_vec(number).value()?,
+            );
+        }
+        Ok(())
+    }
+
+    fn commit(self) -> ProviderResult<()> {
+        *self.committed.lock() = self.state.into_inner();
+        Ok(())
+    }
+}
+
+impl<N: NodePrimitives> BlockAppender<N> for &InMemoryHistoryWriter<N> {
+    fn append_header(
+        &mut self,
+        header: &HeaderTy<N>,
+        _total_difficulty: U256,
+        hash: &BlockHash,
+    ) -> ProviderResult<()> {
+        let mut state = self.state.lock();
+        let expected = state.headers.last_key_value().map_or(0, |(number, _)| number + 1);
+        if header.number() != expected {
+            return Err(ProviderError::UnexpectedStaticFileBlockNumber(
+                StaticFileSegment::Headers,
+                header.number(),
+                expected,
+            ))
+        }
+        state.headers.insert(header.number(), (*hash, header.clone()));
+        Ok(())
+    }
+
+    fn append_body(&mut self, number: BlockNumber, body: &BodyTy<N>) -> ProviderResult<()> {
+        let mut state = self.state.lock();
+        let first_tx_num =
+            state.body_indices.last_key_value().map_or(0, |(_, indices)| indices.next_tx_num());
+        state.body_indices.insert(
+            number,
+            StoredBlockBodyIndices { first_tx_num, tx_count: body.transactions().len() as u64 },
+        );
+        state.bodies.insert(number, body.clone());
+        Ok(())
+    }
+
+    fn append_receipts(&mut self, number: BlockNumber, receipts: &[ReceiptTy<N>]) -> Result<()> {
+        let mut state = self.state.lock();
+        let indices = state.body_indices(number)?;
+        if indices.tx_count != receipts.len() as u64 {
+            return Err(eyre!(
+                "block {number} has {} transactions but {} receipts",
+                indices.tx_count,
+                receipts.len()
+            ))
+        }
+        state.receipts.extend(indices.tx_num_range().zip(receipts.iter().cloned()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{import, verify_era1_file, ImportConfig};
+    use alloy_consensus::transaction::{SignerRecoverable, TxHashRef};
+    use reth_chainspec::DEV;
+    use reth_db_common::init::init_genesis;
+    use reth_era_downloader::read_dir;
+    use reth_provider::test_utils::create_test_provider_factory_with_chain_spec;
+    use std::num::NonZeroUsize;
+    use tempfile::tempdir;
+
+    #[test]
+    fn generates_files_that_verify() {
+        let dir = tempdir().unwrap();
+        let config = ExportConfig {
+            dir: dir.path().to_path_buf(),
+            max_blocks_per_file: 3,
+            ..Default::default()
+        };
+        let history =
+            SyntheticHistory { blocks: 5, transactions_per_block: 2, ..Default::default() };
+
+        let files = history.write_era1(&config).unwrap();
+        let ranges = files
+            .iter()
+            .map(|file| verify_era1_file::<Header, BlockBody>(file, None, None).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![0..=2, 3..=4]);
+
+        let blocks = history.generate();
+        assert_eq!(blocks[1].header.parent_hash, blocks[0].block_hash);
+        assert_eq!(blocks[4].total_difficulty, U256::from(5));
+        assert!(blocks.iter().all(|block| block.receipts.len() == 2));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn imports_into_dev_chain() {
+        let dir = tempdir().unwrap();
+        let history = SyntheticHistory {
+            blocks: 8,
+            transactions_per_block: 3,
+            transaction_types: vec![TxType::Legacy, TxType::Eip2930, TxType::Eip1559],
+            ..SyntheticHistory::for_chain(&**DEV)
+        };
+        let blocks = history.generate();
+        assert_eq!(blocks[0].header.number, 1);
+        assert_eq!(
+            blocks[0].body.transactions.iter().map(|tx| tx.tx_type()).collect::<Vec<_>>(),
+            history.transaction_types
+        );
+        history
+            .write_era1(&ExportConfig {
+                dir: dir.path().to_path_buf(),
+                network: "dev".to_string(),
+                write_listing: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let provider_factory = create_test_provider_factory_with_chain_spec(DEV.clone());
+        init_genesis(&provider_factory).unwrap();
+        let collector_dir = tempdir().unwrap();
+        let mut collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
+        let summary = import::<Era1, _, _, _, _, _, _>(
+            read_dir(dir.path().to_path_buf(), 0).unwrap(),
+            &provider_factory,
+            &mut collector,
+            &ImportConfig::default(),
+            &mut (),
+        )
+        .unwrap();
+        assert_eq!(summary.last_block, 8);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn imports_into_memory() {
+        let dir = tempdir().unwrap();
+        let history = SyntheticHistory {
+            blocks: 6,
+            transactions_per_block: 2,
+            ..SyntheticHistory::for_chain(&**DEV)
+        };
+        let blocks = history.generate();
+        history
+            .write_era1(&ExportConfig {
+                dir: dir.path().to_path_buf(),
+                network: "dev".to_string(),
+                max_blocks_per_file: 4,
+                write_listing: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let storage = InMemoryHistory::<EthPrimitives>::with_genesis(DEV.genesis_header().clone());
+        let collector_dir = tempdir().unwrap();
+        let mut collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
+        let summary = import::<Era1, _, _, _, _, _, _>(
+            read_dir(dir.path().to_path_buf(), 0).unwrap(),
+            &storage,
+            &mut collector,
+            &ImportConfig {
+                sender_recovery_threads: Some(NonZeroUsize::MIN),
+                index_hashes: true,
+                ..Default::default()
+            },
+            &mut (),
+        )
+        .unwrap();
+        assert_eq!(summary.last_block, 6);
+
+        let writer = storage.history_writer().unwrap();
+        assert_eq!(writer.stored_history().unwrap(), StoredHistory { headers: 6, bodies: 6 });
+        assert_eq!(writer.get_stage_checkpoint(StageId::Bodies).unwrap().unwrap().block_number, 6);
+        for block in &blocks {
+            let number = block.header.number;
+            assert_eq!(storage.header(number).as_ref(), Some(&block.header));
+            assert_eq!(storage.body(number).as_ref(), Some(&block.body));
+            assert_eq!(storage.block_number(block.block_hash), Some(number));
+        }
+        let last = blocks.last().unwrap().body.transactions.last().unwrap();
+        let tx_number = storage.transaction_number(*last.tx_hash()).unwrap();
+        assert_eq!(tx_number, 11);
+        assert_eq!(storage.sender(tx_number), Some(last.recover_signer().unwrap()));
+    }
+}
@@ -0,0 +1,53 @@
+//! Write throttling for imports running next to a live node.
+
+use std::{num::NonZeroU64, time::Duration};
+
+/// Caps how fast [`import`](crate::import) writes, so it leaves disk bandwidth to the hot path.
+///
+/// The import commits once per ERA file, so the limit is enforced by pausing after each commit
+/// until the file's share of the budget has elapsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportThrottle {
+    /// At most this many bytes of ERA files imported per second.
+    BytesPerSecond(NonZeroU64),
+    /// At most `blocks` blocks imported per `interval`.
+    BlocksPerInterval {
+        /// Blocks allowed per interval.
+        blocks: NonZeroU64,
+        /// Length of the interval.
+        interval: Duration,
+    },
+}
+
+impl ImportThrottle {
+    /// Returns how long to pause after importing `bytes` of ERA files holding `blocks` blocks in
+    /// `elapsed`, to stay within the limit.
+    pub fn delay(self, elapsed: Duration, bytes: u64, blocks: u64) -> Duration {
+        let budget = match self {
+            Self::BytesPerSecond(rate) => Duration::from_secs_f64(bytes as f64 / rate.get() as f64),
+            Self::BlocksPerInterval { blocks: rate, interval } => {
+                interval.mul_f64(blocks as f64 / rate.get() as f64)
+            }
+        };
+        budget.saturating_sub(elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_covers_remaining_budget() {
+        let throttle = ImportThrottle::BytesPerSecond(NonZeroU64::new(1_000).unwrap());
+        assert_eq!(throttle.delay(Duration::from_secs(1), 3_000, 0), Duration::from_secs(2));
+        assert_eq!(throttle.delay(Duration::from_secs(5), 3_000, 0), Duration::ZERO);
+
+        let throttle = ImportThrottle::BlocksPerInterval {
+            blocks: NonZeroU64::new(100).unwrap(),
+            interval: Duration::from_secs(10),
+        };
+        assert_eq!(throttle.delay(Duration::from_secs(5), 0, 50), Duration::ZERO);
+        assert_eq!(throttle.delay(Duration::from_secs(5), 0, 200), Duration::from_secs(15));
+    }
+}
@@ -0,0 +1,386 @@
+//! Consistency checks between decoded ERA block data and the headers that commit to it.
+//!
+//! An archive is only trusted as far as its headers link up; everything else a block tuple carries
+//! is checked against the roots in its header before it is written.
+
+use crate::export::{map_parallel, ChunkAccumulator};
+use alloy_consensus::{
+    proofs::calculate_receipt_root, BlockHeader, ReceiptEnvelope, Sealable, EMPTY_OMMER_ROOT_HASH,
+};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{BlockNumber, U256};
+use reth_db_api::table::Value;
+use reth_era::{
+    common::{decode::DecodeCompressedRlp, file_ops::FileReader},
+    era1::{file::Era1Reader, types::execution::Accumulator},
+};
+use reth_primitives_traits::{BlockBody, FullBlockBody, FullBlockHeader};
+use std::{
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// How a block whose contents don't match the roots committed in its header is handled on import.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerificationMode {
+    /// Reject the archive at the first mismatching block.
+    #[default]
+    Strict,
+    /// Log the mismatch and import the block anyway.
+    ///
+    /// Meant for recovering what is left of a damaged archive; the imported history may not
+    /// match the canonical chain.
+    Warn,
+}
+
+impl VerificationMode {
+    /// Applies the mode to the outcome of a content check, turning a failure into a warning when
+    /// running in [`VerificationMode::Warn`].
+    pub fn apply(self, outcome: eyre::Result<()>) -> eyre::Result<()> {
+        match (self, outcome) {
+            (Self::Warn, Err(error)) => {
+                warn!(target: "era::history::import", %error, "Importing block that does not match its header");
+                Ok(())
+            }
+            (_, outcome) => outcome,
+        }
+    }
+}
+
+/// Checks that `body`'s transactions and ommers hash to the roots committed in `header`.
+pub fn verify_body_roots<H, B>(header: &H, body: &B) -> eyre::Result<()>
+where
+    H: BlockHeader,
+    B: BlockBody,
+{
+    let tx_root = body.calculate_tx_root();
+    if tx_root != header.transactions_root() {
+        eyre::bail!(
+            "transactions root mismatch at block {}: header commits to {}, transactions hash to \
+             {tx_root}",
+            header.number(),
+            header.transactions_root(),
+        );
+    }
+
+    let ommers_hash = body.calculate_ommers_root();
+    if Some(header.ommers_hash()) != ommers_hash {
+        eyre::bail!(
+            "ommers hash mismatch at block {}: header commits to {}, ommers hash to {}",
+            header.number(),
+            header.ommers_hash(),
+            ommers_hash.unwrap_or(EMPTY_OMMER_ROOT_HASH),
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that `receipts`, bloom included, hash to `header`'s receipts root.
+///
+/// `.era1` stores receipts in their network encoding, which decodes into [`ReceiptEnvelope`]s for
+/// every pre-merge transaction type.
+///
+/// [`ReceiptEnvelope`]: alloy_consensus::ReceiptEnvelope
+pub fn verify_receipts_root<H, R>(header: &H, receipts: &[R]) -> eyre::Result<()>
+where
+    H: BlockHeader,
+    R: Encodable2718,
+{
+    let root = calculate_receipt_root(receipts);
+
+    if root != header.receipts_root() {
+        eyre::bail!(
+            "receipts root mismatch at block {}: header commits to {}, receipts hash to {root}",
+            header.number(),
+            header.receipts_root(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that `header`, at `total_difficulty`, is a proof-of-work block the merge at
+/// `terminal_total_difficulty` allows.
+///
+/// Proof-of-stake blocks, which carry zero difficulty, are rejected, as is any proof-of-work block
+/// extending a chain that already reached the terminal total difficulty.
+pub fn verify_pre_merge<H: BlockHeader>(
+    header: &H,
+    total_difficulty: U256,
+    terminal_total_difficulty: U256,
+) -> eyre::Result<()> {
+    let difficulty = header.difficulty();
+    if difficulty.is_zero() {
+        eyre::bail!(
+            "block {} is post-merge, pre-merge history must end at the terminal block",
+            header.number()
+        );
+    }
+
+    let parent_total_difficulty = total_difficulty.checked_sub(difficulty).ok_or_else(|| {
+        eyre::eyre!(
+            "block {} has total difficulty {total_difficulty} below its own difficulty \
+             {difficulty}",
+            header.number()
+        )
+    })?;
+    if parent_total_difficulty >= terminal_total_difficulty {
+        eyre::bail!(
+            "block {} extends total difficulty {parent_total_difficulty}, past the terminal total \
+             difficulty {terminal_total_difficulty}",
+            header.number()
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that the ERA file at `path` is named for `network`, as in
+/// `<network>-<era-number>-<short-hash>.<ext>`.
+///
+/// Archives carry no chain id, so their names are all that tells one network's files from
+/// another's before their blocks are read.
+pub fn verify_network(path: &Path, network: &str) -> eyre::Result<()> {
+    let named_for_network = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix(network))
+        .is_some_and(|rest| rest.starts_with('-'));
+    if !named_for_network {
+        eyre::bail!(
+            "ERA file {} is not named for network {network}, refusing to import another \
+             network's history",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks the `.era1` file at `path` on its own, without a database, returning the blocks it
+/// holds.
+///
+/// Every block must decode and match the roots committed in its header, and each header past the
+/// first must link to its predecessor by parent hash. The accumulator is recomputed from the
+/// blocks' hashes and total difficulties and compared with the file's. With `network` set, the
+/// file must be named for it, and with `terminal_total_difficulty` set, every block is checked
+/// with [`verify_pre_merge`].
+pub fn verify_era1_file<BH, BB>(
+    path: &Path,
+    network: Option<&str>,
+    terminal_total_difficulty: Option<U256>,
+) -> eyre::Result<RangeInclusive<BlockNumber>>
+where
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<OmmerHeader = BH>,
+{
+    if let Some(network) = network {
+        verify_network(path, network)?;
+    }
+
+    let file = Era1Reader::open(path, "")?;
+    let mut records = Vec::with_capacity(file.group.blocks.len());
+    let mut parent_hash = None;
+    for block in &file.group.blocks {
+        let header: BH = block.header.decode()?;
+        let body: BB = block.body.decode()?;
+        let receipts: Vec<ReceiptEnvelope> = block.receipts.decode()?;
+        verify_body_roots(&header, &body)?;
+        verify_receipts_root(&header, &receipts)?;
+
+        if let Some(expected) = parent_hash &&
+            header.parent_hash() != expected
+        {
+            eyre::bail!(
+                "broken header chain at block {}: parent hash {} does not match hash {expected} \
+                 of block {}",
+                header.number(),
+                header.parent_hash(),
+                header.number() - 1,
+            );
+        }
+
+        let total_difficulty = block.total_difficulty.value;
+        if let Some(terminal_total_difficulty) = terminal_total_difficulty {
+            verify_pre_merge(&header, total_difficulty, terminal_total_difficulty)?;
+        }
+
+        let hash = header.hash_slow();
+        parent_hash = Some(hash);
+        records.push((hash, total_difficulty));
+    }
+
+    let root = Accumulator::from_pairs(&records)?.root;
+    if root != file.group.accumulator.root {
+        eyre::bail!(
+            "accumulator root mismatch in {}: file commits to {}, blocks hash to {root}",
+            path.display(),
+            file.group.accumulator.root,
+        );
+    }
+
+    Ok(file.block_range())
+}
+
+/// Checks the `.era1` files at `paths` with [`verify_era1_file`], up to `threads` at once.
+///
+/// Returns each file's outcome, in the order of `paths`.
+pub fn verify_era1_files<BH, BB>(
+    paths: &[PathBuf],
+    network: Option<&str>,
+    terminal_total_difficulty: Option<U256>,
+    threads: NonZeroUsize,
+) -> Vec<eyre::Result<RangeInclusive<BlockNumber>>>
+where
+    BH: FullBlockHeader + Value,
+    BB: FullBlockBody<OmmerHeader = BH>,
+{
+    map_parallel(threads, paths, |path| {
+        verify_era1_file::<BH, BB>(path, network, terminal_total_difficulty)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Era1, EraBlockWriter, ExportBlock, ExportConfig};
+    use alloy_consensus::{Eip658Value, Header, Receipt, ReceiptWithBloom};
+    use alloy_primitives::{Bloom, B256};
+    use reth_ethereum_primitives::{BlockBody, Receipt as EthReceipt};
+    use reth_primitives_traits::BlockBody as _;
+    use tempfile::tempdir;
+
+    fn receipts() -> Vec<ReceiptEnvelope> {
+        (1..=2)
+            .map(|i| {
+                ReceiptEnvelope::Legacy(ReceiptWithBloom::new(
+                    Receipt {
+                        status: Eip658Value::Eip658(true),
+                        cumulative_gas_used: 21_000 * i,
+                        logs: vec![],
+                    },
+                    Bloom::default(),
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accepts_receipts_matching_header() {
+        let receipts = receipts();
+        let header = Header {
+            number: 1,
+            receipts_root: calculate_receipt_root(&receipts),
+            ..Default::default()
+        };
+
+        verify_receipts_root(&header, &receipts).unwrap();
+    }
+
+    #[test]
+    fn rejects_receipts_not_matching_header() {
+        let header =
+            Header { number: 7, receipts_root: B256::repeat_byte(1), ..Default::default() };
+
+        let err = verify_receipts_root(&header, &receipts()).unwrap_err().to_string();
+        assert!(err.contains("receipts root mismatch at block 7"), "{err}");
+    }
+
+    #[test]
+    fn accepts_body_matching_header() {
+        let body = BlockBody { ommers: vec![Header::default()], ..Default::default() };
+        let header = Header {
+            transactions_root: body.calculate_tx_root(),
+            ommers_hash: body.calculate_ommers_root().unwrap(),
+            ..Default::default()
+        };
+
+        verify_body_roots(&header, &body).unwrap();
+    }
+
+    #[test]
+    fn rejects_body_not_matching_header() {
+        let body = BlockBody::default();
+
+        let header =
+            Header { number: 3, transactions_root: B256::repeat_byte(1), ..Default::default() };
+        let err = verify_body_roots(&header, &body).unwrap_err().to_string();
+        assert!(err.contains("transactions root mismatch at block 3"), "{err}");
+
+        let header = Header { number: 3, ommers_hash: B256::repeat_byte(2), ..Default::default() };
+        let err = verify_body_roots(&header, &body).unwrap_err().to_string();
+        assert!(err.contains("ommers hash mismatch at block 3"), "{err}");
+
+        assert!(VerificationMode::Warn.apply(verify_body_roots(&header, &body)).is_ok());
+        assert!(VerificationMode::Strict.apply(verify_body_roots(&header, &body)).is_err());
+    }
+
+    #[test]
+    fn rejects_blocks_past_terminal_total_difficulty() {
+        let ttd = U256::from(100);
+        let header = |difficulty: u64| Header {
+            number: 5,
+            difficulty: U256::from(difficulty),
+            ..Default::default()
+        };
+
+        // The terminal block may overshoot, as long as its parent is short of the TTD.
+        verify_pre_merge(&header(10), U256::from(105), ttd).unwrap();
+
+        let err = verify_pre_merge(&header(10), U256::from(115), ttd).unwrap_err().to_string();
+        assert!(err.contains("block 5 extends total difficulty 105"), "{err}");
+
+        let err = verify_pre_merge(&header(0), U256::from(105), ttd).unwrap_err().to_string();
+        assert!(err.contains("block 5 is post-merge"), "{err}");
+    }
+
+    /// Writes blocks `0..3` to an `.era1` file in `dir`, linked by parent hash if `linked`.
+    fn write_file(dir: &Path, linked: bool) -> PathBuf {
+        let mut parent_hash = B256::ZERO;
+        let blocks = (0..3u64)
+            .map(|number| {
+                let header = Header {
+                    number,
+                    parent_hash: if linked { parent_hash } else { B256::repeat_byte(7) },
+                    difficulty: U256::from(1),
+                    ..Default::default()
+                };
+                let block_hash = header.hash_slow();
+                parent_hash = block_hash;
+                ExportBlock::<_, _, EthReceipt> {
+                    header,
+                    block_hash,
+                    body: BlockBody::default(),
+                    receipts: Vec::new(),
+                    total_difficulty: U256::from(number + 1),
+                }
+            })
+            .collect::<Vec<_>>();
+        Era1::write_file(&ExportConfig { dir: dir.to_path_buf(), ..Default::default() }, &blocks)
+            .unwrap()
+    }
+
+    #[test]
+    fn verifies_era1_files_without_a_database() {
+        let (linked, broken) = (tempdir().unwrap(), tempdir().unwrap());
+        let paths = [write_file(linked.path(), true), write_file(broken.path(), false)];
+
+        let outcomes = verify_era1_files::<Header, BlockBody>(
+            &paths,
+            Some("mainnet"),
+            Some(U256::from(10)),
+            NonZeroUsize::new(2).unwrap(),
+        );
+        assert_eq!(outcomes[0].as_ref().unwrap(), &(0..=2));
+        let err = outcomes[1].as_ref().unwrap_err().to_string();
+        assert!(err.contains("broken header chain at block 1"), "{err}");
+
+        let err = verify_era1_file::<Header, BlockBody>(&paths[0], Some("sepolia"), None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is not named for network sepolia"), "{err}");
+    }
+}
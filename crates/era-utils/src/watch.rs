@@ -0,0 +1,200 @@
+//! Import of ERA files as they are copied into a watched directory.
+
+use crate::{
+    import, shutdown::is_shutting_down, verify_era1_file, Era1, Ere, HistoryWriter,
+    HistoryWriterFactory, ImportConfig,
+};
+use alloy_primitives::{BlockHash, BlockNumber};
+use futures_util::stream;
+use reth_db_api::table::Value;
+use reth_era::common::file_ops::EraFileType;
+use reth_era_downloader::EraMeta;
+use reth_etl::Collector;
+use reth_fs_util as fs;
+use reth_primitives_traits::{Block, FullBlockBody, FullBlockHeader, FullReceipt, NodePrimitives};
+use reth_storage_api::NodePrimitivesProvider;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+/// Imports the ERA files copied into a directory as they appear, so archives can be fed to a node
+/// by syncing them into a folder, such as with `rsync`.
+///
+/// Each [`DirectoryImporter::poll`] imports, in file name order, the `.era1` and `.ere` files that
+/// kept the same size since the previous poll, leaving files still being copied for a later one.
+/// `.era1` files are first checked on their own with [`verify_era1_file`]; one that fails is
+/// skipped until it changes. A file that fails to import, such as one ahead of a file still
+/// missing, ends the poll and is retried by the next.
+///
+/// Other files are ignored. `.e2hs` files are reported once, as they can't be imported.
+#[derive(Debug)]
+pub struct DirectoryImporter {
+    dir: PathBuf,
+    config: ImportConfig,
+    /// Size of each file not yet imported when last seen.
+    seen: HashMap<PathBuf, u64>,
+    /// Size of each file imported or skipped, so it is only looked at again once it changes.
+    done: HashMap<PathBuf, u64>,
+}
+
+impl DirectoryImporter {
+    /// Creates an importer of the files copied into `dir`, imported with `config`.
+    ///
+    /// Files already in `dir` are imported by the second poll, and the blocks they hold that are
+    /// already stored are skipped.
+    pub fn new(dir: impl Into<PathBuf>, config: ImportConfig) -> Self {
+        Self { dir: dir.into(), config, seen: HashMap::new(), done: HashMap::new() }
+    }
+
+    /// Returns the watched directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Imports every file that finished copying into the directory since the last poll.
+    ///
+    /// Returns the paths of the imported files. Stops early once [`ImportConfig::shutdown`]
+    /// fires.
+    pub fn poll<PF, B, BB, BH>(
+        &mut self,
+        provider_factory: &PF,
+        hash_collector: &mut Collector<BlockHash, BlockNumber>,
+    ) -> eyre::Result<Vec<PathBuf>>
+    where
+        B: Block<Header = BH, Body = BB>,
+        BH: FullBlockHeader + Value,
+        BB: FullBlockBody<
+            Transaction = <<<PF as HistoryWriterFactory>::Writer as NodePrimitivesProvider>::Primitives as NodePrimitives>::SignedTx,
+            OmmerHeader = BH,
+        >,
+        PF: HistoryWriterFactory<
+            Writer: HistoryWriter<
+                Primitives: NodePrimitives<Block = B, BlockHeader = BH, BlockBody = BB, Receipt: FullReceipt>,
+            >,
+        >,
+    {
+        let mut imported = Vec::new();
+
+        for (path, size, era_type) in self.settled_files()? {
+            if is_shutting_down(self.config.shutdown.as_ref()) {
+                break
+            }
+
+            if era_type == EraFileType::Era1 &&
+                let Err(error) = verify_era1_file::<BH, BB>(
+                    &path,
+                    self.config.network.as_deref(),
+                    self.config.terminal_total_difficulty,
+                )
+            {
+                warn!(target: "era::history::import", path = %path.display(), %error, "Skipping invalid ERA file until it changes");
+                self.done.insert(path, size);
+                continue
+            }
+
+            let file = stream::iter([Ok(WatchedFile(path.clone()))]);
+            let outcome = match era_type {
+                EraFileType::Ere => import::<Ere, _, _, _, _, _, _>(
+                    file,
+                    provider_factory,
+                    hash_collector,
+                    &self.config,
+                    &mut (),
+                ),
+                _ => import::<Era1, _, _, _, _, _, _>(
+                    file,
+                    provider_factory,
+                    hash_collector,
+                    &self.config,
+                    &mut (),
+                ),
+            };
+            if let Err(error) = outcome {
+                warn!(target: "era::history::import", path = %path.display(), %error, "Failed to import ERA file, retrying on the next poll");
+                break
+            }
+
+            self.done.insert(path.clone(), size);
+            imported.push(path);
+        }
+
+        Ok(imported)
+    }
+
+    /// Returns the importable files that kept their size since the last poll, in file name order,
+    /// along with their size and type.
+    fn settled_files(&mut self) -> eyre::Result<Vec<(PathBuf, u64, EraFileType)>> {
+        let mut settled = Vec::new();
+        let mut seen = HashMap::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+            let size = entry.metadata()?.len();
+            if self.done.get(&path) == Some(&size) {
+                continue
+            }
+
+            match EraFileType::from_filename(name) {
+                Some(era_type @ (EraFileType::Era1 | EraFileType::Ere)) => {
+                    if self.seen.get(&path) == Some(&size) {
+                        settled.push((path.clone(), size, era_type));
+                    }
+                    seen.insert(path, size);
+                }
+                _ if name.ends_with(".e2hs") => {
+                    warn!(target: "era::history::import", path = %path.display(), "Ignoring `.e2hs` file, which can't be imported");
+                    self.done.insert(path, size);
+                }
+                _ => {}
+            }
+        }
+
+        self.seen = seen;
+        settled.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        Ok(settled)
+    }
+}
+
+/// A file of the watched directory, left in place once imported.
+#[derive(Debug)]
+struct WatchedFile(PathBuf);
+
+impl EraMeta for WatchedFile {
+    /// A no-op, the directory is left as it was synced.
+    fn mark_as_processed(&self) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_files_once_they_stop_growing() {
+        let dir = tempfile::tempdir().unwrap();
+        let era1 = dir.path().join("mainnet-00001-a5364e9a.era1");
+        let ere = dir.path().join("mainnet-00000-5ec1ffb8.ere");
+        let mut importer = DirectoryImporter::new(dir.path(), ImportConfig::default());
+
+        std::fs::write(&era1, [0; 8]).unwrap();
+        std::fs::write(&ere, [0; 8]).unwrap();
+        std::fs::write(dir.path().join("mainnet-00000-5ec1ffb8.e2hs"), [0; 8]).unwrap();
+        std::fs::write(dir.path().join(".mainnet-00002-f5e0a4d1.era1.x7Yq2b"), [0; 8]).unwrap();
+        assert!(importer.settled_files().unwrap().is_empty());
+
+        std::fs::write(&era1, [0; 16]).unwrap();
+        assert_eq!(importer.settled_files().unwrap(), vec![(ere.clone(), 8, EraFileType::Ere)]);
+
+        importer.done.insert(ere, 8);
+        assert_eq!(importer.settled_files().unwrap(), vec![(era1, 16, EraFileType::Era1)]);
+    }
+}
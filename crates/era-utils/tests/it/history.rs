@@ -1,13 +1,20 @@
 use crate::{ClientWithFakeIndex, FileMeta, ITHACA_ERA_INDEX_URL};
+use futures_util::TryStreamExt;
 use reqwest::{Client, Url};
+use reth_chainspec::{EthereumHardfork, EthereumHardforks, MAINNET};
 use reth_db_common::init::init_genesis;
 use reth_era::era1::types::execution::MAX_BLOCKS_PER_ERA1;
-use reth_era_downloader::{EraClient, EraStream, EraStreamConfig};
-use reth_era_utils::{export, import, Era1, Ere, ExportConfig};
+use reth_era_downloader::{EraClient, EraMeta, EraStream, EraStreamConfig};
+use reth_era_utils::{
+    export, export_parallel, import, verify_roundtrip, Era1, Ere, ExportConfig, ExportTuning,
+    FinalizedExporter, ImportConfig,
+};
 use reth_etl::Collector;
 use reth_fs_util as fs;
-use reth_provider::{test_utils::create_test_provider_factory, BlockNumReader, BlockReader};
-use std::str::FromStr;
+use reth_provider::{
+    test_utils::create_test_provider_factory, BlockNumReader, BlockReader, ChainStateBlockWriter,
+};
+use std::{num::NonZeroUsize, path::PathBuf, str::FromStr};
 use tempfile::tempdir;
 
 const EXPORT_FIRST_BLOCK: u64 = 0;
@@ -38,8 +45,15 @@ async fn test_history_imports_from_fresh_state_successfully() {
     let mut hash_collector = Collector::new(4096, folder);
 
     let expected_block_number = 8191;
-    let actual_block_number =
-        import::<Era1, _, _, _, _, _, _>(stream, &pf, &mut hash_collector, None).unwrap();
+    let actual_block_number = import::<Era1, _, _, _, _, _, _>(
+        stream,
+        &pf,
+        &mut hash_collector,
+        &ImportConfig::default(),
+        &mut (),
+    )
+    .unwrap()
+    .last_block;
 
     assert_eq!(actual_block_number, expected_block_number);
 }
@@ -67,9 +81,17 @@ async fn test_roundtrip_export_after_import() {
     let folder = Some(folder.path().to_owned());
     let mut hash_collector = Collector::new(4096, folder);
 
-    // Import blocks from one era1 file into database
-    let last_imported_block_height =
-        import::<Era1, _, _, _, _, _, _>(stream, &pf, &mut hash_collector, None).unwrap();
+    // Import blocks from one era1 file into database, holding them to the mainnet merge
+    let terminal_total_difficulty = MAINNET.ethereum_fork_activation(EthereumHardfork::Paris).ttd();
+    let last_imported_block_height = import::<Era1, _, _, _, _, _, _>(
+        stream,
+        &pf,
+        &mut hash_collector,
+        &ImportConfig { terminal_total_difficulty, ..Default::default() },
+        &mut (),
+    )
+    .unwrap()
+    .last_block;
 
     assert_eq!(last_imported_block_height, 8191);
     let provider_ref = pf.provider_rw().unwrap().0;
@@ -93,7 +115,18 @@ async fn test_roundtrip_export_after_import() {
         first_block_number: EXPORT_FIRST_BLOCK,      // 0
         last_block_number: EXPORT_LAST_BLOCK,        // 899
         max_blocks_per_file: EXPORT_BLOCKS_PER_FILE, // 250 blocks per file
+        time_range: None,
         network: "mainnet".to_string(),
+        write_listing: false,
+        write_cids: false,
+        naming: Default::default(),
+        profile: Default::default(),
+        spec_compliant: false,
+        tuning: Default::default(),
+        terminal_total_difficulty,
+        resume: false,
+        checksum: Default::default(),
+        shutdown: None,
     };
 
     // Export blocks from database to era1 files
@@ -111,6 +144,25 @@ async fn test_roundtrip_export_after_import() {
         "Should create {expected_files_number} files for {EXPORT_TOTAL_BLOCKS} blocks with {EXPORT_BLOCKS_PER_FILE} blocks per file"
     );
 
+    // Building several files at once, each from its own provider, must produce the same files.
+    let parallel_folder = tempdir().unwrap();
+    let parallel_config = ExportConfig {
+        dir: parallel_folder.path().to_path_buf(),
+        tuning: ExportTuning {
+            parallel_files: NonZeroUsize::new(3).unwrap(),
+            compression_threads: NonZeroUsize::new(2).unwrap(),
+            ..Default::default()
+        },
+        ..export_config.clone()
+    };
+    let parallel_files =
+        export_parallel::<Era1, _>(&pf, &parallel_config).expect("Parallel export should succeed");
+    assert_eq!(parallel_files.len(), exported_files.len());
+    for (sequential, parallel) in exported_files.iter().zip(&parallel_files) {
+        assert_eq!(sequential.file_name(), parallel.file_name());
+        assert_eq!(std::fs::read(sequential).unwrap(), std::fs::read(parallel).unwrap());
+    }
+
     for (i, file_path) in exported_files.iter().enumerate() {
         // Verify file exists and has content
         assert!(file_path.exists(), "File {} should exist", i + 1);
@@ -161,6 +213,40 @@ async fn test_roundtrip_export_after_import() {
     }
 }
 
+/// Importing a hosted era1 file and exporting its blocks again must not lose any history.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_verify_roundtrip_against_source_file() {
+    let url = Url::from_str(ITHACA_ERA_INDEX_URL).unwrap();
+    let download_folder = tempdir().unwrap();
+    let download_folder = download_folder.path().to_owned().into_boxed_path();
+    let client = EraClient::new(ClientWithFakeIndex(Client::new()), url, download_folder);
+    let config = EraStreamConfig::default().with_max_files(1).with_max_concurrent_downloads(1);
+    let sources: Vec<PathBuf> = EraStream::new(client, config)
+        .map_ok(|meta| meta.path().to_path_buf())
+        .try_collect()
+        .await
+        .unwrap();
+
+    let pf = create_test_provider_factory();
+    init_genesis(&pf).unwrap();
+    let collector_dir = tempdir().unwrap();
+    let mut hash_collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
+
+    let export_folder = tempdir().unwrap();
+    let checked = verify_roundtrip(
+        &sources,
+        &pf,
+        &mut hash_collector,
+        &ImportConfig::default(),
+        &ExportConfig { dir: export_folder.path().to_path_buf(), ..Default::default() },
+    )
+    .unwrap();
+
+    assert_eq!(checked.len(), 1);
+    assert_eq!(checked[0].blocks, 0..=8191);
+    assert!(checked[0].is_lossless(), "Round trip lost history: {:?}", checked[0].outcome);
+}
+
 /// Roundtrip for `.ere` files: import era1 data into a database, export it back out as `.ere`,
 /// then reimport those `.ere` files into a fresh database and verify the blocks survive intact.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -178,8 +264,15 @@ async fn test_ere_roundtrip_export_after_import() {
     let collector_dir = tempdir().unwrap();
     let mut hash_collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
 
-    let imported_height =
-        import::<Era1, _, _, _, _, _, _>(stream, &pf, &mut hash_collector, None).unwrap();
+    let imported_height = import::<Era1, _, _, _, _, _, _>(
+        stream,
+        &pf,
+        &mut hash_collector,
+        &ImportConfig::default(),
+        &mut (),
+    )
+    .unwrap()
+    .last_block;
     assert_eq!(imported_height, 8191);
 
     let provider_ref = pf.provider_rw().unwrap().0;
@@ -190,8 +283,19 @@ async fn test_ere_roundtrip_export_after_import() {
         dir: ere_folder.path().to_path_buf(),
         first_block_number: EXPORT_FIRST_BLOCK,
         last_block_number: EXPORT_LAST_BLOCK,
+        time_range: None,
         max_blocks_per_file: EXPORT_BLOCKS_PER_FILE,
         network: "mainnet".to_string(),
+        write_listing: false,
+        write_cids: false,
+        naming: Default::default(),
+        profile: Default::default(),
+        spec_compliant: false,
+        tuning: Default::default(),
+        terminal_total_difficulty: None,
+        resume: false,
+        checksum: Default::default(),
+        shutdown: None,
     };
     let ere_files =
         export::<Ere, _>(&provider_ref, &export_config).expect("ERE export should succeed");
@@ -233,9 +337,15 @@ async fn test_ere_roundtrip_export_after_import() {
     // The exported files are returned in ascending block order, which is the order the importer
     // expects.
     let stream = futures_util::stream::iter(ere_files.into_iter().map(|p| Ok(FileMeta::new(p))));
-    let reimported_height =
-        import::<Ere, _, _, _, _, _, _>(stream, &reimport_pf, &mut reimport_collector, None)
-            .unwrap();
+    let reimported_height = import::<Ere, _, _, _, _, _, _>(
+        stream,
+        &reimport_pf,
+        &mut reimport_collector,
+        &ImportConfig::default(),
+        &mut (),
+    )
+    .unwrap()
+    .last_block;
 
     assert_eq!(
         reimported_height, EXPORT_LAST_BLOCK,
@@ -306,3 +416,52 @@ fn assert_ere_index_offsets_resolve(path: &std::path::Path) {
         }
     }
 }
+
+/// A watched export writes nothing until a whole era has finalized, then writes it exactly once.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_finalized_exporter_writes_each_era_once() {
+    let url = Url::from_str(ITHACA_ERA_INDEX_URL).unwrap();
+    let download_folder = tempdir().unwrap();
+    let download_folder = download_folder.path().to_owned().into_boxed_path();
+    let client = EraClient::new(ClientWithFakeIndex(Client::new()), url, download_folder);
+    let config = EraStreamConfig::default().with_max_files(1).with_max_concurrent_downloads(1);
+    let stream = EraStream::new(client, config);
+
+    let pf = create_test_provider_factory();
+    init_genesis(&pf).unwrap();
+    let collector_dir = tempdir().unwrap();
+    let mut hash_collector = Collector::new(4096, Some(collector_dir.path().to_owned()));
+    import::<Era1, _, _, _, _, _, _>(
+        stream,
+        &pf,
+        &mut hash_collector,
+        &ImportConfig::default(),
+        &mut (),
+    )
+    .unwrap();
+
+    let provider = pf.provider_rw().unwrap().0;
+    let export_folder = tempdir().unwrap();
+    let mut exporter = FinalizedExporter::<Era1>::new(ExportConfig {
+        dir: export_folder.path().to_path_buf(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Nothing finalized yet.
+    assert!(exporter.poll(&provider).unwrap().is_empty());
+
+    // Era 0 is not whole until block 8191 finalizes.
+    provider.save_finalized_block_number(8190).unwrap();
+    assert!(exporter.poll(&provider).unwrap().is_empty());
+
+    provider.save_finalized_block_number(8191).unwrap();
+    let files = exporter.poll(&provider).unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].file_name().unwrap().to_str().unwrap().starts_with("mainnet-00000-"));
+    assert_eq!(exporter.next_block(), MAX_BLOCKS_PER_ERA1 as u64);
+    assert_eq!(exporter.exported_blocks(), Some(0..=8191));
+
+    // Already exported.
+    assert!(exporter.poll(&provider).unwrap().is_empty());
+}
@@ -4,6 +4,9 @@ pub mod compression;
 pub mod decode;
 pub mod file_ops;
 
+#[cfg(feature = "recovery")]
+pub(crate) mod recovered;
+
 /// Maximum number of entries per e2store era file.
 ///
 /// One value for every era format: `.era` files store one beacon block per slot, and
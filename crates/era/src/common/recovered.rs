@@ -0,0 +1,25 @@
+//! Conversion of block tuples into reth blocks with recovered senders.
+
+use crate::{common::decode::DecodeCompressedRlp, e2s::error::E2sError};
+use alloy_consensus::BlockHeader;
+use reth_primitives_traits::{Block, RecoveredBlock, SealedBlock};
+
+/// Decodes a compressed header and body into a [`RecoveredBlock`], sealing the header with its
+/// hash and recovering the sender of each transaction.
+///
+/// Signatures are not checked for low `s` values, which pre-homestead transactions may have.
+pub(crate) fn recover_block<B: Block>(
+    header: &impl DecodeCompressedRlp,
+    body: &impl DecodeCompressedRlp,
+) -> Result<RecoveredBlock<B>, E2sError> {
+    let header: B::Header = header.decode()?;
+    let body: B::Body = body.decode()?;
+
+    let block = SealedBlock::<B>::seal_parts(header, body);
+    let (number, hash) = (block.header().number(), block.hash());
+    block.try_recover_unchecked().map_err(|_| {
+        E2sError::SenderRecovery(format!(
+            "failed to recover the transaction senders of block {number} ({hash})"
+        ))
+    })
+}
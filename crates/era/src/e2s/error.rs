@@ -30,6 +30,10 @@ pub enum E2sError {
     #[error("RLP error: {0}")]
     Rlp(String),
 
+    /// Error recovering the senders of a block's transactions
+    #[error("sender recovery error: {0}")]
+    SenderRecovery(String),
+
     /// Error converting an execution payload into a block
     #[error("execution payload error: {0}")]
     ExecutionPayload(#[from] alloy_rpc_types_engine::PayloadError),
@@ -606,6 +606,17 @@ impl BlockTuple {
         Ok(Block::new(header, body))
     }
 
+    /// Convert to a reth block with its header hash sealed and its transaction senders recovered,
+    /// without going through an `alloy_consensus::Block`.
+    ///
+    /// Signatures are not checked for low `s` values, which pre-homestead transactions may have.
+    #[cfg(feature = "recovery")]
+    pub fn to_recovered_block<B: reth_primitives_traits::Block>(
+        &self,
+    ) -> Result<reth_primitives_traits::RecoveredBlock<B>, E2sError> {
+        crate::common::recovered::recover_block(&self.header, &self.body)
+    }
+
     /// Create from an `alloy_consensus::Block`
     pub fn from_alloy_block<T: Encodable, R: Encodable>(
         block: &Block<T>,
@@ -738,6 +749,54 @@ mod tests {
         assert!(decoded_block.body.withdrawals.is_some());
     }
 
+    #[cfg(feature = "recovery")]
+    #[test]
+    fn test_block_tuple_to_recovered_block() {
+        use alloy_consensus::{transaction::SignerRecoverable, Signed, TxLegacy};
+        use alloy_primitives::{b256, Address, Signature, TxKind};
+        use reth_ethereum_primitives::TransactionSigned;
+
+        // `r` is the x coordinate of the secp256k1 generator, so the signature recovers to a key
+        let signature = Signature::new(
+            U256::from_be_bytes(
+                b256!("0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").0,
+            ),
+            U256::from(1),
+            false,
+        );
+        let transactions = (0..2u64)
+            .map(|nonce| {
+                let tx = TxLegacy {
+                    chain_id: Some(1),
+                    nonce,
+                    gas_price: 1,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(Address::ZERO),
+                    ..Default::default()
+                };
+                TransactionSigned::Legacy(Signed::new_unhashed(tx, signature))
+            })
+            .collect::<Vec<_>>();
+        let senders = transactions
+            .iter()
+            .map(|tx| tx.recover_signer_unchecked().unwrap())
+            .collect::<Vec<_>>();
+
+        let header = create_header();
+        let body = BlockBody { transactions, ommers: vec![], withdrawals: None };
+        let block: reth_ethereum_primitives::Block = Block::new(header.clone(), body);
+
+        let block_tuple =
+            BlockTuple::from_alloy_block(&block, &Vec::<u8>::new(), U256::ZERO).unwrap();
+        let recovered =
+            block_tuple.to_recovered_block::<reth_ethereum_primitives::Block>().unwrap();
+
+        assert_eq!(recovered.hash(), header.hash_slow());
+        assert_eq!(recovered.header().number, 100);
+        assert_eq!(recovered.body().transactions, block.body.transactions);
+        assert_eq!(recovered.senders(), senders.as_slice());
+    }
+
     #[test]
     fn test_single_receipt_compression_roundtrip() {
         let test_receipt = create_test_receipt(TxType::Eip1559, true, 21000, 2);
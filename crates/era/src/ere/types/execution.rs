@@ -594,6 +594,17 @@ impl BlockTuple {
         Ok(Block::new(header, body))
     }
 
+    /// Convert to a reth block with its header hash sealed and its transaction senders recovered,
+    /// without going through an `alloy_consensus::Block`.
+    ///
+    /// Signatures are not checked for low `s` values, which pre-homestead transactions may have.
+    #[cfg(feature = "recovery")]
+    pub fn to_recovered_block<B: reth_primitives_traits::Block>(
+        &self,
+    ) -> Result<reth_primitives_traits::RecoveredBlock<B>, E2sError> {
+        crate::common::recovered::recover_block(&self.header, &self.body)
+    }
+
     /// Create from an `alloy_consensus::Block`, attaching the given receipts and total difficulty.
     pub fn from_alloy_block<T: Encodable, R: Encodable>(
         block: &Block<T>,
@@ -195,8 +195,15 @@ where
         Commands::Import(command) => {
             runner.run_blocking_until_ctrl_c(command.execute::<N, _>(components, rt))
         }
-        Commands::ImportEra(command) => runner.run_blocking_until_ctrl_c(command.execute::<N>(rt)),
-        Commands::ExportEra(command) => runner.run_blocking_until_ctrl_c(command.execute::<N>(rt)),
+        Commands::ImportEra(command) => {
+            runner.run_blocking_until_shutdown(move |shutdown| command.execute::<N>(rt, shutdown))
+        }
+        Commands::ExportEra(command) => {
+            runner.run_blocking_until_shutdown(move |shutdown| command.execute::<N>(rt, shutdown))
+        }
+        Commands::Era(command) => {
+            runner.run_blocking_until_shutdown(move |shutdown| command.execute::<N>(rt, shutdown))
+        }
         Commands::DumpGenesis(command) => runner.run_blocking_until_ctrl_c(command.execute()),
         Commands::Db(command) => {
             runner.run_blocking_command_until_exit(|ctx| command.execute::<N>(ctx))
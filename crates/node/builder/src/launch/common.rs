@@ -486,6 +486,7 @@ where
         // Validate static files configuration
         let static_files_config = &self.toml_config().static_files;
         static_files_config.validate()?;
+        self.toml_config().stages.era.validate()?;
 
         let prune_config = self.prune_config();
 
@@ -1120,18 +1121,33 @@ where
 
     /// Creates the ERA import source based on node configuration.
     ///
-    /// Returns `Some(EraImportSource)` if ERA is enabled in the node config, otherwise `None`.
+    /// Returns `Some(EraImportSource)` if ERA is enabled by the node config or the
+    /// `[stages.era]` section of the toml config, otherwise `None`. A source given on the command
+    /// line takes precedence over the one in the toml config.
     pub fn era_import_source(&self) -> Option<EraImportSource> {
         let node_config = self.node_config();
-        if !node_config.era.enabled {
+        let era_config = &self.toml_config().stages.era;
+        if !node_config.era.enabled && !era_config.enabled {
             return None;
         }
 
+        let source = &node_config.era.source;
+        let (path, url) = if source.path.is_some() || source.url.is_some() {
+            (source.path.clone(), source.url.clone())
+        } else {
+            (era_config.path.clone().map(Into::into), era_config.url.clone())
+        };
         EraImportSource::maybe_new(
-            node_config.era.source.path.clone(),
-            node_config.era.source.url.clone(),
+            path,
+            url,
             || node_config.chain.chain().kind().default_era_host(),
-            || node_config.datadir().data_dir().join("era").into(),
+            || {
+                era_config
+                    .folder
+                    .clone()
+                    .unwrap_or_else(|| node_config.datadir().data_dir().join("era"))
+                    .into()
+            },
         )
     }
 
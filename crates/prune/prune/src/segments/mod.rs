@@ -5,7 +5,8 @@ mod user;
 use crate::{PruneLimiter, PrunerError};
 use alloy_primitives::{BlockNumber, TxNumber};
 use reth_provider::{
-    errors::provider::ProviderResult, BlockReader, PruneCheckpointWriter, StaticFileProviderFactory,
+    errors::provider::ProviderResult, BlockReader, ChainStateBlockReader, PruneCheckpointWriter,
+    StaticFileProviderFactory,
 };
 use reth_prune_types::{
     PruneCheckpoint, PruneMode, PruneProgress, PrunePurpose, PruneSegment, SegmentOutput,
@@ -15,7 +16,7 @@ use reth_stages_types::StageId;
 use reth_static_file_types::StaticFileSegment;
 pub use set::SegmentSet;
 use std::{fmt::Debug, ops::RangeInclusive};
-use tracing::error;
+use tracing::{debug, error};
 pub use user::{
     AccountHistory, Bodies, Receipts as UserReceipts, ReceiptsByLogs, SenderRecovery,
     StorageHistory, TransactionLookup,
@@ -71,6 +72,35 @@ where
     })
 }
 
+/// Lowers the target of `input` to the last block archived outside the node, see
+/// [`ChainStateBlockReader::last_archived_block_number`], so that history expiry only drops blocks
+/// with a durable copy elsewhere. Without an archived block, `input` is left to the prune mode.
+///
+/// Returns `None` if every archived block has already been pruned.
+pub(crate) fn limit_to_archived<Provider>(
+    provider: &Provider,
+    input: PruneInput,
+) -> ProviderResult<Option<PruneInput>>
+where
+    Provider: ChainStateBlockReader,
+{
+    let Some(archived) = provider.last_archived_block_number()? else { return Ok(Some(input)) };
+    if input.to_block <= archived {
+        return Ok(Some(input))
+    }
+
+    if input
+        .previous_checkpoint
+        .and_then(|checkpoint| checkpoint.block_number)
+        .is_some_and(|pruned| pruned >= archived)
+    {
+        return Ok(None)
+    }
+
+    debug!(target: "pruner", to_block = input.to_block, archived, "History expiry limited to archived blocks");
+    Ok(Some(PruneInput { to_block: archived, ..input }))
+}
+
 /// Deletes ALL static file jars for a given segment.
 ///
 /// This is used for `PruneMode::Full` where all data should be removed, including the highest jar.
@@ -3,7 +3,9 @@ use crate::{
     PrunerError,
 };
 use alloy_primitives::BlockNumber;
-use reth_provider::{BlockReader, PruneCheckpointReader, StaticFileProviderFactory};
+use reth_provider::{
+    BlockReader, ChainStateBlockReader, PruneCheckpointReader, StaticFileProviderFactory,
+};
 use reth_prune_types::{
     PruneInterruptReason, PruneMode, PrunePurpose, PruneSegment, SegmentOutput,
     SegmentOutputCheckpoint,
@@ -13,7 +15,9 @@ use tracing::{debug, instrument};
 
 /// Segment responsible for pruning transactions in static files.
 ///
-/// This segment is controlled by the `bodies_history` configuration.
+/// This segment is controlled by the `bodies_history` configuration. Once the node has archived
+/// history, see [`ChainStateBlockReader::last_archived_block_number`], no block past the archive
+/// is pruned.
 #[derive(Debug)]
 pub struct Bodies {
     mode: PruneMode,
@@ -89,7 +93,8 @@ impl Bodies {
 
 impl<Provider> Segment<Provider> for Bodies
 where
-    Provider: StaticFileProviderFactory + BlockReader + PruneCheckpointReader,
+    Provider:
+        StaticFileProviderFactory + BlockReader + PruneCheckpointReader + ChainStateBlockReader,
 {
     fn segment(&self) -> PruneSegment {
         PruneSegment::Bodies
@@ -123,6 +128,9 @@ where
 
         // Use the coordinated to_block instead of input.to_block
         let adjusted_input = PruneInput { to_block, ..input };
+        let Some(adjusted_input) = segments::limit_to_archived(provider, adjusted_input)? else {
+            return Ok(SegmentOutput::done())
+        };
         segments::prune_static_files(provider, adjusted_input, StaticFileSegment::Transactions)
     }
 }
@@ -135,8 +143,8 @@ mod tests {
     use reth_exex_types::FinishedExExHeight;
     use reth_provider::{
         test_utils::{create_test_provider_factory, MockNodeTypesWithDB},
-        DBProvider, DatabaseProviderFactory, ProviderFactory, PruneCheckpointWriter,
-        StaticFileWriter,
+        ChainStateBlockWriter, DBProvider, DatabaseProviderFactory, ProviderFactory,
+        PruneCheckpointWriter, StaticFileWriter,
     };
     use reth_prune_types::{PruneMode, PruneProgress, PruneSegment};
     use reth_static_file_types::{
@@ -371,6 +379,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn archived_block_limits_pruning() {
+        let factory = create_test_provider_factory();
+        let tip = 1_499_999;
+        setup_static_file_jars(&factory, tip);
+
+        // Jars 0 and 1 (0-999_999) are below the target, but only part of jar 1 is archived.
+        let provider = factory.database_provider_rw().unwrap();
+        provider.save_archived_block_number(600_000).unwrap();
+        provider.commit().unwrap();
+
+        let bodies = Bodies::new(PruneMode::Before(1_000_000), None);
+        let segments: Vec<Box<dyn Segment<_>>> = vec![Box::new(bodies)];
+
+        let (_, finished_exex_height_rx) = tokio::sync::watch::channel(FinishedExExHeight::NoExExs);
+
+        let mut pruner = Pruner::new_with_factory(
+            factory.clone(),
+            segments,
+            5,
+            10000,
+            None,
+            finished_exex_height_rx,
+        );
+
+        let result = pruner.run(tip).expect("pruner run");
+
+        let (_, output) = &result.segments[0];
+        assert_eq!(output.pruned, 1000);
+        assert_eq!(output.checkpoint.as_ref().and_then(|cp| cp.block_number), Some(499_999));
+        assert_eq!(
+            factory.static_file_provider().get_lowest_range_end(StaticFileSegment::Transactions),
+            Some(999_999)
+        );
+    }
+
     #[test]
     fn min_block_updated_on_sync() {
         // Regression test: update_index must update min_block to prevent stale values
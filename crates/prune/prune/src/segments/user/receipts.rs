@@ -1,16 +1,21 @@
 use crate::{
-    segments::{PruneInput, Segment},
+    segments::{self, PruneInput, Segment},
     PrunerError,
 };
 use reth_db_api::{table::Value, transaction::DbTxMut};
 use reth_primitives_traits::NodePrimitives;
 use reth_provider::{
-    errors::provider::ProviderResult, BlockReader, DBProvider, NodePrimitivesProvider,
-    PruneCheckpointWriter, StaticFileProviderFactory, StorageSettingsCache, TransactionsProvider,
+    errors::provider::ProviderResult, BlockReader, ChainStateBlockReader, DBProvider,
+    NodePrimitivesProvider, PruneCheckpointWriter, StaticFileProviderFactory, StorageSettingsCache,
+    TransactionsProvider,
 };
 use reth_prune_types::{PruneCheckpoint, PruneMode, PrunePurpose, PruneSegment, SegmentOutput};
 use tracing::instrument;
 
+/// Segment responsible for pruning receipts according to the user-configured settings.
+///
+/// Once the node has archived history, see [`ChainStateBlockReader::last_archived_block_number`],
+/// no block past the archive is pruned.
 #[derive(Debug)]
 pub struct Receipts {
     mode: PruneMode,
@@ -28,6 +33,7 @@ where
         + PruneCheckpointWriter
         + TransactionsProvider
         + BlockReader
+        + ChainStateBlockReader
         + StorageSettingsCache
         + StaticFileProviderFactory
         + NodePrimitivesProvider<Primitives: NodePrimitives<Receipt: Value>>,
@@ -51,7 +57,10 @@ where
         ret(level = "trace")
     )]
     fn prune(&self, provider: &Provider, input: PruneInput) -> Result<SegmentOutput, PrunerError> {
-        crate::segments::receipts::prune(provider, input)
+        let Some(input) = segments::limit_to_archived(provider, input)? else {
+            return Ok(SegmentOutput::done())
+        };
+        segments::receipts::prune(provider, input)
     }
 
     fn save_checkpoint(
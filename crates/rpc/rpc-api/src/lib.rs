@@ -25,6 +25,7 @@ mod net;
 mod otterscan;
 mod reth;
 mod reth_engine;
+mod reth_era;
 mod rpc;
 mod testing;
 mod trace;
@@ -33,6 +34,7 @@ mod validation;
 mod web3;
 
 pub use reth::RethJitAction;
+pub use reth_era::{EraExportFormat, EraExportRequest, EraImportRequest, EraJob, EraStatus};
 pub use testing::{TestingBuildBlockRequestV1, TESTING_BUILD_BLOCK_V1, TESTING_COMMIT_BLOCK_V1};
 
 /// re-export of all server traits
@@ -52,6 +54,7 @@ pub mod servers {
         otterscan::OtterscanServer,
         reth::RethApiServer,
         reth_engine::{RethEngineApiServer, RethNewPayloadInput, RethPayloadStatus},
+        reth_era::RethEraApiServer,
         rpc::RpcApiServer,
         testing::TestingApiServer,
         trace::TraceApiServer,
@@ -84,6 +87,7 @@ pub mod clients {
         otterscan::OtterscanClient,
         reth::RethApiClient,
         reth_engine::RethEngineApiClient,
+        reth_era::RethEraApiClient,
         rpc::RpcApiClient,
         testing::TestingApiClient,
         trace::TraceApiClient,
@@ -0,0 +1,101 @@
+//! Reth-specific ERA history archiving API.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Reth API namespace for archiving block history into ERA files and importing it back.
+///
+/// These are admin methods: they read and write files on the node's host, so they should only be
+/// exposed to trusted callers.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "reth"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "reth"))]
+pub trait RethEraApi {
+    /// Returns the state of the ERA import or export running, or the one that ran last.
+    #[method(name = "eraStatus")]
+    async fn reth_era_status(&self) -> RpcResult<EraStatus>;
+
+    /// Starts exporting stored blocks into ERA files in the background.
+    ///
+    /// Fails if an import or export is already running. Follow its progress with
+    /// `reth_eraStatus`.
+    #[method(name = "startEraExport")]
+    async fn reth_start_era_export(&self, request: EraExportRequest) -> RpcResult<()>;
+
+    /// Starts importing the ERA files of a directory on the node's host in the background.
+    ///
+    /// Fails if an import or export is already running. Follow its progress with
+    /// `reth_eraStatus`.
+    #[method(name = "startEraImport")]
+    async fn reth_start_era_import(&self, request: EraImportRequest) -> RpcResult<()>;
+}
+
+/// Parameters of `reth_startEraExport`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EraExportRequest {
+    /// Directory on the node's host to write the files to.
+    pub dir: PathBuf,
+    /// First block to export.
+    #[serde(with = "alloy_serde::quantity")]
+    pub first_block: u64,
+    /// Last block to export.
+    #[serde(with = "alloy_serde::quantity")]
+    pub last_block: u64,
+    /// Format of the files to write, `era1` unless given.
+    #[serde(default)]
+    pub format: EraExportFormat,
+}
+
+/// ERA file formats `reth_startEraExport` can write.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EraExportFormat {
+    /// Execution blocks written in the `.era1` format.
+    #[default]
+    Era1,
+    /// Execution blocks written in the `.ere` format.
+    Ere,
+    /// Pre-merge execution blocks with accumulator proofs, written in the `.e2hs` format.
+    E2hs,
+}
+
+/// Parameters of `reth_startEraImport`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EraImportRequest {
+    /// Directory on the node's host holding the ERA files, all of a single format.
+    pub dir: PathBuf,
+    /// Last block to import; every file in the directory is imported unless given.
+    #[serde(default, with = "alloy_serde::quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<u64>,
+}
+
+/// State of the ERA import or export running, or the one that ran last, as returned by
+/// `reth_eraStatus`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EraStatus {
+    /// The job running or that ran last, `None` if none was started.
+    pub job: Option<EraJob>,
+    /// Whether [`EraStatus::job`] is still running.
+    pub running: bool,
+    /// Last block an import has committed so far. Not tracked for exports.
+    #[serde(default, with = "alloy_serde::quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub last_block: Option<u64>,
+    /// Files imported so far, or written by a finished export.
+    pub files: Vec<PathBuf>,
+    /// Why the job failed, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Kind of job reported by [`EraStatus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EraJob {
+    /// An import started with `reth_startEraImport`.
+    Import,
+    /// An export started with `reth_startEraExport`.
+    Export,
+}
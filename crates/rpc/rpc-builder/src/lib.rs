@@ -1036,6 +1036,7 @@ where
                         // nodebuilder rpc addon stack
                         RethRpcModule::Flashbots |
                         RethRpcModule::Testing |
+                        RethRpcModule::RethEra |
                         RethRpcModule::Other(_) => Default::default(),
                     })
                     .clone()
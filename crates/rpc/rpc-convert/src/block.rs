@@ -0,0 +1,293 @@
+//! Compatibility functions for rpc `Block` type.
+
+use crate::{RpcBlock, RpcConvert, RpcTransaction};
+use alloy_consensus::{
+    transaction::{Recovered, TxHashRef},
+    Header, Sealable, TxEnvelope,
+};
+use alloy_network::{
+    primitives::{BlockResponse, HeaderResponse},
+    AnyNetwork, AnyRpcHeader, Ethereum, Network,
+};
+use alloy_primitives::B256;
+use alloy_rlp::Encodable;
+use alloy_rpc_types_eth::{Block, BlockTransactions, BlockTransactionsKind, TransactionInfo};
+use core::error;
+use reth_primitives_traits::{
+    AlloyBlockHeader, BlockBody, BlockTy, HeaderTy, RecoveredBlock, SealedHeader, TxTy,
+};
+
+/// Converts `block` into an RPC block, see [`RpcConvert::convert_block`].
+pub(crate) fn convert_block<C>(
+    converter: &C,
+    block: &RecoveredBlock<BlockTy<C::Primitives>>,
+    kind: BlockTransactionsKind,
+) -> Result<RpcBlock<C::Network>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    let transactions = match kind {
+        BlockTransactionsKind::Hashes => transaction_hashes(block),
+        BlockTransactionsKind::Full => {
+            BlockTransactions::Full(converter.convert_transactions(block)?)
+        }
+    };
+
+    block_with_transactions(converter, block, transactions)
+}
+
+/// Converts `block` into an RPC block, moving its transactions, see
+/// [`RpcConvert::convert_block_owned`].
+pub(crate) fn convert_block_owned<C>(
+    converter: &C,
+    block: RecoveredBlock<BlockTy<C::Primitives>>,
+    kind: BlockTransactionsKind,
+) -> Result<RpcBlock<C::Network>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    if kind.is_hashes() {
+        return convert_block(converter, &block, kind)
+    }
+
+    let mut rpc_block =
+        block_with_transactions(converter, &block, BlockTransactions::Full(Vec::new()))?;
+    let block_info = block_tx_info(&block);
+    let count = block.body().transaction_count();
+    rpc_block.transactions = BlockTransactions::Full(fill_transactions(
+        converter,
+        block_info,
+        count,
+        block.into_transactions_recovered(),
+    )?);
+
+    Ok(rpc_block)
+}
+
+/// Converts the transactions of `block` into RPC transactions, see
+/// [`RpcConvert::convert_transactions`].
+pub(crate) fn convert_transactions<C>(
+    converter: &C,
+    block: &RecoveredBlock<BlockTy<C::Primitives>>,
+) -> Result<Vec<RpcTransaction<C::Network>>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    fill_transactions(
+        converter,
+        block_tx_info(block),
+        block.body().transaction_count(),
+        block.transactions_recovered().map(|tx| tx.cloned()),
+    )
+}
+
+/// Converts the header, uncles and withdrawals of `block` into an RPC block with `transactions`.
+fn block_with_transactions<C>(
+    converter: &C,
+    block: &RecoveredBlock<BlockTy<C::Primitives>>,
+    transactions: BlockTransactions<RpcTransaction<C::Network>>,
+) -> Result<RpcBlock<C::Network>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    let body = block.body();
+    let uncles = body
+        .ommers()
+        .map(|ommers| ommers.iter().map(|ommer| ommer.hash_slow()).collect())
+        .unwrap_or_default();
+    let withdrawals = body.withdrawals().cloned();
+    let header = converter.convert_header(block.clone_sealed_header(), block.rlp_length())?;
+
+    Ok(Block { header, uncles, transactions, withdrawals })
+}
+
+/// Returns the hashes of the transactions of `block`.
+fn transaction_hashes<B: reth_primitives_traits::Block, T>(
+    block: &RecoveredBlock<B>,
+) -> BlockTransactions<T> {
+    BlockTransactions::Hashes(block.body().transactions().iter().map(|tx| *tx.tx_hash()).collect())
+}
+
+/// Returns the [`TransactionInfo`] shared by all transactions of `block`.
+///
+/// The block hash, number, timestamp and base fee are read once for the whole block.
+fn block_tx_info<B: reth_primitives_traits::Block>(block: &RecoveredBlock<B>) -> TransactionInfo {
+    TransactionInfo {
+        block_hash: Some(block.hash()),
+        block_number: Some(block.number()),
+        block_timestamp: Some(block.timestamp()),
+        base_fee: block.base_fee_per_gas(),
+        ..Default::default()
+    }
+}
+
+/// Fills `count` block transactions `txs` in block order, with `block_info` and their hash and
+/// index.
+fn fill_transactions<C>(
+    converter: &C,
+    block_info: TransactionInfo,
+    count: usize,
+    txs: impl Iterator<Item = Recovered<TxTy<C::Primitives>>>,
+) -> Result<Vec<RpcTransaction<C::Network>>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    let mut transactions = Vec::with_capacity(count);
+    for (index, tx) in txs.enumerate() {
+        let tx_info =
+            TransactionInfo { hash: Some(*tx.tx_hash()), index: Some(index as u64), ..block_info };
+        transactions.push(converter.fill(tx, tx_info)?);
+    }
+    Ok(transactions)
+}
+
+/// Converts `header` into an RPC uncle block, see [`RpcConvert::convert_uncle`].
+pub(crate) fn convert_uncle<C>(
+    converter: &C,
+    header: HeaderTy<C::Primitives>,
+) -> Result<RpcBlock<C::Network>, C::Error>
+where
+    C: RpcConvert + ?Sized,
+{
+    let block = alloy_consensus::Block::<TxEnvelope, _>::uncle(header);
+    let size = block.length();
+    let header = converter.convert_header(SealedHeader::new_unhashed(block.header), size)?;
+
+    Ok(Block { uncles: vec![], header, transactions: BlockTransactions::Uncle, withdrawals: None })
+}
+
+/// Converts an RPC uncle block of network `N`, as returned by `eth_getUncle*`, back into its
+/// header.
+///
+/// Uncle blocks carry no transactions, so a block response with any is rejected. Once
+/// serialized, uncle blocks can't be told apart from blocks without transactions, which are
+/// accepted as well.
+pub fn uncle_from_response<N, H>(block: N::BlockResponse) -> Result<H, HeaderResponseError>
+where
+    N: Network,
+    H: TryFromHeaderResponse<N, Error = HeaderResponseError>,
+{
+    let header = block.header();
+    if !block.transactions().is_empty() {
+        return Err(HeaderResponseError::NotAnUncle { hash: header.hash() })
+    }
+    H::from_header_response(header.clone())
+}
+
+/// Converts a header response of the RPC API of network `N` into a consensus header.
+///
+/// This is the reverse of [`FromConsensusHeader`](crate::FromConsensusHeader), for consumers that
+/// backfill headers from remote nodes. The hash of the response is checked against the converted
+/// header, so a header can't be sealed with the hash of another.
+pub trait TryFromHeaderResponse<N: Network>: Sized {
+    /// An associated conversion error.
+    type Error: error::Error;
+
+    /// Converts `header`, a header response of network `N`, into `Self`.
+    fn from_header_response(header: N::HeaderResponse) -> Result<Self, Self::Error>;
+}
+
+impl TryFromHeaderResponse<Ethereum> for Header {
+    type Error = HeaderResponseError;
+
+    fn from_header_response(header: alloy_rpc_types_eth::Header) -> Result<Self, Self::Error> {
+        ensure_hash(header.inner, header.hash)
+    }
+}
+
+impl TryFromHeaderResponse<AnyNetwork> for Header {
+    type Error = HeaderResponseError;
+
+    fn from_header_response(header: AnyRpcHeader) -> Result<Self, Self::Error> {
+        let hash = header.hash;
+        let header = header
+            .inner
+            .try_into_header()
+            .map_err(|err| HeaderResponseError::Incomplete { hash, reason: err.to_string() })?;
+        ensure_hash(header, hash)
+    }
+}
+
+impl<N: Network> TryFromHeaderResponse<N> for SealedHeader<Header>
+where
+    Header: TryFromHeaderResponse<N>,
+{
+    type Error = <Header as TryFromHeaderResponse<N>>::Error;
+
+    fn from_header_response(header: N::HeaderResponse) -> Result<Self, Self::Error> {
+        let hash = header.hash();
+        Ok(Self::new(Header::from_header_response(header)?, hash))
+    }
+}
+
+/// Conversion of a header RPC response into a consensus header failed.
+#[derive(Debug, thiserror::Error)]
+pub enum HeaderResponseError {
+    /// The response lacks fields the consensus header requires.
+    #[error("header {hash} is incomplete: {reason}")]
+    Incomplete {
+        /// Hash of the header.
+        hash: B256,
+        /// Which fields are missing.
+        reason: String,
+    },
+
+    /// The block response has transactions, so it can't be an uncle.
+    #[error("block {hash} has transactions, so it is not an uncle")]
+    NotAnUncle {
+        /// Hash of the block.
+        hash: B256,
+    },
+
+    /// The converted header doesn't hash to the hash of the response.
+    #[error("header {hash} converts into a header with hash {computed}")]
+    HashMismatch {
+        /// Hash of the header, as in the response.
+        hash: B256,
+        /// Hash of the converted header.
+        computed: B256,
+    },
+}
+
+/// Returns `header` if it hashes to `hash`.
+fn ensure_hash(header: Header, hash: B256) -> Result<Header, HeaderResponseError> {
+    let computed = header.hash_slow();
+    if computed != hash {
+        return Err(HeaderResponseError::HashMismatch { hash, computed })
+    }
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_the_hash_of_header_responses() {
+        let header = Header { number: 7, ..Default::default() };
+        let response = alloy_rpc_types_eth::Header::new(header.clone());
+
+        let sealed = <SealedHeader as TryFromHeaderResponse<Ethereum>>::from_header_response(
+            response.clone(),
+        )
+        .unwrap();
+        assert_eq!(sealed.hash(), header.hash_slow());
+        assert_eq!(sealed.header(), &header);
+
+        let forged = alloy_rpc_types_eth::Header { hash: B256::repeat_byte(1), ..response };
+        let err =
+            <Header as TryFromHeaderResponse<Ethereum>>::from_header_response(forged).unwrap_err();
+        assert!(matches!(err, HeaderResponseError::HashMismatch { .. }), "{err}");
+    }
+
+    #[test]
+    fn parses_uncle_responses_back() {
+        let header = Header { number: 7, ..Default::default() };
+        let uncle = Block::<alloy_rpc_types_eth::Transaction>::uncle_from_header(header.clone());
+        assert_eq!(uncle_from_response::<Ethereum, Header>(uncle.clone()).unwrap(), header);
+
+        let block = Block { transactions: BlockTransactions::Hashes(vec![B256::ZERO]), ..uncle };
+        let err = uncle_from_response::<Ethereum, Header>(block).unwrap_err();
+        assert!(matches!(err, HeaderResponseError::NotAnUncle { .. }), "{err}");
+    }
+}
@@ -0,0 +1,118 @@
+//! Assembly of `eth_feeHistory` responses.
+
+use alloy_rpc_types_eth::FeeHistory;
+
+/// Builds a [`FeeHistory`] response from the fee data of a range of blocks, oldest first.
+///
+/// Besides one entry per block, the base fee and blob base fee lists end with the fees of the
+/// block after the newest one, which are passed to [`Self::build`].
+#[derive(Debug)]
+pub struct FeeHistoryBuilder {
+    base_fee_per_gas: Vec<u128>,
+    gas_used_ratio: Vec<f64>,
+    base_fee_per_blob_gas: Vec<u128>,
+    blob_gas_used_ratio: Vec<f64>,
+    reward: Option<Vec<Vec<u128>>>,
+    oldest_block: u64,
+}
+
+impl FeeHistoryBuilder {
+    /// Creates a builder for `block_count` blocks starting at `oldest_block`.
+    ///
+    /// The response has rewards only if `with_rewards` is set, i.e. if reward percentiles were
+    /// requested.
+    pub fn new(oldest_block: u64, block_count: u64, with_rewards: bool) -> Self {
+        let block_count = block_count as usize;
+        Self {
+            base_fee_per_gas: Vec::with_capacity(block_count + 1),
+            gas_used_ratio: Vec::with_capacity(block_count),
+            base_fee_per_blob_gas: Vec::with_capacity(block_count + 1),
+            blob_gas_used_ratio: Vec::with_capacity(block_count),
+            reward: with_rewards.then(|| Vec::with_capacity(block_count)),
+            oldest_block,
+        }
+    }
+
+    /// Adds the fees and gas usage of the next block of the range.
+    pub fn push_block(
+        &mut self,
+        base_fee_per_gas: u128,
+        gas_used_ratio: f64,
+        base_fee_per_blob_gas: u128,
+        blob_gas_used_ratio: f64,
+    ) {
+        self.base_fee_per_gas.push(base_fee_per_gas);
+        self.gas_used_ratio.push(gas_used_ratio);
+        self.base_fee_per_blob_gas.push(base_fee_per_blob_gas);
+        self.blob_gas_used_ratio.push(blob_gas_used_ratio);
+    }
+
+    /// Adds the rewards at the requested percentiles of the next block of the range.
+    ///
+    /// Ignored if the builder was created without rewards.
+    pub fn push_rewards(&mut self, rewards: Vec<u128>) {
+        if let Some(reward) = &mut self.reward {
+            reward.push(rewards);
+        }
+    }
+
+    /// Finishes the response with the base fee and blob base fee of the block after the range.
+    pub fn build(
+        mut self,
+        next_base_fee_per_gas: u128,
+        next_base_fee_per_blob_gas: u128,
+    ) -> FeeHistory {
+        self.base_fee_per_gas.push(next_base_fee_per_gas);
+        self.base_fee_per_blob_gas.push(next_base_fee_per_blob_gas);
+        FeeHistory {
+            base_fee_per_gas: self.base_fee_per_gas,
+            gas_used_ratio: self.gas_used_ratio,
+            base_fee_per_blob_gas: self.base_fee_per_blob_gas,
+            blob_gas_used_ratio: self.blob_gas_used_ratio,
+            oldest_block: self.oldest_block,
+            reward: self.reward,
+        }
+    }
+}
+
+/// Returns the share of `gas_limit` a block used.
+pub const fn gas_used_ratio(gas_used: u64, gas_limit: u64) -> f64 {
+    gas_used as f64 / gas_limit as f64
+}
+
+/// Returns whether `percentiles` are valid reward percentiles: between 0 and 100 and
+/// monotonically increasing.
+pub fn valid_reward_percentiles(percentiles: &[f64]) -> bool {
+    percentiles.iter().all(|p| (0.0..=100.0).contains(p)) &&
+        percentiles.windows(2).all(|w| w[0] <= w[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_the_fees_of_the_next_block() {
+        let mut builder = FeeHistoryBuilder::new(10, 2, true);
+        builder.push_block(7, gas_used_ratio(15, 30), 1, 0.0);
+        builder.push_rewards(vec![1, 2]);
+        builder.push_block(8, gas_used_ratio(30, 30), 2, 1.0);
+        builder.push_rewards(vec![3, 4]);
+
+        let history = builder.build(9, 3);
+        assert_eq!(history.oldest_block, 10);
+        assert_eq!(history.base_fee_per_gas, vec![7, 8, 9]);
+        assert_eq!(history.gas_used_ratio, vec![0.5, 1.0]);
+        assert_eq!(history.base_fee_per_blob_gas, vec![1, 2, 3]);
+        assert_eq!(history.reward, Some(vec![vec![1, 2], vec![3, 4]]));
+
+        let mut builder = FeeHistoryBuilder::new(10, 1, false);
+        builder.push_block(7, 0.5, 1, 0.0);
+        builder.push_rewards(vec![1]);
+        assert_eq!(builder.build(8, 2).reward, None);
+
+        assert!(valid_reward_percentiles(&[0.0, 50.0, 50.0, 100.0]));
+        assert!(!valid_reward_percentiles(&[50.0, 10.0]));
+        assert!(!valid_reward_percentiles(&[100.5]));
+    }
+}
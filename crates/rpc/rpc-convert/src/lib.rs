@@ -10,11 +10,29 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod block;
+mod fees;
+mod mapper;
+mod overrides;
+mod receipt;
+mod request;
 mod rpc;
 pub mod transaction;
 
+pub use block::{uncle_from_response, HeaderResponseError, TryFromHeaderResponse};
+pub use fees::{gas_used_ratio, valid_reward_percentiles, FeeHistoryBuilder};
+pub use mapper::{MapWith, TxInfoMapperExt};
+pub use overrides::apply_evm_overrides;
+pub use receipt::{block_receipt_inputs, from_rpc_log, into_rpc_log};
+pub use request::{
+    build_typed_transaction, fill_transaction_request, signing_preimage, FillTransactionError,
+    FilledTransactionRequest, TransactionDefaults,
+};
 pub use rpc::*;
-pub use transaction::{RpcConvert, RpcConverter, TransactionConversionError};
+pub use transaction::{
+    ReceiptMapper, RpcConvert, RpcConverter, TransactionConversionError, TransactionResponseError,
+    TryFromTransactionResponse,
+};
 
 pub use alloy_evm::rpc::{CallFees, CallFeesError, EthTxEnvError, TryIntoTxEnv};
 
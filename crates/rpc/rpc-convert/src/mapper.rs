@@ -0,0 +1,71 @@
+//! Combinators layering [`TxInfoMapper`]s.
+
+use alloy_rpc_types_eth::TransactionInfo;
+use reth_rpc_traits::TxInfoMapper;
+use std::fmt;
+
+/// Extension trait for [`TxInfoMapper`], so networks can layer behavior on top of an existing
+/// mapper instead of reimplementing it.
+///
+/// `()` is the identity mapper, passing [`TransactionInfo`] through, so it can be a base layer
+/// too.
+pub trait TxInfoMapperExt<T>: TxInfoMapper<T> + Sized {
+    /// Maps the output of `self` with `f`, which is also given the transaction.
+    ///
+    /// Errors of `self` are converted into errors of `f`.
+    fn map_with<F, Out, E>(self, f: F) -> MapWith<Self, F>
+    where
+        F: Fn(&T, Self::Out) -> Result<Out, E>,
+        E: From<Self::Err>,
+    {
+        MapWith { mapper: self, f }
+    }
+}
+
+impl<T, M: TxInfoMapper<T>> TxInfoMapperExt<T> for M {}
+
+/// A [`TxInfoMapper`] mapping the output of another one, see [`TxInfoMapperExt::map_with`].
+#[derive(Clone)]
+pub struct MapWith<M, F> {
+    mapper: M,
+    f: F,
+}
+
+impl<M: fmt::Debug, F> fmt::Debug for MapWith<M, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapWith").field("mapper", &self.mapper).finish_non_exhaustive()
+    }
+}
+
+impl<T, M, F, Out, E> TxInfoMapper<T> for MapWith<M, F>
+where
+    M: TxInfoMapper<T>,
+    F: Fn(&T, M::Out) -> Result<Out, E>,
+    E: From<M::Err>,
+{
+    type Out = Out;
+    type Err = E;
+
+    fn try_map(&self, tx: &T, tx_info: TransactionInfo) -> Result<Self::Out, Self::Err> {
+        let out = self.mapper.try_map(tx, tx_info)?;
+        (self.f)(tx, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    #[test]
+    fn map_with_layers_on_the_base_mapper() {
+        let mapper =
+            ().map_with(|tx: &u64, info: TransactionInfo| Ok::<_, Infallible>((info.index, *tx)))
+                .map_with(|tx: &u64, (index, value): (Option<u64>, u64)| {
+                    Ok::<_, Infallible>(index.map(|index| index + *tx + value))
+                });
+
+        let info = TransactionInfo { index: Some(2), ..Default::default() };
+        assert_eq!(mapper.try_map(&3, info), Ok(Some(8)));
+    }
+}
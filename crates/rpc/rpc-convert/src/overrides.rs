@@ -0,0 +1,28 @@
+//! Conversion of the overrides of RPC calls into changes to the EVM's block and state.
+
+use alloy_evm::overrides::{
+    apply_block_overrides, apply_state_overrides, OverrideBlockHashes, StateOverrideError,
+};
+use alloy_rpc_types_eth::state::EvmOverrides;
+use revm::{context::BlockEnv, Database, DatabaseCommit};
+
+/// Applies the block and state `overrides` of a call to `block_env` and `db`.
+///
+/// Block overrides go first, so limits and fees derived from the block afterwards, like the gas
+/// limit of the call or its transaction environment, see the overridden values.
+pub fn apply_evm_overrides<DB>(
+    overrides: EvmOverrides,
+    db: &mut DB,
+    block_env: &mut BlockEnv,
+) -> Result<(), StateOverrideError<DB::Error>>
+where
+    DB: Database + DatabaseCommit + OverrideBlockHashes,
+{
+    if let Some(block_overrides) = overrides.block {
+        apply_block_overrides(*block_overrides, db, block_env);
+    }
+    if let Some(state_overrides) = overrides.state {
+        apply_state_overrides(state_overrides, db)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Compatibility functions for rpc `Receipt` type.
+
+use crate::transaction::ConvertReceiptInput;
+use alloy_consensus::{transaction::TxHashRef, TxReceipt};
+use alloy_primitives::Log;
+use alloy_rpc_types_eth::TransactionInfo;
+use reth_primitives_traits::{
+    AlloyBlockHeader, BlockTy, NodePrimitives, RecoveredBlock, TransactionMeta,
+};
+
+/// Pairs the transactions of `block` with their `receipts`, in block order, into inputs of
+/// [`RpcConvert::convert_receipts`](crate::RpcConvert::convert_receipts).
+///
+/// Each input carries the gas its transaction used and the index of its first log within the
+/// block, worked out from the receipts before it, along with the [`TransactionMeta`] the RPC
+/// receipt takes its transaction index, effective gas price and blob gas price from. Inputs can
+/// be filtered afterwards without affecting the others.
+pub fn block_receipt_inputs<'a, N, R>(
+    block: &'a RecoveredBlock<BlockTy<N>>,
+    receipts: R,
+) -> impl Iterator<Item = ConvertReceiptInput<'a, N>> + 'a
+where
+    N: NodePrimitives,
+    R: IntoIterator<Item = N::Receipt>,
+    R::IntoIter: 'a,
+{
+    let block_hash = block.hash();
+    let block_number = block.number();
+    let base_fee = block.base_fee_per_gas();
+    let excess_blob_gas = block.excess_blob_gas();
+    let timestamp = block.timestamp();
+    let mut gas_used = 0;
+    let mut next_log_index = 0;
+
+    block.transactions_recovered().zip(receipts).enumerate().map(move |(index, (tx, receipt))| {
+        let meta = TransactionMeta {
+            tx_hash: *tx.tx_hash(),
+            index: index as u64,
+            block_hash,
+            block_number,
+            base_fee,
+            excess_blob_gas,
+            timestamp,
+        };
+
+        let cumulative_gas_used = receipt.cumulative_gas_used();
+        let logs_len = receipt.logs().len();
+        let input = ConvertReceiptInput {
+            tx,
+            gas_used: cumulative_gas_used - gas_used,
+            next_log_index,
+            meta,
+            receipt,
+        };
+
+        gas_used = cumulative_gas_used;
+        next_log_index += logs_len;
+
+        input
+    })
+}
+
+/// Converts `log`, emitted by the transaction of `tx_info`, into an RPC log at `log_index` within
+/// its block.
+///
+/// `removed` marks logs of blocks that were reorged out. See [`from_rpc_log`] for the reverse.
+pub const fn into_rpc_log(
+    log: Log,
+    tx_info: TransactionInfo,
+    log_index: u64,
+    removed: bool,
+) -> alloy_rpc_types_eth::Log {
+    alloy_rpc_types_eth::Log {
+        inner: log,
+        block_hash: tx_info.block_hash,
+        block_number: tx_info.block_number,
+        block_timestamp: tx_info.block_timestamp,
+        transaction_hash: tx_info.hash,
+        transaction_index: tx_info.index,
+        log_index: Some(log_index),
+        removed,
+    }
+}
+
+/// Converts an RPC `log` back into the log emitted by its transaction, dropping the block and
+/// transaction it was attached to.
+pub fn from_rpc_log(log: alloy_rpc_types_eth::Log) -> Log {
+    log.inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+    use alloy_primitives::{Address, LogData, Signature, B256};
+    use reth_ethereum_primitives::{
+        Block, BlockBody, EthPrimitives, Receipt, Transaction, TransactionSigned,
+    };
+
+    #[test]
+    fn works_out_receipt_inputs_of_a_block() {
+        let tx = TransactionSigned::new_unhashed(
+            Transaction::Legacy(Default::default()),
+            Signature::test_signature(),
+        );
+        let block = RecoveredBlock::new_unhashed(
+            Block {
+                header: Header { number: 7, base_fee_per_gas: Some(3), ..Default::default() },
+                body: BlockBody { transactions: vec![tx; 3], ..Default::default() },
+            },
+            vec![Address::ZERO; 3],
+        );
+        let log = Log { address: Address::repeat_byte(1), data: LogData::empty() };
+        let receipts =
+            [(21_000, 2), (50_000, 0), (71_000, 1)].map(|(cumulative_gas_used, logs)| Receipt {
+                cumulative_gas_used,
+                logs: vec![log.clone(); logs],
+                ..Default::default()
+            });
+
+        let inputs = block_receipt_inputs::<EthPrimitives, _>(&block, receipts).collect::<Vec<_>>();
+        let gas_used = inputs.iter().map(|input| input.gas_used).collect::<Vec<_>>();
+        assert_eq!(gas_used, [21_000, 29_000, 21_000]);
+        let log_indexes = inputs.iter().map(|input| input.next_log_index).collect::<Vec<_>>();
+        assert_eq!(log_indexes, [0, 2, 2]);
+        for (index, input) in inputs.iter().enumerate() {
+            assert_eq!(input.meta.index, index as u64);
+            assert_eq!(input.meta.block_hash, block.hash());
+            assert_eq!(input.meta.block_number, 7);
+            assert_eq!(input.meta.base_fee, Some(3));
+        }
+    }
+
+    #[test]
+    fn rpc_logs_round_trip() {
+        let log = Log { address: Address::repeat_byte(1), data: LogData::empty() };
+        let tx_info = TransactionInfo {
+            hash: Some(B256::repeat_byte(2)),
+            index: Some(3),
+            block_hash: Some(B256::repeat_byte(4)),
+            block_number: Some(5),
+            block_timestamp: Some(6),
+            base_fee: None,
+        };
+
+        let rpc_log = into_rpc_log(log.clone(), tx_info, 7, false);
+        assert_eq!(rpc_log.transaction_hash, tx_info.hash);
+        assert_eq!(rpc_log.transaction_index, Some(3));
+        assert_eq!(rpc_log.block_timestamp, Some(6));
+        assert_eq!(rpc_log.log_index, Some(7));
+        assert_eq!(from_rpc_log(rpc_log), log);
+    }
+}
@@ -0,0 +1,468 @@
+//! Conversion of RPC transaction requests into unsigned transactions.
+
+use crate::TransactionConversionError;
+use alloy_consensus::{SignableTransaction, TxType, TypedTransaction};
+use alloy_primitives::{Bytes, TxKind};
+use alloy_rpc_types_eth::TransactionRequest;
+use std::future::Future;
+
+/// Builds the unsigned transaction `request` describes.
+///
+/// The transaction is built as the type the request sets. Requests without one get a type picked
+/// from the populated fields, see [`TransactionRequest::preferred_type`]: an authorization list
+/// makes an EIP-7702 transaction, blob versioned hashes or a sidecar an EIP-4844 one, a gas price
+/// with an access list an EIP-2930 one and a gas price alone a legacy one. Anything else is an
+/// EIP-1559 transaction. Blob transactions are built without their sidecar, which isn't signed
+/// over.
+///
+/// Fields that contradict each other or that the type can't carry are rejected, and so are
+/// requests missing fields the type requires.
+pub fn build_typed_transaction(
+    mut request: TransactionRequest,
+) -> Result<TypedTransaction, TransactionConversionError> {
+    let tx_type = match request.transaction_type {
+        Some(requested) => TxType::try_from(requested)
+            .map_err(|_| TransactionConversionError::UnsupportedType(requested))?,
+        None => request.preferred_type(),
+    };
+    check_request(&request, tx_type)?;
+
+    // An EIP-2930 transaction may have an empty access list, like the later types.
+    if tx_type == TxType::Eip2930 {
+        request.access_list.get_or_insert_default();
+    }
+
+    let complete = match tx_type {
+        TxType::Legacy => request.complete_legacy(),
+        TxType::Eip2930 => request.complete_2930(),
+        TxType::Eip1559 => request.complete_1559(),
+        TxType::Eip4844 => request.complete_4844(),
+        TxType::Eip7702 => request.complete_7702(),
+    };
+    if let Err(fields) = complete &&
+        let Some(err) = TransactionConversionError::missing_fields(tx_type, fields)
+    {
+        return Err(err)
+    }
+
+    match tx_type {
+        TxType::Legacy => request.build_legacy().map(Into::into),
+        TxType::Eip2930 => request.build_2930().map(Into::into),
+        TxType::Eip1559 => request.build_1559().map(Into::into),
+        TxType::Eip4844 => request.build_4844_variant().map(Into::into),
+        TxType::Eip7702 => request.build_7702().map(Into::into),
+    }
+    .map_err(|err| TransactionConversionError::Unbuildable { tx_type, reason: err.to_string() })
+}
+
+/// Looks up the defaults [`fill_transaction_request`] fills into a request of type `R`.
+///
+/// Each lookup is only made if the request is missing the field it's for.
+pub trait TransactionDefaults<R> {
+    /// An associated error of the lookups.
+    type Error;
+
+    /// Returns the nonce of `request`, e.g. the next nonce of its sender.
+    fn nonce(&self, request: &R) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Returns the max fee per blob gas of blob transactions.
+    fn blob_base_fee(&self) -> impl Future<Output = Result<u128, Self::Error>> + Send;
+
+    /// Returns the gas limit of `request`, e.g. an estimate of the request filled so far.
+    fn gas_limit(&self, request: &R) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Returns the tip, e.g. the suggestion of the fee oracle.
+    fn max_priority_fee_per_gas(&self) -> impl Future<Output = Result<u128, Self::Error>> + Send;
+
+    /// Returns the base fee of the latest block.
+    fn base_fee(&self) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+}
+
+/// A request filled by [`fill_transaction_request`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilledTransactionRequest<R> {
+    /// The request, with all the fields its transaction type requires.
+    pub request: R,
+    /// The RLP encoding of the unsigned transaction, see [`signing_preimage`].
+    pub preimage: Bytes,
+}
+
+/// Error of [`fill_transaction_request`].
+#[derive(Debug, thiserror::Error)]
+pub enum FillTransactionError<E> {
+    /// Looking up a default failed.
+    #[error(transparent)]
+    Lookup(E),
+    /// The filled request doesn't describe a valid transaction.
+    #[error(transparent)]
+    Conversion(#[from] TransactionConversionError),
+}
+
+/// Fills the fields of `request` that `eth_fillTransaction` defaults, like go-ethereum does, and
+/// returns it along with the [`signing_preimage`] of its transaction.
+///
+/// The chain id is always set to `chain_id`, and a missing value to zero. Missing fields are
+/// looked up with `defaults`, in this order: the nonce, the max fee per blob gas of blob
+/// transactions, the gas limit, the tip and the base fee. The max fee per gas is set to twice the
+/// base fee plus the tip, so the transaction stays valid if the base fee rises.
+///
+/// Fees are only filled in if the request has no gas price. Requests for legacy or EIP-2930
+/// transactions get a gas price of the base fee plus the tip instead.
+pub async fn fill_transaction_request<R, D>(
+    mut request: R,
+    chain_id: u64,
+    defaults: &D,
+) -> Result<FilledTransactionRequest<R>, FillTransactionError<D::Error>>
+where
+    R: AsRef<TransactionRequest> + AsMut<TransactionRequest>,
+    D: TransactionDefaults<R>,
+{
+    let tx = request.as_mut();
+    tx.value.get_or_insert_default();
+    tx.chain_id = Some(chain_id);
+
+    if request.as_ref().nonce.is_none() {
+        let nonce = defaults.nonce(&request).await.map_err(FillTransactionError::Lookup)?;
+        request.as_mut().nonce = Some(nonce);
+    }
+
+    let tx = request.as_ref();
+    if tx.has_eip4844_fields() && tx.max_fee_per_blob_gas.is_none() {
+        let blob_base_fee = defaults.blob_base_fee().await.map_err(FillTransactionError::Lookup)?;
+        request.as_mut().max_fee_per_blob_gas = Some(blob_base_fee);
+    }
+
+    // Checks the sidecar itself rather than its EIP-4844 form, to handle both the EIP-4844 and
+    // the EIP-7594 sidecar formats
+    let tx = request.as_mut();
+    if tx.sidecar.is_some() && tx.blob_versioned_hashes.is_none() {
+        tx.populate_blob_hashes();
+    }
+
+    if request.as_ref().gas.is_none() {
+        let gas_limit = defaults.gas_limit(&request).await.map_err(FillTransactionError::Lookup)?;
+        request.as_mut().gas = Some(gas_limit);
+    }
+
+    let tx = request.as_ref();
+    if tx.gas_price.is_none() && matches!(tx.transaction_type, Some(0 | 1)) {
+        let tip =
+            defaults.max_priority_fee_per_gas().await.map_err(FillTransactionError::Lookup)?;
+        let base_fee = defaults.base_fee().await.map_err(FillTransactionError::Lookup)?;
+        request.as_mut().gas_price = Some(tip + base_fee as u128);
+    } else if tx.gas_price.is_none() {
+        let tip = match request.as_ref().max_priority_fee_per_gas {
+            Some(tip) => tip,
+            None => {
+                let tip = defaults
+                    .max_priority_fee_per_gas()
+                    .await
+                    .map_err(FillTransactionError::Lookup)?;
+                request.as_mut().max_priority_fee_per_gas = Some(tip);
+                tip
+            }
+        };
+        if request.as_ref().max_fee_per_gas.is_none() {
+            // `2 * base_fee` leaves headroom, like go-ethereum's `setLondonFeeDefaults`. The
+            // sender still pays `base_fee + min(tip, max_fee_per_gas - base_fee)`.
+            let base_fee = defaults.base_fee().await.map_err(FillTransactionError::Lookup)? as u128;
+            request.as_mut().max_fee_per_gas = Some(base_fee * 2 + tip);
+        }
+    }
+
+    let preimage = signing_preimage(request.as_ref().clone())?;
+    Ok(FilledTransactionRequest { request, preimage })
+}
+
+/// Returns the RLP encoding of the unsigned transaction `request` describes, which is what its
+/// sender signs the hash of.
+///
+/// See [`build_typed_transaction`] for how the transaction is built.
+pub fn signing_preimage(request: TransactionRequest) -> Result<Bytes, TransactionConversionError> {
+    Ok(build_typed_transaction(request)?.encoded_for_signing().into())
+}
+
+/// Checks that the fields of `request`, built as a `tx_type` transaction, don't contradict each
+/// other and can all be carried by that type.
+fn check_request(
+    request: &TransactionRequest,
+    tx_type: TxType,
+) -> Result<(), TransactionConversionError> {
+    let conflict =
+        |field, other| Err(TransactionConversionError::ConflictingFields { field, other });
+    let unexpected = |field| Err(TransactionConversionError::UnexpectedField { tx_type, field });
+
+    if request.gas_price.is_some() {
+        if request.max_fee_per_gas.is_some() {
+            return conflict("gasPrice", "maxFeePerGas")
+        }
+        if request.max_priority_fee_per_gas.is_some() {
+            return conflict("gasPrice", "maxPriorityFeePerGas")
+        }
+    }
+    if let (Some(max_priority_fee_per_gas), Some(max_fee_per_gas)) =
+        (request.max_priority_fee_per_gas, request.max_fee_per_gas) &&
+        max_priority_fee_per_gas > max_fee_per_gas
+    {
+        return Err(TransactionConversionError::TipAboveFeeCap {
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+        })
+    }
+    if request.authorization_list.is_some() && request.has_eip4844_blob_data() {
+        return conflict("authorizationList", "blobVersionedHashes")
+    }
+    if let (Some(input), Some(data)) = (&request.input.input, &request.input.data) &&
+        input != data
+    {
+        return conflict("input", "data")
+    }
+    let dynamic_fee = !matches!(tx_type, TxType::Legacy | TxType::Eip2930);
+    if dynamic_fee && request.gas_price.is_some() {
+        return unexpected("gasPrice")
+    }
+    if !dynamic_fee && request.max_fee_per_gas.is_some() {
+        return unexpected("maxFeePerGas")
+    }
+    if !dynamic_fee && request.max_priority_fee_per_gas.is_some() {
+        return unexpected("maxPriorityFeePerGas")
+    }
+    if tx_type == TxType::Legacy && request.access_list.is_some() {
+        return unexpected("accessList")
+    }
+    if tx_type != TxType::Eip4844 && request.blob_versioned_hashes.is_some() {
+        return unexpected("blobVersionedHashes")
+    }
+    if tx_type != TxType::Eip4844 && request.sidecar.is_some() {
+        return unexpected("sidecar")
+    }
+    if tx_type != TxType::Eip7702 && request.authorization_list.is_some() {
+        return unexpected("authorizationList")
+    }
+    if matches!(tx_type, TxType::Eip4844 | TxType::Eip7702) &&
+        matches!(request.to, Some(TxKind::Create))
+    {
+        return Err(TransactionConversionError::ContractCreation { tx_type })
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use std::future::ready;
+
+    fn request() -> TransactionRequest {
+        TransactionRequest {
+            to: Some(TxKind::Call(Address::ZERO)),
+            nonce: Some(0),
+            gas: Some(21_000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn picks_the_type_from_the_populated_fields() {
+        let legacy = TransactionRequest { gas_price: Some(1), ..request() };
+        assert_eq!(build_typed_transaction(legacy).unwrap().tx_type(), TxType::Legacy);
+
+        let eip1559 = TransactionRequest {
+            max_fee_per_gas: Some(2),
+            max_priority_fee_per_gas: Some(1),
+            ..request()
+        };
+        assert_eq!(build_typed_transaction(eip1559).unwrap().tx_type(), TxType::Eip1559);
+
+        let eip4844 = TransactionRequest {
+            max_fee_per_gas: Some(2),
+            max_priority_fee_per_gas: Some(1),
+            max_fee_per_blob_gas: Some(1),
+            blob_versioned_hashes: Some(vec![B256::ZERO]),
+            ..request()
+        };
+        assert_eq!(build_typed_transaction(eip4844).unwrap().tx_type(), TxType::Eip4844);
+    }
+
+    #[test]
+    fn builds_the_requested_type() {
+        let eip2930 =
+            TransactionRequest { gas_price: Some(1), transaction_type: Some(1), ..request() };
+        assert_eq!(build_typed_transaction(eip2930).unwrap().tx_type(), TxType::Eip2930);
+
+        let eip1559 = TransactionRequest {
+            max_fee_per_gas: Some(2),
+            max_priority_fee_per_gas: Some(1),
+            transaction_type: Some(2),
+            ..request()
+        };
+        assert_eq!(build_typed_transaction(eip1559).unwrap().tx_type(), TxType::Eip1559);
+
+        let err = build_typed_transaction(TransactionRequest {
+            max_fee_per_gas: Some(2),
+            transaction_type: Some(0),
+            ..request()
+        })
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                TransactionConversionError::UnexpectedField {
+                    tx_type: TxType::Legacy,
+                    field: "maxFeePerGas"
+                }
+            ),
+            "{err}"
+        );
+
+        let err = build_typed_transaction(TransactionRequest {
+            transaction_type: Some(0x7f),
+            ..request()
+        })
+        .unwrap_err();
+        assert!(matches!(err, TransactionConversionError::UnsupportedType(0x7f)), "{err}");
+    }
+
+    #[test]
+    fn rejects_conflicting_fields() {
+        let err = build_typed_transaction(TransactionRequest {
+            gas_price: Some(1),
+            max_fee_per_gas: Some(1),
+            ..request()
+        })
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                TransactionConversionError::ConflictingFields {
+                    field: "gasPrice",
+                    other: "maxFeePerGas"
+                }
+            ),
+            "{err}"
+        );
+
+        let err = build_typed_transaction(TransactionRequest {
+            max_fee_per_gas: Some(1),
+            max_priority_fee_per_gas: Some(2),
+            ..request()
+        })
+        .unwrap_err();
+        assert!(matches!(err, TransactionConversionError::TipAboveFeeCap { .. }), "{err}");
+
+        let err = build_typed_transaction(TransactionRequest {
+            gas_price: Some(1),
+            transaction_type: Some(2),
+            ..request()
+        })
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                TransactionConversionError::UnexpectedField {
+                    tx_type: TxType::Eip1559,
+                    field: "gasPrice"
+                }
+            ),
+            "{err}"
+        );
+
+        let err =
+            build_typed_transaction(TransactionRequest { nonce: None, ..request() }).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                TransactionConversionError::MissingFields { tx_type: TxType::Eip1559, fields }
+                    if fields.contains(&"nonce")
+            ),
+            "{err}"
+        );
+    }
+
+    /// Defaults that fail the lookups they have no value for.
+    #[derive(Default)]
+    struct Defaults {
+        gas_limit: Option<u64>,
+        tip: Option<u128>,
+        base_fee: Option<u64>,
+    }
+
+    impl TransactionDefaults<TransactionRequest> for Defaults {
+        type Error = &'static str;
+
+        fn nonce(
+            &self,
+            _: &TransactionRequest,
+        ) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+            ready(Err("no nonce"))
+        }
+
+        fn blob_base_fee(&self) -> impl Future<Output = Result<u128, Self::Error>> + Send {
+            ready(Err("no blob base fee"))
+        }
+
+        fn gas_limit(
+            &self,
+            _: &TransactionRequest,
+        ) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+            ready(self.gas_limit.ok_or("no gas limit"))
+        }
+
+        fn max_priority_fee_per_gas(
+            &self,
+        ) -> impl Future<Output = Result<u128, Self::Error>> + Send {
+            ready(self.tip.ok_or("no tip"))
+        }
+
+        fn base_fee(&self) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+            ready(self.base_fee.ok_or("no base fee"))
+        }
+    }
+
+    #[test]
+    fn fills_only_missing_fields() {
+        let request = TransactionRequest { gas: None, max_fee_per_gas: Some(10), ..request() };
+        let defaults = Defaults { gas_limit: Some(21_000), tip: Some(2), ..Default::default() };
+
+        let FilledTransactionRequest { request: filled, preimage } =
+            futures::executor::block_on(fill_transaction_request(request, 1, &defaults)).unwrap();
+
+        assert_eq!(filled.chain_id, Some(1));
+        assert_eq!(filled.value, Some(Default::default()));
+        assert_eq!(filled.gas, Some(21_000));
+        assert_eq!(filled.max_priority_fee_per_gas, Some(2));
+        assert_eq!(filled.max_fee_per_gas, Some(10));
+        assert_eq!(preimage, signing_preimage(filled).unwrap());
+    }
+
+    #[test]
+    fn fills_a_gas_price_for_legacy_requests() {
+        let request = TransactionRequest { transaction_type: Some(0), ..request() };
+        let defaults = Defaults { tip: Some(2), base_fee: Some(10), ..Default::default() };
+
+        let filled = futures::executor::block_on(fill_transaction_request(request, 1, &defaults))
+            .unwrap()
+            .request;
+
+        assert_eq!(filled.gas_price, Some(12));
+        assert_eq!(filled.max_fee_per_gas, None);
+        assert_eq!(filled.max_priority_fee_per_gas, None);
+        assert_eq!(build_typed_transaction(filled).unwrap().tx_type(), TxType::Legacy);
+    }
+
+    #[test]
+    fn fails_on_requests_that_stay_invalid() {
+        let request = TransactionRequest {
+            transaction_type: Some(0),
+            max_fee_per_gas: Some(10),
+            ..request()
+        };
+        let defaults = Defaults { tip: Some(2), base_fee: Some(10), ..Default::default() };
+
+        let err = futures::executor::block_on(fill_transaction_request(request, 1, &defaults))
+            .unwrap_err();
+
+        assert!(matches!(err, FillTransactionError::Conversion(_)), "{err:?}");
+    }
+}
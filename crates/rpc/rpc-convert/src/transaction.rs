@@ -1,19 +1,28 @@
 //! Compatibility functions for rpc `Transaction` type.
 use crate::{
-    RpcHeader, RpcReceipt, RpcTransaction, RpcTxReq, RpcTypes, SignableTxRequest, TryIntoTxEnv,
+    RpcBlock, RpcHeader, RpcReceipt, RpcTransaction, RpcTxReq, RpcTypes, SignableTxRequest,
+    TryIntoTxEnv,
 };
-use alloy_consensus::{error::ValueError, transaction::Recovered};
-use alloy_primitives::Address;
-use alloy_rpc_types_eth::TransactionInfo;
+use alloy_consensus::{
+    error::ValueError,
+    transaction::{from_eip155_value, Recovered, TxHashRef},
+    EthereumTxEnvelope, EthereumTypedTransaction, TxEip1559, TxEip2930, TxEip4844, TxEip7702,
+    TxEnvelope, TxLegacy, TxType,
+};
+use alloy_network::{AnyNetwork, AnyRpcTransaction, AnyTxEnvelope, Ethereum, UnknownTxEnvelope};
+use alloy_primitives::{Address, Signature, TxKind, B256, U128, U64, U8};
+use alloy_rpc_types_eth::{BlockTransactionsKind, TransactionInfo, TransactionRequest};
+use alloy_serde::{OtherFields, WithOtherFields};
 use core::error;
 use dyn_clone::DynClone;
 use reth_evm::{BlockEnvFor, ConfigureEvm, EvmEnvFor, SpecFor, TxEnvFor};
 use reth_primitives_traits::{
-    BlockTy, HeaderTy, NodePrimitives, SealedBlock, SealedHeader, SealedHeaderFor, TransactionMeta,
-    TxTy,
+    BlockTy, HeaderTy, NodePrimitives, ReceiptTy, RecoveredBlock, SealedBlock, SealedHeader,
+    SealedHeaderFor, TransactionMeta, TxTy,
 };
 use reth_rpc_traits::{FromConsensusHeader, FromConsensusTx, TryIntoSimTx, TxInfoMapper};
-use std::{convert::Infallible, error::Error, fmt, fmt::Debug, marker::PhantomData};
+use serde::de::DeserializeOwned;
+use std::{convert::Infallible, error::Error, fmt, fmt::Debug, marker::PhantomData, sync::Arc};
 
 /// Input for [`RpcConvert::convert_receipts`].
 #[derive(Debug, Clone)]
@@ -127,11 +136,28 @@ pub trait RpcConvert: Send + Sync + Unpin + Debug + DynClone + 'static {
     /// Wrapper for `fill()` with default `TransactionInfo`
     /// Create a new rpc transaction result for a _pending_ signed transaction, setting block
     /// environment related fields to `None`.
+    ///
+    /// See [`RpcConvert::fill_pending_with_base_fee`] to also fill in the effective gas price.
     fn fill_pending(
         &self,
         tx: Recovered<TxTy<Self::Primitives>>,
     ) -> Result<RpcTransaction<Self::Network>, Self::Error> {
-        self.fill(tx, TransactionInfo::default())
+        self.fill_pending_with_base_fee(tx, None)
+    }
+
+    /// Create a new rpc transaction result for a _pending_ or _queued_ signed transaction, which
+    /// isn't part of any block.
+    ///
+    /// The block hash, number, index and timestamp are left `None` rather than made up. The
+    /// effective gas price is derived from `base_fee`, which should be the base fee the
+    /// transaction would pay if included in the next block, e.g. the pending base fee of the pool.
+    fn fill_pending_with_base_fee(
+        &self,
+        tx: Recovered<TxTy<Self::Primitives>>,
+        base_fee: Option<u64>,
+    ) -> Result<RpcTransaction<Self::Network>, Self::Error> {
+        let tx_info = TransactionInfo { hash: Some(*tx.tx_hash()), base_fee, ..Default::default() };
+        self.fill(tx, tx_info)
     }
 
     /// Create a new rpc transaction result for a mined transaction, using the given block hash,
@@ -145,6 +171,17 @@ pub trait RpcConvert: Send + Sync + Unpin + Debug + DynClone + 'static {
         tx_info: TransactionInfo,
     ) -> Result<RpcTransaction<Self::Network>, Self::Error>;
 
+    /// Converts all transactions of `block` to RPC transactions, in block order.
+    ///
+    /// The block hash, number, timestamp and base fee are read once for the whole block, and
+    /// every transaction is filled with them and its index, see [`Self::fill`].
+    fn convert_transactions(
+        &self,
+        block: &RecoveredBlock<BlockTy<Self::Primitives>>,
+    ) -> Result<Vec<RpcTransaction<Self::Network>>, Self::Error> {
+        crate::block::convert_transactions(self, block)
+    }
+
     /// Builds a fake transaction from a transaction request for inclusion into block built in
     /// `eth_simulateV1`.
     fn build_simulate_v1_transaction(
@@ -177,12 +214,78 @@ pub trait RpcConvert: Send + Sync + Unpin + Debug + DynClone + 'static {
         block: &SealedBlock<BlockTy<Self::Primitives>>,
     ) -> Result<Vec<RpcReceipt<Self::Network>>, Self::Error>;
 
+    /// Converts the receipts of `block`, in block order, to RPC representations.
+    ///
+    /// The gas used, log indexes and transaction metadata of each receipt are worked out from the
+    /// block and the receipts before it, see [`block_receipt_inputs`](crate::block_receipt_inputs).
+    fn convert_block_receipts(
+        &self,
+        block: &RecoveredBlock<BlockTy<Self::Primitives>>,
+        receipts: Vec<ReceiptTy<Self::Primitives>>,
+    ) -> Result<Vec<RpcReceipt<Self::Network>>, Self::Error> {
+        let mut inputs = Vec::with_capacity(receipts.len());
+        inputs.extend(crate::block_receipt_inputs::<Self::Primitives, _>(block, receipts));
+        self.convert_receipts_with_block(inputs, block.sealed_block())
+    }
+
     /// Converts a primitive header to an RPC header.
     fn convert_header(
         &self,
         header: SealedHeaderFor<Self::Primitives>,
         block_size: usize,
     ) -> Result<RpcHeader<Self::Network>, Self::Error>;
+
+    /// Converts a primitive block to an RPC block.
+    ///
+    /// If `kind` is [`BlockTransactionsKind::Full`], the block contains all transaction objects,
+    /// see [`Self::convert_transactions`]. Otherwise, it only contains the transaction hashes.
+    fn convert_block(
+        &self,
+        block: &RecoveredBlock<BlockTy<Self::Primitives>>,
+        kind: BlockTransactionsKind,
+    ) -> Result<RpcBlock<Self::Network>, Self::Error> {
+        crate::block::convert_block(self, block, kind)
+    }
+
+    /// Converts a primitive block to an RPC block, consuming it.
+    ///
+    /// Unlike [`Self::convert_block`], the transactions are moved into the RPC transactions
+    /// instead of being cloned.
+    fn convert_block_owned(
+        &self,
+        block: RecoveredBlock<BlockTy<Self::Primitives>>,
+        kind: BlockTransactionsKind,
+    ) -> Result<RpcBlock<Self::Network>, Self::Error> {
+        crate::block::convert_block_owned(self, block, kind)
+    }
+
+    /// Converts a shared primitive block to an RPC block.
+    ///
+    /// If `block` is the only reference to the block, it is consumed like in
+    /// [`Self::convert_block_owned`]. Otherwise its transactions are cloned like in
+    /// [`Self::convert_block`].
+    fn convert_block_shared(
+        &self,
+        block: Arc<RecoveredBlock<BlockTy<Self::Primitives>>>,
+        kind: BlockTransactionsKind,
+    ) -> Result<RpcBlock<Self::Network>, Self::Error> {
+        match Arc::try_unwrap(block) {
+            Ok(block) => self.convert_block_owned(block, kind),
+            Err(block) => self.convert_block(&block, kind),
+        }
+    }
+
+    /// Converts an ommer header to an RPC uncle block, as returned by `eth_getUncle*`.
+    ///
+    /// Uncle blocks have an empty body: no transactions, uncles or withdrawals. Their size is
+    /// the size of the header wrapped in such a block. See
+    /// [`uncle_from_response`](crate::uncle_from_response) for the reverse.
+    fn convert_uncle(
+        &self,
+        header: HeaderTy<Self::Primitives>,
+    ) -> Result<RpcBlock<Self::Network>, Self::Error> {
+        crate::block::convert_uncle(self, header)
+    }
 }
 
 dyn_clone::clone_trait_object!(
@@ -367,17 +470,194 @@ where
     }
 }
 
+/// Maps `RpcReceipt` built by the [`ReceiptConverter`] of an [`RpcConverter`].
+///
+/// Where:
+/// * `N` are the node primitives the receipt was converted from.
+/// * `RpcReceipt` is a receipt response object of the RPC API.
+///
+/// The mapping function is accompanied by the transaction the receipt belongs to and its
+/// [`TransactionMeta`], so chains with extra receipt fields can fill them in without reimplementing
+/// the receipt conversion.
+///
+/// The `ReceiptMapper` has two blanket implementations:
+/// * `()` returning the receipt unchanged and is used as default for [`RpcConverter`].
+/// * `Fn(RpcReceipt, Recovered<&Tx>, TransactionMeta) -> Result<RpcReceipt, E>` and can be applied
+///   using [`RpcConverter::with_receipt_mapper`].
+pub trait ReceiptMapper<N: NodePrimitives, RpcReceipt>:
+    Clone + Unpin + Send + Sync + 'static
+{
+    /// An associated error that can occur during the mapping.
+    type Err;
+
+    /// Maps `receipt` of `tx`.
+    fn try_map(
+        &self,
+        receipt: RpcReceipt,
+        tx: Recovered<&N::SignedTx>,
+        meta: TransactionMeta,
+    ) -> Result<RpcReceipt, Self::Err>;
+}
+
+impl<N: NodePrimitives, RpcReceipt> ReceiptMapper<N, RpcReceipt> for () {
+    type Err = Infallible;
+
+    fn try_map(
+        &self,
+        receipt: RpcReceipt,
+        _tx: Recovered<&N::SignedTx>,
+        _meta: TransactionMeta,
+    ) -> Result<RpcReceipt, Self::Err> {
+        Ok(receipt)
+    }
+}
+
+impl<N, RpcReceipt, F, E> ReceiptMapper<N, RpcReceipt> for F
+where
+    N: NodePrimitives,
+    F: Fn(RpcReceipt, Recovered<&N::SignedTx>, TransactionMeta) -> Result<RpcReceipt, E>
+        + Clone
+        + Unpin
+        + Send
+        + Sync
+        + 'static,
+{
+    type Err = E;
+
+    fn try_map(
+        &self,
+        receipt: RpcReceipt,
+        tx: Recovered<&N::SignedTx>,
+        meta: TransactionMeta,
+    ) -> Result<RpcReceipt, Self::Err> {
+        self(receipt, tx, meta)
+    }
+}
+
 /// Conversion into transaction RPC response failed.
 #[derive(Debug, thiserror::Error)]
 pub enum TransactionConversionError {
     /// Required fields are missing from the transaction request.
+    #[error("{tx_type} transaction request is missing fields: {}", fields.join(", "))]
+    MissingFields {
+        /// Type of the transaction the request was built as.
+        tx_type: TxType,
+        /// RPC names of the missing fields, such as `maxFeePerGas`.
+        fields: Vec<&'static str>,
+    },
+
+    /// Two fields of the transaction request contradict each other.
+    #[error("transaction request can't set both `{field}` and `{other}`")]
+    ConflictingFields {
+        /// Name of the first field.
+        field: &'static str,
+        /// Name of the field it conflicts with.
+        other: &'static str,
+    },
+
+    /// The transaction request sets a type no transaction can be built as.
+    #[error("transaction request has unsupported type {0}")]
+    UnsupportedType(u8),
+
+    /// The transaction request sets a field its type can't carry.
+    #[error("{tx_type} transactions can't set `{field}`")]
+    UnexpectedField {
+        /// Type of the transaction the request was built as.
+        tx_type: TxType,
+        /// Name of the field.
+        field: &'static str,
+    },
+
+    /// The priority fee of the transaction request exceeds its max fee.
+    #[error("max priority fee per gas {max_priority_fee_per_gas} exceeds max fee per gas {max_fee_per_gas}")]
+    TipAboveFeeCap {
+        /// The max priority fee per gas of the request.
+        max_priority_fee_per_gas: u128,
+        /// The max fee per gas of the request.
+        max_fee_per_gas: u128,
+    },
+
+    /// The transaction request creates a contract, which its type can't.
+    #[error("{tx_type} transactions can't create contracts")]
+    ContractCreation {
+        /// Type of the transaction the request was built as.
+        tx_type: TxType,
+    },
+
+    /// The transaction request passed the checks, but still couldn't be built as its type.
+    #[error("failed to build {tx_type} transaction from request: {reason}")]
+    Unbuildable {
+        /// Type of the transaction the request was built as.
+        tx_type: TxType,
+        /// Why the transaction couldn't be built.
+        reason: String,
+    },
+
+    /// The receipt converter returned a receipt count other than the transaction count.
+    #[error("converted {receipts} receipts for {transactions} transactions")]
+    ReceiptCountMismatch {
+        /// Number of converted receipts.
+        receipts: usize,
+        /// Number of transactions the receipts were converted for.
+        transactions: usize,
+    },
+
+    /// The transaction request can't be converted into a transaction.
     #[error("Failed to convert transaction into RPC response: {0}")]
-    FromTxReq(String),
+    FromTxReq(#[source] Box<dyn Error + Send + Sync>),
 
     /// Other conversion errors.
     #[error("{0}")]
     Other(String),
 }
+
+impl TransactionConversionError {
+    /// Returns a [`Self::MissingFields`] naming the `fields` a `tx_type` transaction request lacks,
+    /// or `None` if it lacks none but the sidecar.
+    ///
+    /// `fields` are named as by [`TransactionRequest::missing_keys`], and reported by their RPC
+    /// names. Blob transactions are built from their versioned hashes, so the sidecar isn't
+    /// required to convert them.
+    pub fn missing_fields(tx_type: TxType, fields: Vec<&'static str>) -> Option<Self> {
+        let fields = fields
+            .into_iter()
+            .filter(|field| *field != "sidecar")
+            .map(rpc_field_name)
+            .collect::<Vec<_>>();
+        (!fields.is_empty()).then_some(Self::MissingFields { tx_type, fields })
+    }
+}
+
+/// Returns the RPC name of the transaction request field alloy names `field`.
+fn rpc_field_name(field: &'static str) -> &'static str {
+    match field {
+        "gas_limit" => "gas",
+        "gas_price" => "gasPrice",
+        "max_fee_per_gas" => "maxFeePerGas",
+        "max_priority_fee_per_gas" => "maxPriorityFeePerGas",
+        "max_fee_per_blob_gas" => "maxFeePerBlobGas",
+        "access_list" => "accessList",
+        "authorization_list" => "authorizationList",
+        field => field,
+    }
+}
+
+/// Returns a [`TransactionConversionError::MissingFields`] if `err` is the error of an Ethereum
+/// transaction request that couldn't be built because it lacks fields, or `None` otherwise.
+fn missing_fields_of(
+    err: &(dyn Error + Send + Sync + 'static),
+) -> Option<TransactionConversionError> {
+    let request = err
+        .downcast_ref::<ValueError<TransactionRequest>>()
+        .map(ValueError::value)
+        .or_else(|| {
+            err.downcast_ref::<ValueError<WithOtherFields<TransactionRequest>>>()
+                .map(|err| &err.value().inner)
+        })?;
+    let (tx_type, fields) = request.missing_keys().err()?;
+    TransactionConversionError::missing_fields(tx_type, fields)
+}
+
 /// Generic RPC response object converter for `Evm` and network `Network`.
 ///
 /// The main purpose of this struct is to provide an implementation of [`RpcConvert`] for generic
@@ -393,6 +673,21 @@ pub enum TransactionConversionError {
 ///   implemented for a dedicated struct that is assigned to `Map`. If [`FromConsensusTx::TxInfo`]
 ///   is [`TransactionInfo`] then `()` can be used as `Map` which trivially passes over the input
 ///   object.
+///
+/// # Custom transaction types
+///
+/// None of the conversions match on transaction types, so a chain adds its own by making the
+/// signed transaction of its [`NodePrimitives`] an envelope with an extra variant under its type
+/// byte, and plugging in a converter for each direction that has to know about it:
+/// * its RPC view: [`FromConsensusTx`] for the RPC transaction, or [`RpcTxConverter`] through
+///   [`RpcConverter::with_rpc_tx_converter`]. [`TxInfoMapper`] adds the block context it needs.
+/// * its receipt: [`ReceiptConverter`], or [`ReceiptMapper`] for extra fields only.
+/// * its execution: [`TryIntoTxEnv`] for the request, or [`TxEnvConverter`] through
+///   [`RpcConverter::with_tx_env_converter`], and the same for [`TryIntoSimTx`] and
+///   [`SimTxConverter`].
+/// * parsing responses back: [`TryFromTransactionResponse`] for the envelope.
+///
+/// Each of the converters can wrap the default one, `()`, and delegate the built-in variants to it.
 pub struct RpcConverter<
     Network,
     Evm,
@@ -402,6 +697,7 @@ pub struct RpcConverter<
     SimTx = (),
     RpcTx = (),
     TxEnv = (),
+    ReceiptMap = (),
 > {
     network: PhantomData<Network>,
     evm: PhantomData<Evm>,
@@ -411,10 +707,11 @@ pub struct RpcConverter<
     tx_env_converter: TxEnv,
     sim_tx_converter: SimTx,
     rpc_tx_converter: RpcTx,
+    receipt_mapper: ReceiptMap,
 }
 
-impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv> fmt::Debug
-    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
+impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap> fmt::Debug
+    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RpcConverter").finish_non_exhaustive()
@@ -431,19 +728,20 @@ impl<Network, Evm, Receipt> RpcConverter<Network, Evm, Receipt> {
             header_converter: (),
             mapper: (),
             tx_env_converter: (),
+            receipt_mapper: (),
             sim_tx_converter: (),
             rpc_tx_converter: (),
         }
     }
 }
 
-impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
-    RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
+impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
+    RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
 {
     /// Converts the network type
     pub fn with_network<N>(
         self,
-    ) -> RpcConverter<N, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv> {
+    ) -> RpcConverter<N, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter,
@@ -452,6 +750,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
             ..
         } = self;
         RpcConverter {
@@ -463,6 +762,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -470,7 +770,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     pub fn with_tx_env_converter<TxEnvNew>(
         self,
         tx_env_converter: TxEnvNew,
-    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnvNew> {
+    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnvNew, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter,
@@ -479,6 +779,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             evm,
             sim_tx_converter,
             rpc_tx_converter,
+            receipt_mapper,
             ..
         } = self;
         RpcConverter {
@@ -490,6 +791,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -497,7 +799,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     pub fn with_header_converter<HeaderNew>(
         self,
         header_converter: HeaderNew,
-    ) -> RpcConverter<Network, Evm, Receipt, HeaderNew, Map, SimTx, RpcTx, TxEnv> {
+    ) -> RpcConverter<Network, Evm, Receipt, HeaderNew, Map, SimTx, RpcTx, TxEnv, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter: _,
@@ -507,6 +809,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         } = self;
         RpcConverter {
             receipt_converter,
@@ -517,6 +820,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -524,7 +828,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     pub fn with_mapper<MapNew>(
         self,
         mapper: MapNew,
-    ) -> RpcConverter<Network, Evm, Receipt, Header, MapNew, SimTx, RpcTx, TxEnv> {
+    ) -> RpcConverter<Network, Evm, Receipt, Header, MapNew, SimTx, RpcTx, TxEnv, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter,
@@ -534,6 +838,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         } = self;
         RpcConverter {
             receipt_converter,
@@ -544,6 +849,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -551,7 +857,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     pub fn with_sim_tx_converter<SimTxNew>(
         self,
         sim_tx_converter: SimTxNew,
-    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTxNew, RpcTx, TxEnv> {
+    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTxNew, RpcTx, TxEnv, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter,
@@ -560,6 +866,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             evm,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
             ..
         } = self;
         RpcConverter {
@@ -571,6 +878,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -578,7 +886,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     pub fn with_rpc_tx_converter<RpcTxNew>(
         self,
         rpc_tx_converter: RpcTxNew,
-    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTxNew, TxEnv> {
+    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTxNew, TxEnv, ReceiptMap> {
         let Self {
             receipt_converter,
             header_converter,
@@ -587,6 +895,7 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             evm,
             sim_tx_converter,
             tx_env_converter,
+            receipt_mapper,
             ..
         } = self;
         RpcConverter {
@@ -598,6 +907,36 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
             sim_tx_converter,
             rpc_tx_converter,
             tx_env_converter,
+            receipt_mapper,
+        }
+    }
+
+    /// Configures the receipt mapper.
+    pub fn with_receipt_mapper<ReceiptMapNew>(
+        self,
+        receipt_mapper: ReceiptMapNew,
+    ) -> RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMapNew> {
+        let Self {
+            receipt_converter,
+            header_converter,
+            mapper,
+            network,
+            evm,
+            sim_tx_converter,
+            rpc_tx_converter,
+            tx_env_converter,
+            receipt_mapper: _,
+        } = self;
+        RpcConverter {
+            receipt_converter,
+            header_converter,
+            mapper,
+            network,
+            evm,
+            sim_tx_converter,
+            rpc_tx_converter,
+            tx_env_converter,
+            receipt_mapper,
         }
     }
 
@@ -619,8 +958,42 @@ impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
     }
 }
 
-impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv> Default
-    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
+impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
+    RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
+{
+    /// Maps converted `receipts` with the receipt mapper, each along with the transaction it
+    /// belongs to from `txs`.
+    ///
+    /// Fails if the receipt converter didn't return one receipt per transaction, as receipts
+    /// would otherwise be mapped along with the wrong transaction or dropped.
+    fn map_receipts<N, Rpc, E>(
+        &self,
+        receipts: Vec<Rpc>,
+        txs: Vec<(Recovered<&N::SignedTx>, TransactionMeta)>,
+    ) -> Result<Vec<Rpc>, E>
+    where
+        N: NodePrimitives,
+        ReceiptMap: ReceiptMapper<N, Rpc>,
+        E: From<ReceiptMap::Err> + From<TransactionConversionError>,
+    {
+        if receipts.len() != txs.len() {
+            return Err(TransactionConversionError::ReceiptCountMismatch {
+                receipts: receipts.len(),
+                transactions: txs.len(),
+            }
+            .into())
+        }
+
+        receipts
+            .into_iter()
+            .zip(txs)
+            .map(|(receipt, (tx, meta))| Ok(self.receipt_mapper.try_map(receipt, tx, meta)?))
+            .collect()
+    }
+}
+
+impl<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap> Default
+    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
 where
     Receipt: Default,
     Header: Default,
@@ -628,6 +1001,7 @@ where
     SimTx: Default,
     RpcTx: Default,
     TxEnv: Default,
+    ReceiptMap: Default,
 {
     fn default() -> Self {
         Self {
@@ -639,6 +1013,7 @@ where
             sim_tx_converter: Default::default(),
             rpc_tx_converter: Default::default(),
             tx_env_converter: Default::default(),
+            receipt_mapper: Default::default(),
         }
     }
 }
@@ -652,7 +1027,8 @@ impl<
         SimTx: Clone,
         RpcTx: Clone,
         TxEnv: Clone,
-    > Clone for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
+        ReceiptMap: Clone,
+    > Clone for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
 {
     fn clone(&self) -> Self {
         Self {
@@ -664,12 +1040,13 @@ impl<
             sim_tx_converter: self.sim_tx_converter.clone(),
             rpc_tx_converter: self.rpc_tx_converter.clone(),
             tx_env_converter: self.tx_env_converter.clone(),
+            receipt_mapper: self.receipt_mapper.clone(),
         }
     }
 }
 
-impl<N, Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv> RpcConvert
-    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv>
+impl<N, Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap> RpcConvert
+    for RpcConverter<Network, Evm, Receipt, Header, Map, SimTx, RpcTx, TxEnv, ReceiptMap>
 where
     N: NodePrimitives,
     Network: RpcTypes<TransactionRequest: SignableTxRequest<N::SignedTx>>,
@@ -682,6 +1059,7 @@ where
                        + From<<Map as TxInfoMapper<TxTy<N>>>::Err>
                        + From<RpcTx::Err>
                        + From<Header::Err>
+                       + From<ReceiptMap::Err>
                        + Error
                        + Unpin
                        + Sync
@@ -694,10 +1072,11 @@ where
         + Debug,
     Header: HeaderConverter<HeaderTy<N>, RpcHeader<Network>>,
     Map: TxInfoMapper<TxTy<N>> + Clone + Debug + Unpin + Send + Sync + 'static,
-    SimTx: SimTxConverter<RpcTxReq<Network>, TxTy<N>>,
+    SimTx: SimTxConverter<RpcTxReq<Network>, TxTy<N>, Err: Send + Sync + 'static>,
     RpcTx:
         RpcTxConverter<TxTy<N>, Network::TransactionResponse, <Map as TxInfoMapper<TxTy<N>>>::Out>,
     TxEnv: TxEnvConverter<RpcTxReq<Network>, Evm>,
+    ReceiptMap: ReceiptMapper<N, RpcReceipt<Network>>,
 {
     type Primitives = N;
     type Evm = Evm;
@@ -719,10 +1098,10 @@ where
         &self,
         request: RpcTxReq<Network>,
     ) -> Result<TxTy<N>, Self::Error> {
-        Ok(self
-            .sim_tx_converter
-            .convert_sim_tx(request)
-            .map_err(|e| TransactionConversionError::FromTxReq(e.to_string()))?)
+        Ok(self.sim_tx_converter.convert_sim_tx(request).map_err(|err| {
+            let err: Box<dyn Error + Send + Sync> = Box::new(err);
+            missing_fields_of(&*err).unwrap_or(TransactionConversionError::FromTxReq(err))
+        })?)
     }
 
     fn tx_env(
@@ -737,7 +1116,8 @@ where
         &self,
         receipts: Vec<ConvertReceiptInput<'_, Self::Primitives>>,
     ) -> Result<Vec<RpcReceipt<Self::Network>>, Self::Error> {
-        self.receipt_converter.convert_receipts(receipts)
+        let txs = receipts.iter().map(|input| (input.tx, input.meta)).collect();
+        self.map_receipts::<N, _, _>(self.receipt_converter.convert_receipts(receipts)?, txs)
     }
 
     fn convert_receipts_with_block(
@@ -745,7 +1125,11 @@ where
         receipts: Vec<ConvertReceiptInput<'_, Self::Primitives>>,
         block: &SealedBlock<BlockTy<Self::Primitives>>,
     ) -> Result<Vec<RpcReceipt<Self::Network>>, Self::Error> {
-        self.receipt_converter.convert_receipts_with_block(receipts, block)
+        let txs = receipts.iter().map(|input| (input.tx, input.meta)).collect();
+        self.map_receipts::<N, _, _>(
+            self.receipt_converter.convert_receipts_with_block(receipts, block)?,
+            txs,
+        )
     }
 
     fn convert_header(
@@ -756,3 +1140,439 @@ where
         Ok(self.header_converter.convert_header(header, block_size)?)
     }
 }
+
+/// Converts a transaction response of the RPC API of network `N` into a consensus transaction.
+///
+/// This is the reverse of [`FromConsensusTx`], for tools that replay RPC data into reth types.
+pub trait TryFromTransactionResponse<N: alloy_network::Network>: Sized {
+    /// An associated conversion error.
+    type Error: error::Error;
+
+    /// Converts `tx`, a transaction response of network `N`, into `Self`.
+    fn from_transaction_response(tx: N::TransactionResponse) -> Result<Self, Self::Error>;
+}
+
+/// Converts Ethereum transactions into the envelope of Ethereum nodes, their `TransactionSigned`.
+///
+/// The RPC types take the hash of a response as is, so the transaction is hashed again and must
+/// match it.
+impl TryFromTransactionResponse<Ethereum> for EthereumTxEnvelope<TxEip4844> {
+    type Error = TransactionResponseError;
+
+    fn from_transaction_response(
+        tx: alloy_rpc_types_eth::Transaction,
+    ) -> Result<Self, Self::Error> {
+        rehash_tx(tx.into_inner())
+    }
+}
+
+/// Converts transactions of any network, as long as they are of an Ethereum transaction type.
+///
+/// Transactions the RPC types couldn't make sense of, [`AnyTxEnvelope::Unknown`], are decoded
+/// field by field, so the error names the field a response is missing or got wrong. Either way,
+/// the transaction must hash to the hash of the response.
+impl TryFromTransactionResponse<AnyNetwork> for EthereumTxEnvelope<TxEip4844> {
+    type Error = TransactionResponseError;
+
+    fn from_transaction_response(tx: AnyRpcTransaction) -> Result<Self, Self::Error> {
+        match tx.into_inner().into_inner() {
+            AnyTxEnvelope::Ethereum(tx) => rehash_tx(tx),
+            AnyTxEnvelope::Unknown(tx) => decode_unknown_tx(tx),
+        }
+    }
+}
+
+/// Conversion of a transaction RPC response into a consensus transaction failed.
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionResponseError {
+    /// The transaction type is not one of the node's.
+    #[error("transaction {hash} has unsupported type {ty:#04x}")]
+    UnsupportedType {
+        /// Hash of the transaction.
+        hash: B256,
+        /// Type of the transaction.
+        ty: u8,
+    },
+
+    /// A field the transaction type requires is missing from the response.
+    #[error("transaction {hash} is missing field `{field}`")]
+    MissingField {
+        /// Hash of the transaction.
+        hash: B256,
+        /// Name of the field in the response.
+        field: &'static str,
+    },
+
+    /// A field of the response doesn't hold a valid value.
+    #[error("transaction {hash} has an invalid `{field}`: {reason}")]
+    InvalidField {
+        /// Hash of the transaction.
+        hash: B256,
+        /// Name of the field in the response.
+        field: &'static str,
+        /// Why the value is invalid.
+        reason: String,
+    },
+
+    /// The converted transaction doesn't hash to the hash of the response.
+    #[error("transaction {hash} converts into a transaction with hash {computed}")]
+    HashMismatch {
+        /// Hash of the transaction, as in the response.
+        hash: B256,
+        /// Hash of the converted transaction.
+        computed: B256,
+    },
+}
+
+/// Converts `tx`, as read from a response, hashing it again rather than taking the hash of the
+/// response.
+fn rehash_tx(tx: TxEnvelope) -> Result<EthereumTxEnvelope<TxEip4844>, TransactionResponseError> {
+    let hash = *tx.tx_hash();
+    let tx = EthereumTxEnvelope::<TxEip4844>::from(tx);
+    let signature = *tx.signature();
+    ensure_tx_hash(EthereumTxEnvelope::new_unhashed(tx.into_typed_transaction(), signature), hash)
+}
+
+/// Returns `tx` if it hashes to `hash`, the hash of its response.
+fn ensure_tx_hash(
+    tx: EthereumTxEnvelope<TxEip4844>,
+    hash: B256,
+) -> Result<EthereumTxEnvelope<TxEip4844>, TransactionResponseError> {
+    let computed = *tx.tx_hash();
+    if computed != hash {
+        return Err(TransactionResponseError::HashMismatch { hash, computed })
+    }
+    Ok(tx)
+}
+
+/// Decodes a transaction of an Ethereum type from the fields of `tx`.
+fn decode_unknown_tx(
+    tx: UnknownTxEnvelope,
+) -> Result<EthereumTxEnvelope<TxEip4844>, TransactionResponseError> {
+    let UnknownTxEnvelope { hash, inner } = tx;
+    let fields = ResponseFields { hash, fields: &inner.fields };
+
+    // Legacy transactions carry the parity of their signature in `v`, along with the chain id.
+    let mut legacy_parity = None;
+    let typed = match inner.ty.0 {
+        0 => {
+            let v = fields.required::<U64>("v")?.to::<u64>();
+            let (parity, v_chain_id) = from_eip155_value(v.into())
+                .ok_or_else(|| fields.invalid("v", format!("{v} is not a legacy `v` value")))?;
+            legacy_parity = Some(parity);
+            EthereumTypedTransaction::Legacy(TxLegacy {
+                chain_id: fields.optional::<U64>("chainId")?.map(|id| id.to()).or(v_chain_id),
+                nonce: fields.required::<U64>("nonce")?.to(),
+                gas_price: fields.required::<U128>("gasPrice")?.to(),
+                gas_limit: fields.required::<U64>("gas")?.to(),
+                to: fields.kind()?,
+                value: fields.required("value")?,
+                input: fields.required("input")?,
+            })
+        }
+        1 => EthereumTypedTransaction::Eip2930(TxEip2930 {
+            chain_id: fields.required::<U64>("chainId")?.to(),
+            nonce: fields.required::<U64>("nonce")?.to(),
+            gas_price: fields.required::<U128>("gasPrice")?.to(),
+            gas_limit: fields.required::<U64>("gas")?.to(),
+            to: fields.kind()?,
+            value: fields.required("value")?,
+            access_list: fields.required("accessList")?,
+            input: fields.required("input")?,
+        }),
+        2 => EthereumTypedTransaction::Eip1559(TxEip1559 {
+            chain_id: fields.required::<U64>("chainId")?.to(),
+            nonce: fields.required::<U64>("nonce")?.to(),
+            gas_limit: fields.required::<U64>("gas")?.to(),
+            max_fee_per_gas: fields.required::<U128>("maxFeePerGas")?.to(),
+            max_priority_fee_per_gas: fields.required::<U128>("maxPriorityFeePerGas")?.to(),
+            to: fields.kind()?,
+            value: fields.required("value")?,
+            access_list: fields.required("accessList")?,
+            input: fields.required("input")?,
+        }),
+        3 => EthereumTypedTransaction::Eip4844(TxEip4844 {
+            chain_id: fields.required::<U64>("chainId")?.to(),
+            nonce: fields.required::<U64>("nonce")?.to(),
+            gas_limit: fields.required::<U64>("gas")?.to(),
+            max_fee_per_gas: fields.required::<U128>("maxFeePerGas")?.to(),
+            max_priority_fee_per_gas: fields.required::<U128>("maxPriorityFeePerGas")?.to(),
+            to: fields.required("to")?,
+            value: fields.required("value")?,
+            access_list: fields.required("accessList")?,
+            blob_versioned_hashes: fields.required("blobVersionedHashes")?,
+            max_fee_per_blob_gas: fields.required::<U128>("maxFeePerBlobGas")?.to(),
+            input: fields.required("input")?,
+        }),
+        4 => EthereumTypedTransaction::Eip7702(TxEip7702 {
+            chain_id: fields.required::<U64>("chainId")?.to(),
+            nonce: fields.required::<U64>("nonce")?.to(),
+            gas_limit: fields.required::<U64>("gas")?.to(),
+            max_fee_per_gas: fields.required::<U128>("maxFeePerGas")?.to(),
+            max_priority_fee_per_gas: fields.required::<U128>("maxPriorityFeePerGas")?.to(),
+            to: fields.required("to")?,
+            value: fields.required("value")?,
+            access_list: fields.required("accessList")?,
+            authorization_list: fields.required("authorizationList")?,
+            input: fields.required("input")?,
+        }),
+        ty => return Err(TransactionResponseError::UnsupportedType { hash, ty }),
+    };
+
+    let parity = match legacy_parity {
+        Some(parity) => parity,
+        None => fields.parity()?,
+    };
+    let signature = Signature::new(fields.required("r")?, fields.required("s")?, parity);
+    ensure_tx_hash(EthereumTxEnvelope::new_unhashed(typed, signature), hash)
+}
+
+/// The fields of a transaction RPC response, read one at a time.
+struct ResponseFields<'a> {
+    hash: B256,
+    fields: &'a OtherFields,
+}
+
+impl ResponseFields<'_> {
+    /// Reads `field`, which must be present.
+    fn required<T: DeserializeOwned>(
+        &self,
+        field: &'static str,
+    ) -> Result<T, TransactionResponseError> {
+        self.optional(field)?
+            .ok_or(TransactionResponseError::MissingField { hash: self.hash, field })
+    }
+
+    /// Reads `field`, which may be missing or null.
+    fn optional<T: DeserializeOwned>(
+        &self,
+        field: &'static str,
+    ) -> Result<Option<T>, TransactionResponseError> {
+        match self.fields.get_deserialized::<Option<T>>(field) {
+            Some(value) => value.map_err(|err| self.invalid(field, err.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the recipient, a missing one standing for contract creation.
+    fn kind(&self) -> Result<TxKind, TransactionResponseError> {
+        Ok(self.optional::<Address>("to")?.map_or(TxKind::Create, TxKind::Call))
+    }
+
+    /// Reads the parity of the signature of a typed transaction, from `yParity` or else `v`.
+    fn parity(&self) -> Result<bool, TransactionResponseError> {
+        if let Some(parity) = self.optional::<U8>("yParity")? {
+            return parity_bit(parity).ok_or_else(|| self.invalid("yParity", "must be 0 or 1"))
+        }
+        let v = self.required::<U8>("v")?;
+        parity_bit(v).ok_or_else(|| self.invalid("v", "must be 0 or 1"))
+    }
+
+    /// Returns the error of `field` holding an invalid value.
+    fn invalid(&self, field: &'static str, reason: impl Into<String>) -> TransactionResponseError {
+        TransactionResponseError::InvalidField { hash: self.hash, field, reason: reason.into() }
+    }
+}
+
+/// Returns the parity bit `value` stands for, if it's 0 or 1.
+fn parity_bit(value: U8) -> Option<bool> {
+    match value.to::<u8>() {
+        0 => Some(false),
+        1 => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_ethereum_primitives::{EthPrimitives, TransactionSigned};
+
+    fn any_tx(ty: &str) -> serde_json::Value {
+        serde_json::json!({
+            "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "type": ty,
+            "chainId": "0x1",
+            "nonce": "0x0",
+            "gas": "0x5208",
+            "maxFeePerGas": "0x3b9aca00",
+            "to": "0x0202020202020202020202020202020202020202",
+            "value": "0x1",
+            "accessList": [],
+            "input": "0x",
+            "r": "0x1",
+            "s": "0x1",
+            "yParity": "0x0",
+            "from": "0x0303030303030303030303030303030303030303",
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionIndex": null,
+        })
+    }
+
+    fn convert(tx: serde_json::Value) -> Result<TransactionSigned, TransactionResponseError> {
+        let tx: AnyRpcTransaction = serde_json::from_value(tx).unwrap();
+        <TransactionSigned as TryFromTransactionResponse<AnyNetwork>>::from_transaction_response(tx)
+    }
+
+    #[test]
+    fn converts_rpc_transactions_back() {
+        let signature = Signature::test_signature();
+        let signer = Address::repeat_byte(3);
+        let txs: [EthereumTypedTransaction<TxEip4844>; 5] = [
+            TxLegacy { chain_id: Some(1), gas_price: 1, to: TxKind::Create, ..Default::default() }
+                .into(),
+            TxEip2930 { chain_id: 1, gas_price: 1, ..Default::default() }.into(),
+            TxEip1559 { chain_id: 1, max_fee_per_gas: 2, ..Default::default() }.into(),
+            TxEip4844 {
+                chain_id: 1,
+                blob_versioned_hashes: vec![B256::repeat_byte(1)],
+                ..Default::default()
+            }
+            .into(),
+            TxEip7702 { chain_id: 1, ..Default::default() }.into(),
+        ];
+
+        for tx in txs {
+            let tx = TransactionSigned::new_unhashed(tx, signature);
+            let rpc_tx = alloy_rpc_types_eth::Transaction::from_transaction(
+                Recovered::new_unchecked(TxEnvelope::from(tx.clone()), signer),
+                TransactionInfo::default(),
+            );
+            let json = serde_json::to_value(&rpc_tx).unwrap();
+
+            let converted =
+                <TransactionSigned as TryFromTransactionResponse<Ethereum>>::from_transaction_response(
+                    serde_json::from_value(json.clone()).unwrap(),
+                )
+                .unwrap();
+            assert_eq!(converted, tx);
+            assert_eq!(convert(json).unwrap(), tx);
+        }
+    }
+
+    #[test]
+    fn rejects_responses_with_another_hash() {
+        let tx = TransactionSigned::new_unhashed(
+            TxEip1559 { chain_id: 1, max_fee_per_gas: 2, ..Default::default() }.into(),
+            Signature::test_signature(),
+        );
+        let rpc_tx = alloy_rpc_types_eth::Transaction::from_transaction(
+            Recovered::new_unchecked(TxEnvelope::from(tx), Address::ZERO),
+            TransactionInfo::default(),
+        );
+        let mut json = serde_json::to_value(&rpc_tx).unwrap();
+        json["hash"] = serde_json::json!(B256::repeat_byte(1));
+
+        let err =
+            <TransactionSigned as TryFromTransactionResponse<Ethereum>>::from_transaction_response(
+                serde_json::from_value(json.clone()).unwrap(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, TransactionResponseError::HashMismatch { .. }), "{err}");
+
+        let err = convert(json).unwrap_err();
+        assert!(matches!(err, TransactionResponseError::HashMismatch { .. }), "{err}");
+    }
+
+    #[test]
+    fn names_the_field_an_unknown_tx_is_missing() {
+        // Without its priority fee, the RPC types can't make sense of the transaction.
+        let tx: AnyRpcTransaction = serde_json::from_value(any_tx("0x2")).unwrap();
+        assert!(tx.as_envelope().is_none());
+
+        let err = convert(any_tx("0x2")).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                TransactionResponseError::MissingField { field: "maxPriorityFeePerGas", .. }
+            ),
+            "{err}"
+        );
+
+        let err = convert(any_tx("0x7e")).unwrap_err();
+        assert!(matches!(err, TransactionResponseError::UnsupportedType { ty: 0x7e, .. }), "{err}");
+    }
+
+    #[test]
+    fn maps_receipts_along_with_their_transactions() {
+        let converter = RpcConverter::<Ethereum, (), ()>::new(()).with_receipt_mapper(
+            |receipt: u64, tx: Recovered<&TransactionSigned>, meta: TransactionMeta| {
+                assert_eq!(*tx.tx_hash(), meta.tx_hash);
+                Ok::<_, TransactionConversionError>(receipt + meta.index)
+            },
+        );
+        let tx = TransactionSigned::new_unhashed(
+            TxLegacy::default().into(),
+            Signature::test_signature(),
+        );
+        let txs = |count: u64| {
+            (0..count)
+                .map(|index| {
+                    let meta =
+                        TransactionMeta { tx_hash: *tx.tx_hash(), index, ..Default::default() };
+                    (Recovered::new_unchecked(&tx, Address::ZERO), meta)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mapped = converter
+            .map_receipts::<EthPrimitives, _, TransactionConversionError>(vec![10, 20], txs(2))
+            .unwrap();
+        assert_eq!(mapped, [10, 21]);
+
+        let err = converter
+            .map_receipts::<EthPrimitives, _, TransactionConversionError>(vec![10, 20], txs(1))
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                TransactionConversionError::ReceiptCountMismatch { receipts: 2, transactions: 1 }
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn names_the_fields_a_tx_request_is_missing() {
+        let request = TransactionRequest {
+            to: Some(TxKind::Call(Address::ZERO)),
+            gas_price: Some(1),
+            ..Default::default()
+        };
+
+        let (tx_type, fields) = request.missing_keys().unwrap_err();
+        let err = TransactionConversionError::missing_fields(tx_type, fields).unwrap();
+        assert!(
+            matches!(
+                &err,
+                TransactionConversionError::MissingFields { tx_type: TxType::Legacy, fields }
+                    if fields == &["nonce", "gas"]
+            ),
+            "{err}"
+        );
+
+        let err = TransactionConversionError::missing_fields(
+            TxType::Eip1559,
+            vec!["max_fee_per_gas", "max_priority_fee_per_gas"],
+        )
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "EIP-1559 transaction request is missing fields: maxFeePerGas, maxPriorityFeePerGas"
+        );
+
+        assert!(
+            TransactionConversionError::missing_fields(TxType::Eip4844, vec!["sidecar"]).is_none()
+        );
+
+        // Only an unbuildable request reports its missing fields, other errors are kept as is.
+        let err = ValueError::new(request, "Transaction is not buildable");
+        assert!(matches!(
+            missing_fields_of(&err),
+            Some(TransactionConversionError::MissingFields { tx_type: TxType::Legacy, .. })
+        ));
+        let err = TransactionConversionError::Other("unsupported".to_string());
+        assert!(missing_fields_of(&err).is_none());
+    }
+}
@@ -5,14 +5,12 @@ use crate::{
     node::RpcNodeCoreExt, EthApiTypes, FromEthApiError, FullEthApiTypes, RpcBlock, RpcNodeCore,
     RpcReceipt,
 };
-use alloy_consensus::{transaction::TxHashRef, TxReceipt};
 use alloy_eips::BlockId;
-use alloy_rlp::Encodable;
-use alloy_rpc_types_eth::{Block, BlockTransactions, Index};
+use alloy_rpc_types_eth::Index;
 use futures::Future;
 use reth_node_api::BlockBody;
-use reth_primitives_traits::{AlloyBlockHeader, RecoveredBlock, SealedHeader, TransactionMeta};
-use reth_rpc_convert::{transaction::ConvertReceiptInput, RpcConvert, RpcHeader};
+use reth_primitives_traits::RecoveredBlock;
+use reth_rpc_convert::{RpcConvert, RpcHeader};
 use reth_storage_api::{BlockIdReader, BlockReader, ProviderHeader, ProviderReceipt, ProviderTx};
 use reth_transaction_pool::{PoolTransaction, TransactionPool};
 use std::sync::Arc;
@@ -62,12 +60,7 @@ pub trait EthBlocks: LoadBlock<RpcConvert: RpcConvert<Primitives = Self::Primiti
         async move {
             let Some(block) = self.recovered_block(block_id).await? else { return Ok(None) };
 
-            let block = block.clone_into_rpc_block(
-                full.into(),
-                |tx, tx_info| self.converter().fill(tx, tx_info),
-                |header, size| self.converter().convert_header(header, size),
-            )?;
-            Ok(Some(block))
+            Ok(Some(self.converter().convert_block_shared(block, full.into())?))
         }
     }
 
@@ -93,50 +86,9 @@ pub trait EthBlocks: LoadBlock<RpcConvert: RpcConvert<Primitives = Self::Primiti
     {
         async move {
             if let Some((block, receipts)) = self.load_block_and_receipts(block_id).await? {
-                let block_number = block.number();
-                let base_fee = block.base_fee_per_gas();
-                let block_hash = block.hash();
-                let excess_blob_gas = block.excess_blob_gas();
-                let timestamp = block.timestamp();
-                let mut gas_used = 0;
-                let mut next_log_index = 0;
-
-                let inputs = block
-                    .transactions_recovered()
-                    .zip(Arc::unwrap_or_clone(receipts))
-                    .enumerate()
-                    .map(|(idx, (tx, receipt))| {
-                        let meta = TransactionMeta {
-                            tx_hash: *tx.tx_hash(),
-                            index: idx as u64,
-                            block_hash,
-                            block_number,
-                            base_fee,
-                            excess_blob_gas,
-                            timestamp,
-                        };
-
-                        let cumulative_gas_used = receipt.cumulative_gas_used();
-                        let logs_len = receipt.logs().len();
-
-                        let input = ConvertReceiptInput {
-                            tx,
-                            gas_used: cumulative_gas_used - gas_used,
-                            next_log_index,
-                            meta,
-                            receipt,
-                        };
-
-                        gas_used = cumulative_gas_used;
-                        next_log_index += logs_len;
-
-                        input
-                    })
-                    .collect::<Vec<_>>();
-
                 return Ok(self
                     .converter()
-                    .convert_receipts_with_block(inputs, block.sealed_block())
+                    .convert_block_receipts(&block, Arc::unwrap_or_clone(receipts))
                     .map(Some)?)
             }
 
@@ -228,21 +180,9 @@ pub trait EthBlocks: LoadBlock<RpcConvert: RpcConvert<Primitives = Self::Primiti
             uncles
                 .into_iter()
                 .nth(index.into())
-                .map(|header| {
-                    let block =
-                        alloy_consensus::Block::<alloy_consensus::TxEnvelope, _>::uncle(header);
-                    let size = block.length();
-                    let header = self
-                        .converter()
-                        .convert_header(SealedHeader::new_unhashed(block.header), size)?;
-                    Ok(Block {
-                        uncles: vec![],
-                        header,
-                        transactions: BlockTransactions::Uncle,
-                        withdrawals: None,
-                    })
-                })
+                .map(|header| self.converter().convert_uncle(header))
                 .transpose()
+                .map_err(Self::Error::from)
         }
     }
 }
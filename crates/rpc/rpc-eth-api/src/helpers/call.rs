@@ -31,7 +31,7 @@ use reth_revm::{
     database::StateProviderDatabase,
     db::{bal::EvmDatabaseError, State},
 };
-use reth_rpc_convert::{RpcConvert, RpcTxReq};
+use reth_rpc_convert::{apply_evm_overrides, RpcConvert, RpcTxReq};
 use reth_rpc_eth_types::{
     cache::db::StateProviderTraitObjWrapper,
     error::{AsEthApiError, FromEthApiError},
@@ -891,13 +891,8 @@ pub trait Call:
         // set nonce to None so that the correct nonce is chosen by the EVM
         request.as_mut().take_nonce();
 
-        if let Some(block_overrides) = overrides.block {
-            apply_block_overrides(*block_overrides, db, evm_env.block_env.inner_mut());
-        }
-        if let Some(state_overrides) = overrides.state {
-            apply_state_overrides(state_overrides, db)
-                .map_err(EthApiError::from_state_overrides_err)?;
-        }
+        apply_evm_overrides(overrides, db, evm_env.block_env.inner_mut())
+            .map_err(EthApiError::from_state_overrides_err)?;
 
         let mut tx_env = self.create_txn_env(&evm_env, request, &mut *db)?;
 
@@ -2,7 +2,6 @@
 
 use super::{Call, LoadPendingBlock};
 use crate::{AsEthApiError, FromEthApiError, IntoEthApiError};
-use alloy_evm::overrides::{apply_block_overrides, apply_state_overrides};
 use alloy_network::TransactionBuilder;
 use alloy_primitives::{TxKind, U256};
 use alloy_rpc_types_eth::{state::EvmOverrides, BlockId};
@@ -17,7 +16,7 @@ use reth_revm::{
     database::{EvmStateProvider, StateProviderDatabase},
     db::{bal::EvmDatabaseError, State},
 };
-use reth_rpc_convert::{RpcConvert, RpcTxReq};
+use reth_rpc_convert::{apply_evm_overrides, RpcConvert, RpcTxReq};
 use reth_rpc_eth_types::{
     error::{
         api::{FromEvmHalt, FromRevert},
@@ -84,14 +83,8 @@ pub trait EstimateCall: Call {
         // overrides for `gasLimit`, `baseFee` and `blobBaseFee` are visible to estimation.
         // Mirrors geth's behavior, see:
         // <https://github.com/ethereum/go-ethereum/pull/30695>
-        if let Some(block_overrides) = overrides.block {
-            apply_block_overrides(*block_overrides, &mut db, evm_env.block_env.inner_mut());
-        }
-
-        // Apply any state overrides if specified.
-        if let Some(state_override) = overrides.state {
-            apply_state_overrides(state_override, &mut db).map_err(Self::Error::from_eth_err)?;
-        }
+        apply_evm_overrides(overrides, &mut db, evm_env.block_env.inner_mut())
+            .map_err(Self::Error::from_eth_err)?;
 
         // the gas limit of the corresponding block
         let block_gas_limit = evm_env.block_env.gas_limit();
@@ -9,8 +9,11 @@ use alloy_rpc_types_eth::{BlockNumberOrTag, FeeHistory};
 use futures::{Future, StreamExt};
 use reth_chainspec::{ChainSpecProvider, EthChainSpec};
 use reth_primitives_traits::BlockBody;
+use reth_rpc_convert::{gas_used_ratio, valid_reward_percentiles, FeeHistoryBuilder};
 use reth_rpc_eth_types::{
-    fee_history::calculate_reward_percentiles_for_block, utils::checked_blob_gas_used_ratio,
+    fee_history::calculate_reward_percentiles_for_block,
+    gas_price::{next_block_base_fee, next_block_blob_gas_price},
+    utils::checked_blob_gas_used_ratio,
     EthApiError, FeeHistoryCache, FeeHistoryEntry, GasPriceOracle, RpcInvalidTransactionError,
 };
 use reth_storage_api::{
@@ -86,8 +89,7 @@ pub trait EthFees:
             // increasing and 0 <= p <= 100
             // Note: The types used ensure that the percentiles are never < 0
             if let Some(percentiles) = &reward_percentiles &&
-                (percentiles.iter().any(|p| *p < 0.0 || *p > 100.0) ||
-                    percentiles.windows(2).any(|w| w[0] > w[1]))
+                !valid_reward_percentiles(percentiles)
             {
                 return Err(EthApiError::InvalidRewardPercentiles.into())
             }
@@ -145,18 +147,8 @@ pub trait EthFees:
             let start_block = end_block_plus - block_count;
 
             // Collect base fees, gas usage ratios and (optionally) reward percentile data
-            // Pre-allocate capacity: base_fee and blob_fee need +1 for the next block's fee
-            let mut base_fee_per_gas: Vec<u128> = Vec::with_capacity(block_count as usize + 1);
-            let mut gas_used_ratio: Vec<f64> = Vec::with_capacity(block_count as usize);
-
-            let mut base_fee_per_blob_gas: Vec<u128> = Vec::with_capacity(block_count as usize + 1);
-            let mut blob_gas_used_ratio: Vec<f64> = Vec::with_capacity(block_count as usize);
-
-            let mut rewards: Vec<Vec<u128>> = if reward_percentiles.is_some() {
-                Vec::with_capacity(block_count as usize)
-            } else {
-                Vec::new()
-            };
+            let mut history =
+                FeeHistoryBuilder::new(start_block, block_count, reward_percentiles.is_some());
 
             // Check if the requested range is within the cache bounds
             let fee_entries = self.fee_history_cache().get_history(start_block, end_block).await;
@@ -167,35 +159,34 @@ pub trait EthFees:
                 }
 
                 for entry in &fee_entries {
-                    base_fee_per_gas
-                        .push(entry.header.base_fee_per_gas().unwrap_or_default() as u128);
-                    gas_used_ratio.push(entry.gas_used_ratio);
-                    base_fee_per_blob_gas.push(entry.base_fee_per_blob_gas.unwrap_or_default());
-                    blob_gas_used_ratio.push(entry.blob_gas_used_ratio);
+                    history.push_block(
+                        entry.header.base_fee_per_gas().unwrap_or_default() as u128,
+                        entry.gas_used_ratio,
+                        entry.base_fee_per_blob_gas.unwrap_or_default(),
+                        entry.blob_gas_used_ratio,
+                    );
 
                     if let Some(percentiles) = &reward_percentiles {
                         let mut block_rewards = Vec::with_capacity(percentiles.len());
                         for &percentile in percentiles {
                             block_rewards.push(self.approximate_percentile(entry, percentile));
                         }
-                        rewards.push(block_rewards);
+                        history.push_rewards(block_rewards);
                     }
                 }
                 let last_entry = fee_entries.last().expect("is not empty");
 
                 // Also need to include the `base_fee_per_gas` and `base_fee_per_blob_gas` for the
                 // next block
-                base_fee_per_gas.push(
-                    self.provider()
-                        .chain_spec()
-                        .next_block_base_fee(&last_entry.header, last_entry.header.timestamp())
+                Ok(history.build(
+                    next_block_base_fee(&*self.provider().chain_spec(), &last_entry.header)
                         .unwrap_or_default() as u128,
-                );
-
-                base_fee_per_blob_gas.push(last_entry.next_block_blob_fee().unwrap_or_default());
+                    last_entry.next_block_blob_fee().unwrap_or_default(),
+                ))
             } else {
                 // read the requested header range
-                let headers = self.provider()
+                let headers = self
+                    .provider()
                     .sealed_headers_range(start_block..=end_block)
                     .map_err(Self::Error::from_eth_err)?;
                 if headers.len() != block_count as usize {
@@ -204,26 +195,26 @@ pub trait EthFees:
 
                 let chain_spec = self.provider().chain_spec();
                 for header in &headers {
-                    base_fee_per_gas.push(header.base_fee_per_gas().unwrap_or_default() as u128);
-                    gas_used_ratio.push(header.gas_used() as f64 / header.gas_limit() as f64);
-
                     let blob_params = chain_spec
                         .blob_params_at_timestamp(header.timestamp())
                         .unwrap_or_else(BlobParams::cancun);
 
-                    base_fee_per_blob_gas.push(header.blob_fee(blob_params).unwrap_or_default());
-                    blob_gas_used_ratio.push(checked_blob_gas_used_ratio(
-                        header.blob_gas_used().unwrap_or_default(),
-                        blob_params.max_blob_gas_per_block(),
-                    ));
+                    history.push_block(
+                        header.base_fee_per_gas().unwrap_or_default() as u128,
+                        gas_used_ratio(header.gas_used(), header.gas_limit()),
+                        header.blob_fee(blob_params).unwrap_or_default(),
+                        checked_blob_gas_used_ratio(
+                            header.blob_gas_used().unwrap_or_default(),
+                            blob_params.max_blob_gas_per_block(),
+                        ),
+                    );
                 }
 
                 if let Some(percentiles) = reward_percentiles.as_ref().filter(|p| !p.is_empty()) {
                     let hashes: Vec<_> = headers.iter().map(|h| h.hash()).collect();
-                    let mut stream =
-                        futures::stream::iter(hashes)
-                            .map(|hash| self.cache().get_block_and_receipts(hash))
-                            .buffered(4);
+                    let mut stream = futures::stream::iter(hashes)
+                        .map(|hash| self.cache().get_block_and_receipts(hash))
+                        .buffered(4);
                     let mut header_idx = 0;
                     while let Some(result) = stream.next().await {
                         let header = &headers[header_idx];
@@ -231,7 +222,7 @@ pub trait EthFees:
                         let (block, receipts) = result
                             .map_err(Self::Error::from_eth_err)?
                             .ok_or(EthApiError::InvalidBlockRange)?;
-                        rewards.push(
+                        history.push_rewards(
                             calculate_reward_percentiles_for_block(
                                 percentiles,
                                 header.base_fee_per_gas().unwrap_or_default(),
@@ -249,29 +240,15 @@ pub trait EthFees:
                 //
                 // The unwrap is safe since we checked earlier that we got at least 1 header.
                 let last_header = headers.last().expect("is present");
-                base_fee_per_gas.push(
-                    chain_spec
-                        .next_block_base_fee(last_header.header(), last_header.timestamp())
-                        .unwrap_or_default() as u128,
-                );
-                // Same goes for the `base_fee_per_blob_gas`:
-                // > "[..] includes the next block after the newest of the returned range, because this value can be derived from the newest block.
-                base_fee_per_blob_gas.push(
-                    last_header
-                    .maybe_next_block_blob_fee(
-                        chain_spec.blob_params_at_timestamp(last_header.timestamp())
-                    ).unwrap_or_default()
-                );
-            };
-
-            Ok(FeeHistory {
-                base_fee_per_gas,
-                gas_used_ratio,
-                base_fee_per_blob_gas,
-                blob_gas_used_ratio,
-                oldest_block: start_block,
-                reward: reward_percentiles.map(|_| rewards),
-            })
+                Ok(history.build(
+                    next_block_base_fee(&*chain_spec, last_header.header()).unwrap_or_default()
+                        as u128,
+                    // Same goes for the `base_fee_per_blob_gas`:
+                    // > "[..] includes the next block after the newest of the returned range, because this value can be derived from the newest block.
+                    next_block_blob_gas_price(&*chain_spec, last_header.header())
+                        .unwrap_or_default(),
+                ))
+            }
         }
     }
 
@@ -347,13 +324,11 @@ where
                         .latest_header()
                         .map_err(Self::Error::from_eth_err)?
                         .ok_or(EthApiError::HeaderNotFound(BlockNumberOrTag::Latest.into()))?;
-                    let pending_base_fee = self
-                        .provider()
-                        .chain_spec()
-                        .next_block_base_fee(&latest, latest.timestamp())
-                        .ok_or(EthApiError::InvalidTransaction(
-                            RpcInvalidTransactionError::TxTypeNotSupported,
-                        ))?;
+                    let pending_base_fee =
+                        next_block_base_fee(&*self.provider().chain_spec(), latest.header())
+                            .ok_or(EthApiError::InvalidTransaction(
+                                RpcInvalidTransactionError::TxTypeNotSupported,
+                            ))?;
                     U256::from(pending_base_fee)
                 }
             };
@@ -397,11 +372,7 @@ where
             self.provider()
                 .latest_header()
                 .map_err(Self::Error::from_eth_err)?
-                .and_then(|h| {
-                    h.maybe_next_block_blob_fee(
-                        self.provider().chain_spec().blob_params_at_timestamp(h.timestamp()),
-                    )
-                })
+                .and_then(|h| next_block_blob_gas_price(&*self.provider().chain_spec(), h.header()))
                 .ok_or(EthApiError::ExcessBlobGasNotSet.into())
                 .map(U256::from)
         }
@@ -415,11 +386,7 @@ where
                 .latest_header()
                 .map_err(Self::Error::from_eth_err)?
                 .ok_or(EthApiError::HeaderNotFound(BlockNumberOrTag::Latest.into()))?;
-            Ok(self
-                .provider()
-                .chain_spec()
-                .next_block_base_fee(&header, header.timestamp())
-                .map(U256::from))
+            Ok(next_block_base_fee(&*self.provider().chain_spec(), header.header()).map(U256::from))
         }
     }
 
@@ -13,15 +13,18 @@ use alloy_consensus::{
 };
 use alloy_dyn_abi::TypedData;
 use alloy_eips::{eip2718::Encodable2718, BlockId};
-use alloy_network::{TransactionBuilder, TransactionBuilder4844};
-use alloy_primitives::{Address, Bytes, TxHash, B256, U256};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, Bytes, TxHash, B256};
 use alloy_rpc_types_eth::{state::EvmOverrides, TransactionInfo};
 use futures::{Future, StreamExt};
 use reth_chain_state::CanonStateSubscriptions;
 use reth_primitives_traits::{
     BlockBody, Recovered, RecoveredBlock, SignedTransaction, TxTy, WithEncoded,
 };
-use reth_rpc_convert::{transaction::RpcConvert, RpcTxReq, TransactionConversionError};
+use reth_rpc_convert::{
+    fill_transaction_request, transaction::RpcConvert, FillTransactionError, RpcTxReq,
+    TransactionConversionError, TransactionDefaults,
+};
 use reth_rpc_eth_types::{
     block::convert_transaction_receipt,
     utils::binary_search,
@@ -186,10 +189,14 @@ pub trait EthTransactions: LoadTransaction<Provider: BlockReaderIdExt> {
 
     /// Returns all transactions from the local pending pool.
     fn pending_transactions(&self) -> Result<Vec<RpcTransaction<Self::NetworkTypes>>, Self::Error> {
+        let base_fee = Some(self.pool().block_info().pending_basefee);
         self.pool()
             .pending_transactions()
             .into_iter()
-            .map(|tx| self.converter().fill_pending(tx.transaction.clone_into_consensus()))
+            .map(|tx| {
+                self.converter()
+                    .fill_pending_with_base_fee(tx.transaction.clone_into_consensus(), base_fee)
+            })
             .collect::<Result<Vec<_>, _>>()
             .map_err(Self::Error::from)
     }
@@ -401,7 +408,10 @@ pub trait EthTransactions: LoadTransaction<Provider: BlockReaderIdExt> {
                     RpcNodeCore::pool(self).get_transaction_by_sender_and_nonce(sender, nonce)
             {
                 let transaction = tx.transaction.clone_into_consensus();
-                return Ok(Some(self.converter().fill_pending(transaction)?));
+                let base_fee = Some(RpcNodeCore::pool(self).block_info().pending_basefee);
+                return Ok(Some(
+                    self.converter().fill_pending_with_base_fee(transaction, base_fee)?,
+                ));
             }
 
             // Note: we can't optimize for contracts (account with code) and cannot shortcircuit if
@@ -537,69 +547,24 @@ pub trait EthTransactions: LoadTransaction<Provider: BlockReaderIdExt> {
     /// Fills the defaults on a given unsigned transaction.
     fn fill_transaction(
         &self,
-        mut request: RpcTxReq<Self::NetworkTypes>,
+        request: RpcTxReq<Self::NetworkTypes>,
     ) -> impl Future<Output = Result<FillTransaction<TxTy<Self::Primitives>>, Self::Error>> + Send
     where
         Self: EthApiSpec + LoadBlock + EstimateCall + LoadFee,
     {
         async move {
-            if request.as_ref().value().is_none() {
-                request.as_mut().set_value(U256::ZERO);
-            }
-
-            if request.as_ref().nonce().is_none() {
-                let nonce = self.next_available_nonce_for(&request).await?;
-                request.as_mut().set_nonce(nonce);
-            }
-
-            let chain_id = self.chain_id();
-            request.as_mut().set_chain_id(chain_id.to());
-
-            if request.as_ref().has_eip4844_fields() &&
-                request.as_ref().max_fee_per_blob_gas().is_none()
-            {
-                let blob_fee = self.blob_base_fee().await?;
-                request.as_mut().set_max_fee_per_blob_gas(blob_fee.to());
-            }
-
-            // Use `sidecar.is_some()` instead of `blob_sidecar().is_some()` to handle
-            // both EIP-4844 (v0) and EIP-7594 (v1) sidecar formats
-            if request.as_ref().sidecar.is_some() &&
-                request.as_ref().blob_versioned_hashes.is_none()
-            {
-                request.as_mut().populate_blob_hashes();
-            }
-
-            if request.as_ref().gas_limit().is_none() {
-                let estimated_gas = self
-                    .estimate_gas_at(request.clone(), BlockId::pending(), EvmOverrides::default())
-                    .await?;
-                request.as_mut().set_gas_limit(estimated_gas.to());
-            }
-
-            if request.as_ref().gas_price().is_none() {
-                let tip = if let Some(tip) = request.as_ref().max_priority_fee_per_gas() {
-                    tip
-                } else {
-                    let tip = self.suggested_priority_fee().await?.to::<u128>();
-                    request.as_mut().set_max_priority_fee_per_gas(tip);
-                    tip
-                };
-                if request.as_ref().max_fee_per_gas().is_none() {
-                    let header =
-                        self.provider().latest_header().map_err(Self::Error::from_eth_err)?;
-                    let base_fee = header.and_then(|h| h.base_fee_per_gas()).unwrap_or_default();
-                    // Use `2 * base_fee` as headroom, matching go-ethereum's
-                    // `setLondonFeeDefaults`, so the transaction does not
-                    // become invalid if the base fee rises before it is
-                    // included. This does not increase the effective price the sender pays:
-                    // `max_fee_per_gas` is only an upper bound and the sender still pays
-                    // `base_fee + min(tip, max_fee_per_gas - base_fee)`.
-                    request.as_mut().set_max_fee_per_gas(base_fee as u128 * 2 + tip);
-                }
-            }
+            let filled = fill_transaction_request(
+                request,
+                self.chain_id().to(),
+                &EthTransactionDefaults(self),
+            )
+            .await
+            .map_err(|err| match err {
+                FillTransactionError::Lookup(err) => err,
+                FillTransactionError::Conversion(err) => Self::Error::from_eth_err(err),
+            })?;
 
-            let tx = self.converter().build_simulate_v1_transaction(request)?;
+            let tx = self.converter().build_simulate_v1_transaction(filled.request)?;
 
             let raw = tx.encoded_2718().into();
 
@@ -682,6 +647,51 @@ pub trait EthTransactions: LoadTransaction<Provider: BlockReaderIdExt> {
     }
 }
 
+/// Looks up the defaults of `eth_fillTransaction` with an `eth_` API.
+#[derive(Debug)]
+struct EthTransactionDefaults<'a, T>(&'a T);
+
+impl<T> TransactionDefaults<RpcTxReq<T::NetworkTypes>> for EthTransactionDefaults<'_, T>
+where
+    T: EthTransactions + EthApiSpec + LoadBlock + EstimateCall + LoadFee,
+{
+    type Error = T::Error;
+
+    fn nonce(
+        &self,
+        request: &RpcTxReq<T::NetworkTypes>,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        let request = request.clone();
+        async move { self.0.next_available_nonce_for(&request).await }
+    }
+
+    fn blob_base_fee(&self) -> impl Future<Output = Result<u128, Self::Error>> + Send {
+        async move { Ok(self.0.blob_base_fee().await?.to()) }
+    }
+
+    fn gas_limit(
+        &self,
+        request: &RpcTxReq<T::NetworkTypes>,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        let request = request.clone();
+        async move {
+            let overrides = EvmOverrides::default();
+            Ok(self.0.estimate_gas_at(request, BlockId::pending(), overrides).await?.to())
+        }
+    }
+
+    fn max_priority_fee_per_gas(&self) -> impl Future<Output = Result<u128, Self::Error>> + Send {
+        async move { Ok(self.0.suggested_priority_fee().await?.to()) }
+    }
+
+    fn base_fee(&self) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        async move {
+            let header = self.0.provider().latest_header().map_err(T::Error::from_eth_err)?;
+            Ok(header.and_then(|h| h.base_fee_per_gas()).unwrap_or_default())
+        }
+    }
+}
+
 /// Loads a transaction from database.
 ///
 /// Behaviour shared by several `eth_` RPC methods, not exclusive to `eth_` transactions RPC
@@ -22,7 +22,7 @@ use reth_storage_api::BlockReaderIdExt;
 use serde::{Deserialize, Serialize};
 use tracing::trace;
 
-use crate::utils::checked_blob_gas_used_ratio;
+use crate::{gas_price::blob_gas_price, utils::checked_blob_gas_used_ratio};
 
 use super::{EthApiError, EthStateCache};
 
@@ -375,9 +375,7 @@ where
         Self {
             header: block.header().clone(),
             gas_used_ratio: header.gas_used() as f64 / header.gas_limit() as f64,
-            base_fee_per_blob_gas: header
-                .excess_blob_gas()
-                .and_then(|excess_blob_gas| Some(blob_params?.calc_blob_fee(excess_blob_gas))),
+            base_fee_per_blob_gas: blob_gas_price(header.excess_blob_gas(), blob_params),
             blob_gas_used_ratio: checked_blob_gas_used_ratio(
                 block.body().blob_gas_used(),
                 blob_params
@@ -396,8 +394,7 @@ where
     ///
     /// See also [`Self::next_block_excess_blob_gas`]
     pub fn next_block_blob_fee(&self) -> Option<u128> {
-        self.next_block_excess_blob_gas()
-            .and_then(|excess_blob_gas| Some(self.blob_params?.calc_blob_fee(excess_blob_gas)))
+        blob_gas_price(self.next_block_excess_blob_gas(), self.blob_params)
     }
 
     /// Calculate excess blob gas for the next block according to the EIP-4844 spec.
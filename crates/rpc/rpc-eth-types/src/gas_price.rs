@@ -0,0 +1,85 @@
+//! Gas price formulas of the `eth_` namespace, so RPC handlers and downstream indexers derive the
+//! fees of a block or transaction the same way.
+
+use alloy_consensus::{BlockHeader, Transaction};
+use alloy_eips::eip7840::BlobParams;
+use reth_chainspec::EthChainSpec;
+
+/// Returns the price per gas `tx` pays in a block with `base_fee`.
+///
+/// Dynamic fee transactions pay the base fee plus their priority fee, capped at their max fee,
+/// see EIP-1559. Other transactions pay their gas price.
+pub fn effective_gas_price(tx: &impl Transaction, base_fee: Option<u64>) -> u128 {
+    tx.effective_gas_price(base_fee)
+}
+
+/// Returns the price per blob gas of a block with `excess_blob_gas` under `blob_params`, see
+/// EIP-4844.
+///
+/// Returns `None` if either is missing, as for blocks before Cancun.
+pub fn blob_gas_price(
+    excess_blob_gas: Option<u64>,
+    blob_params: Option<BlobParams>,
+) -> Option<u128> {
+    Some(blob_params?.calc_blob_fee(excess_blob_gas?))
+}
+
+/// Returns the base fee of the block after `parent`, with the base fee params `chain_spec` has
+/// active at the timestamp of `parent`.
+///
+/// Returns `None` if `parent` has no base fee, as before London.
+pub fn next_block_base_fee<C: EthChainSpec>(chain_spec: &C, parent: &C::Header) -> Option<u64> {
+    chain_spec.next_block_base_fee(parent, parent.timestamp())
+}
+
+/// Returns the price per blob gas of the block after `parent`, with the blob params `chain_spec`
+/// has active at the timestamp of `parent`.
+///
+/// Returns `None` if `parent` has no excess blob gas or no blob params are active, as before
+/// Cancun.
+pub fn next_block_blob_gas_price<C: EthChainSpec>(
+    chain_spec: &C,
+    parent: &C::Header,
+) -> Option<u128> {
+    parent.maybe_next_block_blob_fee(chain_spec.blob_params_at_timestamp(parent.timestamp()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Header, TxEip1559, TxLegacy};
+    use reth_chainspec::MAINNET;
+
+    #[test]
+    fn effective_gas_price_caps_the_tip_at_the_max_fee() {
+        let tx =
+            TxEip1559 { max_fee_per_gas: 30, max_priority_fee_per_gas: 5, ..Default::default() };
+        assert_eq!(effective_gas_price(&tx, Some(10)), 15);
+        assert_eq!(effective_gas_price(&tx, Some(28)), 30);
+        assert_eq!(
+            effective_gas_price(&TxLegacy { gas_price: 7, ..Default::default() }, Some(3)),
+            7
+        );
+    }
+
+    #[test]
+    fn blob_gas_price_needs_excess_blob_gas_and_params() {
+        let params = BlobParams::cancun();
+        assert_eq!(blob_gas_price(Some(0), Some(params)), Some(1));
+        assert_eq!(blob_gas_price(None, Some(params)), None);
+        assert_eq!(blob_gas_price(Some(0), None), None);
+    }
+
+    #[test]
+    fn next_block_fees_follow_the_parent() {
+        let parent = Header {
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+        assert_eq!(next_block_base_fee(&**MAINNET, &parent), Some(1_000_000_000));
+        assert_eq!(next_block_base_fee(&**MAINNET, &Header::default()), None);
+        assert_eq!(next_block_blob_gas_price(&**MAINNET, &parent), None);
+    }
+}
@@ -18,6 +18,7 @@ pub mod capabilities;
 pub mod error;
 pub mod fee_history;
 pub mod gas_oracle;
+pub mod gas_price;
 pub mod id_provider;
 pub mod logs_utils;
 pub mod pending_block;
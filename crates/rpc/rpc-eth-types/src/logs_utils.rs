@@ -5,10 +5,11 @@
 use alloy_consensus::{transaction::TxHashRef, TxReceipt};
 use alloy_eips::BlockNumHash;
 use alloy_primitives::TxHash;
-use alloy_rpc_types_eth::{Filter, Log};
+use alloy_rpc_types_eth::{Filter, Log, TransactionInfo};
 use reth_chainspec::ChainInfo;
 use reth_errors::ProviderError;
 use reth_primitives_traits::{BlockBody, RecoveredBlock, SignedTransaction};
+use reth_rpc_convert::into_rpc_log;
 use reth_storage_api::{BlockReader, ProviderBlock};
 use std::sync::Arc;
 use thiserror::Error;
@@ -37,18 +38,16 @@ where
     for (receipt_idx, (tx_hash, receipt)) in tx_hashes_and_receipts.into_iter().enumerate() {
         for log in receipt.logs() {
             if filter.matches(log) {
-                let log = Log {
-                    inner: log.clone(),
+                let tx_info = TransactionInfo {
+                    hash: Some(tx_hash),
+                    // The transaction and receipt index is always the same.
+                    index: Some(receipt_idx as u64),
                     block_hash: Some(block_num_hash.hash),
                     block_number: Some(block_num_hash.number),
-                    transaction_hash: Some(tx_hash),
-                    // The transaction and receipt index is always the same.
-                    transaction_index: Some(receipt_idx as u64),
-                    log_index: Some(log_index),
-                    removed,
                     block_timestamp: Some(block_timestamp),
+                    base_fee: None,
                 };
-                all_logs.push(log);
+                all_logs.push(into_rpc_log(log.clone(), tx_info, log_index, removed));
             }
             log_index += 1;
         }
@@ -131,18 +130,16 @@ where
                     };
                 }
 
-                let log = Log {
-                    inner: log.clone(),
+                let tx_info = TransactionInfo {
+                    hash: transaction_hash,
+                    // The transaction and receipt index is always the same.
+                    index: Some(receipt_idx as u64),
                     block_hash: Some(block_num_hash.hash),
                     block_number: Some(block_num_hash.number),
-                    transaction_hash,
-                    // The transaction and receipt index is always the same.
-                    transaction_index: Some(receipt_idx as u64),
-                    log_index: Some(log_index),
-                    removed,
                     block_timestamp: Some(block_timestamp),
+                    base_fee: None,
                 };
-                all_logs.push(log);
+                all_logs.push(into_rpc_log(log.clone(), tx_info, log_index, removed));
             }
             log_index += 1;
         }
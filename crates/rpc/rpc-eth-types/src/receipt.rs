@@ -1,14 +1,20 @@
 //! RPC receipt response builder, extends a layer one receipt with layer two data.
 
-use crate::EthApiError;
+use crate::{
+    gas_price::{blob_gas_price, effective_gas_price},
+    EthApiError,
+};
 use alloy_consensus::{ReceiptEnvelope, Transaction};
 use alloy_eips::eip7840::BlobParams;
 use alloy_primitives::{Address, TxKind};
-use alloy_rpc_types_eth::{Log, TransactionReceipt};
+use alloy_rpc_types_eth::{Log, TransactionInfo, TransactionReceipt};
 use reth_chainspec::EthChainSpec;
 use reth_ethereum_primitives::Receipt;
 use reth_primitives_traits::{NodePrimitives, TransactionMeta};
-use reth_rpc_convert::transaction::{ConvertReceiptInput, ReceiptConverter};
+use reth_rpc_convert::{
+    into_rpc_log,
+    transaction::{ConvertReceiptInput, ReceiptConverter},
+};
 use std::sync::Arc;
 
 /// Builds an [`TransactionReceipt`] obtaining the inner receipt envelope from the given closure.
@@ -26,7 +32,7 @@ where
     let blob_gas_used = tx.blob_gas_used();
     // Blob gas price should only be present if the transaction is a blob transaction
     let blob_gas_price =
-        blob_gas_used.and_then(|_| Some(blob_params?.calc_blob_fee(meta.excess_blob_gas?)));
+        blob_gas_used.and_then(|_| blob_gas_price(meta.excess_blob_gas, blob_params));
 
     let (contract_address, to) = match tx.kind() {
         TxKind::Create => (Some(from.create(tx.nonce())), None),
@@ -43,7 +49,7 @@ where
         to,
         gas_used,
         contract_address,
-        effective_gas_price: tx.effective_gas_price(meta.base_fee),
+        effective_gas_price: effective_gas_price(*tx, meta.base_fee),
         // EIP-4844 fields
         blob_gas_price,
         blob_gas_used,
@@ -79,21 +85,20 @@ impl<ChainSpec> EthReceiptConverter<ChainSpec> {
         Self {
             chain_spec,
             build_rpc_receipt: |receipt: Receipt, next_log_index, meta: TransactionMeta| {
+                let tx_info = TransactionInfo {
+                    hash: Some(meta.tx_hash),
+                    index: Some(meta.index),
+                    block_hash: Some(meta.block_hash),
+                    block_number: Some(meta.block_number),
+                    block_timestamp: Some(meta.timestamp),
+                    base_fee: meta.base_fee,
+                };
                 let mut log_index = next_log_index;
                 receipt
                     .map_logs(|log| {
                         let idx = log_index;
                         log_index += 1;
-                        Log {
-                            inner: log,
-                            block_hash: Some(meta.block_hash),
-                            block_number: Some(meta.block_number),
-                            block_timestamp: Some(meta.timestamp),
-                            transaction_hash: Some(meta.tx_hash),
-                            transaction_index: Some(meta.index),
-                            log_index: Some(idx as u64),
-                            removed: false,
-                        }
+                        into_rpc_log(log, tx_info, idx as u64, false)
                     })
                     .into()
             },
@@ -12,7 +12,7 @@ use alloy_network::{NetworkTransactionBuilder, TransactionBuilder};
 use alloy_rpc_types_eth::{
     simulate::{SimBlock, SimCallResult, SimulateError, SimulatedBlock},
     state::StateOverride,
-    BlockId, BlockOverrides, BlockTransactionsKind,
+    BlockId, BlockOverrides, BlockTransactionsKind, TransactionInfo,
 };
 use jsonrpsee_types::{error::INTERNAL_ERROR_CODE, ErrorObject};
 use reth_evm::{
@@ -22,7 +22,7 @@ use reth_evm::{
 use reth_primitives_traits::{
     BlockBody as _, BlockTy, NodePrimitives, Recovered, RecoveredBlock, SealedHeader,
 };
-use reth_rpc_convert::{RpcBlock, RpcConvert, RpcTxReq};
+use reth_rpc_convert::{into_rpc_log, RpcBlock, RpcConvert, RpcTxReq};
 use reth_rpc_server_types::result::{block_id_to_str, rpc_err};
 use reth_storage_api::{noop::NoopProvider, StateProvider};
 use revm::{
@@ -544,17 +544,16 @@ where
                 logs: logs
                     .into_iter()
                     .map(|log| {
-                        log_index += 1;
-                        alloy_rpc_types_eth::Log {
-                            inner: log,
-                            log_index: Some(log_index - 1),
-                            transaction_index: Some(index as u64),
-                            transaction_hash: Some(*tx.tx_hash()),
+                        let tx_info = TransactionInfo {
+                            hash: Some(*tx.tx_hash()),
+                            index: Some(index as u64),
                             block_hash: Some(block.hash()),
                             block_number: Some(block.header().number()),
                             block_timestamp: Some(block.header().timestamp()),
-                            ..Default::default()
-                        }
+                            base_fee: None,
+                        };
+                        log_index += 1;
+                        into_rpc_log(log, tx_info, log_index - 1, false)
                     })
                     .collect(),
                 status: true,
@@ -564,11 +563,7 @@ where
         calls.push(call);
     }
 
-    let block = block.into_rpc_block(
-        txs_kind,
-        |tx, tx_info| converter.fill(tx, tx_info),
-        |header, size| converter.convert_header(header, size),
-    )?;
+    let block = converter.convert_block_owned(block, txs_kind)?;
     Ok(SimulatedBlock { inner: block, calls })
 }
 
@@ -325,6 +325,9 @@ pub enum RethRpcModule {
     Mev,
     /// `testing_` module
     Testing,
+    /// `reth_era` module, the `reth_` methods that archive and import ERA history
+    #[strum(serialize = "reth_era")]
+    RethEra,
     /// Custom RPC module not part of the standard set
     #[strum(default)]
     #[serde(untagged)]
@@ -350,6 +353,7 @@ impl RethRpcModule {
         Self::Miner,
         Self::Mev,
         Self::Testing,
+        Self::RethEra,
     ];
 
     /// Returns the number of standard variants (excludes Other)
@@ -410,6 +414,7 @@ impl AsRef<str> for RethRpcModule {
             Self::Miner => "miner",
             Self::Mev => "mev",
             Self::Testing => "testing",
+            Self::RethEra => "reth_era",
         }
     }
 }
@@ -433,6 +438,7 @@ impl FromStr for RethRpcModule {
             "miner" => Self::Miner,
             "mev" => Self::Mev,
             "testing" => Self::Testing,
+            "reth_era" => Self::RethEra,
             // Any unknown module becomes Other
             other => Self::Other(other.to_string()),
         })
@@ -843,13 +843,10 @@ where
             let rlp = alloy_rlp::encode(entry.block.sealed_block()).into();
             let hash = entry.block.hash();
 
-            let block = entry
-                .block
-                .clone_into_rpc_block(
-                    BlockTransactionsKind::Full,
-                    |tx, tx_info| self.eth_api().converter().fill(tx, tx_info),
-                    |header, size| self.eth_api().converter().convert_header(header, size),
-                )
+            let block = self
+                .eth_api()
+                .converter()
+                .convert_block(&entry.block, BlockTransactionsKind::Full)
                 .map_err(|err| Eth::Error::from(err).into())?;
 
             let bad_block =
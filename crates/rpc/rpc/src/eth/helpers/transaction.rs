@@ -135,6 +135,7 @@ mod tests {
     use alloy_consensus::{
         BlobTransactionSidecar, Block, Header, SidecarBuilder, SimpleCoder, Transaction,
     };
+    use alloy_eips::eip2718::Decodable2718;
     use alloy_primitives::{map::AddressMap, Address, Bytes, U256};
     use alloy_rpc_types_eth::request::TransactionRequest;
     use reth_chainspec::{ChainSpec, ChainSpecBuilder};
@@ -390,6 +391,31 @@ mod tests {
         assert!(filled.tx.is_eip1559());
     }
 
+    #[tokio::test]
+    async fn test_fill_transaction_raw_encodes_tx() {
+        let address = Address::random();
+        let accounts = AddressMap::from_iter([(
+            address,
+            ExtendedAccount::new(3, U256::from(10_000_000_000_000_000_000u64)),
+        )]);
+
+        let eth_api = mock_eth_api(accounts);
+
+        let tx_req = TransactionRequest {
+            from: Some(address),
+            to: Some(Address::random().into()),
+            value: Some(U256::from(1000)),
+            ..Default::default()
+        };
+
+        let filled =
+            eth_api.fill_transaction(tx_req).await.expect("fill_transaction should succeed");
+
+        // `raw` is the EIP-2718 envelope of `tx`
+        let decoded = Decodable2718::decode_2718(&mut filled.raw.as_ref()).unwrap();
+        assert_eq!(filled.tx, decoded);
+    }
+
     #[tokio::test]
     async fn test_fill_transaction_eip4844_blob_fee() {
         let address = Address::random();
@@ -9,15 +9,116 @@ use reth_rpc_eth_types::receipt::EthReceiptConverter;
 pub type EthRpcConverter<ChainSpec> =
     RpcConverter<Ethereum, EthEvmConfig, EthReceiptConverter<ChainSpec>>;
 
-//tests for simulate
+//tests for simulate and block conversion
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_consensus::{Transaction, TxType};
-    use alloy_rpc_types_eth::TransactionRequest;
+    use alloy_consensus::{Header, Transaction, TxLegacy, TxType};
+    use alloy_primitives::{Address, Log, LogData, Signature, TxKind};
+    use alloy_rpc_types_eth::{BlockTransactions, BlockTransactionsKind, TransactionRequest};
     use reth_chainspec::MAINNET;
+    use reth_ethereum_primitives::{Block, BlockBody, Receipt, TransactionSigned};
+    use reth_primitives_traits::RecoveredBlock;
+    use reth_rpc_convert::RpcConvert;
     use reth_rpc_eth_types::simulate::resolve_transaction;
     use revm::database::CacheDB;
+    use std::sync::Arc;
+
+    fn test_block() -> RecoveredBlock<Block> {
+        let transactions = (0..3)
+            .map(|nonce| {
+                TransactionSigned::new_unhashed(
+                    TxLegacy {
+                        nonce,
+                        gas_price: 10,
+                        gas_limit: 21_000,
+                        to: TxKind::Call(Address::ZERO),
+                        ..Default::default()
+                    }
+                    .into(),
+                    Signature::test_signature(),
+                )
+            })
+            .collect();
+        RecoveredBlock::new_unhashed(
+            Block {
+                header: Header { number: 7, base_fee_per_gas: Some(3), ..Default::default() },
+                body: BlockBody { transactions, ..Default::default() },
+            },
+            vec![Address::repeat_byte(1); 3],
+        )
+    }
+
+    #[test]
+    fn converts_blocks_with_full_transactions_or_hashes() {
+        let converter = EthRpcConverter::new(EthReceiptConverter::new(MAINNET.clone()));
+        let block = test_block();
+
+        let rpc_block = converter.convert_block(&block, BlockTransactionsKind::Hashes).unwrap();
+        let BlockTransactions::Hashes(hashes) = &rpc_block.transactions else {
+            panic!("expected hashes, got {:?}", rpc_block.transactions)
+        };
+        assert_eq!(
+            hashes,
+            &block.body().transactions.iter().map(|tx| *tx.tx_hash()).collect::<Vec<_>>()
+        );
+
+        let full = converter.convert_block(&block, BlockTransactionsKind::Full).unwrap();
+        let BlockTransactions::Full(transactions) = &full.transactions else {
+            panic!("expected full transactions, got {:?}", full.transactions)
+        };
+        assert_eq!(transactions.len(), 3);
+        for ((index, tx), hash) in transactions.iter().enumerate().zip(hashes) {
+            assert_eq!(*tx.inner.tx_hash(), *hash);
+            assert_eq!(tx.transaction_index, Some(index as u64));
+            assert_eq!(tx.block_hash, Some(block.hash()));
+            assert_eq!(tx.block_number, Some(7));
+            assert_eq!(tx.inner.signer(), Address::repeat_byte(1));
+        }
+        assert_eq!(full.header.hash, block.hash());
+        assert_eq!(&converter.convert_transactions(&block).unwrap(), transactions);
+
+        // Owned and shared blocks convert the same as borrowed ones.
+        let owned =
+            converter.convert_block_owned(block.clone(), BlockTransactionsKind::Full).unwrap();
+        assert_eq!(owned, full);
+        let shared = Arc::new(block.clone());
+        let _other = Arc::clone(&shared);
+        assert_eq!(
+            converter.convert_block_shared(shared, BlockTransactionsKind::Full).unwrap(),
+            full
+        );
+    }
+
+    #[test]
+    fn converts_block_receipts_with_block_wide_log_indexes() {
+        let converter = EthRpcConverter::new(EthReceiptConverter::new(MAINNET.clone()));
+        let block = test_block();
+        let log = Log { address: Address::repeat_byte(2), data: LogData::empty() };
+        let receipts = [(21_000, 2), (42_000, 0), (63_000, 1)]
+            .map(|(cumulative_gas_used, logs)| Receipt {
+                tx_type: TxType::Legacy,
+                cumulative_gas_used,
+                logs: vec![log.clone(); logs],
+                success: true,
+            })
+            .to_vec();
+
+        let receipts = converter.convert_block_receipts(&block, receipts).unwrap();
+        assert_eq!(receipts.len(), 3);
+        for (index, receipt) in receipts.iter().enumerate() {
+            assert_eq!(receipt.transaction_index, Some(index as u64));
+            assert_eq!(receipt.block_hash, Some(block.hash()));
+            assert_eq!(receipt.gas_used, 21_000);
+            // Legacy transactions pay their gas price whatever the base fee.
+            assert_eq!(receipt.effective_gas_price, 10);
+        }
+        let log_indexes = receipts
+            .iter()
+            .flat_map(|receipt| receipt.inner.logs().iter().map(|log| log.log_index))
+            .collect::<Vec<_>>();
+        assert_eq!(log_indexes, [Some(0), Some(1), Some(2)]);
+    }
 
     #[test]
     fn test_resolve_transaction_empty_request() {
@@ -4,7 +4,7 @@ use alloy_consensus::{transaction::TxHashRef, BlockHeader};
 use alloy_eips::BlockNumberOrTag;
 use alloy_evm::{env::BlockEnvironment, overrides::apply_block_overrides};
 use alloy_primitives::U256;
-use alloy_rpc_types_eth::{BlockId, Log};
+use alloy_rpc_types_eth::{BlockId, Log, TransactionInfo};
 use alloy_rpc_types_mev::{
     BundleItem, Inclusion, MevSendBundle, Privacy, RefundConfig, SimBundleLogs, SimBundleOverrides,
     SimBundleResponse, Validity,
@@ -13,6 +13,7 @@ use jsonrpsee::core::RpcResult;
 use reth_evm::{ConfigureEvm, Evm};
 use reth_primitives_traits::Recovered;
 use reth_rpc_api::MevSimApiServer;
+use reth_rpc_convert::into_rpc_log;
 use reth_rpc_eth_api::{
     helpers::{block::LoadBlock, Call, EthTransactions},
     FromEthApiError, FromEvmError,
@@ -373,16 +374,15 @@ where
                             .into_logs()
                             .into_iter()
                             .map(|inner| {
-                                let full_log = Log {
-                                    inner,
+                                let tx_info = TransactionInfo {
+                                    hash: Some(*item.tx.tx_hash()),
+                                    index: Some(tx_index as u64),
                                     block_hash: Some(current_block.hash()),
                                     block_number: Some(current_block.number()),
                                     block_timestamp: Some(current_block.timestamp()),
-                                    transaction_hash: Some(*item.tx.tx_hash()),
-                                    transaction_index: Some(tx_index as u64),
-                                    log_index: Some(log_index),
-                                    removed: false,
+                                    base_fee: None,
                                 };
+                                let full_log = into_rpc_log(inner, tx_info, log_index, false);
                                 log_index += 1;
                                 full_log
                             })
@@ -48,6 +48,7 @@ where
                 BTreeMap<String, <RpcTxB::Network as RpcTypes>::TransactionResponse>,
             >,
             resp_builder: &RpcTxB,
+            base_fee: u64,
         ) -> Result<(), RpcTxB::Error>
         where
             Tx: PoolTransaction,
@@ -55,7 +56,8 @@ where
         {
             content.entry(tx.sender()).or_default().insert(
                 tx.nonce().to_string(),
-                resp_builder.fill_pending(tx.clone_into_consensus())?,
+                resp_builder
+                    .fill_pending_with_base_fee(tx.clone_into_consensus(), Some(base_fee))?,
             );
 
             Ok(())
@@ -63,12 +65,19 @@ where
 
         let AllPoolTransactions { pending, queued } = self.pool.all_transactions();
 
+        let base_fee = self.pool.block_info().pending_basefee;
+
         let mut content = TxpoolContent::default();
         for pending in pending {
-            insert::<_, Eth>(&pending.transaction, &mut content.pending, &self.converter)?;
+            insert::<_, Eth>(
+                &pending.transaction,
+                &mut content.pending,
+                &self.converter,
+                base_fee,
+            )?;
         }
         for queued in queued {
-            insert::<_, Eth>(&queued.transaction, &mut content.queued, &self.converter)?;
+            insert::<_, Eth>(&queued.transaction, &mut content.queued, &self.converter, base_fee)?;
         }
 
         Ok(content)
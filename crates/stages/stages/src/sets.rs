@@ -273,8 +273,10 @@ where
         let mut builder = StageSetBuilder::default();
 
         if self.era_import_source.is_some() {
-            builder = builder
-                .add_stage(EraStage::new(self.era_import_source, self.stages_config.etl.clone()));
+            builder = builder.add_stage(
+                EraStage::new(self.era_import_source, self.stages_config.etl.clone())
+                    .with_config(&self.stages_config.era),
+            );
         }
 
         builder
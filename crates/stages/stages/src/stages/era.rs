@@ -2,7 +2,7 @@ use crate::{StageCheckpoint, StageId};
 use alloy_primitives::{BlockHash, BlockNumber};
 use futures_util::{Stream, StreamExt};
 use reqwest::{Client, Url};
-use reth_config::config::EtlConfig;
+use reth_config::config::{EraConfig, EtlConfig};
 use reth_db_api::{table::Value, transaction::DbTxMut};
 use reth_era::{
     common::file_ops::{EraFileType, StreamReader},
@@ -13,10 +13,10 @@ use reth_era::{
 use reth_era_downloader::{read_dir, read_era_dir, EraClient, EraMeta, EraStream, EraStreamConfig};
 use reth_era_utils as era;
 use reth_etl::Collector;
-use reth_primitives_traits::{FullBlockBody, FullBlockHeader, NodePrimitives};
+use reth_primitives_traits::{FullBlockBody, FullBlockHeader, FullReceipt, NodePrimitives};
 use reth_provider::{
-    BlockReader, BlockWriter, DBProvider, StageCheckpointWriter, StaticFileProviderFactory,
-    StaticFileWriter,
+    BlockReader, BlockWriter, DBProvider, RocksDBProviderFactory, StageCheckpointReader,
+    StageCheckpointWriter, StaticFileProviderFactory, StorageSettingsCache,
 };
 use reth_stages_api::{ExecInput, ExecOutput, Stage, StageError, UnwindInput, UnwindOutput};
 use reth_static_file_types::StaticFileSegment;
@@ -43,8 +43,13 @@ type ThreadSafeEraStream<Header, Body> =
 /// post-merge beacon blocks embed an execution payload. The reader is selected per file from its
 /// extension (`.era` vs `.era1` vs `.ere`/`.erae`).
 ///
-/// Imports block headers and bodies up to the configured target block. Receipts are generated by
-/// execution, which is not done in this stage.
+/// Imports block headers and bodies up to the configured target block, advancing the headers and
+/// bodies checkpoints along with its own so initial sync skips downloading them over devp2p.
+/// Receipts are generated by execution, which is not done in this stage; the execution stage
+/// would truncate receipts written ahead of its checkpoint anyway.
+///
+/// Unwinding leaves the imported data to the headers and bodies stages, which unwind it, and
+/// reopens the source from the unwound checkpoint on the next execution.
 pub struct EraStage<Header, Body, StreamFactory> {
     /// The `source` creates `stream`.
     source: Option<StreamFactory>,
@@ -55,10 +60,18 @@ pub struct EraStage<Header, Body, StreamFactory> {
     item: Option<Item<Header, Body>>,
     /// A stream of [`Item`]s, i.e. iterators over block `Header` and `Body` pairs.
     stream: Option<ThreadSafeEraStream<Header, Body>>,
+    /// How files are downloaded from a remote source.
+    stream_config: EraStreamConfig,
+    /// How each file is imported.
+    import_config: era::ImportConfig,
 }
 
 trait EraStreamFactory<Header, Body> {
-    fn create(self, input: ExecInput) -> Result<ThreadSafeEraStream<Header, Body>, StageError>;
+    fn create(
+        self,
+        input: ExecInput,
+        stream_config: EraStreamConfig,
+    ) -> Result<ThreadSafeEraStream<Header, Body>, StageError>;
 }
 
 impl<Header, Body> EraStreamFactory<Header, Body> for EraImportSource
@@ -66,7 +79,11 @@ where
     Header: FullBlockHeader + Value,
     Body: FullBlockBody<OmmerHeader = Header>,
 {
-    fn create(self, input: ExecInput) -> Result<ThreadSafeEraStream<Header, Body>, StageError> {
+    fn create(
+        self,
+        input: ExecInput,
+        stream_config: EraStreamConfig,
+    ) -> Result<ThreadSafeEraStream<Header, Body>, StageError> {
         match self {
             Self::Path(path) => {
                 // Consensus `.era` files are slot-indexed and ship no `checksums.txt`, so they use
@@ -89,7 +106,7 @@ where
                 // the pipeline skip already-imported blocks.
                 let is_era = EraFileType::from_url(url.as_str()) == EraFileType::Era;
                 let client = EraClient::new(Client::new(), url, folder);
-                let mut config = EraStreamConfig::default();
+                let mut config = stream_config;
                 if !is_era {
                     config = config.start_from(input.next_block());
                 }
@@ -162,6 +179,8 @@ impl<Header: Debug, Body: Debug, F: Debug> Debug for EraStage<Header, Body, F> {
             .field("hash_collector", &self.hash_collector)
             .field("item", &self.item.is_some())
             .field("stream", &"dyn Stream")
+            .field("stream_config", &self.stream_config)
+            .field("import_config", &self.import_config)
             .finish()
     }
 }
@@ -174,19 +193,54 @@ impl<Header, Body, F> EraStage<Header, Body, F> {
             item: None,
             stream: None,
             hash_collector: Collector::new(etl_config.file_size, etl_config.dir),
+            stream_config: EraStreamConfig::default(),
+            import_config: era::ImportConfig::default(),
         }
     }
+
+    /// Applies the `[stages.era]` section of the node configuration: how many files are
+    /// downloaded at once and kept on disk.
+    ///
+    /// The source is configured separately, with the stage's [`EraImportSource`].
+    pub fn with_config(mut self, config: &EraConfig) -> Self {
+        self.stream_config = EraStreamConfig::default()
+            .with_max_files(config.max_files)
+            .with_max_concurrent_downloads(config.max_concurrent_downloads);
+        self
+    }
+
+    /// Sets how each file is imported.
+    ///
+    /// Blocks are always written as in [`era::ImportMode::Database`], up to the stage target
+    /// rather than [`era::ImportConfig::to_block`], and without throttling. Senders and
+    /// transaction hashes are left to their own stages. The pipeline never imports a block that
+    /// doesn't match its header, so verification stays [`era::VerificationMode::Strict`]; use
+    /// `reth import-era` to import such blocks anyway.
+    pub fn with_import_config(mut self, import_config: era::ImportConfig) -> Self {
+        self.import_config = era::ImportConfig {
+            mode: era::ImportMode::Database,
+            verification: era::VerificationMode::Strict,
+            throttle: None,
+            sender_recovery_threads: None,
+            index_hashes: false,
+            ..import_config
+        };
+        self
+    }
 }
 
 impl<Provider, N, F> Stage<Provider> for EraStage<N::BlockHeader, N::BlockBody, F>
 where
     Provider: DBProvider<Tx: DbTxMut>
         + StaticFileProviderFactory<Primitives = N>
+        + StorageSettingsCache
+        + RocksDBProviderFactory
         + BlockWriter<Block = N::Block>
         + BlockReader<Block = N::Block>
+        + StageCheckpointReader
         + StageCheckpointWriter,
     F: EraStreamFactory<N::BlockHeader, N::BlockBody> + Send + Sync + Clone,
-    N: NodePrimitives<BlockHeader: Value>,
+    N: NodePrimitives<BlockHeader: Value, Receipt: FullReceipt>,
 {
     fn id(&self) -> StageId {
         StageId::Era
@@ -204,7 +258,7 @@ where
         if self.stream.is_none() &&
             let Some(source) = self.source.clone()
         {
-            self.stream.replace(source.create(input)?);
+            self.stream.replace(source.create(input, self.stream_config.clone())?);
         }
         if let Some(stream) = &mut self.stream &&
             let Some(next) = ready!(stream.poll_next_unpin(cx))
@@ -227,18 +281,16 @@ where
                 .get_highest_static_file_block(StaticFileSegment::Headers)
                 .unwrap_or_default();
 
-            // Although headers were downloaded in reverse order, the collector iterates it in
-            // ascending order
-            let mut writer = static_file_provider.latest_writer(StaticFileSegment::Headers)?;
-
             let height = era::process_iter(
                 era,
-                &mut writer,
                 provider,
                 &mut self.hash_collector,
                 last_header_number..=input.target(),
+                &self.import_config,
+                &mut (),
             )
-            .map_err(|e| StageError::Fatal(e.into()))?;
+            .map_err(|e| StageError::Fatal(e.into()))?
+            .last_block;
 
             if !self.hash_collector.is_empty() {
                 era::build_index(provider, &mut self.hash_collector)
@@ -284,6 +336,11 @@ where
         _provider: &Provider,
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
+        // The source is positioned past the unwound blocks, so continuing it would leave a gap.
+        self.item = None;
+        self.stream = None;
+        self.hash_collector.clear();
+
         Ok(UnwindOutput { checkpoint: input.checkpoint.with_block_number(input.unwind_to) })
     }
 }
@@ -342,7 +399,7 @@ mod tests {
     };
     use reth_ethereum_primitives::{Block, TransactionSigned};
     use reth_primitives_traits::SealedBlock;
-    use reth_provider::BlockHashReader;
+    use reth_provider::{BlockHashReader, StaticFileWriter};
     use reth_testing_utils::generators::{
         self, random_block_range, random_header, BlockRangeParams,
     };
@@ -473,6 +530,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unwind_reopens_source() {
+        let runner = EraTestRunner::default();
+        let mut stage = runner.stage();
+        stage.stream = Some(Box::new(stream::empty()));
+        stage.item = Some(Box::new(iter::empty()));
+
+        let provider = runner.db().factory.database_provider_rw().unwrap();
+        let input =
+            UnwindInput { checkpoint: StageCheckpoint::new(10), unwind_to: 5, bad_block: None };
+        let output = stage.unwind(&provider, input).unwrap();
+
+        assert_eq!(output.checkpoint.block_number, 5);
+        assert!(stage.stream.is_none());
+        assert!(stage.item.is_none());
+    }
+
     mod test_runner {
         use super::*;
         use crate::test_utils::{TestRunnerError, TestStageDB};
@@ -743,6 +817,7 @@ mod tests {
             fn create(
                 self,
                 _input: ExecInput,
+                _stream_config: EraStreamConfig,
             ) -> Result<ThreadSafeEraStream<Header, BlockBody<TransactionSigned>>, StageError>
             {
                 let stream = stream::iter(vec![self.0]);
@@ -578,6 +578,8 @@ pub enum ChainStateKey {
     LastFinalizedBlock,
     /// Last safe block key
     LastSafeBlock,
+    /// Last block of the history archived outside the node key
+    LastArchivedBlock,
 }
 
 impl Encode for ChainStateKey {
@@ -587,6 +589,7 @@ impl Encode for ChainStateKey {
         match self {
             Self::LastFinalizedBlock => [0],
             Self::LastSafeBlock => [1],
+            Self::LastArchivedBlock => [2],
         }
     }
 }
@@ -596,6 +599,7 @@ impl Decode for ChainStateKey {
         match value {
             [0] => Ok(Self::LastFinalizedBlock),
             [1] => Ok(Self::LastSafeBlock),
+            [2] => Ok(Self::LastArchivedBlock),
             _ => Err(crate::DatabaseError::Decode),
         }
     }
@@ -7,7 +7,7 @@ use alloy_eips::{BlockHashOrNumber, BlockId, BlockNumberOrTag};
 use alloy_primitives::{BlockNumber, TxNumber, B256};
 use core::ops::RangeInclusive;
 use reth_primitives_traits::{Block as _, RecoveredBlock, SealedHeader, SealedOrRecoveredBlock};
-use reth_storage_errors::provider::ProviderResult;
+use reth_storage_errors::provider::{ProviderError, ProviderResult};
 
 /// A helper enum that represents the origin of the requested block.
 ///
@@ -422,6 +422,14 @@ pub trait ChainStateBlockReader: Send {
     ///
     /// If no safe block has been written yet, this returns `None`.
     fn last_safe_block_number(&self) -> ProviderResult<Option<BlockNumber>>;
+    /// Returns the last block of the history durably archived outside the node, such as in ERA
+    /// files, which history expiry may drop every block up to.
+    ///
+    /// If no archived block has been written yet, or the provider doesn't record one, this
+    /// returns `None`.
+    fn last_archived_block_number(&self) -> ProviderResult<Option<BlockNumber>> {
+        Ok(None)
+    }
 }
 
 /// Functionality to write the last known chain blocks to the database.
@@ -431,4 +439,11 @@ pub trait ChainStateBlockWriter: Send {
 
     /// Saves the given safe block number in the DB.
     fn save_safe_block_number(&self, block_number: BlockNumber) -> ProviderResult<()>;
+
+    /// Saves the given archived block number in the DB.
+    ///
+    /// Returns [`ProviderError::UnsupportedProvider`] if the provider doesn't record one.
+    fn save_archived_block_number(&self, _block_number: BlockNumber) -> ProviderResult<()> {
+        Err(ProviderError::UnsupportedProvider)
+    }
 }
@@ -1,10 +1,10 @@
 use crate::NodePrimitivesProvider;
 use alloc::vec::Vec;
-use alloy_primitives::BlockNumber;
+use alloy_primitives::{BlockNumber, TxNumber};
 use reth_db_models::StoredBlockBodyIndices;
 use reth_execution_types::{Chain, ExecutionOutcome};
 use reth_primitives_traits::{Block, NodePrimitives, RecoveredBlock};
-use reth_storage_errors::provider::ProviderResult;
+use reth_storage_errors::provider::{ProviderError, ProviderResult};
 use reth_trie_common::HashedPostStateSorted;
 
 /// `BlockExecution` Writer
@@ -66,6 +66,24 @@ pub trait BlockWriter {
         bodies: Vec<(BlockNumber, Option<&<Self::Block as Block>::Body>)>,
     ) -> ProviderResult<()>;
 
+    /// Appends the body indices of a block extending the canonical chain, numbering its
+    /// transactions from `first_tx_num`, along with the parts of `body` kept in the database, such
+    /// as ommers and withdrawals.
+    ///
+    /// Unlike [`Self::append_block_bodies`], the transactions are not written: the caller appends
+    /// them to the transactions static file segment itself.
+    ///
+    /// Returns [`ProviderError::UnsupportedProvider`] unless the provider supports writing body
+    /// indices on their own.
+    fn append_block_body_indices(
+        &self,
+        _block_number: BlockNumber,
+        _body: &<Self::Block as Block>::Body,
+        _first_tx_num: TxNumber,
+    ) -> ProviderResult<()> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
     /// Removes all blocks above the given block number from the database.
     ///
     /// Note: This does not remove state or execution data.
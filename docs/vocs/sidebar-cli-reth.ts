@@ -29,6 +29,29 @@ export const rethCliSidebar: SidebarItem = {
             text: "reth export-era",
             link: "/cli/reth/export-era"
         },
+        {
+            text: "reth era",
+            link: "/cli/reth/era",
+            collapsed: true,
+            items: [
+                {
+                    text: "reth era import",
+                    link: "/cli/reth/era/import"
+                },
+                {
+                    text: "reth era export",
+                    link: "/cli/reth/era/export"
+                },
+                {
+                    text: "reth era verify",
+                    link: "/cli/reth/era/verify"
+                },
+                {
+                    text: "reth era serve",
+                    link: "/cli/reth/era/serve"
+                }
+            ]
+        },
         {
             text: "reth dump-genesis",
             link: "/cli/reth/dump-genesis"